@@ -0,0 +1,129 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use a_star::algo::alt::Landmarks;
+use a_star::algo::astar::{self, TieBreak};
+use a_star::algo::context::SearchContext;
+use a_star::algo::frontier::Frontier;
+use a_star::algo::intern::{self, InternedGraph};
+use a_star::algo::{bidirectional, fringe, Heuristic};
+use a_star::load::{build_heur_data, build_map, Duplicates, Unit};
+
+/// The bundled routes and straight-line distances
+const ROUTES: &str = include_str!("../routes.txt");
+const HEURISTIC: &str = include_str!("../euclidian.txt");
+
+/// The width and height of the generated grid, in cells
+const GRID_SIZE: usize = 100;
+
+/// The # of landmarks the grid's heuristic is computed from
+const GRID_LANDMARKS: usize = 8;
+
+/// The # of locations on the generated dense graph, about a quarter of
+/// whose pairs are joined by a route
+const DENSE_SIZE: usize = 300;
+
+///
+/// Generates a square grid of routes between neighbouring cells, with
+/// weights varying from 1 to 5 so that routes are not all equally short
+///
+fn grid_routes() -> String
+{
+    let mut routes = String::new();
+    for x in 0..GRID_SIZE
+    {
+        for y in 0..GRID_SIZE
+        {
+            if x + 1 < GRID_SIZE { routes += &format!("c{}_{},c{}_{},{}\n", x, y, x + 1, y, 1 + (x * 7 + y * 3) % 5); }
+            if y + 1 < GRID_SIZE { routes += &format!("c{}_{},c{}_{},{}\n", x, y, x, y + 1, 1 + (x * 3 + y * 11) % 4); }
+        }
+    }
+    routes
+}
+
+///
+/// Generates a dense graph, whose locations have so many routes between
+/// them that searches often find shorter routes to locations already
+/// queued, with weights varying from 1 to 97
+///
+fn dense_routes() -> String
+{
+    let mut routes = String::new();
+    for i in 0..DENSE_SIZE
+    {
+        for j in i + 1..DENSE_SIZE
+        {
+            if (i * 7 + j * 13) % 4 == 0 { routes += &format!("n{},n{},{}\n", i, j, 1 + (i * 31 + j * 17) % 97); }
+        }
+    }
+    routes
+}
+
+fn parsing(c: &mut Criterion)
+{
+    let grid = grid_routes();
+    c.bench_function("parse bundled routes", |b| b.iter(|| build_map(black_box(ROUTES), false, Duplicates::default(), ',', Unit::default()).unwrap()));
+    c.bench_function("parse bundled heuristic", |b| b.iter(|| build_heur_data(black_box(HEURISTIC), None, Unit::default()).unwrap()));
+    c.bench_function("parse grid routes", |b| b.iter(|| build_map(black_box(&grid), false, Duplicates::default(), ',', Unit::default()).unwrap()));
+}
+
+///
+/// Benchmarks every search between two locations, over the Graph and
+/// over its InternedGraph with each frontier
+///
+/// - name: the Graph's name, to label each benchmark
+/// - graph: the routes to search
+/// - heuristic: the heuristic to search with
+/// - start, end: the locations to route between
+///
+fn searches(c: &mut Criterion, name: &str, graph: &a_star::algo::RouteGraph, heuristic: &dyn Heuristic, start: &str, end: &str)
+{
+    let (start, end) = (graph.nodes().find(|&node| node == start).unwrap(), graph.nodes().find(|&node| node == end).unwrap());
+    let mut interned = InternedGraph::new(graph);
+    interned.set_heuristic(heuristic);
+
+    let mut group = c.benchmark_group(name);
+    group.bench_function("A*", |b| b.iter(|| astar::astar(graph, Some(heuristic), start, end, TieBreak::default())));
+    group.bench_function("Djikstra", |b| b.iter(|| astar::astar(graph, None, start, end, TieBreak::default())));
+    group.bench_function("Fringe", |b| b.iter(|| fringe::fringe_search(graph, heuristic, start, end)));
+    for frontier in Frontier::ALL
+    {
+        let mut context = SearchContext::new(frontier);
+        group.bench_function(format!("interned A* ({})", frontier.name()), |b| b.iter(||
+            intern::astar(&interned, Some(heuristic), start, end, TieBreak::default(), &mut context, |_| ())));
+        group.bench_function(format!("interned Djikstra ({})", frontier.name()), |b| b.iter(||
+            intern::astar(&interned, None, start, end, TieBreak::default(), &mut context, |_| ())));
+    }
+    let mut context = SearchContext::default();
+    group.bench_function("interned Fringe", |b| b.iter(|| intern::fringe_search(&interned, heuristic, start, end, &mut context)));
+    group.bench_function("bidirectional A*", |b| b.iter(|| bidirectional::bidirectional(&interned, Some(heuristic), start, end, &mut context)));
+    group.bench_function("bidirectional Djikstra", |b| b.iter(|| bidirectional::bidirectional(&interned, None, start, end, &mut context)));
+    group.finish();
+}
+
+fn bundled(c: &mut Criterion)
+{
+    let routes = build_map(ROUTES, false, Duplicates::default(), ',', Unit::default()).unwrap();
+    let heuristic = build_heur_data(HEURISTIC, None, Unit::default()).unwrap();
+    searches(c, "bundled Seattle to Miami", &routes.graph, &heuristic, "Seattle", "Miami");
+}
+
+fn grid(c: &mut Criterion)
+{
+    let text = grid_routes();
+    let routes = build_map(&text, false, Duplicates::default(), ',', Unit::default()).unwrap();
+    let heuristic = Landmarks::new(&routes.graph, GRID_LANDMARKS);
+    let end = format!("c{}_{}", GRID_SIZE - 1, GRID_SIZE - 1);
+    searches(c, "grid corner to corner", &routes.graph, &heuristic, "c0_0", &end);
+}
+
+fn dense(c: &mut Criterion)
+{
+    let text = dense_routes();
+    let routes = build_map(&text, false, Duplicates::default(), ',', Unit::default()).unwrap();
+    let heuristic = Landmarks::new(&routes.graph, GRID_LANDMARKS);
+    let end = format!("n{}", DENSE_SIZE - 1);
+    searches(c, "dense first to last", &routes.graph, &heuristic, "n0", &end);
+}
+
+criterion_group!(benches, parsing, bundled, grid, dense);
+criterion_main!(benches);
@@ -0,0 +1,169 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use petgraph::Direction::{Incoming, Outgoing};
+use petgraph::EdgeType;
+
+use super::{Heuristic, Network};
+
+///
+/// An estimate above the true shortest distance, which can lead A* to a
+/// route longer than the shortest
+///
+/// - node: the node the estimate is made from
+/// - goal: the node the estimate is made to
+/// - estimate: the heuristic's estimate
+/// - distance: the shortest distance from node to goal
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overestimate<'a>
+{
+    pub node: &'a str,
+    pub goal: &'a str,
+    pub estimate: u64,
+    pub distance: u64,
+}
+
+///
+/// An edge across which the estimate to a goal falls by more than the
+/// edge's weight, so that A* may expand a node again after finding a
+/// shorter route to it
+///
+/// - from, to: the edge's nodes, in the direction travelled
+/// - goal: the node the estimates are made to
+/// - weight: the edge's weight
+/// - from_estimate, to_estimate: the estimates from each of the edge's
+///   nodes to the goal
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inconsistency<'a>
+{
+    pub from: &'a str,
+    pub to: &'a str,
+    pub goal: &'a str,
+    pub weight: u64,
+    pub from_estimate: u64,
+    pub to_estimate: u64,
+}
+
+///
+/// The result of checking a heuristic against the true shortest distances
+/// to some goals
+///
+/// - goals: the # of goals checked
+/// - pairs: the # of (node, goal) estimates checked, of nodes which can
+///   reach the goal
+/// - overestimates: every inadmissible estimate, by how far over it is,
+///   largest first
+/// - inconsistencies: every edge the estimates are inconsistent across,
+///   by how far, largest first
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeuristicCheck<'a>
+{
+    pub goals: usize,
+    pub pairs: usize,
+    pub overestimates: Vec<Overestimate<'a>>,
+    pub inconsistencies: Vec<Inconsistency<'a>>,
+}
+
+impl Overestimate<'_>
+{
+    ///
+    /// - return: how far the estimate is over the distance
+    ///
+    pub fn excess(&self) -> u64
+    {
+        self.estimate - self.distance
+    }
+}
+
+impl Inconsistency<'_>
+{
+    ///
+    /// - return: how far the estimate falls across the edge beyond its
+    ///   weight
+    ///
+    pub fn excess(&self) -> u64
+    {
+        self.from_estimate - self.weight - self.to_estimate
+    }
+}
+
+///
+/// Checks a heuristic's estimates to each goal against the shortest
+/// distance to it, found by a search back from the goal, and checks that
+/// the estimates never fall by more than an edge's weight across it
+///
+/// - graph: the Graph searched with the heuristic. Edges are only
+///   travelled in the directions it allows
+/// - heuristic: the heuristic to check
+/// - goals: the goals to check estimates to
+///
+/// - return: the HeuristicCheck
+///
+pub fn check_heuristic<'a, Ty: EdgeType>(graph: &Network<'a, Ty>, heuristic: &dyn Heuristic, goals: &[&'a str]) -> HeuristicCheck<'a>
+{
+    let mut check = HeuristicCheck { goals: goals.len(), pairs: 0, overestimates: Vec::new(), inconsistencies: Vec::new() };
+    for &goal in goals
+    {
+        for (node, distance) in distances_to(graph, goal)
+        {
+            check.pairs += 1;
+            let estimate = heuristic.estimate(node, goal);
+            if estimate > distance
+            {
+                check.overestimates.push(Overestimate { node, goal, estimate, distance });
+            }
+        }
+
+        for from in graph.nodes()
+        {
+            let from_estimate = heuristic.estimate(from, goal);
+            for to in graph.neighbors_directed(from, Outgoing)
+            {
+                let weight = graph.edge_weight(from, to).cloned().unwrap_or(0);
+                let to_estimate = heuristic.estimate(to, goal);
+                if from_estimate > weight.saturating_add(to_estimate)
+                {
+                    check.inconsistencies.push(Inconsistency { from, to, goal, weight, from_estimate, to_estimate });
+                }
+            }
+        }
+    }
+
+    check.overestimates.sort_by_key(|overestimate| Reverse(overestimate.excess()));
+    check.inconsistencies.sort_by_key(|inconsistency| Reverse(inconsistency.excess()));
+    check
+}
+
+///
+/// Runs Djikstra's algorithm back from goal, along the edges reaching
+/// each node
+///
+/// - return: the shortest distance to goal from every node which can
+///   reach it
+///
+fn distances_to<'a, Ty: EdgeType>(graph: &Network<'a, Ty>, goal: &'a str) -> HashMap<&'a str, u64>
+{
+    let mut dist = HashMap::new();
+    let mut queue = BinaryHeap::new();
+    dist.insert(goal, 0);
+    queue.push(Reverse((0, goal)));
+
+    while let Some(Reverse((node_dist, node))) = queue.pop()
+    {
+        if dist[node] < node_dist { continue; }
+        for from in graph.neighbors_directed(node, Incoming)
+        {
+            let alt_route = node_dist + graph.edge_weight(from, node).cloned().unwrap_or(0);
+            if dist.get(from).is_none_or(|d| alt_route < *d)
+            {
+                dist.insert(from, alt_route);
+                queue.push(Reverse((alt_route, from)));
+            }
+        }
+    }
+
+    dist
+}
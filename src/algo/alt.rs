@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+
+use super::{shortest_path_tree, Heuristic, RouteGraph};
+use crate::load::sidecar::{Artifact, Reader};
+
+/// The number of landmarks selected when none is specified
+pub const DEFAULT_LANDMARK_COUNT: usize = 8;
+
+///
+/// An ALT (A*, Landmarks, Triangle inequality) heuristic. A handful of
+/// landmark nodes are chosen, and exact distances from each of them to
+/// every node are precomputed. By the triangle inequality, for any
+/// landmark L, |d(L, goal) - d(L, node)| never overestimates the distance
+/// between node and goal, so the heuristic is admissible for any Graph
+/// without hand-written heuristic data.
+///
+pub struct Landmarks<'a>
+{
+    landmarks: Vec<&'a str>,
+    dists: Vec<HashMap<&'a str, u64>>,
+}
+
+impl<'a> Landmarks<'a>
+{
+    ///
+    /// Selects landmarks and computes their distance tables
+    ///
+    /// Landmarks are chosen by farthest-point selection: each new landmark
+    /// is the node farthest from all landmarks chosen so far, which spreads
+    /// them around the edges of the Graph where their bounds are tightest.
+    /// Nodes unreachable from every landmark are chosen first, so every
+    /// connected component receives at least one.
+    ///
+    /// - graph: the Graph to preprocess
+    /// - count: the # of landmarks to select (capped at the # of nodes)
+    ///
+    pub fn new(graph: &RouteGraph<'a>, count: usize) -> Self
+    {
+        let mut landmarks = Vec::new();
+        let mut dists: Vec<HashMap<&'a str, u64>> = Vec::new();
+
+        // Seed the selection with the node farthest from an arbitrary node
+        let mut next = graph.nodes().next().map(|seed|
+        {
+            let seed_dists = shortest_path_tree(graph, seed).dist;
+            farthest(graph, &[seed_dists])
+        });
+
+        while let Some(landmark) = next
+        {
+            if landmarks.len() >= count { break; }
+
+            landmarks.push(landmark);
+            dists.push(shortest_path_tree(graph, landmark).dist);
+
+            next = Some(farthest(graph, &dists)).filter(|node| !landmarks.contains(node));
+        }
+
+        Landmarks { landmarks, dists }
+    }
+
+    ///
+    /// - return: the selected landmark nodes
+    ///
+    pub fn landmarks(&self) -> &[&'a str]
+    {
+        &self.landmarks
+    }
+}
+
+impl<'a> Artifact<'a> for Landmarks<'a>
+{
+    const KIND: &'static [u8; 8] = b"LANDMARK";
+    const EXTENSION: &'static str = "alt";
+
+    // The # of landmarks, then each landmark's node, then each landmark's
+    // distance to every node, or u64::MAX where it does not reach
+    fn to_bytes(&self, graph: &RouteGraph<'a>) -> Vec<u8>
+    {
+        let mut bytes = (self.landmarks.len() as u32).to_le_bytes().to_vec();
+        for landmark in &self.landmarks
+        {
+            let index = graph.nodes().position(|node| node == *landmark).expect("Landmarks are nodes on the Graph");
+            bytes.extend_from_slice(&(index as u32).to_le_bytes());
+        }
+        for dist in &self.dists
+        {
+            for node in graph.nodes()
+            {
+                bytes.extend_from_slice(&dist.get(node).copied().unwrap_or(u64::MAX).to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    fn from_bytes(graph: &RouteGraph<'a>, bytes: &[u8]) -> Option<Self>
+    {
+        let names = graph.nodes().collect::<Vec<&'a str>>();
+        let mut reader = Reader::new(bytes);
+
+        let count = reader.u32()? as usize;
+        let landmarks = (0..count)
+            .map(|_| names.get(reader.u32()? as usize).copied())
+            .collect::<Option<Vec<&'a str>>>()?;
+
+        let mut dists = Vec::with_capacity(count);
+        for _ in 0..count
+        {
+            let mut dist = HashMap::new();
+            for node in &names
+            {
+                let cost = reader.u64()?;
+                if cost != u64::MAX { dist.insert(*node, cost); }
+            }
+            dists.push(dist);
+        }
+
+        if !reader.is_done() { return None; }
+        Some(Landmarks { landmarks, dists })
+    }
+}
+
+impl<'a> Heuristic for Landmarks<'a>
+{
+    fn estimate(&self, node: &str, goal: &str) -> u64
+    {
+        // Take the tightest lower bound offered by any landmark which
+        // reaches both nodes
+        self.dists.iter()
+            .filter_map(|dist| match (dist.get(node), dist.get(goal))
+            {
+                (Some(to_node), Some(to_goal)) => Some(to_node.abs_diff(*to_goal)),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+///
+/// Finds the node whose distance to its nearest landmark is greatest.
+/// Nodes unreachable from every landmark count as infinitely far
+///
+fn farthest<'a>(graph: &RouteGraph<'a>, dists: &[HashMap<&'a str, u64>]) -> &'a str
+{
+    graph.nodes()
+        .max_by_key(|node| dists.iter()
+            .map(|dist| *dist.get(node).unwrap_or(&u64::MAX))
+            .min()
+            .unwrap_or(u64::MAX))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::Landmarks;
+    use crate::algo::{shortest_path_tree, Heuristic, RouteGraph};
+
+    #[test]
+    fn estimates_never_exceed_distances()
+    {
+        // A ring of 24 locations with chords across it, weights spread
+        // by a simple generator, and a separate pair apart from the rest
+        let names = (0..26).map(|i| format!("n{}", i)).collect::<Vec<String>>();
+        let mut graph = RouteGraph::new();
+        let mut state = 7u64;
+        let mut weight = || { state = (state * 48271) % 2147483647; 1 + state % 90 };
+        for i in 0..24
+        {
+            graph.add_edge(names[i].as_str(), names[(i + 1) % 24].as_str(), weight());
+            if i % 3 == 0 { graph.add_edge(names[i].as_str(), names[(i + 11) % 24].as_str(), weight()); }
+        }
+        graph.add_edge(names[24].as_str(), names[25].as_str(), weight());
+
+        for count in 1..=4
+        {
+            let landmarks = Landmarks::new(&graph, count);
+            for goal in graph.nodes()
+            {
+                let dist = shortest_path_tree(&graph, goal).dist;
+                for node in graph.nodes()
+                {
+                    let estimate = landmarks.estimate(node, goal);
+                    match dist.get(node)
+                    {
+                        Some(&distance) => assert!(estimate <= distance, "{} to {}: estimated {}, but {} away", node, goal, estimate, distance),
+                        None => assert_eq!(estimate, 0),
+                    }
+                }
+            }
+
+            // Each landmark's estimates to the locations it reaches are exact
+            for &landmark in landmarks.landmarks()
+            {
+                for (node, distance) in shortest_path_tree(&graph, landmark).dist
+                {
+                    assert_eq!(landmarks.estimate(node, landmark), distance);
+                }
+            }
+        }
+    }
+}
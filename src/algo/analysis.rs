@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use petgraph::algo::tarjan_scc;
+
+use super::dag::DirectedRouteGraph;
+use super::RouteGraph;
+
+///
+/// The structure of a route Graph: where it is connected, and where a
+/// single failure would break it apart
+///
+/// - components: the nodes of each connected component
+/// - bridges: every edge whose removal would disconnect its endpoints
+/// - articulation_points: every node whose removal would disconnect
+///   some of the remaining nodes
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Analysis<'a>
+{
+    pub components: Vec<Vec<&'a str>>,
+    pub bridges: Vec<(&'a str, &'a str)>,
+    pub articulation_points: Vec<&'a str>,
+}
+
+///
+/// Analyzes the structure of an undirected Graph. Bridges and articulation
+/// points are found in a single depth-first search with Tarjan's low-link
+/// values: the earliest-discovered node each subtree can reach without
+/// travelling back along the edge it was entered by
+///
+/// - graph: the Graph to analyze
+///
+/// - return: the Analysis, with every list sorted by name
+///
+pub fn analyze<'a>(graph: &RouteGraph<'a>) -> Analysis<'a>
+{
+    let mut components = tarjan_scc(graph);
+    for component in components.iter_mut() { component.sort(); }
+    components.sort();
+
+    let mut discovered: HashMap<&'a str, usize> = HashMap::new();
+    let mut low: HashMap<&'a str, usize> = HashMap::new();
+    let mut bridges = Vec::new();
+    let mut articulation_points = Vec::new();
+
+    for root in graph.nodes()
+    {
+        if discovered.contains_key(root) { continue; }
+
+        discovered.insert(root, discovered.len());
+        low.insert(root, discovered[root]);
+        let mut root_children = 0;
+
+        // Each stack entry is a node, its parent, and its
+        // neighbors still to be explored
+        let mut stack = vec![(root, None, graph.neighbors(root).collect::<Vec<&'a str>>())];
+        while let Some((node, parent, unexplored)) = stack.last_mut()
+        {
+            let (node, parent) = (*node, *parent);
+            match unexplored.pop()
+            {
+                Some(next) if Some(next) == parent => (),
+                Some(next) => match discovered.get(next)
+                {
+                    // A back edge to an ancestor
+                    Some(&order) => { low.insert(node, low[node].min(order)); },
+                    None =>
+                    {
+                        discovered.insert(next, discovered.len());
+                        low.insert(next, discovered[next]);
+                        stack.push((next, Some(node), graph.neighbors(next).collect()));
+                    },
+                },
+                None =>
+                {
+                    stack.pop();
+                    let parent = match parent
+                    {
+                        Some(parent) => parent,
+                        None => continue,
+                    };
+
+                    low.insert(parent, low[parent].min(low[node]));
+
+                    // The subtree cannot reach above node, so the edge
+                    // into it is its only connection
+                    if low[node] > discovered[parent] { bridges.push((parent.min(node), parent.max(node))); }
+
+                    // The subtree cannot reach above parent, so parent
+                    // separates it (the root only if it has several subtrees)
+                    if parent == root { root_children += 1; }
+                    else if low[node] >= discovered[parent] && !articulation_points.contains(&parent)
+                    {
+                        articulation_points.push(parent);
+                    }
+                },
+            }
+        }
+
+        if root_children > 1 { articulation_points.push(root); }
+    }
+
+    bridges.sort();
+    articulation_points.sort();
+    Analysis { components, bridges, articulation_points }
+}
+
+///
+/// Computes the strongly connected components of a directed Graph: the
+/// groups of nodes which can each reach every other node in the group
+///
+/// - graph: the Graph to analyze
+///
+/// - return: the nodes of each component, sorted by name
+///
+pub fn strongly_connected_components<'a>(graph: &DirectedRouteGraph<'a>) -> Vec<Vec<&'a str>>
+{
+    let mut components = tarjan_scc(graph);
+    for component in components.iter_mut() { component.sort(); }
+    components.sort();
+    components
+}
+
+///
+/// Which connected component each node of an undirected Graph belongs
+/// to, so that whether two nodes can be connected at all is answered
+/// without a search
+///
+#[derive(Debug, Clone, Default)]
+pub struct Components<'a>
+{
+    component: HashMap<&'a str, usize>,
+    members: Vec<Vec<&'a str>>,
+}
+
+impl<'a> Components<'a>
+{
+    ///
+    /// Labels every node with its component by a breadth-first search
+    /// from each node not yet labelled
+    ///
+    /// - graph: the Graph to label
+    ///
+    pub fn new(graph: &RouteGraph<'a>) -> Self
+    {
+        let mut components = Components::default();
+        for root in graph.nodes()
+        {
+            if components.component.contains_key(root) { continue; }
+
+            let id = components.members.len();
+            let mut members = vec![root];
+            components.component.insert(root, id);
+            let mut i = 0;
+            while i < members.len()
+            {
+                for neighbor in graph.neighbors(members[i])
+                {
+                    if components.component.contains_key(neighbor) { continue; }
+                    components.component.insert(neighbor, id);
+                    members.push(neighbor);
+                }
+                i += 1;
+            }
+
+            members.sort_unstable();
+            components.members.push(members);
+        }
+        components
+    }
+
+    ///
+    /// - return: the # of components
+    ///
+    pub fn count(&self) -> usize
+    {
+        self.members.len()
+    }
+
+    ///
+    /// - node: the node to look up
+    ///
+    /// - return: the index of node's component, or None if node is not on
+    ///   the Graph
+    ///
+    pub fn component(&self, node: &str) -> Option<usize>
+    {
+        self.component.get(node).copied()
+    }
+
+    ///
+    /// - component: the index of the component
+    ///
+    /// - return: the component's nodes, sorted by name
+    ///
+    pub fn members(&self, component: usize) -> &[&'a str]
+    {
+        &self.members[component]
+    }
+
+    ///
+    /// - return: whether a and b are in the same component
+    ///
+    pub fn connected(&self, a: &str, b: &str) -> bool
+    {
+        self.component(a).is_some() && self.component(a) == self.component(b)
+    }
+}
@@ -0,0 +1,221 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+
+use priority_queue::PriorityQueue;
+
+use super::{build_route, Heuristic, PathResult, RouteGraph};
+
+/// The heuristic weight the first search is inflated by
+pub const DEFAULT_INITIAL_WEIGHT: f64 = 3.0;
+
+/// How much the heuristic weight is reduced by after each solution
+pub const DEFAULT_WEIGHT_STEP: f64 = 0.5;
+
+///
+/// Computes the shortest route between two nodes using Anytime Repairing A*.
+///
+/// A first route is found quickly with a heavily inflated heuristic, which
+/// is guaranteed to be no longer than weight times the optimal route. The
+/// weight is then reduced step by step, each search reusing the work of the
+/// last, so ever better routes are produced until the weight reaches 1 (and
+/// the route is optimal) or the deadline passes.
+///
+/// - graph: the Graph to search
+/// - heuristic: estimates the distance between any two nodes
+/// - start: the start location on the Graph
+/// - end: the end location on the Graph
+/// - initial_weight: the first heuristic weight, at least 1
+/// - step: the amount the weight is reduced by after each search, more
+///   than 0
+/// - deadline: when to stop improving, if ever
+/// - on_solution: called with every improved route and the weight
+///   bounding its suboptimality
+///
+/// - return: Either an Ok Result with the best route found (None if end
+///   cannot be reached), or an Err if the weight or step could never
+///   bring the weight down to 1
+///
+#[allow(clippy::too_many_arguments)]
+pub fn ara_star<'a>(graph: &RouteGraph<'a>, heuristic: &dyn Heuristic, start: &'a str, end: &'a str,
+                    initial_weight: f64, step: f64, deadline: Option<Instant>,
+                    on_solution: &mut dyn FnMut(&PathResult<'a>, f64)) -> Result<Option<PathResult<'a>>, String>
+{
+    if !initial_weight.is_finite() || initial_weight < 1.0
+    {
+        return Err(format!("The initial heuristic weight must be a number of at least 1, not {}.", initial_weight));
+    }
+    if !step.is_finite() || step <= 0.0
+    {
+        return Err(format!("The heuristic weight's step must be a number more than 0, not {}.", step));
+    }
+
+    let mut search = Search
+    {
+        graph,
+        heuristic,
+        end,
+        weight: initial_weight,
+        dist: HashMap::new(),
+        prev: HashMap::new(),
+        open: PriorityQueue::new(),
+        closed: HashSet::new(),
+        incons: HashSet::new(),
+    };
+    search.dist.insert(start, 0);
+    search.open.push(start, Reverse(search.key(start)));
+
+    let mut best: Option<PathResult<'a>> = None;
+    loop
+    {
+        let complete = search.improve_path(deadline);
+
+        // Publish the route if it improves on the last
+        if let Some(&cost) = search.dist.get(end)
+        {
+            if best.as_ref().is_none_or(|route| cost < route.cost)
+            {
+                let route = build_route(&search.prev, start, end, cost);
+                on_solution(&route, search.weight);
+                best = Some(route);
+            }
+        }
+
+        if !complete || search.weight <= 1.0 { break; }
+
+        // Tighten the weight, and move every node improved while closed
+        // back onto the open list, re-keyed for the new weight
+        search.weight = (search.weight - step).max(1.0);
+        let reopened = search.open.iter()
+            .map(|(node, _)| *node)
+            .chain(search.incons.drain())
+            .collect::<Vec<&'a str>>();
+        search.open.clear();
+        for node in reopened
+        {
+            let key = search.key(node);
+            search.open.push(node, Reverse(key));
+        }
+        search.closed.clear();
+    }
+
+    Ok(best)
+}
+
+///
+/// The search state shared between ARA*'s successive searches
+///
+struct Search<'a, 'g>
+{
+    graph: &'g RouteGraph<'a>,
+    heuristic: &'g dyn Heuristic,
+    end: &'a str,
+    weight: f64,
+
+    dist: HashMap<&'a str, u64>,
+    prev: HashMap<&'a str, &'a str>,
+    open: PriorityQueue<&'a str, Reverse<u64>>,
+    closed: HashSet<&'a str>,
+
+    // Nodes improved after being closed in the current search
+    incons: HashSet<&'a str>,
+}
+
+impl<'a, 'g> Search<'a, 'g>
+{
+    ///
+    /// A node's priority: its distance plus its weighted heuristic
+    ///
+    fn key(&self, node: &'a str) -> u64
+    {
+        self.dist[node] + (self.heuristic.estimate(node, self.end) as f64 * self.weight).round() as u64
+    }
+
+    ///
+    /// Expands nodes until no open node could improve on the route to end
+    ///
+    /// - return: false if the deadline passed before the search completed
+    ///
+    fn improve_path(&mut self, deadline: Option<Instant>) -> bool
+    {
+        while let Some((_, Reverse(min_key))) = self.open.peek()
+        {
+            if self.dist.contains_key(self.end) && self.key(self.end) <= *min_key { break; }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) { return false; }
+
+            let (node, _) = self.open.pop().unwrap();
+            self.closed.insert(node);
+
+            for edge in self.graph.edges(node)
+            {
+                let alt_route = self.dist[node] + edge.2;
+                if self.dist.get(edge.1).is_some_and(|d| alt_route >= *d) { continue; }
+
+                self.dist.insert(edge.1, alt_route);
+                self.prev.insert(edge.1, node);
+
+                // Closed nodes wait for the next search, rather than
+                // being expanded twice in this one
+                if self.closed.contains(edge.1) { self.incons.insert(edge.1); }
+                else
+                {
+                    let key = self.key(edge.1);
+                    self.open.push(edge.1, Reverse(key));
+                }
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::ara_star;
+    use crate::algo::alt::Landmarks;
+    use crate::algo::astar::{astar, TieBreak};
+    use crate::algo::RouteGraph;
+
+    fn graph() -> RouteGraph<'static>
+    {
+        let mut graph = RouteGraph::new();
+        for &(from, to, weight) in &[("a", "b", 40), ("a", "c", 10), ("c", "d", 10), ("d", "b", 10), ("b", "e", 30),
+                                     ("d", "f", 50), ("f", "e", 5), ("c", "g", 15), ("g", "h", 15), ("h", "e", 35)]
+        {
+            graph.add_edge(from, to, weight);
+        }
+        graph
+    }
+
+    #[test]
+    fn final_route_is_optimal()
+    {
+        let graph = graph();
+        let landmarks = Landmarks::new(&graph, 2);
+        let optimal = astar(&graph, None, "a", "e", TieBreak::default()).0.unwrap().cost;
+
+        let mut solutions = Vec::new();
+        let route = ara_star(&graph, &landmarks, "a", "e", 5.0, 1.5, None, &mut |route, weight| solutions.push((route.cost, weight)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(route.cost, optimal);
+        assert_eq!(route.nodes.windows(2).map(|leg| graph.edge_weight(leg[0], leg[1]).unwrap()).sum::<u64>(), optimal);
+
+        // Every solution improves on the last, within its weight of optimal
+        assert!(solutions.windows(2).all(|pair| pair[1].0 < pair[0].0));
+        assert!(solutions.iter().all(|&(cost, weight)| cost as f64 <= optimal as f64 * weight));
+        assert_eq!(solutions.last().map(|&(cost, _)| cost), Some(optimal));
+    }
+
+    #[test]
+    fn rejects_weights_which_never_reach_one()
+    {
+        let graph = graph();
+        let landmarks = Landmarks::new(&graph, 2);
+        for &(initial_weight, step) in &[(3.0, 0.0), (3.0, -1.0), (3.0, f64::NAN), (f64::INFINITY, 0.5), (0.5, 0.5), (f64::NAN, 0.5)]
+        {
+            assert!(ara_star(&graph, &landmarks, "a", "e", initial_weight, step, None, &mut |_, _| ()).is_err());
+        }
+    }
+}
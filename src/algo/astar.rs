@@ -0,0 +1,254 @@
+use std::cmp::Reverse;
+
+use log::debug;
+use petgraph::EdgeType;
+use priority_queue::PriorityQueue;
+
+use super::{build_route, search_map, Heuristic, Network, PathResult, SearchMap};
+
+///
+/// A node taken from the queue during a search
+///
+/// - order: the # of nodes taken before it, from 0
+/// - node: the node taken
+/// - parent: the node it was reached from, or None for a source
+/// - g: its distance from the source, as a weight
+/// - frontier: the # of nodes left queued once it was taken
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expansion<'a>
+{
+    pub order: usize,
+    pub node: &'a str,
+    pub parent: Option<&'a str>,
+    pub g: u64,
+    pub frontier: usize,
+}
+
+///
+/// How A* chooses between queued nodes with equal f-values. The choice
+/// never changes the route's distance, but can greatly change the # of
+/// nodes considered, especially on grid-like Graphs with many equal routes
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TieBreak
+{
+    /// Prefer the node furthest from the start (and so nearest the end)
+    #[default]
+    HigherG,
+
+    /// Prefer the node with the smallest estimate to the end
+    LowerH,
+
+    /// Prefer the node queued earliest
+    Fifo,
+
+    /// Prefer the node queued latest
+    Lifo,
+}
+
+impl TieBreak
+{
+    /// Every tie-breaking strategy
+    pub const ALL: [TieBreak; 4] = [TieBreak::HigherG, TieBreak::LowerH, TieBreak::Fifo, TieBreak::Lifo];
+
+    ///
+    /// - return: the strategy's display name
+    ///
+    pub fn name(self) -> &'static str
+    {
+        match self
+        {
+            TieBreak::HigherG => "higher g",
+            TieBreak::LowerH => "lower h",
+            TieBreak::Fifo => "FIFO",
+            TieBreak::Lifo => "LIFO",
+        }
+    }
+
+    ///
+    /// Builds a node's priority in a max-priority queue, ordering first
+    /// by f-value and then by this strategy. Any remaining ties go to the
+    /// node queued earliest, so that searches are deterministic
+    ///
+    /// - g: the node's distance from the start
+    /// - h: the node's estimate to the end
+    /// - order: the # of nodes queued before this one
+    ///
+    pub(crate) fn priority(self, g: u64, h: u64, order: u64) -> (Reverse<u64>, u64, Reverse<u64>)
+    {
+        let preference = match self
+        {
+            TieBreak::HigherG => g,
+            TieBreak::LowerH => u64::MAX - h,
+            TieBreak::Fifo => u64::MAX - order,
+            TieBreak::Lifo => order,
+        };
+
+        (Reverse(g + h), preference, Reverse(order))
+    }
+}
+
+///
+/// Computes the shortest route between two nodes on a Graph
+/// Uses either A* or Djikstra's algorithm, depending on whether
+/// a heuristic is supplied
+///
+/// - graph: the Graph to search
+/// - heuristic: the A* heuristic, or None for Djikstra's algorithm
+/// - start: the start location on the Graph
+/// - end: the end location on the Graph
+/// - tie_break: how to choose between nodes with equal f-values
+///
+/// - return: the route (None if end cannot be reached), and the # of
+///   nodes considered
+///
+pub fn astar<'a, Ty: EdgeType>(graph: &Network<'a, Ty>, heuristic: Option<&dyn Heuristic>, start: &'a str, end: &'a str, tie_break: TieBreak) -> (Option<PathResult<'a>>, usize)
+{
+    astar_to_any(graph, heuristic, start, &[end], tie_break)
+}
+
+///
+/// Computes the shortest route from a node to the nearest of a set of
+/// goal nodes, stopping as soon as any goal is reached. With A*, each
+/// node's heuristic is its estimate to the closest goal
+///
+/// - graph: the Graph to search
+/// - heuristic: the A* heuristic, or None for Djikstra's algorithm
+/// - start: the start location on the Graph
+/// - goals: the acceptable end locations on the Graph
+/// - tie_break: how to choose between nodes with equal f-values
+///
+/// - return: the route (None if no goal can be reached), and the # of
+///   nodes considered. The goal reached is the route's last node
+///
+pub fn astar_to_any<'a, Ty: EdgeType>(graph: &Network<'a, Ty>, heuristic: Option<&dyn Heuristic>, start: &'a str, goals: &[&'a str], tie_break: TieBreak) -> (Option<PathResult<'a>>, usize)
+{
+    astar_multi(graph, heuristic, &[start], goals, tie_break)
+}
+
+///
+/// Computes the shortest route from any of a set of source nodes to any
+/// of a set of goal nodes. Every source is queued at distance 0, so the
+/// route found starts at whichever source is nearest its goal
+///
+/// - graph: the Graph to search
+/// - heuristic: the A* heuristic, or None for Djikstra's algorithm
+/// - starts: the acceptable start locations on the Graph
+/// - goals: the acceptable end locations on the Graph
+/// - tie_break: how to choose between nodes with equal f-values
+///
+/// - return: the route (None if no goal can be reached), and the # of
+///   nodes considered. The source and goal chosen are the route's first
+///   and last nodes
+///
+pub fn astar_multi<'a, Ty: EdgeType>(graph: &Network<'a, Ty>, heuristic: Option<&dyn Heuristic>, starts: &[&'a str], goals: &[&'a str], tie_break: TieBreak) -> (Option<PathResult<'a>>, usize)
+{
+    astar_observed(graph, heuristic, starts, goals, tie_break, |_| ())
+}
+
+///
+/// Computes the shortest route as astar_multi does, while reporting each
+/// node as it is considered, such as to show how far the search spread
+///
+/// - graph, heuristic, starts, goals, tie_break: as for astar_multi
+/// - on_expand: called with the Expansion of each node considered, in
+///   the order they are taken from the queue
+///
+/// - return: the route (None if no goal can be reached), and the # of
+///   nodes considered
+///
+pub fn astar_observed<'a, Ty: EdgeType, F: FnMut(Expansion<'a>)>(graph: &Network<'a, Ty>, heuristic: Option<&dyn Heuristic>, starts: &[&'a str], goals: &[&'a str], tie_break: TieBreak, mut on_expand: F) -> (Option<PathResult<'a>>, usize)
+{
+    // The estimate to the nearest goal, or 0 without a heuristic
+    let estimate = |node: &str| match heuristic
+    {
+        Some(heuristic) => goals.iter().map(|goal| heuristic.estimate(node, goal)).min().unwrap_or(0),
+        None => 0,
+    };
+
+    // Create a priority queue, which will hold all route information,
+    // and automatically supply the shortest distance route
+    // Push start nodes onto queue
+    let mut route_dists = PriorityQueue::new();
+    let mut queued = 0;
+    for &start in starts
+    {
+        route_dists.push(start, tie_break.priority(0, estimate(start), queued));
+        queued += 1;
+    }
+
+    //
+    // A HashMap for each node's distance from start on the Graph.
+    // The key is the node in question, while the value is the distance.
+    //
+    // Ensures quick retrieval of distances, and stores base distance while
+    // performing A* search (routes_dists will store base distance + heuristic
+    // in this case)
+    //
+    let mut dist: SearchMap<&str, u64> = search_map(graph.node_count());
+    for &start in starts { dist.insert(start, 0); }
+
+    // A marker for each node in the Graph, representing which adjacent
+    // node provides the path of least distance
+    let mut prev: SearchMap<&str, &str> = search_map(graph.node_count());
+
+    // Counter for total # of nodes considered
+    let mut node_counter = 0;
+
+    // Loop through all routes, while there any existing routes
+    while let Some(min_route) = route_dists.pop()
+    {
+        on_expand(Expansion
+        {
+            order: node_counter,
+            node: min_route.0,
+            parent: prev.get(min_route.0).cloned(),
+            g: dist[min_route.0],
+            frontier: route_dists.len(),
+        });
+        node_counter += 1;
+
+        // If min_route is a destination node, the route is complete
+        if goals.contains(&min_route.0)
+        {
+            // Sources are never relaxed, so the route's source is
+            // the first node with no previous node
+            let mut source = min_route.0;
+            while let Some(prv) = prev.get(source) { source = prv; }
+
+            return (Some(build_route(&prev, source, min_route.0, dist[min_route.0])), node_counter);
+        }
+
+        // For every frontier node for the min_route node
+        for edge in graph.edges(min_route.0)
+        {
+            // min_route.0 and edge.0 will be the same value
+
+            // Find the total weight distance between min_route node and its
+            // edge node.
+            let alt_route = dist[min_route.0] + edge.2;
+
+            // If that value does not yet exist in dist, or if dist is greater,
+            // update dist and prev, and push alt_route into queue
+            if !dist.contains_key(edge.1) || alt_route < dist[edge.1]
+            {
+                // Set dist of edge node to alt_route value
+                dist.insert(edge.1, alt_route);
+
+                // Set prev of edge node to min_route - it is the new
+                // previous node to the edge node
+                prev.insert(edge.1, min_route.0);
+                debug!("Relaxed {} through {}, to a weight of {}", edge.1, min_route.0, alt_route);
+
+                // Update edge node on routes priority queue to alt_route
+                // Include heuristic if a_star
+                route_dists.push(edge.1, tie_break.priority(alt_route, estimate(edge.1), queued));
+                queued += 1;
+            }
+        }
+    }
+
+    // If no other routes exist, destination could not be reached
+    (None, node_counter)
+}
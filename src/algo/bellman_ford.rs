@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+///
+/// Finds a cycle of negative total weight using the Bellman-Ford algorithm.
+/// Dijkstra's algorithm and A* assume no such cycle exists; with one, a
+/// route could be made shorter forever by going around it again.
+///
+/// Every node starts at distance 0, as if from a virtual source linked to
+/// all of them, so cycles anywhere in the Graph are found. If distances
+/// still shorten after one round per node, the node last shortened leads
+/// back into a negative cycle.
+///
+/// - arcs: every one-way edge, as (from, to, weight). An undirected edge
+///   is two arcs, so any negative undirected edge is itself a cycle
+///
+/// - return: the nodes around a negative cycle, beginning and ending at
+///   the same node, or None if no negative cycle exists
+///
+pub fn find_negative_cycle<'a>(arcs: &[(&'a str, &'a str, i64)]) -> Option<Vec<&'a str>>
+{
+    let mut dist: HashMap<&'a str, i64> = HashMap::new();
+    let mut prev: HashMap<&'a str, &'a str> = HashMap::new();
+    for &(from, to, _) in arcs
+    {
+        dist.insert(from, 0);
+        dist.insert(to, 0);
+    }
+
+    let mut last_relaxed = None;
+    for _ in 0..dist.len()
+    {
+        last_relaxed = None;
+        for &(from, to, weight) in arcs
+        {
+            let alt_route = dist[from] + weight;
+            if alt_route < dist[to]
+            {
+                dist.insert(to, alt_route);
+                prev.insert(to, from);
+                last_relaxed = Some(to);
+            }
+        }
+
+        // Distances have settled, so no negative cycle exists
+        last_relaxed?;
+    }
+
+    // Step back once per node, which is certain to land on the cycle
+    let mut node = last_relaxed?;
+    for _ in 0..dist.len() { node = prev[node]; }
+
+    let mut cycle = vec![node];
+    let mut current = prev[node];
+    while current != node
+    {
+        cycle.push(current);
+        current = prev[current];
+    }
+    cycle.push(node);
+    cycle.reverse();
+
+    Some(cycle)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::find_negative_cycle;
+
+    ///
+    /// Checks that a cycle is closed, follows the arcs, and weighs less
+    /// than nothing in total
+    ///
+    fn assert_negative_cycle(arcs: &[(&str, &str, i64)], cycle: &[&str])
+    {
+        assert!(cycle.len() > 2);
+        assert_eq!(cycle.first(), cycle.last());
+        let weight = cycle.windows(2)
+            .map(|leg| arcs.iter().filter(|&&(from, to, _)| (from, to) == (leg[0], leg[1])).map(|&(_, _, weight)| weight).min().unwrap())
+            .sum::<i64>();
+        assert!(weight < 0, "{:?} weighs {}", cycle, weight);
+    }
+
+    #[test]
+    fn finds_a_negative_cycle()
+    {
+        let arcs = [("d", "a", 4), ("a", "b", 2), ("b", "c", -5), ("c", "a", 1), ("c", "e", 3), ("e", "d", 2)];
+        let cycle = find_negative_cycle(&arcs).unwrap();
+        assert_negative_cycle(&arcs, &cycle);
+
+        let mut around = cycle[..cycle.len() - 1].to_vec();
+        around.sort_unstable();
+        assert_eq!(around, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn a_negative_undirected_edge_is_a_cycle()
+    {
+        let arcs = [("x", "y", 7), ("y", "x", 7), ("y", "z", -3), ("z", "y", -3)];
+        let cycle = find_negative_cycle(&arcs).unwrap();
+        assert_negative_cycle(&arcs, &cycle);
+        assert!(cycle == ["y", "z", "y"] || cycle == ["z", "y", "z"]);
+    }
+
+    #[test]
+    fn finds_no_cycle_where_none_is_negative()
+    {
+        // A negative arc off any cycle, and a cycle weighing exactly 0
+        assert_eq!(find_negative_cycle(&[("a", "b", -3), ("b", "c", 2), ("a", "c", 1)]), None);
+        assert_eq!(find_negative_cycle(&[("a", "b", -3), ("b", "c", 2), ("c", "a", 1)]), None);
+        assert_eq!(find_negative_cycle(&[("a", "b", 5), ("b", "a", 5)]), None);
+        assert_eq!(find_negative_cycle(&[]), None);
+    }
+}
@@ -0,0 +1,291 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use super::context::{Counters, SearchContext, UNREACHED};
+use super::intern::SearchGraph;
+use super::{Heuristic, PathResult};
+
+// The halves of a search: forward from the start, backward from the end
+const FORWARD: usize = 0;
+const BACKWARD: usize = 1;
+
+///
+/// The buffers each half of a bidirectional search keeps its state in,
+/// owned by a SearchContext across queries as its own buffers are
+///
+/// - dist: each node's distance from the half's source, or u64::MAX if
+///   unreached, read by the other half as it runs
+/// - prev: each node's neighbour toward the half's source, or UNREACHED
+/// - expanded: whether each node has been expanded by the half
+/// - touched: every node the half has reached in the current query
+///
+#[derive(Default)]
+pub(crate) struct Halves
+{
+    dist: [Vec<AtomicU64>; 2],
+    prev: [Vec<u32>; 2],
+    expanded: [Vec<bool>; 2],
+    touched: [Vec<u32>; 2],
+}
+
+impl Halves
+{
+    ///
+    /// Clears both halves for a new query, sizing them for the Graph's
+    /// nodes if they were sized for another Graph
+    ///
+    /// - nodes: the # of nodes on the Graph about to be searched
+    ///
+    fn reset(&mut self, nodes: usize)
+    {
+        for side in [FORWARD, BACKWARD]
+        {
+            if self.dist[side].len() != nodes
+            {
+                self.dist[side] = (0..nodes).map(|_| AtomicU64::new(u64::MAX)).collect();
+                self.prev[side] = vec![UNREACHED; nodes];
+                self.expanded[side] = vec![false; nodes];
+                self.touched[side].clear();
+            }
+            for id in self.touched[side].drain(..)
+            {
+                self.dist[side][id as usize].store(u64::MAX, Ordering::Relaxed);
+                self.prev[side][id as usize] = UNREACHED;
+                self.expanded[side][id as usize] = false;
+            }
+        }
+    }
+}
+
+///
+/// What the halves of a search share as they run
+///
+/// - best: the shortest route found through a node both halves have
+///   reached, as (cost, node), or (u64::MAX, UNREACHED)
+/// - bound: best's cost, read without locking
+/// - tops: the key each half last took from its queue, which no key it
+///   takes later is below
+/// - done: set once either half knows best to be the shortest route
+///
+struct Meeting
+{
+    best: Mutex<(u64, u32)>,
+    bound: AtomicU64,
+    tops: [AtomicU64; 2],
+    done: AtomicBool,
+}
+
+impl Meeting
+{
+    ///
+    /// Records a route through a node both halves have reached, if it is
+    /// shorter than the best found
+    ///
+    fn offer(&self, cost: u64, node: u32)
+    {
+        if cost >= self.bound.load(Ordering::SeqCst) { return; }
+
+        let mut best = self.best.lock().unwrap();
+        if cost < best.0
+        {
+            *best = (cost, node);
+            self.bound.store(cost, Ordering::SeqCst);
+        }
+    }
+}
+
+///
+/// Computes the shortest route by searching forward from the start and
+/// backward from the end at once, each half on its own thread, so that a
+/// single query on a large Graph finishes sooner than a search from one
+/// end would. Each time a half relaxes an edge, it reads the other half's
+/// distance to the node reached, and records the route through it if it
+/// is the shortest yet found.
+///
+/// Without a heuristic, as bidirectional Djikstra, a half stops once the
+/// keys last taken by both halves sum to no less than the best route's
+/// cost. With one, as symmetric bidirectional A*, a half stops once the
+/// f-value it takes is no less than the best route's cost. The forward
+/// half estimates toward the end, reading the Graph's precomputed
+/// estimates when it has them, and the backward half estimates from the
+/// start. Either half stopping stops the other
+///
+/// - graph: the Graph to search, which must hold the edges reaching each
+///   location for the backward half to follow
+/// - heuristic: the A* heuristic, or None for Djikstra's algorithm
+/// - start: the start location on the Graph
+/// - end: the end location on the Graph
+/// - context: the buffers to search in. Its Counters are left counting
+///   what both halves did together, with the peak frontier being the sum
+///   of each half's
+///
+/// - return: the route (None if end cannot be reached) and the # of
+///   nodes considered by both halves, or None if the Graph cannot be
+///   searched backward
+///
+pub fn bidirectional<'a, G: SearchGraph<'a> + Sync>(graph: &G, heuristic: Option<&dyn Heuristic>, start: &str, end: &str, context: &mut SearchContext) -> Option<(Option<PathResult<'a>>, usize)>
+{
+    let (start, end) = match (graph.id(start), graph.id(end))
+    {
+        (Some(start), Some(end)) => (start, end),
+        _ => return Some((None, 0)),
+    };
+    // The backward half follows the edges reaching each node
+    let _ = graph.edges_into(end)?;
+
+    context.counters = Counters::default();
+    if start == end
+    {
+        context.counters = Counters { pushes: 1, pops: 1, peak_frontier: 1, ..Counters::default() };
+        return Some((Some(PathResult { nodes: vec![graph.name(start)], cost: 0 }), 1));
+    }
+
+    let halves = &mut context.halves;
+    halves.reset(graph.len());
+    for (side, source) in [(FORWARD, start), (BACKWARD, end)]
+    {
+        halves.dist[side][source as usize].store(0, Ordering::SeqCst);
+        halves.touched[side].push(source);
+    }
+
+    let meeting = Meeting
+    {
+        best: Mutex::new((u64::MAX, UNREACHED)),
+        bound: AtomicU64::new(u64::MAX),
+        tops: [AtomicU64::new(0), AtomicU64::new(0)],
+        done: AtomicBool::new(false),
+    };
+    let (start_name, goal) = (graph.name(start), graph.name(end));
+    let table = heuristic.and_then(|_| graph.estimates_to(end));
+    let forward = |node: u32| match (table, heuristic)
+    {
+        (Some(table), _) => table.get(node),
+        (None, Some(heuristic)) => heuristic.estimate(graph.name(node), goal),
+        (None, None) => 0,
+    };
+    let backward = |node: u32| heuristic.map_or(0, |heuristic| heuristic.estimate(start_name, graph.name(node)));
+
+    let Halves { dist, prev, expanded, touched } = halves;
+    let [forward_prev, backward_prev] = prev;
+    let [forward_expanded, backward_expanded] = expanded;
+    let [forward_touched, backward_touched] = touched;
+    let dist = &*dist;
+    let meeting = &meeting;
+    let sum_tops = heuristic.is_none();
+
+    let (ahead, behind) = std::thread::scope(|scope|
+    {
+        let behind = scope.spawn(move || search_half(graph, BACKWARD, end, &backward, dist, backward_prev, backward_expanded, backward_touched, meeting, sum_tops));
+        let ahead = search_half(graph, FORWARD, start, &forward, dist, forward_prev, forward_expanded, forward_touched, meeting, sum_tops);
+        (ahead, behind.join().expect("The backward half of a search panicked"))
+    });
+
+    let counters = &mut context.counters;
+    for half in [ahead.1, behind.1]
+    {
+        counters.pushes += half.pushes;
+        counters.pops += half.pops;
+        counters.relaxations += half.relaxations;
+        counters.stale_pops += half.stale_pops;
+        counters.duplicate_expansions += half.duplicate_expansions;
+        counters.peak_frontier += half.peak_frontier;
+    }
+    let node_counter = ahead.0 + behind.0;
+
+    let (cost, meet) = *meeting.best.lock().unwrap();
+    if meet == UNREACHED { return Some((None, node_counter)); }
+
+    // Join the start's half of the route to the end's half at the node
+    // they met at
+    let prev = &context.halves.prev;
+    let mut nodes = vec![graph.name(meet)];
+    let mut current = meet;
+    while current != start
+    {
+        current = prev[FORWARD][current as usize];
+        nodes.push(graph.name(current));
+    }
+    nodes.reverse();
+    current = meet;
+    while current != end
+    {
+        current = prev[BACKWARD][current as usize];
+        nodes.push(graph.name(current));
+    }
+
+    Some((Some(PathResult { nodes, cost }), node_counter))
+}
+
+///
+/// Runs one half of a bidirectional search until either half knows the
+/// best route found to be the shortest, or its queue empties
+///
+/// - side: FORWARD, following the edges leaving each node, or BACKWARD,
+///   following the edges reaching each node
+/// - source: the node the half searches from
+/// - estimate: the half's estimate from each node to the other's source
+/// - dist: both halves' distances
+/// - prev, expanded, touched: the half's own buffers
+/// - meeting: what the halves share
+/// - sum_tops: whether a half may stop once both halves' keys sum to the
+///   best route's cost, as without a heuristic
+///
+/// - return: the # of nodes the half considered, and its Counters
+///
+#[allow(clippy::too_many_arguments)]
+fn search_half<'a, G: SearchGraph<'a>, E: Fn(u32) -> u64>(graph: &G, side: usize, source: u32, estimate: &E, dist: &[Vec<AtomicU64>; 2], prev: &mut [u32], expanded: &mut [bool], touched: &mut Vec<u32>, meeting: &Meeting, sum_tops: bool) -> (usize, Counters)
+{
+    let other = 1 - side;
+    let mut counters = Counters { pushes: 1, peak_frontier: 1, ..Counters::default() };
+    let mut node_counter = 0;
+
+    let mut queue = BinaryHeap::new();
+    queue.push(Reverse((estimate(source), 0, source)));
+
+    while let Some(Reverse((key, g, node))) = queue.pop()
+    {
+        counters.pops += 1;
+        if g != dist[side][node as usize].load(Ordering::Relaxed) { counters.stale_pops += 1; continue; }
+
+        meeting.tops[side].store(key, Ordering::SeqCst);
+        if meeting.done.load(Ordering::SeqCst) { break; }
+
+        let below = if sum_tops { meeting.tops[other].load(Ordering::SeqCst) } else { 0 };
+        if key.saturating_add(below) >= meeting.bound.load(Ordering::SeqCst) { break; }
+
+        if std::mem::replace(&mut expanded[node as usize], true) { counters.duplicate_expansions += 1; }
+        node_counter += 1;
+
+        let edges = match side
+        {
+            FORWARD => graph.edges(node),
+            _ => graph.edges_into(node).expect("The backward half follows edges reaching each node"),
+        };
+        for (next, weight) in edges
+        {
+            let alt_route = g + weight;
+            if alt_route < dist[side][next as usize].load(Ordering::Relaxed)
+            {
+                if prev[next as usize] == UNREACHED && next != source { touched.push(next); }
+                dist[side][next as usize].store(alt_route, Ordering::SeqCst);
+                prev[next as usize] = node;
+
+                queue.push(Reverse((alt_route + estimate(next), alt_route, next)));
+                counters.pushes += 1;
+                counters.relaxations += 1;
+            }
+
+            // Each half writes its distance before reading the other's, so
+            // of two halves reaching a node at once, at least one sees both
+            let beyond = dist[other][next as usize].load(Ordering::SeqCst);
+            if beyond != u64::MAX { meeting.offer(dist[side][next as usize].load(Ordering::Relaxed) + beyond, next); }
+        }
+        counters.peak_frontier = counters.peak_frontier.max(queue.len());
+    }
+
+    // The queue emptied, or the best route is known to be the shortest
+    meeting.done.store(true, Ordering::SeqCst);
+    (node_counter, counters)
+}
@@ -0,0 +1,358 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use super::{PathResult, RouteGraph};
+use crate::load::sidecar::{Artifact, Reader};
+
+/// The most nodes a single witness search may settle before giving up
+/// and conservatively adding the shortcut it was trying to avoid
+const WITNESS_SETTLE_LIMIT: usize = 500;
+
+///
+/// An edge in the hierarchy, leading to a node of higher rank. Shortcut
+/// edges record the contracted node they bypass, so that routes through
+/// them can be unpacked back into the original edges
+///
+#[derive(Clone, Copy)]
+struct Arc
+{
+    to: usize,
+    weight: u64,
+    middle: Option<usize>,
+}
+
+///
+/// A contraction hierarchy over a route Graph. Preprocessing contracts the
+/// nodes one at a time, from least to most important, adding shortcut edges
+/// wherever a contraction would otherwise lengthen a shortest path. Queries
+/// are then bidirectional Dijkstra searches which only ever move upwards in
+/// importance, settling a tiny fraction of the nodes a flat search would.
+///
+/// Nodes are indexed internally, so queries never hash location names
+/// beyond resolving the start and end.
+///
+pub struct ContractionHierarchy<'a>
+{
+    names: Vec<&'a str>,
+    index: HashMap<&'a str, usize>,
+
+    // Every node's upward edges, to nodes contracted after it
+    up: Vec<Vec<Arc>>,
+}
+
+impl<'a> ContractionHierarchy<'a>
+{
+    ///
+    /// Preprocesses the Graph into a contraction hierarchy
+    ///
+    /// - graph: the Graph to preprocess
+    ///
+    pub fn new(graph: &RouteGraph<'a>) -> Self
+    {
+        let names = graph.nodes().collect::<Vec<&'a str>>();
+        let index = names.iter().enumerate()
+            .map(|(i, name)| (*name, i))
+            .collect::<HashMap<&'a str, usize>>();
+
+        // The remaining (uncontracted) Graph, with the contracted node each
+        // edge bypasses, if it is a shortcut
+        let mut adj: Vec<HashMap<usize, (u64, Option<usize>)>> = vec![HashMap::new(); names.len()];
+        for (from, to, weight) in graph.all_edges()
+        {
+            let (from, to) = (index[from], index[to]);
+            if from == to { continue; }
+            adj[from].insert(to, (*weight, None));
+            adj[to].insert(from, (*weight, None));
+        }
+
+        let mut up = vec![Vec::new(); names.len()];
+        let mut contracted = vec![false; names.len()];
+        let mut contracted_neighbors = vec![0i64; names.len()];
+
+        // Order nodes by their edge difference: the # of shortcuts their
+        // contraction would add, less the # of edges it would remove
+        let mut order = BinaryHeap::new();
+        for node in 0..names.len()
+        {
+            order.push(Reverse((priority(&adj, &contracted_neighbors, node), node)));
+        }
+
+        while let Some(Reverse((old_priority, node))) = order.pop()
+        {
+            if contracted[node] { continue; }
+
+            // Priorities go stale as neighbors are contracted. Lazily
+            // recompute, and defer the node if it is no longer the minimum
+            let new_priority = priority(&adj, &contracted_neighbors, node);
+            if new_priority > old_priority
+            {
+                if let Some(Reverse((next_priority, _))) = order.peek()
+                {
+                    if new_priority > *next_priority
+                    {
+                        order.push(Reverse((new_priority, node)));
+                        continue;
+                    }
+                }
+            }
+
+            // Add every required shortcut between the node's neighbors
+            for (from, to, weight) in shortcuts(&adj, node)
+            {
+                let better = adj[from].get(&to).is_none_or(|&(existing, _)| weight < existing);
+                if better
+                {
+                    adj[from].insert(to, (weight, Some(node)));
+                    adj[to].insert(from, (weight, Some(node)));
+                }
+            }
+
+            // Every edge remaining on the node leads upwards
+            let neighbors = adj[node].drain().collect::<Vec<(usize, (u64, Option<usize>))>>();
+            for (neighbor, (weight, middle)) in neighbors
+            {
+                adj[neighbor].remove(&node);
+                contracted_neighbors[neighbor] += 1;
+                up[node].push(Arc { to: neighbor, weight, middle });
+            }
+
+            contracted[node] = true;
+        }
+
+        ContractionHierarchy { names, index, up }
+    }
+
+    ///
+    /// Computes the shortest route between two nodes
+    ///
+    /// - start: the start location
+    /// - end: the end location
+    ///
+    /// - return: the route, or None if either location does not exist
+    ///   or end cannot be reached
+    ///
+    pub fn route(&self, start: &str, end: &str) -> Option<PathResult<'a>>
+    {
+        let (start, end) = (*self.index.get(start)?, *self.index.get(end)?);
+
+        // Forward and backward searches, each with its own distances
+        // and predecessors
+        let mut dist = [HashMap::new(), HashMap::new()];
+        let mut prev: [HashMap<usize, usize>; 2] = [HashMap::new(), HashMap::new()];
+        let mut queues = [BinaryHeap::new(), BinaryHeap::new()];
+        for (side, source) in [start, end].iter().enumerate()
+        {
+            dist[side].insert(*source, 0);
+            queues[side].push(Reverse((0, *source)));
+        }
+
+        // The shortest distance found so far, and the node it meets at
+        let mut best: Option<(u64, usize)> = None;
+
+        loop
+        {
+            // Advance whichever search has the smaller frontier distance
+            let side = match (queues[0].peek(), queues[1].peek())
+            {
+                (None, None) => break,
+                (Some(_), None) => 0,
+                (None, Some(_)) => 1,
+                (Some(Reverse((a, _))), Some(Reverse((b, _)))) => if a <= b { 0 } else { 1 },
+            };
+            let Reverse((node_dist, node)) = queues[side].pop().unwrap();
+
+            // Once both frontiers are past the best meeting, nothing better remains
+            if let Some((best_dist, _)) = best
+            {
+                if node_dist >= best_dist { queues[side].clear(); continue; }
+            }
+            if node_dist > dist[side][&node] { continue; }
+
+            if let Some(other) = dist[1 - side].get(&node)
+            {
+                if best.is_none_or(|(best_dist, _)| node_dist + other < best_dist)
+                {
+                    best = Some((node_dist + other, node));
+                }
+            }
+
+            for arc in &self.up[node]
+            {
+                let alt_route = node_dist + arc.weight;
+                if dist[side].get(&arc.to).is_none_or(|d| alt_route < *d)
+                {
+                    dist[side].insert(arc.to, alt_route);
+                    prev[side].insert(arc.to, node);
+                    queues[side].push(Reverse((alt_route, arc.to)));
+                }
+            }
+        }
+
+        let (cost, meet) = best?;
+
+        // Stitch the two halves of the route together at the meeting node
+        let mut hierarchy_route = vec![meet];
+        let mut current = meet;
+        while let Some(&node) = prev[0].get(&current) { hierarchy_route.push(node); current = node; }
+        hierarchy_route.reverse();
+        current = meet;
+        while let Some(&node) = prev[1].get(&current) { hierarchy_route.push(node); current = node; }
+
+        // Expand every shortcut back into the original edges
+        let mut nodes = vec![self.names[start]];
+        for pair in hierarchy_route.windows(2) { self.unpack(pair[0], pair[1], &mut nodes); }
+
+        Some(PathResult { nodes, cost })
+    }
+
+    ///
+    /// Appends the original nodes along the hierarchy edge between
+    /// from and to (excluding from itself) onto nodes
+    ///
+    fn unpack(&self, from: usize, to: usize, nodes: &mut Vec<&'a str>)
+    {
+        // Hierarchy edges are stored on their lower-ranked end
+        let arc = self.up[from].iter().find(|arc| arc.to == to)
+            .or_else(|| self.up[to].iter().find(|arc| arc.to == from))
+            .unwrap();
+
+        match arc.middle
+        {
+            Some(middle) =>
+            {
+                self.unpack(from, middle, nodes);
+                self.unpack(middle, to, nodes);
+            },
+            None => nodes.push(self.names[to]),
+        }
+    }
+}
+
+impl<'a> Artifact<'a> for ContractionHierarchy<'a>
+{
+    const KIND: &'static [u8; 8] = b"CONTRACT";
+    const EXTENSION: &'static str = "ch";
+
+    // Each node's # of upward edges, then each edge's node, weight, and
+    // the node it bypasses, or u32::MAX if it is not a shortcut
+    fn to_bytes(&self, _graph: &RouteGraph<'a>) -> Vec<u8>
+    {
+        let mut bytes = Vec::new();
+        for arcs in &self.up
+        {
+            bytes.extend_from_slice(&(arcs.len() as u32).to_le_bytes());
+            for arc in arcs
+            {
+                bytes.extend_from_slice(&(arc.to as u32).to_le_bytes());
+                bytes.extend_from_slice(&arc.weight.to_le_bytes());
+                bytes.extend_from_slice(&arc.middle.map_or(u32::MAX, |middle| middle as u32).to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    fn from_bytes(graph: &RouteGraph<'a>, bytes: &[u8]) -> Option<Self>
+    {
+        // Nodes are indexed in the Graph's order, as new indexes them
+        let names = graph.nodes().collect::<Vec<&'a str>>();
+        let index = names.iter().enumerate()
+            .map(|(i, name)| (*name, i))
+            .collect::<HashMap<&'a str, usize>>();
+
+        let mut reader = Reader::new(bytes);
+        let mut up = Vec::with_capacity(names.len());
+        for _ in 0..names.len()
+        {
+            let count = reader.u32()? as usize;
+            let mut arcs = Vec::with_capacity(count);
+            for _ in 0..count
+            {
+                let to = reader.u32()? as usize;
+                let weight = reader.u64()?;
+                let middle = Some(reader.u32()?).filter(|middle| *middle != u32::MAX).map(|middle| middle as usize);
+                if to >= names.len() || middle.is_some_and(|middle| middle >= names.len()) { return None; }
+                arcs.push(Arc { to, weight, middle });
+            }
+            up.push(arcs);
+        }
+
+        if !reader.is_done() { return None; }
+        Some(ContractionHierarchy { names, index, up })
+    }
+}
+
+///
+/// Computes a node's contraction priority. Lower is contracted sooner
+///
+fn priority(adj: &[HashMap<usize, (u64, Option<usize>)>], contracted_neighbors: &[i64], node: usize) -> i64
+{
+    shortcuts(adj, node).len() as i64 - adj[node].len() as i64 + contracted_neighbors[node]
+}
+
+///
+/// Finds the shortcuts that contracting node would require: for every pair
+/// of its neighbors, an edge through node, unless a witness path of equal
+/// or shorter length avoids node entirely
+///
+/// - return: each shortcut as (from, to, weight)
+///
+fn shortcuts(adj: &[HashMap<usize, (u64, Option<usize>)>], node: usize) -> Vec<(usize, usize, u64)>
+{
+    let mut shortcuts = Vec::new();
+
+    for (&from, &(in_weight, _)) in &adj[node]
+    {
+        // Only the longest path through node needs witnessing
+        let limit = adj[node].iter()
+            .filter(|(&to, _)| to > from)
+            .map(|(_, &(out_weight, _))| in_weight + out_weight)
+            .max();
+        let limit = match limit { Some(limit) => limit, None => continue };
+
+        let witness = witness_search(adj, from, node, limit);
+        for (&to, &(out_weight, _)) in &adj[node]
+        {
+            if to <= from { continue; }
+
+            let via = in_weight + out_weight;
+            if witness.get(&to).is_none_or(|&d| d > via) { shortcuts.push((from, to, via)); }
+        }
+    }
+
+    shortcuts
+}
+
+///
+/// A Dijkstra search from source which avoids the node being contracted,
+/// and stops at the distance limit or the settle limit
+///
+fn witness_search(adj: &[HashMap<usize, (u64, Option<usize>)>], source: usize, avoid: usize, limit: u64) -> HashMap<usize, u64>
+{
+    let mut dist = HashMap::new();
+    dist.insert(source, 0);
+
+    let mut queue = BinaryHeap::new();
+    queue.push(Reverse((0, source)));
+
+    let mut settled = 0;
+    while let Some(Reverse((node_dist, node))) = queue.pop()
+    {
+        if node_dist > dist[&node] { continue; }
+        if node_dist > limit || settled >= WITNESS_SETTLE_LIMIT { break; }
+        settled += 1;
+
+        for (&next, &(weight, _)) in &adj[node]
+        {
+            if next == avoid { continue; }
+
+            let alt_route = node_dist + weight;
+            if dist.get(&next).is_none_or(|d| alt_route < *d)
+            {
+                dist.insert(next, alt_route);
+                queue.push(Reverse((alt_route, next)));
+            }
+        }
+    }
+
+    dist
+}
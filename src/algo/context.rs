@@ -0,0 +1,176 @@
+use std::cmp::Reverse;
+
+use serde::Serialize;
+
+use super::bidirectional::Halves;
+use super::frontier::{Frontier, Queue};
+
+/// Marks a node with no distance, previous node or fringe version yet
+pub(crate) const UNREACHED: u32 = u32::MAX;
+
+/// A queued node's priority, as built by TieBreak::priority
+type Priority = (Reverse<u64>, u64, Reverse<u64>);
+
+///
+/// What a search over an InternedGraph did, beyond the nodes it
+/// considered, for comparing how frontiers and heuristics perform
+///
+/// - pushes: the # of entries queued, including re-queued nodes
+/// - pops: the # of entries taken from the queue, including stale ones
+/// - relaxations: the # of times a shorter route to a node was found
+/// - stale_pops: the # of entries taken which a node's re-queuing had
+///   made stale, and so were discarded
+/// - duplicate_expansions: the # of times a node already expanded was
+///   expanded again, as inconsistent heuristics may cause
+/// - peak_frontier: the most nodes queued at once
+///
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Counters
+{
+    pub pushes: usize,
+    pub pops: usize,
+    pub relaxations: usize,
+    pub stale_pops: usize,
+    pub duplicate_expansions: usize,
+    pub peak_frontier: usize,
+}
+
+///
+/// The buffers searches over an InternedGraph keep their state in, owned
+/// across queries so that each query clears them instead of allocating
+/// them afresh. Only the entries a query touched are cleared, so a short
+/// query on a large Graph stays cheap
+///
+/// - frontier: the queue A* and Djikstra's algorithm keep their frontier in
+/// - dist: each node's distance from the start, or u64::MAX if unreached
+/// - prev: each node's previous node on its route, or UNREACHED
+/// - versions: each node's fringe version, or UNREACHED
+/// - expanded: whether each node has been expanded
+/// - touched: every node the current query has reached
+/// - queue: the queue, with the # of nodes it was sized for
+/// - counters: what the current query has done
+/// - halves: the buffers each half of a bidirectional search keeps its
+///   state in
+///
+#[derive(Default)]
+pub struct SearchContext
+{
+    frontier: Frontier,
+    pub(crate) dist: Vec<u64>,
+    pub(crate) prev: Vec<u32>,
+    pub(crate) versions: Vec<u32>,
+    pub(crate) expanded: Vec<bool>,
+    touched: Vec<u32>,
+    queue: Option<(Queue<Priority>, usize)>,
+    pub(crate) counters: Counters,
+    pub(crate) halves: Halves,
+}
+
+impl SearchContext
+{
+    ///
+    /// Creates an empty context. Buffers are allocated by the first query
+    ///
+    /// - frontier: the queue A* and Djikstra's algorithm keep their
+    ///   frontier in
+    ///
+    pub fn new(frontier: Frontier) -> Self
+    {
+        SearchContext { frontier, ..SearchContext::default() }
+    }
+
+    ///
+    /// - return: the queue A* and Djikstra's algorithm keep their frontier in
+    ///
+    pub fn frontier(&self) -> Frontier
+    {
+        self.frontier
+    }
+
+    ///
+    /// - return: what the last query searched in this context did
+    ///
+    pub fn counters(&self) -> Counters
+    {
+        self.counters
+    }
+
+    ///
+    /// Clears every buffer and counter for a new query, sizing them for the Graph's
+    /// nodes if they were sized for another Graph
+    ///
+    /// - nodes: the # of nodes on the Graph about to be searched
+    ///
+    pub(crate) fn reset(&mut self, nodes: usize)
+    {
+        if self.dist.len() != nodes
+        {
+            self.dist = vec![u64::MAX; nodes];
+            self.prev = vec![UNREACHED; nodes];
+            self.versions = vec![UNREACHED; nodes];
+            self.expanded = vec![false; nodes];
+            self.touched.clear();
+        }
+        for id in self.touched.drain(..)
+        {
+            self.dist[id as usize] = u64::MAX;
+            self.prev[id as usize] = UNREACHED;
+            self.versions[id as usize] = UNREACHED;
+            self.expanded[id as usize] = false;
+        }
+        self.counters = Counters::default();
+    }
+
+    ///
+    /// Records that a node is being expanded, counting it as a duplicate
+    /// expansion if it has been before
+    ///
+    pub(crate) fn expand(&mut self, node: u32)
+    {
+        if std::mem::replace(&mut self.expanded[node as usize], true) { self.counters.duplicate_expansions += 1; }
+    }
+
+    ///
+    /// Records a node's new distance and previous node
+    ///
+    /// - node: the node reached
+    /// - dist: its distance from the start
+    /// - prev: the node it was reached from, or UNREACHED for the start
+    ///
+    pub(crate) fn reach(&mut self, node: u32, dist: u64, prev: u32)
+    {
+        if self.dist[node as usize] == u64::MAX { self.touched.push(node); }
+        self.dist[node as usize] = dist;
+        self.prev[node as usize] = prev;
+    }
+
+    ///
+    /// Takes the cleared queue for a new query, to be handed back with
+    /// restore_queue once the query is done
+    ///
+    /// - nodes: the # of nodes on the Graph about to be searched
+    ///
+    pub(crate) fn take_queue(&mut self, nodes: usize) -> Queue<Priority>
+    {
+        match self.queue.take()
+        {
+            Some((mut queue, sized)) if sized == nodes && queue.is(self.frontier) =>
+            {
+                queue.clear();
+                queue
+            },
+            _ => self.frontier.queue(nodes),
+        }
+    }
+
+    ///
+    /// Hands back the queue taken by take_queue, to be reused by the next
+    /// query
+    ///
+    /// - nodes: the # of nodes the queue was sized for
+    ///
+    pub(crate) fn restore_queue(&mut self, queue: Queue<Priority>, nodes: usize)
+    {
+        self.queue = Some((queue, nodes));
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::{HashMap, VecDeque};
+
+use petgraph::graphmap::DiGraphMap;
+use petgraph::{Direction, EdgeType};
+
+use super::astar::{self, TieBreak};
+use super::{build_route, Network, PathResult};
+
+///
+/// A directed route Graph, where each edge may only be travelled
+/// from its first node to its second
+///
+pub type DirectedRouteGraph<'a> = DiGraphMap<&'a str, u64>;
+
+///
+/// Orders the nodes of a directed Graph so that every edge leads from an
+/// earlier node to a later one, using Kahn's algorithm
+///
+/// - graph: the Graph to order. Every edge of an undirected Graph leads
+///   both ways, so is a cycle
+///
+/// - return: the topological order, or None if the Graph has a cycle
+///
+pub fn topological_order<'a, Ty: EdgeType>(graph: &Network<'a, Ty>) -> Option<Vec<&'a str>>
+{
+    let mut in_degree: HashMap<&'a str, usize> = graph.nodes()
+        .map(|node| (node, graph.neighbors_directed(node, Direction::Incoming).count()))
+        .collect();
+
+    let mut ready = graph.nodes().filter(|node| in_degree[node] == 0).collect::<VecDeque<&'a str>>();
+    let mut order = Vec::with_capacity(graph.node_count());
+    while let Some(node) = ready.pop_front()
+    {
+        order.push(node);
+        for next in graph.neighbors(node)
+        {
+            let degree = in_degree.get_mut(next).unwrap();
+            *degree -= 1;
+            if *degree == 0 { ready.push_back(next); }
+        }
+    }
+
+    // Nodes on a cycle never reach an in-degree of 0
+    if order.len() == graph.node_count() { Some(order) } else { None }
+}
+
+///
+/// Computes the shortest or longest route between two nodes of a directed
+/// acyclic Graph in linear time. Each node's edges are relaxed once, in
+/// topological order, so every node's distance is final before it is used.
+/// Unlike on Graphs with cycles, the longest route is as easy as the shortest
+///
+/// - graph: the Graph to search
+/// - start: the start location on the Graph
+/// - end: the end location on the Graph
+/// - longest: whether to find the longest route rather than the shortest
+///
+/// - return: Either an Ok Result with the route (None if end cannot be
+///   reached), or an Err if the Graph has a cycle
+///
+pub fn dag_route<'a, Ty: EdgeType>(graph: &Network<'a, Ty>, start: &'a str, end: &'a str, longest: bool) -> Result<Option<PathResult<'a>>, String>
+{
+    let order = topological_order(graph).ok_or_else(|| String::from("Cannot route: the Graph has a cycle."))?;
+    Ok(route_in_order(graph, &order, start, end, longest).0)
+}
+
+///
+/// Computes the shortest route between two nodes with dag_route if the
+/// Graph is acyclic, or else with Djikstra's algorithm, so that it can be
+/// chosen like any other algorithm
+///
+/// - graph: the Graph to search
+/// - start: the start location on the Graph
+/// - end: the end location on the Graph
+/// - tie_break: how Djikstra's algorithm chooses between nodes with equal
+///   distances, if the Graph has a cycle
+///
+/// - return: the route (None if end cannot be reached), and the # of
+///   nodes considered
+///
+pub fn dag_search<'a, Ty: EdgeType>(graph: &Network<'a, Ty>, start: &'a str, end: &'a str, tie_break: TieBreak) -> (Option<PathResult<'a>>, usize)
+{
+    match topological_order(graph)
+    {
+        Some(order) => route_in_order(graph, &order, start, end, false),
+        None => astar::astar(graph, None, start, end, tie_break),
+    }
+}
+
+///
+/// Relaxes each node's edges once, in topological order, from start
+/// until end is reached
+///
+/// - order: the Graph's topological order
+///
+/// - return: the shortest or longest route (None if end cannot be
+///   reached), and the # of nodes whose edges were relaxed
+///
+fn route_in_order<'a, Ty: EdgeType>(graph: &Network<'a, Ty>, order: &[&'a str], start: &'a str, end: &'a str, longest: bool) -> (Option<PathResult<'a>>, usize)
+{
+    let mut node_counter = 0;
+    let mut dist: HashMap<&'a str, u64> = HashMap::new();
+    let mut prev: HashMap<&'a str, &'a str> = HashMap::new();
+    dist.insert(start, 0);
+
+    // Nodes before start in the order can never be reached from it
+    let from = order.iter().position(|node| *node == start);
+    for &node in from.map_or(&[][..], |from| &order[from..])
+    {
+        let node_dist = match dist.get(node)
+        {
+            Some(node_dist) => *node_dist,
+            None => continue,
+        };
+        node_counter += 1;
+        if node == end { break; }
+
+        for edge in graph.edges(node)
+        {
+            let alt_route = node_dist + edge.2;
+            let better = match dist.get(edge.1)
+            {
+                Some(d) => if longest { alt_route > *d } else { alt_route < *d },
+                None => true,
+            };
+
+            if better
+            {
+                dist.insert(edge.1, alt_route);
+                prev.insert(edge.1, node);
+            }
+        }
+    }
+
+    (dist.get(end).map(|cost| build_route(&prev, start, end, *cost)), node_counter)
+}
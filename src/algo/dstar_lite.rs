@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+
+use super::{Heuristic, KeyedQueue, PathResult, RouteGraph};
+
+/// Distance value used for nodes which have not (yet) been reached
+const INFINITY: u64 = u64::MAX;
+
+///
+/// A D* Lite planner, which searches backwards from the goal and keeps its
+/// search state between queries. When an edge weight changes (a road
+/// closure, say), only the affected portion of the search is repaired,
+/// rather than recomputing the whole route from scratch.
+///
+/// The planner owns its own copy of the Graph, so weight changes made
+/// through it never affect the Graph it was created from.
+///
+pub struct DStarLite<'a, 'h>
+{
+    graph: RouteGraph<'a>,
+    heuristic: &'h dyn Heuristic,
+    start: &'a str,
+    goal: &'a str,
+
+    // The start location at the time of the last edge change, and the
+    // accumulated heuristic offset since the search began
+    last: &'a str,
+    km: u64,
+
+    g: HashMap<&'a str, u64>,
+    rhs: HashMap<&'a str, u64>,
+    queue: KeyedQueue<'a>,
+}
+
+impl<'a, 'h> DStarLite<'a, 'h>
+{
+    ///
+    /// Creates a new planner between start and goal. No searching is
+    /// performed until route is first called
+    ///
+    /// - graph: the Graph to plan over (copied)
+    /// - heuristic: estimates the distance between any two nodes
+    /// - start: the current location
+    /// - goal: the destination
+    ///
+    pub fn new(graph: &RouteGraph<'a>, heuristic: &'h dyn Heuristic, start: &'a str, goal: &'a str) -> Self
+    {
+        let mut planner = DStarLite
+        {
+            graph: graph.clone(),
+            heuristic,
+            start,
+            goal,
+            last: start,
+            km: 0,
+            g: HashMap::new(),
+            rhs: HashMap::new(),
+            queue: KeyedQueue::new(),
+        };
+
+        // The search grows outward from the goal
+        planner.rhs.insert(goal, 0);
+        let key = planner.calculate_key(goal);
+        planner.queue.insert(goal, key);
+
+        planner
+    }
+
+    ///
+    /// Computes (or repairs) the shortest route from the current location
+    /// to the goal
+    ///
+    /// - return: the route, or None if the goal cannot be reached
+    ///
+    pub fn route(&mut self) -> Option<PathResult<'a>>
+    {
+        self.compute_shortest_path();
+        if self.g(self.start) == INFINITY { return None; }
+
+        // Walk greedily downhill through g from start to goal
+        let mut nodes = vec![self.start];
+        let mut cost = 0;
+        let mut current = self.start;
+        while current != self.goal
+        {
+            let (next, weight) = self.graph.edges(current)
+                .map(|edge| (edge.1, *edge.2))
+                .min_by_key(|&(next, weight)| weight.saturating_add(self.g(next)))?;
+
+            cost += weight;
+            nodes.push(next);
+            current = next;
+
+            // Guard against walking in circles on an inconsistent state
+            if nodes.len() > self.graph.node_count() { return None; }
+        }
+
+        Some(PathResult { nodes, cost })
+    }
+
+    ///
+    /// Moves the current location, typically one step along the last route
+    ///
+    /// - node: the new current location
+    ///
+    pub fn move_to(&mut self, node: &'a str)
+    {
+        self.start = node;
+    }
+
+    ///
+    /// Changes the weight of the edge between a and b, repairing the search
+    /// state so that the next call to route accounts for it
+    ///
+    /// - a, b: the edge's end nodes
+    /// - weight: the new weight, or None to close the edge entirely
+    ///
+    pub fn update_edge(&mut self, a: &'a str, b: &'a str, weight: Option<u64>)
+    {
+        // Account for any distance travelled since the last change,
+        // so that keys already in the queue remain valid lower bounds
+        self.km += self.heuristic.estimate(self.last, self.start);
+        self.last = self.start;
+
+        match weight
+        {
+            Some(weight) => { self.graph.add_edge(a, b, weight); },
+            None => { self.graph.remove_edge(a, b); }
+        }
+
+        self.update_vertex(a);
+        self.update_vertex(b);
+    }
+
+    fn g(&self, node: &'a str) -> u64
+    {
+        *self.g.get(node).unwrap_or(&INFINITY)
+    }
+
+    fn rhs(&self, node: &'a str) -> u64
+    {
+        *self.rhs.get(node).unwrap_or(&INFINITY)
+    }
+
+    fn calculate_key(&self, node: &'a str) -> (u64, u64)
+    {
+        let min = self.g(node).min(self.rhs(node));
+        (min.saturating_add(self.heuristic.estimate(node, self.start)).saturating_add(self.km), min)
+    }
+
+    ///
+    /// Recomputes node's one-step lookahead distance, and queues
+    /// it if it has become inconsistent
+    ///
+    fn update_vertex(&mut self, node: &'a str)
+    {
+        if node != self.goal
+        {
+            let rhs = self.graph.edges(node)
+                .map(|edge| edge.2.saturating_add(self.g(edge.1)))
+                .min()
+                .unwrap_or(INFINITY);
+            self.rhs.insert(node, rhs);
+        }
+
+        self.queue.remove(node);
+        if self.g(node) != self.rhs(node)
+        {
+            let key = self.calculate_key(node);
+            self.queue.insert(node, key);
+        }
+    }
+
+    ///
+    /// Expands inconsistent nodes until the current location is
+    /// consistent and no queued node could improve on it
+    ///
+    fn compute_shortest_path(&mut self)
+    {
+        while let Some((old_key, node)) = self.queue.top()
+        {
+            if old_key >= self.calculate_key(self.start) && self.rhs(self.start) == self.g(self.start) { break; }
+
+            let new_key = self.calculate_key(node);
+            if old_key < new_key
+            {
+                // The key was computed with an outdated km; requeue
+                self.queue.insert(node, new_key);
+                continue;
+            }
+
+            self.queue.pop();
+            let neighbors = self.graph.neighbors(node).collect::<Vec<&'a str>>();
+
+            if self.g(node) > self.rhs(node)
+            {
+                // Overconsistent: settle the node at its lookahead distance
+                self.g.insert(node, self.rhs(node));
+            }
+            else
+            {
+                // Underconsistent: the node's distance got worse. Reset it
+                // and let it (and its neighbors) be recomputed
+                self.g.insert(node, INFINITY);
+                self.update_vertex(node);
+            }
+
+            for neighbor in neighbors { self.update_vertex(neighbor); }
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::BTreeSet;
+
+use petgraph::EdgeType;
+
+use super::{build_route, search_map, Heuristic, Network, PathResult, SearchMap};
+
+/// The suboptimality bound used when none is specified
+pub const DEFAULT_EPSILON: f64 = 0.5;
+
+///
+/// Computes a route between two nodes using focal search (A*-epsilon),
+/// which is guaranteed to be no longer than (1 + epsilon) times the
+/// shortest route when the heuristic is admissible.
+///
+/// Alongside A*'s open list, ordered by f-value, a "focal" list holds every
+/// open node whose f-value is within (1 + epsilon) of the smallest. Nodes are
+/// expanded from the focal list in order of their estimated distance to go,
+/// diving greedily towards the destination while staying within the bound,
+/// which usually expands far fewer nodes than A*.
+///
+/// - graph: the Graph to search
+/// - heuristic: estimates the distance between any two nodes
+/// - start: the start location on the Graph
+/// - end: the end location on the Graph
+/// - epsilon: the allowed suboptimality (0 behaves like A*)
+///
+/// - return: the route (None if end cannot be reached), and the # of
+///   nodes considered
+///
+pub fn focal_search<'a, Ty: EdgeType>(graph: &Network<'a, Ty>, heuristic: &dyn Heuristic, start: &'a str, end: &'a str, epsilon: f64) -> (Option<PathResult<'a>>, usize)
+{
+    let mut dist: SearchMap<&'a str, u64> = search_map(graph.node_count());
+    let mut prev: SearchMap<&'a str, &'a str> = search_map(graph.node_count());
+
+    // The open list as (f, node), and the focal list as (h, f, node)
+    let mut open: BTreeSet<(u64, &'a str)> = BTreeSet::new();
+    let mut focal: BTreeSet<(u64, u64, &'a str)> = BTreeSet::new();
+
+    // Every open node's current f-value, and the f-value the focal list
+    // currently extends to
+    let mut open_f: SearchMap<&'a str, u64> = search_map(graph.node_count());
+    let mut focal_bound = 0;
+
+    let h = |node: &str| heuristic.estimate(node, end);
+
+    dist.insert(start, 0);
+    open.insert((h(start), start));
+    open_f.insert(start, h(start));
+    focal.insert((h(start), h(start), start));
+
+    let mut node_counter = 0;
+    while let Some(&(f_min, _)) = open.iter().next()
+    {
+        // Resize the focal list to cover every open node within the bound
+        let bound = (f_min as f64 * (1.0 + epsilon.max(0.0))).floor() as u64;
+        if bound > focal_bound
+        {
+            for &(f, node) in open.range((focal_bound + 1, "")..(bound + 1, ""))
+            {
+                focal.insert((h(node), f, node));
+            }
+        }
+        else if bound < focal_bound
+        {
+            for &(f, node) in open.range((bound + 1, "")..(focal_bound + 1, ""))
+            {
+                focal.remove(&(h(node), f, node));
+            }
+        }
+        focal_bound = bound;
+
+        // Expand the focal node closest to the destination
+        let &(node_h, node_f, node) = focal.iter().next().unwrap();
+        focal.remove(&(node_h, node_f, node));
+        open.remove(&(node_f, node));
+        open_f.remove(node);
+
+        node_counter += 1;
+        if node == end { return (Some(build_route(&prev, start, end, dist[end])), node_counter); }
+
+        for edge in graph.edges(node)
+        {
+            let alt_route = dist[node] + edge.2;
+            if dist.get(edge.1).is_some_and(|d| alt_route >= *d) { continue; }
+
+            dist.insert(edge.1, alt_route);
+            prev.insert(edge.1, node);
+
+            // Replace any existing entry, reopening the node if closed
+            if let Some(old_f) = open_f.remove(edge.1)
+            {
+                open.remove(&(old_f, edge.1));
+                focal.remove(&(h(edge.1), old_f, edge.1));
+            }
+
+            let f = alt_route + h(edge.1);
+            open.insert((f, edge.1));
+            open_f.insert(edge.1, f);
+            if f <= focal_bound { focal.insert((h(edge.1), f, edge.1)); }
+        }
+    }
+
+    (None, node_counter)
+}
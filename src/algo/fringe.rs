@@ -0,0 +1,95 @@
+use std::collections::VecDeque;
+
+use petgraph::EdgeType;
+
+use super::{build_route, search_map, Heuristic, Network, PathResult, SearchMap};
+
+///
+/// Computes the shortest route between two nodes using fringe search.
+///
+/// Like IDA*, fringe search works in iterations bounded by an f-value
+/// threshold, but it keeps the frontier (the "fringe") between iterations
+/// instead of restarting from scratch. Nodes within the threshold are
+/// expanded immediately; the rest are deferred to the next iteration.
+/// No priority queue is needed, trading a few extra visits for much
+/// cheaper frontier operations.
+///
+/// - graph: the Graph to search
+/// - heuristic: estimates the distance between any two nodes
+/// - start: the start location on the Graph
+/// - end: the end location on the Graph
+///
+/// - return: the route (None if end cannot be reached), and the # of
+///   nodes considered
+///
+pub fn fringe_search<'a, Ty: EdgeType>(graph: &Network<'a, Ty>, heuristic: &dyn Heuristic, start: &'a str, end: &'a str) -> (Option<PathResult<'a>>, usize)
+{
+    // Each reached node's distance from start, and its previous node
+    let mut dist: SearchMap<&'a str, u64> = search_map(graph.node_count());
+    let mut prev: SearchMap<&'a str, &'a str> = search_map(graph.node_count());
+    dist.insert(start, 0);
+
+    //
+    // The fringe is kept as a list of (node, version) entries. Rather than
+    // searching the list to move or remove a node, its version is bumped,
+    // and any entries carrying an older version are skipped when reached
+    //
+    let mut versions: SearchMap<&'a str, u32> = search_map(graph.node_count());
+    versions.insert(start, 0);
+    let mut now = VecDeque::new();
+    now.push_back((start, 0));
+
+    let mut threshold = heuristic.estimate(start, end);
+    let mut node_counter = 0;
+
+    while !now.is_empty()
+    {
+        // The nodes deferred this iteration, and the smallest f-value among them
+        let mut later = VecDeque::new();
+        let mut next_threshold = u64::MAX;
+
+        while let Some((node, version)) = now.pop_front()
+        {
+            if versions.get(node) != Some(&version) { continue; }
+
+            // Defer any node beyond the current threshold
+            let f = dist[node] + heuristic.estimate(node, end);
+            if f > threshold
+            {
+                next_threshold = next_threshold.min(f);
+                later.push_back((node, version));
+                continue;
+            }
+
+            node_counter += 1;
+            if node == end
+            {
+                return (Some(build_route(&prev, start, end, dist[end])), node_counter);
+            }
+
+            // The node is expanded, so drop it from the fringe
+            versions.insert(node, version + 1);
+
+            // Children are pushed to the front, in reverse, so that they
+            // are visited immediately after their parent in edge order
+            let edges = graph.edges(node).collect::<Vec<(&'a str, &'a str, &u64)>>();
+            for edge in edges.into_iter().rev()
+            {
+                let alt_route = dist[node] + edge.2;
+                if dist.get(edge.1).is_some_and(|d| alt_route >= *d) { continue; }
+
+                dist.insert(edge.1, alt_route);
+                prev.insert(edge.1, node);
+
+                let version = versions.get(edge.1).map_or(0, |v| v + 1);
+                versions.insert(edge.1, version);
+                now.push_front((edge.1, version));
+            }
+        }
+
+        threshold = next_threshold;
+        now = later;
+    }
+
+    (None, node_counter)
+}
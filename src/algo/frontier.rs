@@ -0,0 +1,520 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+use priority_queue::PriorityQueue;
+
+/// The heaviest edge weight the bucket queue is used for. With heavier
+/// edges, the buckets between the lowest and highest f-values queued are
+/// too many to be worth scanning
+pub const MAX_BUCKET_WEIGHT: u64 = 1 << 16;
+
+// Marks a pairing heap link to no node
+const NONE: u32 = u32::MAX;
+
+///
+/// The queue A* and Djikstra's algorithm keep their frontier in, over an
+/// InternedGraph. Every frontier takes nodes in the same order, so routes
+/// and the # of nodes considered are the same under any
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Frontier
+{
+    /// A binary heap which leaves re-queued nodes' old entries in place,
+    /// skipping them when they surface
+    #[default]
+    LazyHeap,
+
+    /// The priority_queue crate's indexed queue, which updates a re-queued
+    /// node's entry in place, at the cost of a hash lookup per operation
+    PriorityQueue,
+
+    /// A bucket per f-value, as in Dial's algorithm, so that only nodes
+    /// sharing an f-value are ever compared. Weights are whole numbers, so
+    /// with light edges the buckets in use are few
+    Buckets,
+
+    /// A radix heap, which relies on A* and Djikstra's algorithm taking
+    /// f-values in non-decreasing order to bucket nodes by the highest bit
+    /// their f-value differs from the last taken in, so that each node is
+    /// moved between buckets at most once per bit
+    RadixHeap,
+
+    /// A pairing heap of the nodes themselves, so that re-queuing a node
+    /// with a better priority cuts it free and melds it with the root in
+    /// constant time, leaving no stale entry behind
+    PairingHeap,
+}
+
+impl Frontier
+{
+    /// Every frontier
+    pub const ALL: [Frontier; 5] = [Frontier::LazyHeap, Frontier::PriorityQueue, Frontier::Buckets, Frontier::RadixHeap, Frontier::PairingHeap];
+
+    ///
+    /// - return: the frontier's display name
+    ///
+    pub fn name(self) -> &'static str
+    {
+        match self
+        {
+            Frontier::LazyHeap => "lazy-heap",
+            Frontier::PriorityQueue => "priority-queue",
+            Frontier::Buckets => "buckets",
+            Frontier::RadixHeap => "radix-heap",
+            Frontier::PairingHeap => "pairing-heap",
+        }
+    }
+
+    ///
+    /// Finds the frontier with the given name, such as "lazy-heap"
+    ///
+    pub fn from_name(name: &str) -> Option<Frontier>
+    {
+        match name.to_lowercase().as_str()
+        {
+            "lazy-heap" | "heap" => Some(Frontier::LazyHeap),
+            "priority-queue" | "indexed" => Some(Frontier::PriorityQueue),
+            "buckets" | "dial" => Some(Frontier::Buckets),
+            "radix-heap" | "radix" => Some(Frontier::RadixHeap),
+            "pairing-heap" | "pairing" => Some(Frontier::PairingHeap),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Creates an empty queue of this kind
+    ///
+    /// - nodes: the # of nodes which may be queued, each with an ID below it
+    ///
+    pub(crate) fn queue<P: Ord + Copy>(self, nodes: usize) -> Queue<P>
+    {
+        match self
+        {
+            Frontier::LazyHeap => Queue::Lazy { heap: BinaryHeap::new(), current: vec![None; nodes], live: 0 },
+            Frontier::PriorityQueue => Queue::Indexed(PriorityQueue::new()),
+            Frontier::Buckets => Queue::Buckets { buckets: VecDeque::new(), lowest: 0, current: vec![None; nodes], live: 0 },
+            Frontier::RadixHeap => Queue::Radix { last: BinaryHeap::new(), buckets: vec![Vec::new(); 64], f: 0, current: vec![None; nodes], live: 0 },
+            Frontier::PairingHeap => Queue::Pairing(PairingHeap::new(nodes)),
+        }
+    }
+}
+
+///
+/// A priority which orders first by an f-value, so that it can be kept in
+/// that f-value's bucket
+///
+pub(crate) trait Bucketed
+{
+    ///
+    /// - return: the f-value. Higher priorities have lower f-values
+    ///
+    fn f(&self) -> u64;
+}
+
+impl Bucketed for (Reverse<u64>, u64, Reverse<u64>)
+{
+    fn f(&self) -> u64
+    {
+        (self.0).0
+    }
+}
+
+///
+/// A max-priority queue of node IDs, where queuing a node again replaces
+/// its priority
+///
+/// - Lazy: the heap of every entry pushed, each node's current priority
+///   (None once taken, or if never queued), and the # of nodes queued
+/// - Indexed: the priority_queue crate's queue
+/// - Buckets: a heap of the entries pushed for each f-value, from the
+///   lowest f-value up, with each node's current priority and the # of
+///   nodes queued as for Lazy. Emptied buckets are moved to the back, to be
+///   reused for higher f-values
+/// - Radix: a heap of the entries pushed with f-values no higher than the
+///   last taken, then a bucket for each bit, holding the entries whose
+///   f-values first differ from it in that bit, with the last f-value
+///   taken, and each node's current priority and the # of nodes queued as
+///   for Lazy
+/// - Pairing: a pairing heap of the nodes queued
+///
+pub(crate) enum Queue<P: Ord>
+{
+    Lazy { heap: BinaryHeap<(P, u32)>, current: Vec<Option<P>>, live: usize },
+    Indexed(PriorityQueue<u32, P>),
+    Buckets { buckets: VecDeque<BinaryHeap<(P, u32)>>, lowest: u64, current: Vec<Option<P>>, live: usize },
+    Radix { last: BinaryHeap<(P, u32)>, buckets: Vec<Vec<(P, u32)>>, f: u64, current: Vec<Option<P>>, live: usize },
+    Pairing(PairingHeap<P>),
+}
+
+///
+/// Files an entry of a radix heap by its f-value, into the heap of entries
+/// no higher than the last f-value taken, or else the bucket for the
+/// highest bit its f-value differs from it in
+///
+fn file<P: Ord + Bucketed>(last: &mut BinaryHeap<(P, u32)>, buckets: &mut [Vec<(P, u32)>], f: u64, entry: (P, u32))
+{
+    match entry.0.f()
+    {
+        // Inconsistent heuristics may queue f-values lower than the last
+        // taken, which are then taken first
+        higher if higher > f => buckets[63 - (higher ^ f).leading_zeros() as usize].push(entry),
+        _ => last.push(entry),
+    }
+}
+
+impl<P: Ord + Copy + Bucketed> Queue<P>
+{
+    ///
+    /// Queues node with the given priority, replacing any it had
+    ///
+    pub(crate) fn push(&mut self, node: u32, priority: P)
+    {
+        match self
+        {
+            Queue::Lazy { heap, current, live } =>
+            {
+                if current[node as usize].replace(priority).is_none() { *live += 1; }
+                heap.push((priority, node));
+            },
+            Queue::Indexed(queue) => { queue.push(node, priority); },
+            Queue::Buckets { buckets, lowest, current, live } =>
+            {
+                // With nothing queued, the buckets may start anywhere
+                if *live == 0 { *lowest = priority.f(); }
+                if current[node as usize].replace(priority).is_none() { *live += 1; }
+
+                // Inconsistent heuristics may queue f-values lower than
+                // the lowest bucket's
+                while priority.f() < *lowest
+                {
+                    buckets.push_front(BinaryHeap::new());
+                    *lowest -= 1;
+                }
+                let bucket = (priority.f() - *lowest) as usize;
+                if buckets.len() <= bucket { buckets.resize_with(bucket + 1, BinaryHeap::new); }
+                buckets[bucket].push((priority, node));
+            },
+            Queue::Radix { last, buckets, f, current, live } =>
+            {
+                if current[node as usize].replace(priority).is_none() { *live += 1; }
+                file(last, buckets, *f, (priority, node));
+            },
+            Queue::Pairing(heap) => heap.push(node, priority),
+        }
+    }
+
+    ///
+    /// Takes the node with the highest priority, discarding any stale
+    /// entries along the way
+    ///
+    /// - stale: incremented for each stale entry discarded
+    ///
+    pub(crate) fn pop(&mut self, stale: &mut usize) -> Option<u32>
+    {
+        match self
+        {
+            Queue::Lazy { heap, current, live } =>
+            {
+                while let Some((priority, node)) = heap.pop()
+                {
+                    if current[node as usize] != Some(priority) { *stale += 1; continue; }
+
+                    current[node as usize] = None;
+                    *live -= 1;
+                    return Some(node);
+                }
+                None
+            },
+            Queue::Indexed(queue) => queue.pop().map(|(node, _)| node),
+            Queue::Buckets { buckets, lowest, current, live } =>
+            {
+                while *live > 0
+                {
+                    let bucket = buckets.front_mut()?;
+                    while let Some((priority, node)) = bucket.pop()
+                    {
+                        if current[node as usize] != Some(priority) { *stale += 1; continue; }
+
+                        current[node as usize] = None;
+                        *live -= 1;
+                        return Some(node);
+                    }
+
+                    buckets.rotate_left(1);
+                    *lowest += 1;
+                }
+                None
+            },
+            Queue::Radix { last, buckets, f, current, live } =>
+            {
+                while *live > 0
+                {
+                    while let Some((priority, node)) = last.pop()
+                    {
+                        if current[node as usize] != Some(priority) { *stale += 1; continue; }
+
+                        current[node as usize] = None;
+                        *live -= 1;
+                        return Some(node);
+                    }
+
+                    // Refile the lowest bucket holding entries around the
+                    // lowest f-value in it, which every entry of it then
+                    // either shares or differs from in a lower bit
+                    let bucket = buckets.iter().position(|bucket| !bucket.is_empty())?;
+                    let mut entries = std::mem::take(&mut buckets[bucket]);
+                    let filed = entries.len();
+                    entries.retain(|&(priority, node)| current[node as usize] == Some(priority));
+                    *stale += filed - entries.len();
+                    if let Some(lowest) = entries.iter().map(|(priority, _)| priority.f()).min() { *f = lowest; }
+                    for entry in entries.drain(..) { file(last, buckets, *f, entry); }
+                    buckets[bucket] = entries;
+                }
+                None
+            },
+            Queue::Pairing(heap) => heap.pop(),
+        }
+    }
+
+    ///
+    /// - return: true if this is a queue of the given kind
+    ///
+    pub(crate) fn is(&self, frontier: Frontier) -> bool
+    {
+        match self
+        {
+            Queue::Lazy { .. } => frontier == Frontier::LazyHeap,
+            Queue::Indexed(_) => frontier == Frontier::PriorityQueue,
+            Queue::Buckets { .. } => frontier == Frontier::Buckets,
+            Queue::Radix { .. } => frontier == Frontier::RadixHeap,
+            Queue::Pairing(_) => frontier == Frontier::PairingHeap,
+        }
+    }
+
+    ///
+    /// Empties the queue, keeping its allocations
+    ///
+    pub(crate) fn clear(&mut self)
+    {
+        match self
+        {
+            Queue::Lazy { heap, current, live } =>
+            {
+                for (_, node) in heap.drain() { current[node as usize] = None; }
+                *live = 0;
+            },
+            Queue::Indexed(queue) => queue.clear(),
+            Queue::Buckets { buckets, current, live, .. } =>
+            {
+                for bucket in buckets
+                {
+                    for (_, node) in bucket.drain() { current[node as usize] = None; }
+                }
+                *live = 0;
+            },
+            Queue::Radix { last, buckets, f, current, live } =>
+            {
+                for (_, node) in last.drain() { current[node as usize] = None; }
+                for bucket in buckets
+                {
+                    for (_, node) in bucket.drain(..) { current[node as usize] = None; }
+                }
+                *f = 0;
+                *live = 0;
+            },
+            Queue::Pairing(heap) => heap.clear(),
+        }
+    }
+
+    ///
+    /// - return: the # of nodes queued
+    ///
+    pub(crate) fn len(&self) -> usize
+    {
+        match self
+        {
+            Queue::Lazy { live, .. } => *live,
+            Queue::Indexed(queue) => queue.len(),
+            Queue::Buckets { live, .. } | Queue::Radix { live, .. } => *live,
+            Queue::Pairing(heap) => heap.live,
+        }
+    }
+}
+
+///
+/// A max-priority pairing heap of node IDs, linked through vectors indexed
+/// by ID
+///
+/// - root: the node with the highest priority, or NONE if empty
+/// - child: each node's leftmost child
+/// - sibling: each node's next sibling to the right
+/// - prev: each node's parent, if it is the leftmost child, or else its
+///   previous sibling
+/// - current: each node's priority, or None if it is not queued
+/// - live: the # of nodes queued
+/// - pairs: the roots being melded by pop, kept to reuse its allocation
+///
+pub(crate) struct PairingHeap<P>
+{
+    root: u32,
+    child: Vec<u32>,
+    sibling: Vec<u32>,
+    prev: Vec<u32>,
+    current: Vec<Option<P>>,
+    live: usize,
+    pairs: Vec<u32>,
+}
+
+impl<P: Ord + Copy> PairingHeap<P>
+{
+    fn new(nodes: usize) -> Self
+    {
+        PairingHeap
+        {
+            root: NONE,
+            child: vec![NONE; nodes],
+            sibling: vec![NONE; nodes],
+            prev: vec![NONE; nodes],
+            current: vec![None; nodes],
+            live: 0,
+            pairs: Vec::new(),
+        }
+    }
+
+    ///
+    /// Queues node with the given priority, replacing any it had. A higher
+    /// priority only cuts the node from its parent; a lower one also sets
+    /// its children free, as they may now outrank it
+    ///
+    fn push(&mut self, node: u32, priority: P)
+    {
+        match self.current[node as usize].replace(priority)
+        {
+            None =>
+            {
+                self.live += 1;
+                self.root = self.meld(self.root, node);
+            },
+            Some(old) if priority >= old =>
+            {
+                if node != self.root
+                {
+                    self.cut(node);
+                    self.root = self.meld(self.root, node);
+                }
+            },
+            Some(_) =>
+            {
+                if node == self.root { self.root = NONE; } else { self.cut(node); }
+                let children = self.merge_pairs(self.child[node as usize]);
+                self.child[node as usize] = NONE;
+                self.root = self.meld(self.root, children);
+                self.root = self.meld(self.root, node);
+            },
+        }
+    }
+
+    ///
+    /// Takes the node with the highest priority
+    ///
+    fn pop(&mut self) -> Option<u32>
+    {
+        let root = self.root;
+        if root == NONE { return None; }
+
+        self.root = self.merge_pairs(self.child[root as usize]);
+        self.child[root as usize] = NONE;
+        self.current[root as usize] = None;
+        self.live -= 1;
+        Some(root)
+    }
+
+    ///
+    /// Empties the heap, keeping its allocations
+    ///
+    fn clear(&mut self)
+    {
+        let mut pending = std::mem::take(&mut self.pairs);
+        if self.root != NONE { pending.push(self.root); }
+        while let Some(node) = pending.pop()
+        {
+            for link in [self.child[node as usize], self.sibling[node as usize]]
+            {
+                if link != NONE { pending.push(link); }
+            }
+            self.child[node as usize] = NONE;
+            self.sibling[node as usize] = NONE;
+            self.prev[node as usize] = NONE;
+            self.current[node as usize] = None;
+        }
+        self.pairs = pending;
+        self.root = NONE;
+        self.live = 0;
+    }
+
+    ///
+    /// Melds two heaps, making the root with the lower priority the
+    /// leftmost child of the other
+    ///
+    /// - return: the melded heap's root
+    ///
+    fn meld(&mut self, a: u32, b: u32) -> u32
+    {
+        if a == NONE { return b; }
+        if b == NONE { return a; }
+
+        let (parent, child) = if self.current[a as usize] >= self.current[b as usize] { (a, b) } else { (b, a) };
+        let first = self.child[parent as usize];
+        self.sibling[child as usize] = first;
+        if first != NONE { self.prev[first as usize] = child; }
+        self.prev[child as usize] = parent;
+        self.child[parent as usize] = child;
+        parent
+    }
+
+    ///
+    /// Detaches a node, with its children, from its parent and siblings
+    ///
+    fn cut(&mut self, node: u32)
+    {
+        let (prev, next) = (self.prev[node as usize], self.sibling[node as usize]);
+        if self.child[prev as usize] == node { self.child[prev as usize] = next; } else { self.sibling[prev as usize] = next; }
+        if next != NONE { self.prev[next as usize] = prev; }
+        self.sibling[node as usize] = NONE;
+        self.prev[node as usize] = NONE;
+    }
+
+    ///
+    /// Melds a list of siblings into one heap: first in pairs from left to
+    /// right, and then each pair into the last from right to left
+    ///
+    /// - first: the leftmost sibling, or NONE
+    ///
+    /// - return: the melded heap's root, or NONE
+    ///
+    fn merge_pairs(&mut self, first: u32) -> u32
+    {
+        let mut pairs = std::mem::take(&mut self.pairs);
+        let mut node = first;
+        while node != NONE
+        {
+            let next = self.sibling[node as usize];
+            let after = if next == NONE { NONE } else { self.sibling[next as usize] };
+            for detached in [node, next]
+            {
+                if detached != NONE
+                {
+                    self.sibling[detached as usize] = NONE;
+                    self.prev[detached as usize] = NONE;
+                }
+            }
+            pairs.push(self.meld(node, next));
+            node = after;
+        }
+
+        let mut root = NONE;
+        while let Some(pair) = pairs.pop() { root = self.meld(pair, root); }
+        self.pairs = pairs;
+        root
+    }
+}
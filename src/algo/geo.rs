@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use super::grid::{open_distance, Connectivity};
+use super::Heuristic;
+use crate::load::Unit;
+
+/// The Earth's mean radius, in miles
+pub const EARTH_RADIUS_MILES: f64 = 3958.8;
+
+/// The Earth's mean radius, in kilometers
+pub const EARTH_RADIUS_KILOMETRES: f64 = 6371.0;
+
+///
+/// Finds the great-circle distance between two points on the Earth,
+/// using the haversine formula
+///
+/// - from: the first point, as (longitude, latitude) in degrees
+/// - to: the second point, as (longitude, latitude) in degrees
+///
+/// - return: the distance between the points, in miles
+///
+pub fn haversine_miles(from: (f64, f64), to: (f64, f64)) -> f64
+{
+    let (lon1, lat1) = (from.0.to_radians(), from.1.to_radians());
+    let (lon2, lat2) = (to.0.to_radians(), to.1.to_radians());
+
+    let a = ((lat2 - lat1) / 2.0).sin().powi(2)
+        + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+
+    2.0 * EARTH_RADIUS_MILES * a.sqrt().min(1.0).asin()
+}
+
+///
+/// A straight-line heuristic over the Earth's surface, computed from each
+/// location's longitude and latitude as it is needed. Routes can be no
+/// shorter than the great circle between their ends, so as long as route
+/// weights are at least their geographic length, the heuristic is
+/// admissible. Locations without coordinates are estimated at 0.
+///
+pub struct GreatCircle<'a>
+{
+    coordinates: HashMap<&'a str, (f64, f64)>,
+    unit: Unit,
+    scale: f64,
+}
+
+impl<'a> GreatCircle<'a>
+{
+    ///
+    /// Estimates distances in miles
+    ///
+    /// - coordinates: each location's (longitude, latitude), in degrees
+    ///
+    pub fn new(coordinates: HashMap<&'a str, (f64, f64)>) -> Self
+    {
+        GreatCircle { coordinates, unit: Unit::Miles, scale: 1.0 }
+    }
+
+    ///
+    /// Estimates distances in kilometers
+    ///
+    /// - coordinates: each location's (longitude, latitude), in degrees
+    ///
+    pub fn in_kilometres(coordinates: HashMap<&'a str, (f64, f64)>) -> Self
+    {
+        GreatCircle { coordinates, unit: Unit::Kilometers, scale: EARTH_RADIUS_KILOMETRES / EARTH_RADIUS_MILES }
+    }
+}
+
+impl<'a> Heuristic for GreatCircle<'a>
+{
+    fn estimate(&self, node: &str, goal: &str) -> u64
+    {
+        match (self.coordinates.get(node), self.coordinates.get(goal))
+        {
+            // Round down to the unit's scale, to never overestimate
+            (Some(&node), Some(&goal)) => self.unit.to_weight(haversine_miles(node, goal) * self.scale).floor() as u64,
+            _ => 0,
+        }
+    }
+}
+
+///
+/// A straight-line heuristic on a plane, computed from each location's
+/// (x, y) position, in miles, as it is needed. This replaces a table of
+/// every pair's distance. Locations without coordinates are estimated
+/// at 0.
+///
+pub struct Euclidean<'a>
+{
+    coordinates: HashMap<&'a str, (f64, f64)>,
+}
+
+impl<'a> Euclidean<'a>
+{
+    ///
+    /// - coordinates: each location's (x, y), in miles
+    ///
+    pub fn new(coordinates: HashMap<&'a str, (f64, f64)>) -> Self
+    {
+        Euclidean { coordinates }
+    }
+}
+
+impl<'a> Heuristic for Euclidean<'a>
+{
+    fn estimate(&self, node: &str, goal: &str) -> u64
+    {
+        match (self.coordinates.get(node), self.coordinates.get(goal))
+        {
+            // Round down to the scale of miles, to never overestimate
+            (Some(&(x1, y1)), Some(&(x2, y2))) => Unit::Miles.to_weight((x2 - x1).hypot(y2 - y1)).floor() as u64,
+            _ => 0,
+        }
+    }
+}
+
+///
+/// The exact distance between two cells of a grid map with no walls in
+/// the way: the Manhattan distance, with four-way connectivity, or the
+/// octile distance, with eight-way connectivity, taking diagonal steps
+/// until level with the goal. Walls only lengthen routes, so the
+/// heuristic is admissible. Locations without coordinates are estimated
+/// at 0.
+///
+pub struct GridDistance<'a>
+{
+    coordinates: HashMap<&'a str, (f64, f64)>,
+    connectivity: Connectivity,
+    straight_cost: u64,
+}
+
+impl<'a> GridDistance<'a>
+{
+    ///
+    /// - coordinates: each cell's (column, row)
+    /// - connectivity: which neighbours of each cell may be stepped to
+    /// - straight_cost: the weight of a step between orthogonal neighbours
+    ///
+    pub fn new(coordinates: HashMap<&'a str, (f64, f64)>, connectivity: Connectivity, straight_cost: u64) -> Self
+    {
+        GridDistance { coordinates, connectivity, straight_cost }
+    }
+}
+
+impl<'a> Heuristic for GridDistance<'a>
+{
+    fn estimate(&self, node: &str, goal: &str) -> u64
+    {
+        match (self.coordinates.get(node), self.coordinates.get(goal))
+        {
+            (Some(&(x1, y1)), Some(&(x2, y2))) => open_distance((x1 as usize, y1 as usize), (x2 as usize, y2 as usize), self.connectivity, self.straight_cost),
+            _ => 0,
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+
+use priority_queue::PriorityQueue;
+
+use super::astar::TieBreak;
+
+///
+/// A cell on a Grid, as (x, y) with (0, 0) at the top-left
+///
+pub type Cell = (usize, usize);
+
+/// The cost of a straight move between adjacent cells, in tenths of a cell
+pub const STRAIGHT_COST: u64 = 10;
+
+///
+/// Which neighbours of a grid cell may be stepped to
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Connectivity
+{
+    /// Up, down, left and right
+    Four,
+    /// The four orthogonal neighbours, and the four diagonal
+    Eight,
+}
+
+///
+/// A rectangular grid map of passable and blocked cells. Moves between
+/// cells are measured in tenths of a cell by default, matching the
+/// fixed-point scheme used for route Graph weights
+///
+#[derive(Debug, Clone)]
+pub struct Grid
+{
+    width: usize,
+    height: usize,
+    passable: Vec<bool>,
+    straight_cost: u64,
+    diagonal_cost: u64,
+}
+
+///
+/// A single computed route across a Grid
+///
+/// - cells: every cell on the route, from start to end (inclusive). For
+///   any-angle searches consecutive cells need not be adjacent
+/// - cost: the total length of the route, in tenths of a cell
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GridPath
+{
+    pub cells: Vec<Cell>,
+    pub cost: u64,
+}
+
+impl Grid
+{
+    ///
+    /// Creates a new Grid with every cell passable
+    ///
+    pub fn new(width: usize, height: usize) -> Self
+    {
+        Grid::with_straight_cost(width, height, STRAIGHT_COST)
+    }
+
+    ///
+    /// Creates a new Grid with every cell passable, and moves measured in
+    /// a finer or coarser unit than tenths of a cell
+    ///
+    /// - straight_cost: the cost of a straight move between adjacent
+    ///   cells. Diagonal moves cost √2 times as much
+    ///
+    pub fn with_straight_cost(width: usize, height: usize, straight_cost: u64) -> Self
+    {
+        Grid { width, height, passable: vec![true; width * height], straight_cost, diagonal_cost: diagonal_cost(straight_cost) }
+    }
+
+    pub fn width(&self) -> usize
+    {
+        self.width
+    }
+
+    pub fn height(&self) -> usize
+    {
+        self.height
+    }
+
+    pub fn straight_cost(&self) -> u64
+    {
+        self.straight_cost
+    }
+
+    pub fn diagonal_cost(&self) -> u64
+    {
+        self.diagonal_cost
+    }
+
+    ///
+    /// Marks a cell as blocked (a wall) or passable
+    ///
+    pub fn set_blocked(&mut self, (x, y): Cell, blocked: bool)
+    {
+        self.passable[y * self.width + x] = !blocked;
+    }
+
+    ///
+    /// - return: true if the cell lies on the Grid and is not blocked
+    ///
+    pub fn is_passable(&self, (x, y): Cell) -> bool
+    {
+        x < self.width && y < self.height && self.passable[y * self.width + x]
+    }
+
+    ///
+    /// Signed variant of is_passable, for coordinates which may fall off
+    /// the top or left of the Grid
+    ///
+    pub(crate) fn is_passable_at(&self, x: i64, y: i64) -> bool
+    {
+        x >= 0 && y >= 0 && self.is_passable((x as usize, y as usize))
+    }
+
+    ///
+    /// Lists the passable cells reachable in one move from cell, with the
+    /// cost of each move. Diagonal moves may not cut the corner of a wall
+    ///
+    /// - cell: the cell to move from
+    /// - connectivity: which neighbours of the cell may be stepped to
+    ///
+    pub fn neighbors(&self, (x, y): Cell, connectivity: Connectivity) -> Vec<(Cell, u64)>
+    {
+        let (x, y) = (x as i64, y as i64);
+        let mut neighbors = Vec::new();
+
+        for &(dx, dy) in &[(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)]
+        {
+            let is_diagonal = dx != 0 && dy != 0;
+            if is_diagonal && connectivity == Connectivity::Four { continue; }
+            if !self.is_passable_at(x + dx, y + dy) { continue; }
+            if is_diagonal && !(self.is_passable_at(x + dx, y) && self.is_passable_at(x, y + dy)) { continue; }
+
+            let cost = if is_diagonal { self.diagonal_cost } else { self.straight_cost };
+            neighbors.push((((x + dx) as usize, (y + dy) as usize), cost));
+        }
+
+        neighbors
+    }
+
+    ///
+    /// The cost of the shortest move sequence between two cells, were no
+    /// walls in the way
+    ///
+    pub fn distance(&self, a: Cell, b: Cell, connectivity: Connectivity) -> u64
+    {
+        open_distance(a, b, connectivity, self.straight_cost)
+    }
+
+    ///
+    /// The straight-line distance between two cells, in the cost of
+    /// straight moves
+    ///
+    pub fn euclidean(&self, a: Cell, b: Cell) -> u64
+    {
+        let (dx, dy) = (a.0 as f64 - b.0 as f64, a.1 as f64 - b.1 as f64);
+        (dx.hypot(dy) * self.straight_cost as f64).round() as u64
+    }
+
+    ///
+    /// Determines whether a straight line between the centers of two cells
+    /// crosses only passable cells. A line passing exactly through a corner
+    /// requires both cells beside the corner to be passable
+    ///
+    pub fn line_of_sight(&self, from: Cell, to: Cell) -> bool
+    {
+        let (dx, dy) = (to.0 as i64 - from.0 as i64, to.1 as i64 - from.1 as i64);
+        let (nx, ny) = (dx.abs(), dy.abs());
+        let (sx, sy) = (dx.signum(), dy.signum());
+        let (mut x, mut y) = (from.0 as i64, from.1 as i64);
+        let (mut ix, mut iy) = (0, 0);
+
+        // Walk every cell the line passes through, stepping in whichever
+        // direction the line crosses a cell boundary first
+        while ix < nx || iy < ny
+        {
+            let decision = (1 + 2 * ix) * ny - (1 + 2 * iy) * nx;
+            if decision == 0
+            {
+                if !self.is_passable_at(x + sx, y) || !self.is_passable_at(x, y + sy) { return false; }
+                x += sx;
+                y += sy;
+                ix += 1;
+                iy += 1;
+            }
+            else if decision < 0 { x += sx; ix += 1; }
+            else { y += sy; iy += 1; }
+
+            if !self.is_passable_at(x, y) { return false; }
+        }
+
+        true
+    }
+}
+
+///
+/// Finds the cost of a diagonal move
+///
+/// - straight_cost: the cost of a straight move between adjacent cells
+///
+/// - return: the cost of a diagonal move, √2 times a straight move's,
+///   rounded to the nearest whole cost
+///
+pub fn diagonal_cost(straight_cost: u64) -> u64
+{
+    (straight_cost as f64 * std::f64::consts::SQRT_2).round() as u64
+}
+
+///
+/// Finds the cost of the shortest move sequence between two cells with no
+/// walls in the way: the Manhattan distance, with four-way connectivity,
+/// or the octile distance, with eight-way connectivity
+///
+/// - a, b: the two cells
+/// - connectivity: which neighbours of each cell may be stepped to
+/// - straight_cost: the cost of a straight move between adjacent cells
+///
+/// - return: the cost, in the unit of straight_cost
+///
+pub fn open_distance(a: Cell, b: Cell, connectivity: Connectivity, straight_cost: u64) -> u64
+{
+    let (dx, dy) = (a.0.abs_diff(b.0) as u64, a.1.abs_diff(b.1) as u64);
+    match connectivity
+    {
+        Connectivity::Four => (dx + dy) * straight_cost,
+        Connectivity::Eight => (dx.max(dy) - dx.min(dy)) * straight_cost + dx.min(dy) * diagonal_cost(straight_cost),
+    }
+}
+
+///
+/// Computes the shortest route across a Grid using A*, moving only
+/// between adjacent cells
+///
+/// - grid: the Grid to search
+/// - start: the start cell
+/// - end: the end cell
+/// - connectivity: which neighbours of each cell may be stepped to
+/// - tie_break: how to choose between cells with equal f-values
+///
+/// - return: the route (None if end cannot be reached), and the # of
+///   cells considered
+///
+pub fn astar(grid: &Grid, start: Cell, end: Cell, connectivity: Connectivity, tie_break: TieBreak) -> (Option<GridPath>, usize)
+{
+    if !grid.is_passable(start) || !grid.is_passable(end) { return (None, 0); }
+
+    let mut dist: HashMap<Cell, u64> = HashMap::new();
+    let mut prev: HashMap<Cell, Cell> = HashMap::new();
+    dist.insert(start, 0);
+
+    let mut open = PriorityQueue::new();
+    open.push(start, tie_break.priority(0, grid.distance(start, end, connectivity), 0));
+    let mut queued = 1;
+
+    let mut node_counter = 0;
+    while let Some((cell, _)) = open.pop()
+    {
+        node_counter += 1;
+        if cell == end
+        {
+            let mut cells = vec![end];
+            let mut current = end;
+            while current != start
+            {
+                current = prev[&current];
+                cells.push(current);
+            }
+            cells.reverse();
+
+            return (Some(GridPath { cells, cost: dist[&end] }), node_counter);
+        }
+
+        for (neighbor, cost) in grid.neighbors(cell, connectivity)
+        {
+            let alt_route = dist[&cell] + cost;
+            if dist.get(&neighbor).is_none_or(|d| alt_route < *d)
+            {
+                dist.insert(neighbor, alt_route);
+                prev.insert(neighbor, cell);
+                open.push(neighbor, tie_break.priority(alt_route, grid.distance(neighbor, end, connectivity), queued));
+                queued += 1;
+            }
+        }
+    }
+
+    (None, node_counter)
+}
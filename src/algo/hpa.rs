@@ -0,0 +1,256 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
+
+use priority_queue::PriorityQueue;
+
+use super::{build_route, PathResult, RouteGraph};
+
+/// The most nodes a cluster is grown to when none is specified
+pub const DEFAULT_CLUSTER_SIZE: usize = 16;
+
+///
+/// A hierarchical (HPA*-style) view of a route Graph. Nodes are partitioned
+/// into clusters of neighboring nodes, and the "border" nodes of each
+/// cluster (those with an edge into another cluster) form a much smaller
+/// abstract Graph. Abstract edges are either original edges between
+/// clusters, or precomputed shortest paths across a single cluster.
+///
+/// Queries search the abstract Graph alone, giving a route of border nodes
+/// almost immediately. A refinement step then expands each abstract edge
+/// back into the original nodes along it.
+///
+pub struct Hierarchy<'a>
+{
+    graph: RouteGraph<'a>,
+    cluster: HashMap<&'a str, usize>,
+    abstract_graph: RouteGraph<'a>,
+
+    // The nodes along every precomputed path across a cluster,
+    // keyed by the path's (from, to) border nodes
+    paths: HashMap<(&'a str, &'a str), Vec<&'a str>>,
+}
+
+impl<'a> Hierarchy<'a>
+{
+    ///
+    /// Partitions the Graph into clusters and builds the abstract Graph
+    ///
+    /// - graph: the Graph to preprocess (copied)
+    /// - cluster_size: the most nodes any one cluster may hold
+    ///
+    pub fn new(graph: &RouteGraph<'a>, cluster_size: usize) -> Self
+    {
+        let cluster = partition(graph, cluster_size.max(1));
+
+        // Border nodes, grouped by cluster
+        let mut borders: HashMap<usize, Vec<&'a str>> = HashMap::new();
+        for node in graph.nodes()
+        {
+            if graph.neighbors(node).any(|next| cluster[next] != cluster[node])
+            {
+                borders.entry(cluster[node]).or_default().push(node);
+            }
+        }
+
+        let mut abstract_graph = RouteGraph::new();
+        for (from, to, weight) in graph.all_edges()
+        {
+            if cluster[from] != cluster[to] { abstract_graph.add_edge(from, to, *weight); }
+        }
+
+        // Connect every pair of border nodes sharing a cluster
+        // by their shortest path across it
+        let mut paths = HashMap::new();
+        for nodes in borders.values()
+        {
+            for &from in nodes
+            {
+                let (dist, prev) = cluster_tree(graph, &cluster, from);
+                for &to in nodes
+                {
+                    if from == to || !dist.contains_key(to) { continue; }
+
+                    abstract_graph.add_edge(from, to, dist[to]);
+                    paths.insert((from, to), build_route(&prev, from, to, dist[to]).nodes);
+                }
+            }
+        }
+
+        Hierarchy { graph: graph.clone(), cluster, abstract_graph, paths }
+    }
+
+    ///
+    /// - return: the # of clusters, and the # of nodes in the abstract Graph
+    ///
+    pub fn size(&self) -> (usize, usize)
+    {
+        (self.cluster.values().max().map_or(0, |max| max + 1), self.abstract_graph.node_count())
+    }
+
+    ///
+    /// Computes the shortest route between two nodes over the abstract Graph.
+    /// The route lists only the start, end, and border nodes passed through;
+    /// pass it to refine for the full route
+    ///
+    /// - start: the start location
+    /// - end: the end location
+    ///
+    /// - return: the abstract route, or None if either location does not
+    ///   exist or end cannot be reached
+    ///
+    pub fn abstract_route(&self, start: &'a str, end: &'a str) -> Option<PathResult<'a>>
+    {
+        if !self.graph.contains_node(start) || !self.graph.contains_node(end) { return None; }
+
+        // Temporarily link start and end into the abstract Graph, by
+        // their shortest paths to the border nodes of their clusters
+        let (start_dist, _) = cluster_tree(&self.graph, &self.cluster, start);
+        let (end_dist, _) = cluster_tree(&self.graph, &self.cluster, end);
+        let start_links = start_dist.iter()
+            .filter(|(node, _)| self.abstract_graph.contains_node(node) || **node == end)
+            .map(|(node, dist)| (*node, *dist))
+            .collect::<Vec<(&'a str, u64)>>();
+
+        let mut dist: HashMap<&'a str, u64> = HashMap::new();
+        let mut prev: HashMap<&'a str, &'a str> = HashMap::new();
+        let mut queue = PriorityQueue::new();
+        dist.insert(start, 0);
+        queue.push(start, Reverse(0));
+
+        while let Some((node, _)) = queue.pop()
+        {
+            if node == end { return Some(build_route(&prev, start, end, dist[end])); }
+
+            let mut links = Vec::new();
+            if node == start { links.extend(start_links.iter().cloned()); }
+            if self.abstract_graph.contains_node(node)
+            {
+                links.extend(self.abstract_graph.edges(node).map(|edge| (edge.1, *edge.2)));
+            }
+            if let Some(to_end) = end_dist.get(node) { links.push((end, *to_end)); }
+
+            for (next, weight) in links
+            {
+                let alt_route = dist[node] + weight;
+                if dist.get(next).is_none_or(|d| alt_route < *d)
+                {
+                    dist.insert(next, alt_route);
+                    prev.insert(next, node);
+                    queue.push(next, Reverse(alt_route));
+                }
+            }
+        }
+
+        None
+    }
+
+    ///
+    /// Expands an abstract route into every original node along it
+    ///
+    /// - route: a route returned by abstract_route
+    ///
+    pub fn refine(&self, route: &PathResult<'a>) -> PathResult<'a>
+    {
+        let mut nodes = vec![route.nodes[0]];
+        for pair in route.nodes.windows(2)
+        {
+            let (from, to) = (pair[0], pair[1]);
+
+            if self.cluster[from] != self.cluster[to] { nodes.push(to); }
+            else if let Some(path) = self.paths.get(&(from, to)) { nodes.extend(&path[1..]); }
+            else
+            {
+                // Links to the query's start and end are not precomputed
+                let (dist, prev) = cluster_tree(&self.graph, &self.cluster, from);
+                nodes.extend(&build_route(&prev, from, to, dist[to]).nodes[1..]);
+            }
+        }
+
+        PathResult { nodes, cost: route.cost }
+    }
+
+    ///
+    /// Computes the shortest route between two nodes, searching the
+    /// abstract Graph and refining the result
+    ///
+    /// - start: the start location
+    /// - end: the end location
+    ///
+    /// - return: the route, or None if either location does not exist
+    ///   or end cannot be reached
+    ///
+    pub fn route(&self, start: &'a str, end: &'a str) -> Option<PathResult<'a>>
+    {
+        self.abstract_route(start, end).map(|route| self.refine(&route))
+    }
+}
+
+///
+/// Partitions the Graph into clusters by breadth-first region growing
+///
+/// - return: the cluster index of every node
+///
+fn partition<'a>(graph: &RouteGraph<'a>, cluster_size: usize) -> HashMap<&'a str, usize>
+{
+    let mut cluster = HashMap::new();
+    let mut count = 0;
+
+    for seed in graph.nodes()
+    {
+        if cluster.contains_key(seed) { continue; }
+
+        // Grow a new cluster outward from the seed until it is full
+        let mut members = 0;
+        let mut frontier = VecDeque::new();
+        frontier.push_back(seed);
+        cluster.insert(seed, count);
+        while let Some(node) = frontier.pop_front()
+        {
+            members += 1;
+            for next in graph.neighbors(node)
+            {
+                if members + frontier.len() >= cluster_size { break; }
+                if cluster.contains_key(next) { continue; }
+
+                cluster.insert(next, count);
+                frontier.push_back(next);
+            }
+        }
+
+        count += 1;
+    }
+
+    cluster
+}
+
+///
+/// Runs Dijkstra's algorithm from source without leaving source's cluster
+///
+/// - return: the distance to, and predecessor of, every node reached
+///
+fn cluster_tree<'a>(graph: &RouteGraph<'a>, cluster: &HashMap<&'a str, usize>, source: &'a str) -> (HashMap<&'a str, u64>, HashMap<&'a str, &'a str>)
+{
+    let mut dist: HashMap<&'a str, u64> = HashMap::new();
+    let mut prev: HashMap<&'a str, &'a str> = HashMap::new();
+    let mut queue = PriorityQueue::new();
+    dist.insert(source, 0);
+    queue.push(source, Reverse(0));
+
+    while let Some((node, _)) = queue.pop()
+    {
+        for edge in graph.edges(node)
+        {
+            if cluster[edge.1] != cluster[source] { continue; }
+
+            let alt_route = dist[node] + edge.2;
+            if dist.get(edge.1).is_none_or(|d| alt_route < *d)
+            {
+                dist.insert(edge.1, alt_route);
+                prev.insert(edge.1, node);
+                queue.push(edge.1, Reverse(alt_route));
+            }
+        }
+    }
+
+    (dist, prev)
+}
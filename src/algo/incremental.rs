@@ -0,0 +1,168 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use super::{build_route, shortest_path_tree, PathResult, RouteGraph};
+
+///
+/// Every shortest route from a fixed start, kept between queries so that
+/// after a single edge is changed only the distances the change affects
+/// are repaired, rather than searching the whole Graph again.
+///
+/// Unlike LpaStar, the search does not own a copy of the Graph: the Graph
+/// is edited by its owner, and the search is told which edge changed
+///
+pub struct IncrementalSearch<'a>
+{
+    start: &'a str,
+    dist: HashMap<&'a str, u64>,
+    prev: HashMap<&'a str, &'a str>,
+
+    // The # of nodes settled by the last full search or repair
+    expanded: usize,
+}
+
+impl<'a> IncrementalSearch<'a>
+{
+    ///
+    /// Searches the whole Graph from start
+    ///
+    /// - graph: the Graph to search
+    /// - start: the start location
+    ///
+    pub fn new(graph: &RouteGraph<'a>, start: &'a str) -> Self
+    {
+        let tree = shortest_path_tree(graph, start);
+        IncrementalSearch { start, expanded: tree.dist.len(), dist: tree.dist, prev: tree.prev }
+    }
+
+    ///
+    /// - return: the start location every route is found from
+    ///
+    pub fn start(&self) -> &'a str
+    {
+        self.start
+    }
+
+    ///
+    /// - goal: the destination
+    ///
+    /// - return: the shortest route from start to goal, or None if the
+    ///   goal cannot be reached
+    ///
+    pub fn route(&self, goal: &'a str) -> Option<PathResult<'a>>
+    {
+        let cost = *self.dist.get(goal)?;
+        Some(build_route(&self.prev, self.start, goal, cost))
+    }
+
+    ///
+    /// - return: the # of nodes settled by the last full search or repair
+    ///
+    pub fn expanded(&self) -> usize
+    {
+        self.expanded
+    }
+
+    ///
+    /// Repairs the distances after the edge between a and b was changed on
+    /// the Graph. If the edge was on a shortest route and got longer (or was
+    /// removed), the nodes routed through it are searched again from their
+    /// neighbors; if it got shorter, the improvement is spread outward from
+    /// it. Nodes the change cannot affect are never touched
+    ///
+    /// - graph: the Graph, already holding the edge's new weight
+    /// - a, b: the changed edge's end nodes
+    ///
+    pub fn update_edge(&mut self, graph: &RouteGraph<'a>, a: &'a str, b: &'a str)
+    {
+        let weight = graph.edge_weight(a, b).copied();
+        let mut queue = BinaryHeap::new();
+
+        // The edge's end further from start, if start routes through it
+        let child = match (self.prev.get(a), self.prev.get(b))
+        {
+            (_, Some(&parent)) if parent == a => Some(b),
+            (Some(&parent), _) if parent == b => Some(a),
+            _ => None,
+        };
+        let parent = if child == Some(a) { b } else { a };
+
+        if let Some(child) = child
+        {
+            let old_weight = self.dist[child] - self.dist[parent];
+            if weight.is_none_or(|weight| weight > old_weight)
+            {
+                // Forget every node routed through the edge, then offer each
+                // the best distance its remaining neighbors give
+                let subtree = self.subtree(graph, child);
+                for node in &subtree
+                {
+                    self.dist.remove(node);
+                    self.prev.remove(node);
+                }
+                for &node in &subtree
+                {
+                    let best = graph.edges(node)
+                        .filter_map(|edge| self.dist.get(edge.1).map(|dist| (dist + edge.2, edge.1)))
+                        .min();
+                    if let Some((dist, via)) = best
+                    {
+                        self.dist.insert(node, dist);
+                        self.prev.insert(node, via);
+                        queue.push(Reverse((dist, node)));
+                    }
+                }
+            }
+        }
+
+        // A shorter (or new) edge may improve either end
+        if let Some(weight) = weight
+        {
+            for (from, to) in [(a, b), (b, a)]
+            {
+                let alt_route = match self.dist.get(from) { Some(dist) => dist + weight, None => continue };
+                if self.dist.get(to).is_none_or(|dist| alt_route < *dist)
+                {
+                    self.dist.insert(to, alt_route);
+                    self.prev.insert(to, from);
+                    queue.push(Reverse((alt_route, to)));
+                }
+            }
+        }
+
+        // Spread the changed distances outward, as Djikstra's does
+        self.expanded = 0;
+        while let Some(Reverse((dist, node))) = queue.pop()
+        {
+            if self.dist.get(node) != Some(&dist) { continue; }
+            self.expanded += 1;
+
+            for edge in graph.edges(node)
+            {
+                let alt_route = dist + edge.2;
+                if self.dist.get(edge.1).is_none_or(|dist| alt_route < *dist)
+                {
+                    self.dist.insert(edge.1, alt_route);
+                    self.prev.insert(edge.1, node);
+                    queue.push(Reverse((alt_route, edge.1)));
+                }
+            }
+        }
+    }
+
+    ///
+    /// - return: root and every node whose shortest route passes through it
+    ///
+    fn subtree(&self, graph: &RouteGraph<'a>, root: &'a str) -> Vec<&'a str>
+    {
+        let mut nodes = vec![root];
+        let mut i = 0;
+        while i < nodes.len()
+        {
+            let node = nodes[i];
+            nodes.extend(graph.neighbors(node).filter(|neighbor| self.prev.get(neighbor) == Some(&node)));
+            i += 1;
+        }
+        nodes
+    }
+}
@@ -0,0 +1,605 @@
+use std::collections::{HashMap, VecDeque};
+use std::iter::{Cloned, Zip};
+use std::slice::Iter;
+
+use log::debug;
+use petgraph::EdgeType;
+
+use super::astar::{Expansion, TieBreak};
+use super::context::{SearchContext, UNREACHED};
+use super::{Heuristic, Network, PathResult};
+
+///
+/// A Graph whose locations are numbered by dense u32 IDs, which the
+/// searches in this module run over
+///
+pub trait SearchGraph<'a>
+{
+    /// Each edge leaving a location, as (neighbour ID, weight)
+    type Edges<'g>: DoubleEndedIterator<Item = (u32, u64)> where Self: 'g;
+
+    ///
+    /// - return: the ID of the named location, or None if it is not on
+    ///   the Graph
+    ///
+    fn id(&self, name: &str) -> Option<u32>;
+
+    ///
+    /// - return: the name of the location with the given ID
+    ///
+    fn name(&self, id: u32) -> &'a str;
+
+    ///
+    /// - return: the # of locations on the Graph
+    ///
+    fn len(&self) -> usize;
+
+    ///
+    /// - return: true if the Graph has no locations
+    ///
+    fn is_empty(&self) -> bool
+    {
+        self.len() == 0
+    }
+
+    ///
+    /// - return: each edge leaving the location with the given ID
+    ///
+    fn edges(&self, id: u32) -> Self::Edges<'_>;
+
+    ///
+    /// - return: each edge reaching the location with the given ID, as
+    ///   (neighbour ID it leaves, weight), or None if the Graph only holds
+    ///   the edges leaving each location
+    ///
+    fn edges_into(&self, _id: u32) -> Option<Self::Edges<'_>>
+    {
+        None
+    }
+
+    ///
+    /// - return: every node's precomputed estimate to the goal, or None if
+    ///   estimates must be made by name
+    ///
+    fn estimates_to(&self, goal: u32) -> Option<Estimates<'_>>;
+}
+
+///
+/// Every node's precomputed estimate to one goal, indexed by ID. Nodes
+/// past the end are estimated at 0
+///
+/// - Ids: the estimates, held in memory
+/// - LittleEndian: the estimates, as little-endian u64s read from a file
+///
+#[derive(Debug, Clone, Copy)]
+pub enum Estimates<'g>
+{
+    Ids(&'g [u64]),
+    LittleEndian(&'g [u8]),
+}
+
+impl Estimates<'_>
+{
+    ///
+    /// - return: the estimate from the node with the given ID
+    ///
+    pub fn get(&self, node: u32) -> u64
+    {
+        match self
+        {
+            Estimates::Ids(estimates) => estimates.get(node as usize).cloned().unwrap_or(0),
+            Estimates::LittleEndian(bytes) =>
+            {
+                let at = node as usize * 8;
+                match bytes.get(at..at + 8)
+                {
+                    Some(estimate) => u64::from_le_bytes([estimate[0], estimate[1], estimate[2], estimate[3], estimate[4], estimate[5], estimate[6], estimate[7]]),
+                    None => 0,
+                }
+            },
+        }
+    }
+}
+
+///
+/// A route Graph with every location interned as a dense u32 ID, so that
+/// searches index vectors instead of hashing names. Built once, when the
+/// routes are loaded, and kept beside the Graph it was built from.
+///
+/// Edges are held in compressed sparse row form: every node's outgoing
+/// edges lie together in neighbours and weights, so that iterating them is
+/// a scan of two contiguous slices
+///
+/// - names: each ID's location name, for reporting routes
+/// - ids: each location name's ID, for starting searches
+/// - offsets: where each ID's edges begin in neighbours and weights, with
+///   one more entry marking where the last ID's edges end
+/// - neighbours: the ID each edge leads to
+/// - weights: the weight of each edge
+/// - incoming: the same arrays for the edges reaching each ID, if the
+///   Graph is directed. Undirected edges reach each ID as they leave it
+/// - heuristic: the heuristic's estimates by ID, if it is a table
+///
+#[derive(Debug, Clone, Default)]
+pub struct InternedGraph<'a>
+{
+    names: Vec<&'a str>,
+    ids: HashMap<&'a str, u32>,
+    offsets: Vec<usize>,
+    neighbours: Vec<u32>,
+    weights: Vec<u64>,
+    incoming: Option<(Vec<usize>, Vec<u32>, Vec<u64>)>,
+    heuristic: Option<HeuristicMatrix>,
+}
+
+impl<'a> InternedGraph<'a>
+{
+    ///
+    /// Interns every node of a Graph, in the Graph's node order
+    ///
+    /// - graph: the Graph to intern. Edges are kept only in the directions
+    ///   it allows
+    ///
+    pub fn new<Ty: EdgeType>(graph: &Network<'a, Ty>) -> Self
+    {
+        let names = graph.nodes().collect::<Vec<&'a str>>();
+        let ids = names.iter().enumerate().map(|(id, &name)| (name, id as u32)).collect::<HashMap<&'a str, u32>>();
+
+        let mut offsets = Vec::with_capacity(names.len() + 1);
+        let mut neighbours = Vec::with_capacity(graph.edge_count());
+        let mut weights = Vec::with_capacity(graph.edge_count());
+        offsets.push(0);
+        for &name in &names
+        {
+            for (_, to, &weight) in graph.edges(name)
+            {
+                neighbours.push(ids[to]);
+                weights.push(weight);
+            }
+            offsets.push(neighbours.len());
+        }
+
+        let incoming = graph.is_directed().then(|| transpose(&offsets, &neighbours, &weights));
+        InternedGraph { names, ids, offsets, neighbours, weights, incoming, heuristic: None }
+    }
+
+    ///
+    /// Renumbers the locations in reverse Cuthill-McKee order: a breadth
+    /// first traversal from the least-connected location of each component,
+    /// visiting neighbours least-connected first, reversed. Neighbours then
+    /// get nearby IDs, so the distances a search reads as it relaxes a
+    /// node's edges lie close together in memory. Routes are unchanged, as
+    /// every location is still found by name
+    ///
+    pub fn reorder(&mut self)
+    {
+        let degree = |id: usize| self.offsets[id + 1] - self.offsets[id];
+        let mut by_degree = (0..self.names.len()).collect::<Vec<usize>>();
+        by_degree.sort_by_key(|&id| degree(id));
+
+        let mut order = Vec::with_capacity(self.names.len());
+        let mut visited = vec![false; self.names.len()];
+        for root in by_degree
+        {
+            if visited[root] { continue; }
+            visited[root] = true;
+            order.push(root);
+
+            let mut next = order.len() - 1;
+            while next < order.len()
+            {
+                let node = order[next];
+                let mut neighbours = self.neighbours[self.offsets[node]..self.offsets[node + 1]].iter()
+                    .map(|&neighbour| neighbour as usize)
+                    .filter(|&neighbour| !std::mem::replace(&mut visited[neighbour], true))
+                    .collect::<Vec<usize>>();
+                neighbours.sort_by_key(|&neighbour| degree(neighbour));
+                order.extend(neighbours);
+                next += 1;
+            }
+        }
+        order.reverse();
+
+        let mut new_ids = vec![0u32; order.len()];
+        for (new_id, &old_id) in order.iter().enumerate() { new_ids[old_id] = new_id as u32; }
+
+        let mut offsets = Vec::with_capacity(self.offsets.len());
+        let mut neighbours = Vec::with_capacity(self.neighbours.len());
+        let mut weights = Vec::with_capacity(self.weights.len());
+        offsets.push(0);
+        for &old_id in &order
+        {
+            for edge in self.offsets[old_id]..self.offsets[old_id + 1]
+            {
+                neighbours.push(new_ids[self.neighbours[edge] as usize]);
+                weights.push(self.weights[edge]);
+            }
+            offsets.push(neighbours.len());
+        }
+
+        self.names = order.iter().map(|&old_id| self.names[old_id]).collect();
+        self.ids = self.names.iter().enumerate().map(|(id, &name)| (name, id as u32)).collect();
+        if self.incoming.is_some() { self.incoming = Some(transpose(&offsets, &neighbours, &weights)); }
+        self.offsets = offsets;
+        self.neighbours = neighbours;
+        self.weights = weights;
+        if let Some(matrix) = &mut self.heuristic { matrix.renumber(&order, &new_ids); }
+    }
+
+    ///
+    /// Precomputes the heuristic's estimates by ID, if it is a table, for
+    /// searches given the same heuristic to read instead
+    ///
+    /// - heuristic: the heuristic searches on the Graph are given
+    ///
+    pub fn set_heuristic(&mut self, heuristic: &dyn Heuristic)
+    {
+        self.heuristic = heuristic.matrix(self);
+    }
+
+    ///
+    /// - return: the heaviest edge's weight, or 0 without edges
+    ///
+    pub fn max_weight(&self) -> u64
+    {
+        self.weights.iter().max().cloned().unwrap_or(0)
+    }
+
+    ///
+    /// - return: the heuristic's estimates by ID, if it is a table
+    ///
+    pub fn heuristic(&self) -> Option<&HeuristicMatrix>
+    {
+        self.heuristic.as_ref()
+    }
+}
+
+impl<'a> SearchGraph<'a> for InternedGraph<'a>
+{
+    type Edges<'g> = Zip<Cloned<Iter<'g, u32>>, Cloned<Iter<'g, u64>>> where Self: 'g;
+
+    fn id(&self, name: &str) -> Option<u32>
+    {
+        self.ids.get(name).cloned()
+    }
+
+    fn name(&self, id: u32) -> &'a str
+    {
+        self.names[id as usize]
+    }
+
+    fn len(&self) -> usize
+    {
+        self.names.len()
+    }
+
+    fn edges(&self, id: u32) -> Self::Edges<'_>
+    {
+        let range = self.offsets[id as usize]..self.offsets[id as usize + 1];
+        self.neighbours[range.clone()].iter().cloned().zip(self.weights[range].iter().cloned())
+    }
+
+    fn edges_into(&self, id: u32) -> Option<Self::Edges<'_>>
+    {
+        match &self.incoming
+        {
+            Some((offsets, neighbours, weights)) =>
+            {
+                let range = offsets[id as usize]..offsets[id as usize + 1];
+                Some(neighbours[range.clone()].iter().cloned().zip(weights[range].iter().cloned()))
+            },
+            None => Some(self.edges(id)),
+        }
+    }
+
+    fn estimates_to(&self, goal: u32) -> Option<Estimates<'_>>
+    {
+        self.heuristic.as_ref().map(|matrix| Estimates::Ids(matrix.to(goal).unwrap_or(&[])))
+    }
+}
+
+///
+/// Transposes edges held in compressed sparse row form, so that each ID's
+/// edges are those reaching it, leading back to the ID they leave
+///
+/// - offsets, neighbours, weights: the edges leaving each ID, as held by
+///   an InternedGraph
+///
+/// - return: the edges reaching each ID, in the same form
+///
+fn transpose(offsets: &[usize], neighbours: &[u32], weights: &[u64]) -> (Vec<usize>, Vec<u32>, Vec<u64>)
+{
+    // Count the edges reaching each ID, then place each edge after those
+    // reaching the same ID before it
+    let mut into_offsets = vec![0; offsets.len()];
+    for &to in neighbours { into_offsets[to as usize + 1] += 1; }
+    for id in 1..into_offsets.len() { into_offsets[id] += into_offsets[id - 1]; }
+
+    let mut next = into_offsets.clone();
+    let (mut into_neighbours, mut into_weights) = (vec![0; neighbours.len()], vec![0; weights.len()]);
+    for from in 0..offsets.len() - 1
+    {
+        for edge in offsets[from]..offsets[from + 1]
+        {
+            let slot = &mut next[neighbours[edge] as usize];
+            into_neighbours[*slot] = from as u32;
+            into_weights[*slot] = weights[edge];
+            *slot += 1;
+        }
+    }
+
+    (into_offsets, into_neighbours, into_weights)
+}
+
+///
+/// Builds the route ending at end by following prev back to start,
+/// naming each location on it
+///
+fn trace_route<'a, G: SearchGraph<'a>>(graph: &G, prev: &[u32], start: u32, end: u32, cost: u64) -> PathResult<'a>
+{
+    let mut nodes = vec![graph.name(end)];
+    let mut current = end;
+    while current != start
+    {
+        current = prev[current as usize];
+        nodes.push(graph.name(current));
+    }
+    nodes.reverse();
+
+    PathResult { nodes, cost }
+}
+
+///
+/// A heuristic table's estimates by ID, held as a slice per goal, each
+/// indexed by the ID of the node the estimate is made from. Goals the
+/// table never mentions have no slice, and nodes missing from a goal's
+/// slice are estimated at 0, as in the table
+///
+/// - nodes: the # of nodes on the InternedGraph
+/// - goals: each goal ID's slice, if it has any estimates
+///
+#[derive(Debug, Clone, Default)]
+pub struct HeuristicMatrix
+{
+    nodes: usize,
+    goals: Vec<Option<Box<[u64]>>>,
+}
+
+impl HeuristicMatrix
+{
+    ///
+    /// Creates a matrix with no estimates
+    ///
+    /// - nodes: the # of nodes on the InternedGraph
+    ///
+    pub fn new(nodes: usize) -> Self
+    {
+        HeuristicMatrix { nodes, goals: vec![None; nodes] }
+    }
+
+    ///
+    /// Sets the estimate from one node to a goal
+    ///
+    pub fn insert(&mut self, node: u32, goal: u32, estimate: u64)
+    {
+        let nodes = self.nodes;
+        self.goals[goal as usize].get_or_insert_with(|| vec![0; nodes].into_boxed_slice())[node as usize] = estimate;
+    }
+
+    ///
+    /// Moves every estimate to the nodes' new IDs, as InternedGraph::reorder
+    /// renumbers them
+    ///
+    /// - order: each new ID's old ID
+    /// - new_ids: each old ID's new ID
+    ///
+    fn renumber(&mut self, order: &[usize], new_ids: &[u32])
+    {
+        let (nodes, mut goals) = (self.nodes, std::mem::take(&mut self.goals));
+        self.goals = order.iter().map(|&old_goal| goals[old_goal].take().map(|estimates|
+        {
+            let mut renumbered = vec![0; nodes].into_boxed_slice();
+            for (old_id, &estimate) in estimates.iter().enumerate() { renumbered[new_ids[old_id] as usize] = estimate; }
+            renumbered
+        })).collect();
+    }
+
+    ///
+    /// - return: every node's estimate to the goal, indexed by ID, or
+    ///   None if every estimate to it is 0
+    ///
+    pub fn to(&self, goal: u32) -> Option<&[u64]>
+    {
+        self.goals[goal as usize].as_deref()
+    }
+}
+
+///
+/// Computes the shortest route as astar::astar_observed does, over an
+/// InternedGraph or other SearchGraph, keeping each node's distance and
+/// previous node in the context's vectors, indexed by ID. Estimates are
+/// read from the Graph's precomputed estimates, when it has them
+///
+/// - graph: the Graph to search
+/// - heuristic: the A* heuristic, or None for Djikstra's algorithm
+/// - start: the start location on the Graph
+/// - end: the end location on the Graph
+/// - tie_break: how to choose between nodes with equal f-values
+/// - context: the buffers to search in, and the queue to keep the
+///   frontier in. Its Counters are left counting what the search did
+/// - on_expand: called with the Expansion of each node considered, in
+///   the order they are taken from the queue
+///
+/// - return: the route (None if end cannot be reached), and the # of
+///   nodes considered
+///
+pub fn astar<'a, G: SearchGraph<'a>, F: FnMut(Expansion<'a>)>(graph: &G, heuristic: Option<&dyn Heuristic>, start: &str, end: &str, tie_break: TieBreak, context: &mut SearchContext, mut on_expand: F) -> (Option<PathResult<'a>>, usize)
+{
+    let (start, end) = match (graph.id(start), graph.id(end))
+    {
+        (Some(start), Some(end)) => (start, end),
+        _ => return (None, 0),
+    };
+    let goal = graph.name(end);
+    let table = heuristic.and_then(|_| graph.estimates_to(end));
+    let estimate = |node: u32| match (table, heuristic)
+    {
+        (Some(table), _) => table.get(node),
+        (None, Some(heuristic)) => heuristic.estimate(graph.name(node), goal),
+        (None, None) => 0,
+    };
+
+    context.reset(graph.len());
+    context.reach(start, 0, UNREACHED);
+
+    let mut route_dists = context.take_queue(graph.len());
+    route_dists.push(start, tie_break.priority(0, estimate(start), 0));
+    let mut queued = 1;
+    let mut node_counter = 0;
+    let mut stale = 0;
+    let mut route = None;
+    context.counters.peak_frontier = 1;
+
+    while let Some(node) = route_dists.pop(&mut stale)
+    {
+        context.expand(node);
+        let g = context.dist[node as usize];
+        let parent = context.prev[node as usize];
+        on_expand(Expansion
+        {
+            order: node_counter,
+            node: graph.name(node),
+            parent: (parent != UNREACHED).then(|| graph.name(parent)),
+            g,
+            frontier: route_dists.len(),
+        });
+        node_counter += 1;
+
+        if node == end
+        {
+            route = Some(trace_route(graph, &context.prev, start, end, g));
+            break;
+        }
+
+        for (next, weight) in graph.edges(node)
+        {
+            let alt_route = g + weight;
+            if alt_route < context.dist[next as usize]
+            {
+                context.reach(next, alt_route, node);
+                debug!("Relaxed {} through {}, to a weight of {}", graph.name(next), graph.name(node), alt_route);
+
+                route_dists.push(next, tie_break.priority(alt_route, estimate(next), queued));
+                queued += 1;
+            }
+        }
+        context.counters.peak_frontier = context.counters.peak_frontier.max(route_dists.len());
+    }
+
+    // Every push past the start's was a relaxation
+    context.counters.pushes = queued as usize;
+    context.counters.relaxations = queued as usize - 1;
+    context.counters.stale_pops = stale;
+    context.counters.pops = node_counter + stale;
+
+    context.restore_queue(route_dists, graph.len());
+    (route, node_counter)
+}
+
+///
+/// Computes the shortest route as fringe::fringe_search does, over an
+/// InternedGraph or other SearchGraph, keeping each node's distance,
+/// previous node and fringe version in the context's vectors, indexed by
+/// ID. Estimates are read from the Graph's precomputed estimates, when it
+/// has them
+///
+/// - graph: the Graph to search
+/// - heuristic: estimates the distance between any two nodes
+/// - start: the start location on the Graph
+/// - end: the end location on the Graph
+/// - context: the buffers to search in. Its Counters are left counting
+///   what the search did, taking the fringe as its queue
+///
+/// - return: the route (None if end cannot be reached), and the # of
+///   nodes considered
+///
+pub fn fringe_search<'a, G: SearchGraph<'a>>(graph: &G, heuristic: &dyn Heuristic, start: &str, end: &str, context: &mut SearchContext) -> (Option<PathResult<'a>>, usize)
+{
+    let (start, end) = match (graph.id(start), graph.id(end))
+    {
+        (Some(start), Some(end)) => (start, end),
+        _ => return (None, 0),
+    };
+    let goal = graph.name(end);
+
+    context.reset(graph.len());
+    context.reach(start, 0, UNREACHED);
+    context.versions[start as usize] = 0;
+
+    let mut now = VecDeque::new();
+    now.push_back((start, 0));
+
+    let table = graph.estimates_to(end);
+    let estimate = |node: u32| match table
+    {
+        Some(table) => table.get(node),
+        None => heuristic.estimate(graph.name(node), goal),
+    };
+
+    let mut threshold = estimate(start);
+    let mut node_counter = 0;
+    context.counters.pushes = 1;
+    context.counters.peak_frontier = 1;
+
+    while !now.is_empty()
+    {
+        let mut later = VecDeque::new();
+        let mut next_threshold = u64::MAX;
+
+        while let Some((node, version)) = now.pop_front()
+        {
+            context.counters.pops += 1;
+            if context.versions[node as usize] != version { context.counters.stale_pops += 1; continue; }
+
+            let f = context.dist[node as usize] + estimate(node);
+            if f > threshold
+            {
+                next_threshold = next_threshold.min(f);
+                later.push_back((node, version));
+                context.counters.pushes += 1;
+                continue;
+            }
+
+            node_counter += 1;
+            context.expand(node);
+            if node == end
+            {
+                return (Some(trace_route(graph, &context.prev, start, end, context.dist[end as usize])), node_counter);
+            }
+
+            context.versions[node as usize] = version + 1;
+
+            // Children are pushed to the front, in reverse, so that they
+            // are visited immediately after their parent in edge order
+            for (next, weight) in graph.edges(node).rev()
+            {
+                let alt_route = context.dist[node as usize] + weight;
+                if alt_route >= context.dist[next as usize] { continue; }
+
+                context.reach(next, alt_route, node);
+
+                let version = context.versions[next as usize].wrapping_add(1);
+                context.versions[next as usize] = version;
+                now.push_front((next, version));
+                context.counters.pushes += 1;
+                context.counters.relaxations += 1;
+            }
+            context.counters.peak_frontier = context.counters.peak_frontier.max(now.len() + later.len());
+        }
+
+        threshold = next_threshold;
+        now = later;
+    }
+
+    (None, node_counter)
+}
@@ -0,0 +1,286 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use priority_queue::PriorityQueue;
+
+use super::grid::{Cell, Connectivity, Grid, GridPath};
+
+///
+/// Computes the shortest route across an 8-connected, uniform-cost Grid
+/// using Jump Point Search.
+///
+/// JPS is A* with symmetry pruning: rather than pushing every neighbor,
+/// it jumps in straight and diagonal lines until reaching a "jump point"
+/// (the end, or a cell with a neighbor that can only be optimally reached
+/// through it), and only queues those. Routes are identical in cost to
+/// plain A*, while far fewer cells pass through the priority queue.
+///
+/// Diagonal moves follow the same rule as Grid::neighbors, never cutting
+/// the corner of a wall.
+///
+/// - grid: the Grid to search
+/// - start: the start cell
+/// - end: the end cell
+///
+/// - return: the route (None if end cannot be reached), and the # of
+///   jump points considered. The route lists every cell, not only the
+///   jump points
+///
+pub fn jump_point_search(grid: &Grid, start: Cell, end: Cell) -> (Option<GridPath>, usize)
+{
+    if !grid.is_passable(start) || !grid.is_passable(end) { return (None, 0); }
+
+    let mut dist: HashMap<Cell, u64> = HashMap::new();
+    let mut prev: HashMap<Cell, Cell> = HashMap::new();
+    dist.insert(start, 0);
+
+    let mut open = PriorityQueue::new();
+    open.push(start, Reverse(grid.distance(start, end, Connectivity::Eight)));
+
+    let mut node_counter = 0;
+    while let Some((cell, _)) = open.pop()
+    {
+        node_counter += 1;
+        if cell == end
+        {
+            return (Some(build_path(&prev, start, end, dist[&end])), node_counter);
+        }
+
+        let (x, y) = (cell.0 as i64, cell.1 as i64);
+        for (dx, dy) in directions(grid, cell, prev.get(&cell).cloned())
+        {
+            let jump_point = match jump(grid, x + dx, y + dy, dx, dy, end)
+            {
+                Some(jump_point) => jump_point,
+                None => continue,
+            };
+
+            let alt_route = dist[&cell] + grid.distance(cell, jump_point, Connectivity::Eight);
+            if dist.get(&jump_point).is_none_or(|d| alt_route < *d)
+            {
+                dist.insert(jump_point, alt_route);
+                prev.insert(jump_point, cell);
+                open.push(jump_point, Reverse(alt_route + grid.distance(jump_point, end, Connectivity::Eight)));
+            }
+        }
+    }
+
+    (None, node_counter)
+}
+
+///
+/// Lists the directions worth searching from cell, given the direction it
+/// was entered from. Directions that a route could take more cheaply
+/// without passing through cell are pruned
+///
+fn directions(grid: &Grid, cell: Cell, parent: Option<Cell>) -> Vec<(i64, i64)>
+{
+    let (x, y) = (cell.0 as i64, cell.1 as i64);
+    let open = |dx: i64, dy: i64| grid.is_passable_at(x + dx, y + dy);
+
+    let parent = match parent
+    {
+        Some(parent) => parent,
+
+        // The start cell searches in every direction
+        None => return grid.neighbors(cell, Connectivity::Eight).into_iter()
+            .map(|((nx, ny), _)| (nx as i64 - x, ny as i64 - y))
+            .collect(),
+    };
+
+    let dx = (x - parent.0 as i64).signum();
+    let dy = (y - parent.1 as i64).signum();
+    let mut directions = Vec::new();
+
+    if dx != 0 && dy != 0
+    {
+        // Diagonal: continue straight along both axes, and diagonally
+        if open(0, dy) { directions.push((0, dy)); }
+        if open(dx, 0) { directions.push((dx, 0)); }
+        if open(0, dy) && open(dx, 0) && open(dx, dy) { directions.push((dx, dy)); }
+    }
+    else if dx != 0
+    {
+        // Horizontal: continue, and turn wherever a wall behind has opened up
+        if open(dx, 0)
+        {
+            directions.push((dx, 0));
+            if open(0, 1) && open(dx, 1) { directions.push((dx, 1)); }
+            if open(0, -1) && open(dx, -1) { directions.push((dx, -1)); }
+        }
+        if open(0, 1) { directions.push((0, 1)); }
+        if open(0, -1) { directions.push((0, -1)); }
+    }
+    else
+    {
+        // Vertical: as horizontal, with the axes swapped
+        if open(0, dy)
+        {
+            directions.push((0, dy));
+            if open(1, 0) && open(1, dy) { directions.push((1, dy)); }
+            if open(-1, 0) && open(-1, dy) { directions.push((-1, dy)); }
+        }
+        if open(1, 0) { directions.push((1, 0)); }
+        if open(-1, 0) { directions.push((-1, 0)); }
+    }
+
+    directions
+}
+
+///
+/// Moves from (x, y) in direction (dx, dy) until a jump point is found
+///
+/// - return: the jump point, or None if a wall or the Grid's edge is
+///   reached first
+///
+fn jump(grid: &Grid, mut x: i64, mut y: i64, dx: i64, dy: i64, end: Cell) -> Option<Cell>
+{
+    let open = |x: i64, y: i64| grid.is_passable_at(x, y);
+
+    loop
+    {
+        if !open(x, y) { return None; }
+        if (x as usize, y as usize) == end { return Some(end); }
+
+        if dx != 0 && dy != 0
+        {
+            // A diagonal move stops wherever either straight
+            // component leads to a jump point
+            if jump(grid, x + dx, y, dx, 0, end).is_some() ||
+               jump(grid, x, y + dy, 0, dy, end).is_some()
+            {
+                return Some((x as usize, y as usize));
+            }
+        }
+        else if dx != 0
+        {
+            // Forced neighbor: a wall behind us on either side has ended
+            if (open(x, y - 1) && !open(x - dx, y - 1)) ||
+               (open(x, y + 1) && !open(x - dx, y + 1))
+            {
+                return Some((x as usize, y as usize));
+            }
+        }
+        else if (open(x - 1, y) && !open(x - 1, y - dy)) ||
+                (open(x + 1, y) && !open(x + 1, y - dy))
+        {
+            return Some((x as usize, y as usize));
+        }
+
+        // Diagonals may not cut corners
+        if !(open(x + dx, y) && open(x, y + dy)) { return None; }
+        x += dx;
+        y += dy;
+    }
+}
+
+///
+/// Builds the route ending at end, filling in every cell between
+/// consecutive jump points
+///
+fn build_path(prev: &HashMap<Cell, Cell>, start: Cell, end: Cell, cost: u64) -> GridPath
+{
+    let mut jump_points = vec![end];
+    let mut current = end;
+    while current != start
+    {
+        current = prev[&current];
+        jump_points.push(current);
+    }
+    jump_points.reverse();
+
+    let mut cells = vec![start];
+    for pair in jump_points.windows(2)
+    {
+        let (mut x, mut y) = (pair[0].0 as i64, pair[0].1 as i64);
+        let (tx, ty) = (pair[1].0 as i64, pair[1].1 as i64);
+        while (x, y) != (tx, ty)
+        {
+            x += (tx - x).signum();
+            y += (ty - y).signum();
+            cells.push((x as usize, y as usize));
+        }
+    }
+
+    GridPath { cells, cost }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::jump_point_search;
+    use crate::algo::astar::TieBreak;
+    use crate::algo::grid::{astar, Cell, Connectivity, Grid};
+
+    ///
+    /// Routes with both Jump Point Search and A*, checking they agree on
+    /// the route's cost and that the JPS route moves one passable cell at
+    /// a time
+    ///
+    /// - return: the cost of the route, or None if end cannot be reached
+    ///
+    fn compare(grid: &Grid, start: Cell, end: Cell) -> Option<u64>
+    {
+        let (jps, _) = jump_point_search(grid, start, end);
+        let (astar, _) = astar(grid, start, end, Connectivity::Eight, TieBreak::default());
+        assert_eq!(jps.as_ref().map(|path| path.cost), astar.as_ref().map(|path| path.cost));
+
+        if let Some(path) = &jps
+        {
+            assert_eq!((path.cells.first(), path.cells.last()), (Some(&start), Some(&end)));
+            for step in path.cells.windows(2)
+            {
+                assert!(grid.neighbors(step[0], Connectivity::Eight).iter().any(|&(cell, _)| cell == step[1]));
+            }
+        }
+        jps.map(|path| path.cost)
+    }
+
+    #[test]
+    fn open_grid()
+    {
+        let grid = Grid::new(10, 10);
+        assert_eq!(compare(&grid, (0, 0), (9, 6)), Some(6 * 14 + 3 * 10));
+        assert_eq!(compare(&grid, (9, 9), (9, 0)), Some(9 * 10));
+        assert_eq!(compare(&grid, (4, 4), (4, 4)), Some(0));
+    }
+
+    #[test]
+    fn walled_grid()
+    {
+        // A wall down column 5, open only at the bottom row
+        let mut grid = Grid::new(10, 10);
+        for y in 0..9 { grid.set_blocked((5, y), true); }
+        for y in 2..6 { grid.set_blocked((2, y), true); }
+
+        assert!(compare(&grid, (0, 0), (9, 0)).is_some());
+        assert!(compare(&grid, (1, 4), (8, 2)).is_some());
+    }
+
+    #[test]
+    fn corner_cutting()
+    {
+        // The diagonal step from (0, 0) to (1, 1) would cut the corner of
+        // the wall at (1, 0), so the route steps around it
+        let mut grid = Grid::new(3, 3);
+        grid.set_blocked((1, 0), true);
+        assert_eq!(compare(&grid, (0, 0), (1, 1)), Some(20));
+
+        // Walls either side of the diagonal close it entirely
+        grid.set_blocked((0, 1), true);
+        assert_eq!(compare(&grid, (0, 0), (1, 1)), None);
+    }
+
+    #[test]
+    fn unreachable()
+    {
+        // The end is walled in
+        let mut grid = Grid::new(8, 8);
+        for &cell in &[(5, 5), (6, 5), (7, 5), (5, 6), (5, 7)] { grid.set_blocked(cell, true); }
+        assert_eq!(compare(&grid, (0, 0), (7, 7)), None);
+
+        // Neither a blocked nor an off-grid cell can be routed to
+        assert_eq!(compare(&grid, (0, 0), (5, 5)), None);
+        assert_eq!(compare(&grid, (0, 0), (8, 0)), None);
+    }
+}
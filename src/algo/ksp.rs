@@ -0,0 +1,459 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use bumpalo::Bump;
+
+use super::astar::{self, TieBreak};
+use super::{shortest_path_tree, PathResult, RouteGraph};
+
+///
+/// The algorithms available for finding the k shortest routes, which
+/// answer the same queries at different costs
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum KspBackend
+{
+    /// Yen's algorithm: a search for every location along each route
+    /// found, giving only routes which never revisit a location. Best
+    /// when a handful of alternatives are wanted
+    #[default]
+    Yen,
+
+    /// Eppstein's algorithm: one search, after which each route is
+    /// nearly free, but routes may revisit locations. Best when dozens or
+    /// hundreds of alternatives are wanted
+    Eppstein,
+}
+
+impl KspBackend
+{
+    /// Every backend
+    pub const ALL: [KspBackend; 2] = [KspBackend::Yen, KspBackend::Eppstein];
+
+    ///
+    /// - return: the backend's display name
+    ///
+    pub fn name(self) -> &'static str
+    {
+        match self
+        {
+            KspBackend::Yen => "yen",
+            KspBackend::Eppstein => "eppstein",
+        }
+    }
+
+    ///
+    /// Finds the backend with the given name, such as "yen"
+    ///
+    pub fn from_name(name: &str) -> Option<KspBackend>
+    {
+        match name.to_lowercase().as_str()
+        {
+            "yen" => Some(KspBackend::Yen),
+            "eppstein" => Some(KspBackend::Eppstein),
+            _ => None,
+        }
+    }
+}
+
+///
+/// A node in a persistent leftist heap of sidetrack edges. A sidetrack
+/// is an edge that leaves the shortest path tree towards the destination;
+/// its key is the extra distance incurred by taking it. Heap nodes are
+/// allocated in the search's arena, and never freed one at a time
+///
+struct SidetrackHeap<'a, 'b>
+{
+    key: u64,
+    rank: usize,
+    edge: (&'a str, &'a str),
+    left: Option<&'b SidetrackHeap<'a, 'b>>,
+    right: Option<&'b SidetrackHeap<'a, 'b>>,
+}
+
+///
+/// The chain of sidetracks taken by a candidate route, stored
+/// newest-first so candidates can share their common prefixes. Links are
+/// allocated in the search's arena
+///
+struct SidetrackChain<'a, 'b>
+{
+    edge: (&'a str, &'a str),
+    parent: Option<&'b SidetrackChain<'a, 'b>>,
+}
+
+fn rank(heap: Option<&SidetrackHeap>) -> usize
+{
+    heap.map_or(0, |h| h.rank)
+}
+
+///
+/// Persistently merges two leftist heaps, leaving both arguments untouched
+///
+/// - arena: where the merged heap's new nodes are allocated
+///
+fn merge<'a, 'b>(arena: &'b Bump, a: Option<&'b SidetrackHeap<'a, 'b>>, b: Option<&'b SidetrackHeap<'a, 'b>>) -> Option<&'b SidetrackHeap<'a, 'b>>
+{
+    match (a, b)
+    {
+        (None, heap) | (heap, None) => heap,
+        (Some(a), Some(b)) =>
+        {
+            // Keep the smaller key at the root
+            let (a, b) = if a.key <= b.key { (a, b) } else { (b, a) };
+
+            let right = merge(arena, a.right, Some(b));
+            let left = a.left;
+
+            // Maintain the leftist property: the left spine is never shorter
+            let (left, right) = if rank(left) >= rank(right) { (left, right) } else { (right, left) };
+
+            Some(arena.alloc(SidetrackHeap { key: a.key, rank: rank(right) + 1, edge: a.edge, left, right }))
+        }
+    }
+}
+
+///
+/// Computes the k shortest routes from start to end using Eppstein's
+/// algorithm. A single reverse Dijkstra search is performed from end,
+/// after which every further route costs only O(log k) to produce, making
+/// this the right choice when dozens or hundreds of alternatives are wanted.
+///
+/// Eppstein's algorithm enumerates walks, not simple paths: for large k
+/// some routes may pass through the same location more than once.
+///
+/// - graph: the Graph to search
+/// - start: the start location on the Graph
+/// - end: the end location on the Graph
+/// - k: the maximum number of routes to return
+/// - arena: where the sidetrack heaps and chains are allocated. It is
+///   reset first, so that one arena's memory can be reused by every query
+///
+/// - return: up to k routes, in non-decreasing order of cost. Empty if
+///   end cannot be reached from start
+///
+pub fn eppstein<'a>(graph: &RouteGraph<'a>, start: &'a str, end: &'a str, k: usize, arena: &mut Bump) -> Vec<PathResult<'a>>
+{
+    arena.reset();
+    let arena = &*arena;
+    let mut routes = Vec::new();
+
+    // Distances from every node to end, and each node's next hop
+    // towards end along the shortest path tree
+    let tree = shortest_path_tree(graph, end);
+    let (dist, next) = (&tree.dist, &tree.prev);
+
+    if k == 0 || !dist.contains_key(start) { return routes; }
+
+    // Visit the shortest path tree breadth-first from end, so each node's
+    // next hop has its heap built before the node itself. Ordering by
+    // distance alone would not do: across a zero-weight edge a node and
+    // its next hop are equally far from end
+    let mut children: HashMap<&'a str, Vec<&'a str>> = HashMap::new();
+    for (&node, &hop) in next { children.entry(hop).or_default().push(node); }
+    let mut order = vec![end];
+    let mut i = 0;
+    while i < order.len()
+    {
+        order.extend(children.get(order[i]).into_iter().flatten());
+        i += 1;
+    }
+
+    //
+    // For every node, a heap of all sidetracks available from it or from
+    // any node further along its tree path to end. Each heap shares
+    // structure with its next hop's heap
+    //
+    let mut heaps: HashMap<&'a str, Option<&SidetrackHeap<'a, '_>>> = HashMap::new();
+    for node in order
+    {
+        let mut heap = match next.get(node)
+        {
+            Some(hop) => heaps[hop],
+            None => None,
+        };
+
+        for edge in graph.edges(node)
+        {
+            // Skip the tree edge itself, and any edge to a node
+            // which cannot reach end
+            if next.get(node) == Some(&edge.1) || !dist.contains_key(edge.1) { continue; }
+
+            let key = edge.2 + dist[edge.1] - dist[node];
+            heap = merge(arena, heap, Some(arena.alloc(SidetrackHeap { key, rank: 1, edge: (node, edge.1), left: None, right: None })));
+        }
+
+        heaps.insert(node, heap);
+    }
+
+    // The first route takes no sidetracks at all
+    routes.push(build_route(graph, start, end, next, None));
+
+    //
+    // Each candidate is a route cost, the heap node supplying its final
+    // sidetrack, and the chain of sidetracks taken before it. Popping
+    // candidates in cost order yields the routes in cost order
+    //
+    let mut candidates = BinaryHeap::new();
+    let mut pending: Vec<(&SidetrackHeap<'a, '_>, Option<&SidetrackChain<'a, '_>>)> = Vec::new();
+    if let Some(root) = heaps[start]
+    {
+        candidates.push(Reverse((dist[start] + root.key, pending.len())));
+        pending.push((root, None));
+    }
+
+    while routes.len() < k
+    {
+        let (cost, index) = match candidates.pop()
+        {
+            Some(Reverse(candidate)) => candidate,
+            None => break,
+        };
+        let (node, prefix) = pending[index];
+
+        let chain = &*arena.alloc(SidetrackChain { edge: node.edge, parent: prefix });
+        let mut route = build_route(graph, start, end, next, Some(chain));
+        route.cost = cost;
+        routes.push(route);
+
+        // Swap the final sidetrack for either of its heap children
+        for child in node.left.into_iter().chain(node.right)
+        {
+            candidates.push(Reverse((cost - node.key + child.key, pending.len())));
+            pending.push((child, prefix));
+        }
+
+        // Or keep it, and append the cheapest sidetrack reachable afterwards
+        if let Some(root) = heaps[node.edge.1]
+        {
+            candidates.push(Reverse((cost + root.key, pending.len())));
+            pending.push((root, Some(chain)));
+        }
+    }
+
+    routes
+}
+
+///
+/// Computes the k shortest routes from start to end with the given
+/// backend
+///
+/// - graph: the Graph to search
+/// - start: the start location on the Graph
+/// - end: the end location on the Graph
+/// - k: the maximum number of routes to return
+/// - backend: the algorithm to compute them with
+/// - arena: where Eppstein's algorithm allocates its sidetracks, as for
+///   eppstein
+///
+/// - return: up to k routes, in non-decreasing order of cost. Empty if
+///   end cannot be reached from start
+///
+pub fn k_shortest<'a>(graph: &RouteGraph<'a>, start: &'a str, end: &'a str, k: usize, backend: KspBackend, arena: &mut Bump) -> Vec<PathResult<'a>>
+{
+    match backend
+    {
+        KspBackend::Yen => yen(graph, start, end, k),
+        KspBackend::Eppstein => eppstein(graph, start, end, k, arena),
+    }
+}
+
+///
+/// Computes the k shortest routes from start to end which never revisit
+/// a location, using Yen's algorithm. Each route found is branched from
+/// at every location along it: the route is followed to that location,
+/// and the rest found by a search which may neither leave it as an
+/// earlier route with the same beginning did, nor return to a location
+/// already passed. The cheapest of these candidates is the next route.
+///
+/// - graph: the Graph to search
+/// - start: the start location on the Graph
+/// - end: the end location on the Graph
+/// - k: the maximum number of routes to return
+///
+/// - return: up to k routes, in non-decreasing order of cost. Empty if
+///   end cannot be reached from start
+///
+pub fn yen<'a>(graph: &RouteGraph<'a>, start: &'a str, end: &'a str, k: usize) -> Vec<PathResult<'a>>
+{
+    let mut routes: Vec<PathResult<'a>> = Vec::new();
+    if k == 0 { return routes; }
+    match astar::astar(graph, None, start, end, TieBreak::default()).0
+    {
+        Some(route) => routes.push(route),
+        None => return routes,
+    }
+
+    let mut candidates: Vec<PathResult<'a>> = Vec::new();
+    while routes.len() < k
+    {
+        let last = routes[routes.len() - 1].nodes.clone();
+        for i in 0..last.len() - 1
+        {
+            let (spur, root) = (last[i], &last[..=i]);
+
+            let mut pruned = graph.clone();
+            for route in routes.iter().filter(|route| route.nodes.len() > i + 1 && route.nodes[..=i] == *root)
+            {
+                pruned.remove_edge(route.nodes[i], route.nodes[i + 1]);
+            }
+            for &node in &root[..i] { pruned.remove_node(node); }
+
+            if let Some(spur_route) = astar::astar(&pruned, None, spur, end, TieBreak::default()).0
+            {
+                let root_cost = root.windows(2).map(|leg| graph.edge_weight(leg[0], leg[1]).unwrap()).sum::<u64>();
+                let candidate = PathResult { nodes: root[..i].iter().cloned().chain(spur_route.nodes).collect(), cost: root_cost + spur_route.cost };
+                if !candidates.contains(&candidate) && !routes.contains(&candidate) { candidates.push(candidate); }
+            }
+        }
+
+        let cheapest = match candidates.iter().enumerate().min_by_key(|(_, candidate)| candidate.cost)
+        {
+            Some((index, _)) => index,
+            None => break,
+        };
+        routes.push(candidates.remove(cheapest));
+    }
+
+    routes
+}
+
+///
+/// Expands a chain of sidetracks into the full sequence of nodes, following
+/// the shortest path tree between consecutive sidetracks
+///
+fn build_route<'a>(graph: &RouteGraph<'a>, start: &'a str, end: &'a str, next: &HashMap<&'a str, &'a str>, chain: Option<&SidetrackChain<'a, '_>>) -> PathResult<'a>
+{
+    // Collect the sidetracks oldest-first
+    let mut sidetracks = Vec::new();
+    let mut link = chain;
+    while let Some(current) = link
+    {
+        sidetracks.push(current.edge);
+        link = current.parent;
+    }
+    sidetracks.reverse();
+
+    let mut nodes = vec![start];
+    let mut cost = 0;
+    let mut current = start;
+    for (from, to) in sidetracks.into_iter().chain(std::iter::once((end, end)))
+    {
+        // Follow the tree until the sidetrack's tail is reached
+        while current != from
+        {
+            let hop = next[current];
+            cost += graph.edge_weight(current, hop).unwrap();
+            nodes.push(hop);
+            current = hop;
+        }
+
+        if from != to
+        {
+            cost += graph.edge_weight(from, to).unwrap();
+            nodes.push(to);
+            current = to;
+        }
+    }
+
+    PathResult { nodes, cost }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use bumpalo::Bump;
+
+    use super::{eppstein, yen};
+    use crate::algo::RouteGraph;
+
+    ///
+    /// A small Graph with several routes from a to f, some of equal cost
+    ///
+    fn graph() -> RouteGraph<'static>
+    {
+        let mut graph = RouteGraph::new();
+        for &(from, to, weight) in &[("a", "b", 10), ("a", "c", 20), ("b", "c", 5), ("b", "d", 30), ("c", "d", 10),
+                                     ("c", "e", 25), ("d", "e", 5), ("d", "f", 20), ("e", "f", 10), ("b", "f", 60)]
+        {
+            graph.add_edge(from, to, weight);
+        }
+        graph
+    }
+
+    ///
+    /// Finds the cost of every route from node to end costing no more than
+    /// bound, by trying every way on from each location reached
+    ///
+    /// - simple: whether routes may not revisit a location
+    ///
+    #[allow(clippy::too_many_arguments)]
+    fn every_cost(graph: &RouteGraph, node: &str, end: &str, cost: u64, bound: u64, simple: bool, visited: &mut Vec<String>, costs: &mut Vec<u64>)
+    {
+        if node == end { costs.push(cost); }
+        visited.push(node.to_string());
+        for (_, next, &weight) in graph.edges(node)
+        {
+            if cost + weight > bound || (simple && visited.iter().any(|visited| visited == next)) { continue; }
+            every_cost(graph, next, end, cost + weight, bound, simple, visited, costs);
+        }
+        visited.pop();
+    }
+
+    ///
+    /// - return: the costs of the k cheapest routes from start to end,
+    ///   given that none of them costs more than bound
+    ///
+    fn first_costs(graph: &RouteGraph, start: &str, end: &str, k: usize, bound: u64, simple: bool) -> Vec<u64>
+    {
+        let mut costs = Vec::new();
+        every_cost(graph, start, end, 0, bound, simple, &mut Vec::new(), &mut costs);
+        costs.sort_unstable();
+        costs.truncate(k);
+        costs
+    }
+
+    #[test]
+    fn eppstein_matches_every_walk()
+    {
+        let graph = graph();
+        let routes = eppstein(&graph, "a", "f", 12, &mut Bump::new());
+        let bound = routes[routes.len() - 1].cost;
+        assert_eq!(routes.iter().map(|route| route.cost).collect::<Vec<u64>>(), first_costs(&graph, "a", "f", 12, bound, false));
+
+        for route in &routes
+        {
+            assert_eq!((route.nodes.first(), route.nodes.last()), (Some(&"a"), Some(&"f")));
+            assert_eq!(route.nodes.windows(2).map(|leg| graph.edge_weight(leg[0], leg[1]).unwrap()).sum::<u64>(), route.cost);
+        }
+    }
+
+    #[test]
+    fn yen_matches_every_simple_route()
+    {
+        let graph = graph();
+        let routes = yen(&graph, "a", "f", 8);
+        let bound = routes[routes.len() - 1].cost;
+        assert_eq!(routes.iter().map(|route| route.cost).collect::<Vec<u64>>(), first_costs(&graph, "a", "f", 8, bound, true));
+
+        for (i, route) in routes.iter().enumerate()
+        {
+            let mut nodes = route.nodes.clone();
+            nodes.sort_unstable();
+            nodes.dedup();
+            assert_eq!(nodes.len(), route.nodes.len());
+            assert!(!routes[..i].contains(route));
+        }
+    }
+
+    #[test]
+    fn unreachable_and_zero()
+    {
+        let mut graph = graph();
+        graph.add_node("z");
+        assert!(eppstein(&graph, "a", "z", 5, &mut Bump::new()).is_empty());
+        assert!(yen(&graph, "a", "z", 5).is_empty());
+        assert!(eppstein(&graph, "a", "f", 0, &mut Bump::new()).is_empty());
+        assert!(yen(&graph, "a", "f", 0).is_empty());
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+
+use super::{Heuristic, KeyedQueue, PathResult, RouteGraph};
+
+/// Distance value used for nodes which have not (yet) been reached
+const INFINITY: u64 = u64::MAX;
+
+///
+/// A Lifelong Planning A* search between a fixed start and goal. The
+/// search state is kept between queries, so after small edge updates
+/// the next query only re-expands the nodes whose distances changed,
+/// reusing the rest of the previous search effort.
+///
+/// The search owns its own copy of the Graph, so weight changes made
+/// through it never affect the Graph it was created from.
+///
+pub struct LpaStar<'a, 'h>
+{
+    graph: RouteGraph<'a>,
+    heuristic: &'h dyn Heuristic,
+    start: &'a str,
+    goal: &'a str,
+
+    g: HashMap<&'a str, u64>,
+    rhs: HashMap<&'a str, u64>,
+    queue: KeyedQueue<'a>,
+
+    // Counter for total # of nodes expanded over the search's lifetime
+    expanded: usize,
+}
+
+impl<'a, 'h> LpaStar<'a, 'h>
+{
+    ///
+    /// Creates a new search between start and goal. No searching is
+    /// performed until route is first called
+    ///
+    /// - graph: the Graph to search over (copied)
+    /// - heuristic: estimates the distance between any two nodes
+    /// - start: the start location
+    /// - goal: the destination
+    ///
+    pub fn new(graph: &RouteGraph<'a>, heuristic: &'h dyn Heuristic, start: &'a str, goal: &'a str) -> Self
+    {
+        let mut search = LpaStar
+        {
+            graph: graph.clone(),
+            heuristic,
+            start,
+            goal,
+            g: HashMap::new(),
+            rhs: HashMap::new(),
+            queue: KeyedQueue::new(),
+            expanded: 0,
+        };
+
+        search.rhs.insert(start, 0);
+        let key = search.calculate_key(start);
+        search.queue.insert(start, key);
+
+        search
+    }
+
+    ///
+    /// Computes (or repairs) the shortest route from start to goal
+    ///
+    /// - return: the route, or None if the goal cannot be reached
+    ///
+    pub fn route(&mut self) -> Option<PathResult<'a>>
+    {
+        self.compute_shortest_path();
+        if self.g(self.goal) == INFINITY { return None; }
+
+        // Walk back from goal, always stepping to the predecessor
+        // which provides the node's distance
+        let mut nodes = vec![self.goal];
+        let mut current = self.goal;
+        while current != self.start
+        {
+            current = self.graph.edges(current)
+                .min_by_key(|edge| self.g(edge.1).saturating_add(*edge.2))?
+                .1;
+            nodes.push(current);
+
+            // Guard against walking in circles on an inconsistent state
+            if nodes.len() > self.graph.node_count() { return None; }
+        }
+        nodes.reverse();
+
+        Some(PathResult { nodes, cost: self.g(self.goal) })
+    }
+
+    ///
+    /// Changes the weight of the edge between a and b, feeding the affected
+    /// nodes into the incremental queue for the next call to route
+    ///
+    /// - a, b: the edge's end nodes
+    /// - weight: the new weight, or None to remove the edge entirely
+    ///
+    pub fn update_edge(&mut self, a: &'a str, b: &'a str, weight: Option<u64>)
+    {
+        match weight
+        {
+            Some(weight) => { self.graph.add_edge(a, b, weight); },
+            None => { self.graph.remove_edge(a, b); }
+        }
+
+        self.update_vertex(a);
+        self.update_vertex(b);
+    }
+
+    ///
+    /// - return: the total # of nodes expanded since the search was created
+    ///
+    pub fn expanded(&self) -> usize
+    {
+        self.expanded
+    }
+
+    fn g(&self, node: &'a str) -> u64
+    {
+        *self.g.get(node).unwrap_or(&INFINITY)
+    }
+
+    fn rhs(&self, node: &'a str) -> u64
+    {
+        *self.rhs.get(node).unwrap_or(&INFINITY)
+    }
+
+    fn calculate_key(&self, node: &'a str) -> (u64, u64)
+    {
+        let min = self.g(node).min(self.rhs(node));
+        (min.saturating_add(self.heuristic.estimate(node, self.goal)), min)
+    }
+
+    ///
+    /// Recomputes node's one-step lookahead distance, and queues
+    /// it if it has become inconsistent
+    ///
+    fn update_vertex(&mut self, node: &'a str)
+    {
+        if node != self.start
+        {
+            let rhs = self.graph.edges(node)
+                .map(|edge| self.g(edge.1).saturating_add(*edge.2))
+                .min()
+                .unwrap_or(INFINITY);
+            self.rhs.insert(node, rhs);
+        }
+
+        self.queue.remove(node);
+        if self.g(node) != self.rhs(node)
+        {
+            let key = self.calculate_key(node);
+            self.queue.insert(node, key);
+        }
+    }
+
+    ///
+    /// Expands inconsistent nodes until the goal is consistent and
+    /// no queued node could improve on it
+    ///
+    fn compute_shortest_path(&mut self)
+    {
+        while let Some((key, node)) = self.queue.top()
+        {
+            if key >= self.calculate_key(self.goal) && self.rhs(self.goal) == self.g(self.goal) { break; }
+
+            self.queue.pop();
+            self.expanded += 1;
+            let neighbors = self.graph.neighbors(node).collect::<Vec<&'a str>>();
+
+            if self.g(node) > self.rhs(node)
+            {
+                // Overconsistent: settle the node at its lookahead distance
+                self.g.insert(node, self.rhs(node));
+            }
+            else
+            {
+                // Underconsistent: the node's distance got worse. Reset it
+                // and let it (and its neighbors) be recomputed
+                self.g.insert(node, INFINITY);
+                self.update_vertex(node);
+            }
+
+            for neighbor in neighbors { self.update_vertex(neighbor); }
+        }
+    }
+}
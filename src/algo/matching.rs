@@ -0,0 +1,598 @@
+///
+/// Computes a maximum-weight matching of a general (not necessarily
+/// bipartite) graph using Edmonds' blossom algorithm with dual variables,
+/// in O(n^3) time. Odd cycles of tight edges are shrunk into "blossoms",
+/// searched as single vertices, and expanded again once augmented through.
+///
+/// Dual variables are stored doubled, so that every computation stays
+/// in integers.
+///
+/// - vertex_count: the # of vertices, numbered from 0
+/// - edges: every edge, as (from, to, weight), with no self-loops
+/// - max_cardinality: whether to find the heaviest of the matchings with
+///   the most edges, rather than the heaviest of all matchings
+///
+/// - return: each vertex's mate, or None if the vertex is unmatched
+///
+pub fn max_weight_matching(vertex_count: usize, edges: &[(usize, usize, i64)], max_cardinality: bool) -> Vec<Option<usize>>
+{
+    if edges.is_empty() { return vec![None; vertex_count]; }
+
+    let mut matching = Matching::new(vertex_count, edges);
+    matching.solve(max_cardinality);
+
+    matching.mate.iter()
+        .map(|&p| if p == NONE { None } else { Some(matching.endpoint[p]) })
+        .collect()
+}
+
+// Marks an absent vertex, edge, endpoint or blossom
+const NONE: usize = usize::MAX;
+
+// Blossom labels: unlabeled, outer (S), inner (T), and S while
+// being scanned for a common base
+const FREE: u8 = 0;
+const OUTER: u8 = 1;
+const INNER: u8 = 2;
+const SCANNED: u8 = 5;
+
+///
+/// The state of the matching search. Each edge k has two endpoints: 2k
+/// (its "from" vertex) and 2k + 1 (its "to" vertex). Indices below
+/// vertex_count are vertices, and those above are blossoms
+///
+struct Matching<'e>
+{
+    n: usize,
+    edges: &'e [(usize, usize, i64)],
+    endpoint: Vec<usize>,
+    neighbend: Vec<Vec<usize>>,
+
+    // The remote endpoint of each vertex's matched edge
+    mate: Vec<usize>,
+
+    label: Vec<u8>,
+    labelend: Vec<usize>,
+    inblossom: Vec<usize>,
+    blossomparent: Vec<usize>,
+    blossomchilds: Vec<Vec<usize>>,
+    blossombase: Vec<usize>,
+    blossomendps: Vec<Vec<usize>>,
+    bestedge: Vec<usize>,
+    blossombestedges: Vec<Option<Vec<usize>>>,
+    unusedblossoms: Vec<usize>,
+    dualvar: Vec<i64>,
+    allowedge: Vec<bool>,
+    queue: Vec<usize>,
+}
+
+impl<'e> Matching<'e>
+{
+    fn new(n: usize, edges: &'e [(usize, usize, i64)]) -> Self
+    {
+        let max_weight = edges.iter().map(|edge| edge.2).max().unwrap_or(0).max(0);
+
+        let mut endpoint = Vec::with_capacity(2 * edges.len());
+        let mut neighbend = vec![Vec::new(); n];
+        for (k, &(i, j, _)) in edges.iter().enumerate()
+        {
+            endpoint.push(i);
+            endpoint.push(j);
+            neighbend[i].push(2 * k + 1);
+            neighbend[j].push(2 * k);
+        }
+
+        let mut dualvar = vec![max_weight; n];
+        dualvar.extend(vec![0; n]);
+
+        Matching
+        {
+            n, edges, endpoint, neighbend,
+            mate: vec![NONE; n],
+            label: vec![FREE; 2 * n],
+            labelend: vec![NONE; 2 * n],
+            inblossom: (0..n).collect(),
+            blossomparent: vec![NONE; 2 * n],
+            blossomchilds: vec![Vec::new(); 2 * n],
+            blossombase: (0..n).chain(vec![NONE; n]).collect(),
+            blossomendps: vec![Vec::new(); 2 * n],
+            bestedge: vec![NONE; 2 * n],
+            blossombestedges: vec![None; 2 * n],
+            unusedblossoms: (n..2 * n).collect(),
+            dualvar,
+            allowedge: vec![false; edges.len()],
+            queue: Vec::new(),
+        }
+    }
+
+    fn slack(&self, k: usize) -> i64
+    {
+        let (i, j, weight) = self.edges[k];
+        self.dualvar[i] + self.dualvar[j] - 2 * weight
+    }
+
+    ///
+    /// - return: every vertex within blossom b, at any depth
+    ///
+    fn leaves(&self, b: usize) -> Vec<usize>
+    {
+        if b < self.n { return vec![b]; }
+
+        let mut leaves = Vec::new();
+        for &child in &self.blossomchilds[b] { leaves.extend(self.leaves(child)); }
+        leaves
+    }
+
+    ///
+    /// Labels vertex w's top-level blossom, reached through endpoint p.
+    /// Inner blossoms also label their mate as outer
+    ///
+    fn assign_label(&mut self, w: usize, t: u8, p: usize)
+    {
+        let b = self.inblossom[w];
+        self.label[w] = t;
+        self.label[b] = t;
+        self.labelend[w] = p;
+        self.labelend[b] = p;
+        self.bestedge[w] = NONE;
+        self.bestedge[b] = NONE;
+
+        if t == OUTER
+        {
+            let leaves = self.leaves(b);
+            self.queue.extend(leaves);
+        }
+        else
+        {
+            let base = self.blossombase[b];
+            let mate = self.mate[base];
+            self.assign_label(self.endpoint[mate], OUTER, mate ^ 1);
+        }
+    }
+
+    ///
+    /// Traces back from two outer vertices joined by an edge
+    ///
+    /// - return: the base of the new blossom formed, or NONE if the
+    ///   paths lead to different roots (an augmenting path)
+    ///
+    fn scan_blossom(&mut self, mut v: usize, mut w: usize) -> usize
+    {
+        let mut path = Vec::new();
+        let mut base = NONE;
+
+        while v != NONE || w != NONE
+        {
+            let mut b = self.inblossom[v];
+            if self.label[b] & 4 != 0
+            {
+                base = self.blossombase[b];
+                break;
+            }
+
+            path.push(b);
+            self.label[b] = SCANNED;
+
+            if self.labelend[b] == NONE { v = NONE; }
+            else
+            {
+                v = self.endpoint[self.labelend[b]];
+                b = self.inblossom[v];
+                v = self.endpoint[self.labelend[b]];
+            }
+
+            if w != NONE { std::mem::swap(&mut v, &mut w); }
+        }
+
+        for b in path { self.label[b] = OUTER; }
+        base
+    }
+
+    ///
+    /// Shrinks the odd cycle closed by edge k into a new blossom
+    ///
+    fn add_blossom(&mut self, base: usize, k: usize)
+    {
+        let (v, w, _) = self.edges[k];
+        let bb = self.inblossom[base];
+        let mut bv = self.inblossom[v];
+        let mut bw = self.inblossom[w];
+
+        let b = self.unusedblossoms.pop().unwrap();
+        self.blossombase[b] = base;
+        self.blossomparent[b] = NONE;
+        self.blossomparent[bb] = b;
+
+        let mut path = Vec::new();
+        let mut endps = Vec::new();
+        while bv != bb
+        {
+            self.blossomparent[bv] = b;
+            path.push(bv);
+            endps.push(self.labelend[bv]);
+            bv = self.inblossom[self.endpoint[self.labelend[bv]]];
+        }
+        path.push(bb);
+        path.reverse();
+        endps.reverse();
+        endps.push(2 * k);
+        while bw != bb
+        {
+            self.blossomparent[bw] = b;
+            path.push(bw);
+            endps.push(self.labelend[bw] ^ 1);
+            bw = self.inblossom[self.endpoint[self.labelend[bw]]];
+        }
+
+        self.label[b] = OUTER;
+        self.labelend[b] = self.labelend[bb];
+        self.dualvar[b] = 0;
+        self.blossomchilds[b] = path.clone();
+        self.blossomendps[b] = endps;
+
+        for leaf in self.leaves(b)
+        {
+            if self.label[self.inblossom[leaf]] == INNER { self.queue.push(leaf); }
+            self.inblossom[leaf] = b;
+        }
+
+        // Find the least-slack edge from the new blossom to each
+        // neighboring outer blossom
+        let mut bestedgeto = vec![NONE; 2 * self.n];
+        for &child in &path
+        {
+            let lists = match self.blossombestedges[child].take()
+            {
+                Some(list) => vec![list],
+                None => self.leaves(child).iter()
+                    .map(|&leaf| self.neighbend[leaf].iter().map(|p| p / 2).collect())
+                    .collect(),
+            };
+
+            for list in lists
+            {
+                for k in list
+                {
+                    let (mut i, mut j, _) = self.edges[k];
+                    if self.inblossom[j] == b { std::mem::swap(&mut i, &mut j); }
+                    let bj = self.inblossom[j];
+                    if bj != b && self.label[bj] == OUTER &&
+                       (bestedgeto[bj] == NONE || self.slack(k) < self.slack(bestedgeto[bj]))
+                    {
+                        bestedgeto[bj] = k;
+                    }
+                }
+            }
+            self.bestedge[child] = NONE;
+        }
+
+        let best_edges = bestedgeto.into_iter().filter(|&k| k != NONE).collect::<Vec<usize>>();
+        self.bestedge[b] = NONE;
+        for &k in &best_edges
+        {
+            if self.bestedge[b] == NONE || self.slack(k) < self.slack(self.bestedge[b]) { self.bestedge[b] = k; }
+        }
+        self.blossombestedges[b] = Some(best_edges);
+    }
+
+    ///
+    /// Expands blossom b back into its children. Unless the search stage
+    /// is over, an inner blossom's children are relabeled along the path
+    /// through it
+    ///
+    fn expand_blossom(&mut self, b: usize, end_stage: bool)
+    {
+        for s in self.blossomchilds[b].clone()
+        {
+            self.blossomparent[s] = NONE;
+            if s < self.n { self.inblossom[s] = s; }
+            else if end_stage && self.dualvar[s] == 0 { self.expand_blossom(s, end_stage); }
+            else
+            {
+                for leaf in self.leaves(s) { self.inblossom[leaf] = s; }
+            }
+        }
+
+        if !end_stage && self.label[b] == INNER
+        {
+            let childs = self.blossomchilds[b].clone();
+            let endps = self.blossomendps[b].clone();
+            let len = childs.len() as i64;
+            let at = |j: i64| ((j % len + len) % len) as usize;
+
+            let entrychild = self.inblossom[self.endpoint[self.labelend[b] ^ 1]];
+            let mut j = childs.iter().position(|&c| c == entrychild).unwrap() as i64;
+            let (jstep, endptrick) = if j & 1 == 1 { j -= len; (1, 0) } else { (-1, 1) };
+
+            // Relabel the even-length path from the entry child to the base
+            let mut p = self.labelend[b];
+            while j != 0
+            {
+                self.label[self.endpoint[p ^ 1]] = FREE;
+                self.label[self.endpoint[endps[at(j - endptrick)] ^ endptrick as usize ^ 1]] = FREE;
+                self.assign_label(self.endpoint[p ^ 1], INNER, p);
+                self.allowedge[endps[at(j - endptrick)] / 2] = true;
+                j += jstep;
+                p = endps[at(j - endptrick)] ^ endptrick as usize;
+                self.allowedge[p / 2] = true;
+                j += jstep;
+            }
+
+            let bv = childs[at(j)];
+            self.label[self.endpoint[p ^ 1]] = INNER;
+            self.label[bv] = INNER;
+            self.labelend[self.endpoint[p ^ 1]] = p;
+            self.labelend[bv] = p;
+            self.bestedge[bv] = NONE;
+            j += jstep;
+
+            // Children off the path may be reachable from outside
+            while childs[at(j)] != entrychild
+            {
+                let bv = childs[at(j)];
+                if self.label[bv] == OUTER
+                {
+                    j += jstep;
+                    continue;
+                }
+
+                if let Some(v) = self.leaves(bv).into_iter().find(|&v| self.label[v] != FREE)
+                {
+                    self.label[v] = FREE;
+                    let mate = self.mate[self.blossombase[bv]];
+                    self.label[self.endpoint[mate]] = FREE;
+                    self.assign_label(v, INNER, self.labelend[v]);
+                }
+                j += jstep;
+            }
+        }
+
+        self.label[b] = FREE;
+        self.labelend[b] = NONE;
+        self.blossomchilds[b].clear();
+        self.blossomendps[b].clear();
+        self.blossombase[b] = NONE;
+        self.blossombestedges[b] = None;
+        self.bestedge[b] = NONE;
+        self.unusedblossoms.push(b);
+    }
+
+    ///
+    /// Swaps matched and unmatched edges along the path through blossom b
+    /// from vertex v to its base, making v the new base
+    ///
+    fn augment_blossom(&mut self, b: usize, v: usize)
+    {
+        let mut t = v;
+        while self.blossomparent[t] != b { t = self.blossomparent[t]; }
+        if t >= self.n { self.augment_blossom(t, v); }
+
+        let len = self.blossomchilds[b].len() as i64;
+        let at = |j: i64| ((j % len + len) % len) as usize;
+        let i = self.blossomchilds[b].iter().position(|&c| c == t).unwrap();
+        let mut j = i as i64;
+        let (jstep, endptrick) = if j & 1 == 1 { j -= len; (1, 0) } else { (-1, 1) };
+
+        while j != 0
+        {
+            j += jstep;
+            let t = self.blossomchilds[b][at(j)];
+            let p = self.blossomendps[b][at(j - endptrick)] ^ endptrick as usize;
+            if t >= self.n { self.augment_blossom(t, self.endpoint[p]); }
+            j += jstep;
+            let t = self.blossomchilds[b][at(j)];
+            if t >= self.n { self.augment_blossom(t, self.endpoint[p ^ 1]); }
+            self.mate[self.endpoint[p]] = p ^ 1;
+            self.mate[self.endpoint[p ^ 1]] = p;
+        }
+
+        self.blossomchilds[b].rotate_left(i);
+        self.blossomendps[b].rotate_left(i);
+        self.blossombase[b] = self.blossombase[self.blossomchilds[b][0]];
+    }
+
+    ///
+    /// Augments the matching along the path through edge k, between
+    /// two outer vertices with different roots
+    ///
+    fn augment_matching(&mut self, k: usize)
+    {
+        let (v, w, _) = self.edges[k];
+        for (mut s, mut p) in [(v, 2 * k + 1), (w, 2 * k)]
+        {
+            loop
+            {
+                let bs = self.inblossom[s];
+                if bs >= self.n { self.augment_blossom(bs, s); }
+                self.mate[s] = p;
+                if self.labelend[bs] == NONE { break; }
+
+                let t = self.endpoint[self.labelend[bs]];
+                let bt = self.inblossom[t];
+                s = self.endpoint[self.labelend[bt]];
+                let j = self.endpoint[self.labelend[bt] ^ 1];
+                if bt >= self.n { self.augment_blossom(bt, j); }
+                self.mate[j] = self.labelend[bt];
+                p = self.labelend[bt] ^ 1;
+            }
+        }
+    }
+
+    fn solve(&mut self, max_cardinality: bool)
+    {
+        let n = self.n;
+
+        // Each stage augments the matching by one edge, or proves
+        // that no further augmentation is worthwhile
+        for _ in 0..n
+        {
+            self.label = vec![FREE; 2 * n];
+            self.bestedge = vec![NONE; 2 * n];
+            for b in n..2 * n { self.blossombestedges[b] = None; }
+            self.allowedge = vec![false; self.edges.len()];
+            self.queue.clear();
+
+            for v in 0..n
+            {
+                if self.mate[v] == NONE && self.label[self.inblossom[v]] == FREE { self.assign_label(v, OUTER, NONE); }
+            }
+
+            let mut augmented = false;
+            loop
+            {
+                // Grow the alternating forest along tight edges
+                while let Some(v) = self.queue.pop()
+                {
+                    if augmented { break; }
+
+                    for p in self.neighbend[v].clone()
+                    {
+                        let k = p / 2;
+                        let w = self.endpoint[p];
+                        if self.inblossom[v] == self.inblossom[w] { continue; }
+
+                        let mut kslack = 0;
+                        if !self.allowedge[k]
+                        {
+                            kslack = self.slack(k);
+                            if kslack <= 0 { self.allowedge[k] = true; }
+                        }
+
+                        if self.allowedge[k]
+                        {
+                            if self.label[self.inblossom[w]] == FREE { self.assign_label(w, INNER, p ^ 1); }
+                            else if self.label[self.inblossom[w]] == OUTER
+                            {
+                                let base = self.scan_blossom(v, w);
+                                if base != NONE { self.add_blossom(base, k); }
+                                else
+                                {
+                                    self.augment_matching(k);
+                                    augmented = true;
+                                    break;
+                                }
+                            }
+                            else if self.label[w] == FREE
+                            {
+                                self.label[w] = INNER;
+                                self.labelend[w] = p ^ 1;
+                            }
+                        }
+                        else if self.label[self.inblossom[w]] == OUTER
+                        {
+                            let b = self.inblossom[v];
+                            if self.bestedge[b] == NONE || kslack < self.slack(self.bestedge[b]) { self.bestedge[b] = k; }
+                        }
+                        else if self.label[w] == FREE && (self.bestedge[w] == NONE || kslack < self.slack(self.bestedge[w]))
+                        {
+                            self.bestedge[w] = k;
+                        }
+                    }
+                }
+
+                if augmented { break; }
+
+                // No tight edge remains: adjust the dual variables by the
+                // largest amount that keeps every slack non-negative
+                let mut delta_type = 0;
+                let mut delta = 0;
+                let mut delta_edge = NONE;
+                let mut delta_blossom = NONE;
+
+                if !max_cardinality
+                {
+                    delta_type = 1;
+                    delta = *self.dualvar[..n].iter().min().unwrap();
+                }
+
+                for v in 0..n
+                {
+                    if self.label[self.inblossom[v]] == FREE && self.bestedge[v] != NONE
+                    {
+                        let d = self.slack(self.bestedge[v]);
+                        if delta_type == 0 || d < delta { delta = d; delta_type = 2; delta_edge = self.bestedge[v]; }
+                    }
+                }
+
+                for b in 0..2 * n
+                {
+                    if self.blossomparent[b] == NONE && self.label[b] == OUTER && self.bestedge[b] != NONE
+                    {
+                        let d = self.slack(self.bestedge[b]) / 2;
+                        if delta_type == 0 || d < delta { delta = d; delta_type = 3; delta_edge = self.bestedge[b]; }
+                    }
+                }
+
+                for b in n..2 * n
+                {
+                    if self.blossombase[b] != NONE && self.blossomparent[b] == NONE && self.label[b] == INNER &&
+                       (delta_type == 0 || self.dualvar[b] < delta)
+                    {
+                        delta = self.dualvar[b];
+                        delta_type = 4;
+                        delta_blossom = b;
+                    }
+                }
+
+                if delta_type == 0
+                {
+                    // No further improvement is possible
+                    delta_type = 1;
+                    delta = (*self.dualvar[..n].iter().min().unwrap()).max(0);
+                }
+
+                for v in 0..n
+                {
+                    match self.label[self.inblossom[v]]
+                    {
+                        OUTER => self.dualvar[v] -= delta,
+                        INNER => self.dualvar[v] += delta,
+                        _ => (),
+                    }
+                }
+                for b in n..2 * n
+                {
+                    if self.blossombase[b] != NONE && self.blossomparent[b] == NONE
+                    {
+                        match self.label[b]
+                        {
+                            OUTER => self.dualvar[b] += delta,
+                            INNER => self.dualvar[b] -= delta,
+                            _ => (),
+                        }
+                    }
+                }
+
+                match delta_type
+                {
+                    1 => break,
+                    2 =>
+                    {
+                        self.allowedge[delta_edge] = true;
+                        let (mut i, j, _) = self.edges[delta_edge];
+                        if self.label[self.inblossom[i]] == FREE { i = j; }
+                        self.queue.push(i);
+                    },
+                    3 =>
+                    {
+                        self.allowedge[delta_edge] = true;
+                        self.queue.push(self.edges[delta_edge].0);
+                    },
+                    _ => self.expand_blossom(delta_blossom, false),
+                }
+            }
+
+            if !augmented { break; }
+
+            // Expand outer blossoms whose dual variable has fallen to zero
+            for b in n..2 * n
+            {
+                if self.blossomparent[b] == NONE && self.blossombase[b] != NONE &&
+                   self.label[b] == OUTER && self.dualvar[b] == 0
+                {
+                    self.expand_blossom(b, true);
+                }
+            }
+        }
+    }
+}
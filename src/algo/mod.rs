@@ -0,0 +1,641 @@
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::BuildHasher;
+use std::cmp::Reverse;
+
+use petgraph::graphmap::{GraphMap, UnGraphMap};
+use petgraph::EdgeType;
+use priority_queue::PriorityQueue;
+
+use astar::TieBreak;
+use context::SearchContext;
+use grid::{Cell, Grid, GridPath};
+use intern::{HeuristicMatrix, InternedGraph, SearchGraph};
+
+pub mod admissibility;
+pub mod alt;
+pub mod analysis;
+pub mod ara_star;
+pub mod astar;
+pub mod bidirectional;
+pub mod bellman_ford;
+pub mod ch;
+pub mod context;
+pub mod dag;
+pub mod dstar_lite;
+pub mod focal;
+pub mod fringe;
+pub mod frontier;
+pub mod geo;
+pub mod grid;
+pub mod hpa;
+pub mod incremental;
+pub mod intern;
+pub mod jps;
+pub mod ksp;
+pub mod lpa_star;
+pub mod matching;
+pub mod postman;
+pub mod mst;
+pub mod sma_star;
+pub mod theta_star;
+pub mod traversal;
+pub mod tsp;
+
+///
+/// The route Graph every algorithm operates on. Nodes are borrowed
+/// location names, and edge weights are whole numbers of the scale of
+/// the routes' Unit, such as tenths of a mile
+///
+pub type RouteGraph<'a> = UnGraphMap<&'a str, u64>;
+
+///
+/// A route Graph of either kind: undirected, as a RouteGraph, or directed,
+/// as a DirectedRouteGraph. Searches over a Network only travel edges in
+/// the directions they allow
+///
+pub type Network<'a, Ty> = GraphMap<&'a str, u64, Ty>;
+
+///
+/// The map searches keep each node's distance and previous node in:
+/// FxHashMap with the fast-hash feature, which hashes short names far
+/// faster than the standard SipHash, or else the standard HashMap
+///
+#[cfg(feature = "fast-hash")]
+pub(crate) type SearchMap<K, V> = rustc_hash::FxHashMap<K, V>;
+#[cfg(not(feature = "fast-hash"))]
+pub(crate) type SearchMap<K, V> = HashMap<K, V>;
+
+///
+/// The set searches keep the nodes they have visited in, hashed as
+/// SearchMap is
+///
+#[cfg(feature = "fast-hash")]
+pub(crate) type SearchSet<K> = rustc_hash::FxHashSet<K>;
+#[cfg(not(feature = "fast-hash"))]
+pub(crate) type SearchSet<K> = std::collections::HashSet<K>;
+
+///
+/// - nodes: the # of nodes on the Graph searched
+///
+/// - return: an empty SearchMap with room for every node, so that it
+///   never grows during the search
+///
+pub(crate) fn search_map<K, V>(nodes: usize) -> SearchMap<K, V>
+{
+    SearchMap::with_capacity_and_hasher(nodes, Default::default())
+}
+
+///
+/// - nodes: the # of nodes on the Graph searched
+///
+/// - return: an empty SearchSet with room for every node
+///
+pub(crate) fn search_set<K>(nodes: usize) -> SearchSet<K>
+{
+    SearchSet::with_capacity_and_hasher(nodes, Default::default())
+}
+
+///
+/// An estimate of the remaining distance between two nodes, used to guide
+/// informed searches. Estimates are in the same units as edge weights.
+/// Heuristics are shared by searches running on several threads
+///
+pub trait Heuristic: Sync
+{
+    ///
+    /// - node: the node the estimate is made from
+    /// - goal: the node the estimate is made to
+    ///
+    fn estimate(&self, node: &str, goal: &str) -> u64;
+
+    ///
+    /// Precomputes estimates by ID on an InternedGraph, for heuristics
+    /// read from a table, so that searches index a slice instead of
+    /// hashing names for every estimate
+    ///
+    /// - graph: the InternedGraph whose IDs index the matrix
+    ///
+    /// - return: the HeuristicMatrix, or None if estimates are made by
+    ///   name as they are needed
+    ///
+    fn matrix(&self, _graph: &InternedGraph) -> Option<HeuristicMatrix>
+    {
+        None
+    }
+}
+
+///
+/// A heuristic table, as read from euclidian.txt, keyed by (node, goal).
+/// Straight-line distances are the same both ways, so a pair missing from
+/// the table is looked up the other way round. Pairs missing both ways are
+/// estimated at 0, which is always admissible
+///
+impl<'a> Heuristic for HashMap<(&'a str, &'a str), u64>
+{
+    fn estimate(&self, node: &str, goal: &str) -> u64
+    {
+        table_estimate(self, node, goal).unwrap_or(0)
+    }
+
+    fn matrix(&self, graph: &InternedGraph) -> Option<HeuristicMatrix>
+    {
+        Some(table_matrix(self, graph))
+    }
+}
+
+///
+/// A heuristic table, as read from euclidian.txt, with the estimate made
+/// for pairs of locations missing from it both ways. Any default above 0
+/// can overestimate, and so lead A* to routes longer than the shortest
+///
+pub struct HeuristicTable<'a>
+{
+    estimates: HashMap<(&'a str, &'a str), u64>,
+    default: u64,
+}
+
+impl<'a> HeuristicTable<'a>
+{
+    ///
+    /// - estimates: the table, keyed by (node, goal)
+    /// - default: the estimate for pairs missing from the table both ways
+    ///
+    pub fn new(estimates: HashMap<(&'a str, &'a str), u64>, default: u64) -> Self
+    {
+        HeuristicTable { estimates, default }
+    }
+
+    ///
+    /// Finds the pairs of different locations on the Graph which the table
+    /// has no estimate between, either way
+    ///
+    /// - graph: the Graph searched with the table
+    /// - examples: the most pairs to return
+    ///
+    /// - return: the # of pairs missing, and the first of them, in the
+    ///   Graph's node order
+    ///
+    pub fn missing<Ty: EdgeType>(&self, graph: &Network<'a, Ty>, examples: usize) -> (usize, Vec<(&'a str, &'a str)>)
+    {
+        let nodes = graph.nodes().collect::<Vec<&'a str>>();
+
+        // Count the pairs the table covers, rather than looking up every
+        // pair, which would take far longer on a large Graph
+        let covered = self.estimates.keys()
+            .filter(|&&(node, goal)| node != goal && graph.contains_node(node) && graph.contains_node(goal))
+            .map(|&(node, goal)| if node < goal { (node, goal) } else { (goal, node) })
+            .collect::<std::collections::HashSet<(&str, &str)>>()
+            .len();
+        let missing = nodes.len() * nodes.len().saturating_sub(1) / 2 - covered;
+
+        let pairs = nodes.iter().enumerate()
+            .flat_map(|(i, &node)| nodes[i + 1..].iter().map(move |&goal| (node, goal)))
+            .filter(|&(node, goal)| table_estimate(&self.estimates, node, goal).is_none())
+            .take(examples.min(missing))
+            .collect();
+
+        (missing, pairs)
+    }
+}
+
+impl Heuristic for HeuristicTable<'_>
+{
+    fn estimate(&self, node: &str, goal: &str) -> u64
+    {
+        if node == goal { return 0; }
+        table_estimate(&self.estimates, node, goal).unwrap_or(self.default)
+    }
+
+    fn matrix(&self, graph: &InternedGraph) -> Option<HeuristicMatrix>
+    {
+        // The matrix estimates missing pairs at 0, so a table with another
+        // default makes its estimates by name
+        (self.default == 0).then(|| table_matrix(&self.estimates, graph))
+    }
+}
+
+///
+/// - return: a heuristic table's estimate from node to goal, or else from
+///   goal to node, or None if it has neither
+///
+fn table_estimate<'t>(table: &HashMap<(&'t str, &'t str), u64>, node: &'t str, goal: &'t str) -> Option<u64>
+{
+    table.get(&(node, goal)).or_else(|| table.get(&(goal, node))).cloned()
+}
+
+///
+/// - return: a heuristic table's estimates by ID on an InternedGraph,
+///   filling in each pair given only one way with its estimate the other
+///
+fn table_matrix(table: &HashMap<(&str, &str), u64>, graph: &InternedGraph) -> HeuristicMatrix
+{
+    let mut matrix = HeuristicMatrix::new(graph.len());
+    for (&(node, goal), &estimate) in table
+    {
+        if let (Some(node), Some(goal)) = (graph.id(node), graph.id(goal))
+        {
+            matrix.insert(node, goal, estimate);
+        }
+    }
+    for (&(node, goal), &estimate) in table
+    {
+        if table.contains_key(&(goal, node)) { continue; }
+        if let (Some(node), Some(goal)) = (graph.id(node), graph.id(goal))
+        {
+            matrix.insert(goal, node, estimate);
+        }
+    }
+    matrix
+}
+
+///
+/// A single computed route through the Graph
+///
+/// - nodes: every location on the route, from start to end (inclusive)
+/// - cost: the total weight of the route
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathResult<'a>
+{
+    pub nodes: Vec<&'a str>,
+    pub cost: u64,
+}
+
+///
+/// One step of a route, from one location to the next
+///
+/// - index: the step's place on the route, from 1
+/// - from: the location the step leaves
+/// - to: the location the step reaches
+/// - distance: the step's weight
+/// - cumulative: the route's weight up to the end of the step
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step<'a>
+{
+    pub index: usize,
+    pub from: &'a str,
+    pub to: &'a str,
+    pub distance: u64,
+    pub cumulative: u64,
+}
+
+impl<'a> PathResult<'a>
+{
+    ///
+    /// Breaks the route into its steps, as turn-by-turn directions
+    ///
+    /// - graph: the Graph the route was found on, for each step's weight
+    ///
+    /// - return: each Step, from start to end
+    ///
+    pub fn steps<Ty: EdgeType>(&self, graph: &Network<'a, Ty>) -> Vec<Step<'a>>
+    {
+        let mut cumulative = 0;
+        self.nodes.windows(2)
+            .enumerate()
+            .map(|(i, leg)|
+            {
+                let distance = graph.edge_weight(leg[0], leg[1]).cloned().unwrap_or(0);
+                cumulative += distance;
+                Step { index: i + 1, from: leg[0], to: leg[1], distance, cumulative }
+            })
+            .collect()
+    }
+}
+
+///
+/// The algorithms available for routing between two nodes on a Graph.
+/// Jump Point Search and Theta* search only a Grid, so they route across
+/// a grid map's Grid with search_grid, and find no route on any Graph
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Algorithm
+{
+    AStar,
+    Dijkstra,
+    BidirectionalAStar,
+    BidirectionalDijkstra,
+    Fringe,
+    Focal,
+    Bfs,
+    BidirectionalBfs,
+    Dfs,
+    Dag,
+    JumpPoint,
+    ThetaStar,
+}
+
+impl Algorithm
+{
+    /// Every routing algorithm
+    pub const ALL: [Algorithm; 10] =
+    [
+        Algorithm::AStar, Algorithm::Dijkstra, Algorithm::BidirectionalAStar, Algorithm::BidirectionalDijkstra,
+        Algorithm::Fringe, Algorithm::Focal, Algorithm::Bfs, Algorithm::BidirectionalBfs, Algorithm::Dfs, Algorithm::Dag,
+    ];
+
+    /// The algorithms which search only a Grid, and are left out of ALL
+    pub const GRID: [Algorithm; 2] = [Algorithm::JumpPoint, Algorithm::ThetaStar];
+
+    ///
+    /// - return: the algorithm's display name
+    ///
+    pub fn name(self) -> &'static str
+    {
+        match self
+        {
+            Algorithm::AStar => "A*",
+            Algorithm::Dijkstra => "Djikstra",
+            Algorithm::BidirectionalAStar => "Bidirectional A*",
+            Algorithm::BidirectionalDijkstra => "Bidirectional Djikstra",
+            Algorithm::Fringe => "Fringe",
+            Algorithm::Focal => "Focal",
+            Algorithm::Bfs => "Breadth-first",
+            Algorithm::BidirectionalBfs => "Bidirectional breadth-first",
+            Algorithm::Dfs => "Depth-first",
+            Algorithm::Dag => "DAG",
+            Algorithm::JumpPoint => "Jump Point Search",
+            Algorithm::ThetaStar => "Theta*",
+        }
+    }
+
+    ///
+    /// Finds the algorithm with the given name, such as "astar" or "bfs"
+    ///
+    pub fn from_name(name: &str) -> Option<Algorithm>
+    {
+        match name.to_lowercase().as_str()
+        {
+            "astar" | "a*" => Some(Algorithm::AStar),
+            "dijkstra" | "djikstra" => Some(Algorithm::Dijkstra),
+            "bidirectional-astar" | "bidirectional a*" => Some(Algorithm::BidirectionalAStar),
+            "bidirectional-dijkstra" | "bidirectional-djikstra" | "bidirectional djikstra" => Some(Algorithm::BidirectionalDijkstra),
+            "fringe" => Some(Algorithm::Fringe),
+            "focal" => Some(Algorithm::Focal),
+            "bfs" | "breadth-first" => Some(Algorithm::Bfs),
+            "bidirectional-bfs" | "bidirectional" | "bidirectional breadth-first" => Some(Algorithm::BidirectionalBfs),
+            "dfs" | "depth-first" => Some(Algorithm::Dfs),
+            "dag" => Some(Algorithm::Dag),
+            "jps" | "jump-point" | "jump point search" => Some(Algorithm::JumpPoint),
+            "theta" | "theta*" | "theta-star" => Some(Algorithm::ThetaStar),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Finds the algorithms named in a list separated by commas, such as
+    /// "astar, dijkstra", or every algorithm for "all"
+    ///
+    /// - return: Either an Ok Result with each algorithm once, in the order
+    ///   named, or an Err naming an algorithm which does not exist
+    ///
+    pub fn from_names(names: &str) -> Result<Vec<Algorithm>, String>
+    {
+        if names.trim().eq_ignore_ascii_case("all") { return Ok(Algorithm::ALL.to_vec()); }
+
+        let mut algorithms = Vec::new();
+        for name in names.split(',').map(str::trim).filter(|name| !name.is_empty())
+        {
+            let algorithm = Algorithm::from_name(name).ok_or_else(|| format!("Unknown algorithm \"{}\".", name))?;
+            if !algorithms.contains(&algorithm) { algorithms.push(algorithm); }
+        }
+
+        match algorithms.is_empty()
+        {
+            true => Err(String::from("No algorithm was given.")),
+            false => Ok(algorithms),
+        }
+    }
+
+    ///
+    /// Routes between two nodes on the Graph with this algorithm
+    ///
+    /// - graph: the Graph to search
+    /// - heuristic: estimates the distance between any two nodes (used
+    ///   only by the informed algorithms)
+    /// - start: the start location on the Graph
+    /// - end: the end location on the Graph
+    /// - tie_break: how to choose between nodes with equal f-values (used
+    ///   only by A* and Djikstra's algorithm)
+    ///
+    /// - return: the route (None if end cannot be reached), and the # of
+    ///   nodes considered. Bidirectional searches intern the Graph first,
+    ///   so are better run with search_interned when repeated. Jump Point
+    ///   Search and Theta* find none, as the Graph is not a Grid
+    ///
+    pub fn search<'a, Ty: EdgeType>(self, graph: &Network<'a, Ty>, heuristic: &dyn Heuristic, start: &'a str, end: &'a str, tie_break: TieBreak) -> (Option<PathResult<'a>>, usize)
+    {
+        match self
+        {
+            Algorithm::AStar => astar::astar(graph, Some(heuristic), start, end, tie_break),
+            Algorithm::Dijkstra => astar::astar(graph, None, start, end, tie_break),
+            Algorithm::BidirectionalAStar | Algorithm::BidirectionalDijkstra =>
+            {
+                self.search_interned(&InternedGraph::new(graph), heuristic, start, end, tie_break, &mut SearchContext::default(), |_| ())
+                    .expect("Interned Graphs can always be searched backward")
+            },
+            Algorithm::Fringe => fringe::fringe_search(graph, heuristic, start, end),
+            Algorithm::Focal => focal::focal_search(graph, heuristic, start, end, focal::DEFAULT_EPSILON),
+            Algorithm::Bfs => traversal::bfs(graph, start, end),
+            Algorithm::BidirectionalBfs => traversal::bidirectional_bfs(graph, start, end),
+            Algorithm::Dfs => traversal::dfs(graph, start, end),
+            Algorithm::Dag => dag::dag_search(graph, start, end, tie_break),
+            Algorithm::JumpPoint | Algorithm::ThetaStar => (None, 0),
+        }
+    }
+
+    ///
+    /// Routes between two nodes on the Graph with this algorithm, as
+    /// search does, passing each node A* and Djikstra's algorithm take
+    /// from their queue to a function as they go. Other algorithms are
+    /// not observed
+    ///
+    /// - on_expand: called with each node taken from the queue, in order
+    ///
+    pub fn search_observed<'a, Ty: EdgeType, F: FnMut(astar::Expansion<'a>)>(self, graph: &Network<'a, Ty>, heuristic: &dyn Heuristic, start: &'a str, end: &'a str, tie_break: TieBreak, on_expand: F) -> (Option<PathResult<'a>>, usize)
+    {
+        match self
+        {
+            Algorithm::AStar => astar::astar_observed(graph, Some(heuristic), &[start], &[end], tie_break, on_expand),
+            Algorithm::Dijkstra => astar::astar_observed(graph, None, &[start], &[end], tie_break, on_expand),
+            _ => self.search(graph, heuristic, start, end, tie_break),
+        }
+    }
+
+    ///
+    /// Routes between two nodes on an InternedGraph, or other SearchGraph,
+    /// with this algorithm, passing each node A* and Djikstra's algorithm
+    /// take from their queue to a function as they go, as search_observed
+    /// does
+    ///
+    /// - graph: the Graph to search
+    /// - heuristic, start, end, tie_break, on_expand: as for search_observed
+    /// - context: the buffers to search in, reused across queries
+    ///
+    /// - return: the route (None if end cannot be reached) and the # of
+    ///   nodes considered, or None if this algorithm only searches a
+    ///   Network, or searches backward and the Graph only holds the edges
+    ///   leaving each location
+    ///
+    #[allow(clippy::too_many_arguments)]
+    pub fn search_interned<'a, G: SearchGraph<'a> + Sync, F: FnMut(astar::Expansion<'a>)>(self, graph: &G, heuristic: &dyn Heuristic, start: &str, end: &str, tie_break: TieBreak, context: &mut SearchContext, on_expand: F) -> Option<(Option<PathResult<'a>>, usize)>
+    {
+        match self
+        {
+            Algorithm::AStar => Some(intern::astar(graph, Some(heuristic), start, end, tie_break, context, on_expand)),
+            Algorithm::Dijkstra => Some(intern::astar(graph, None, start, end, tie_break, context, on_expand)),
+            Algorithm::BidirectionalAStar => bidirectional::bidirectional(graph, Some(heuristic), start, end, context),
+            Algorithm::BidirectionalDijkstra => bidirectional::bidirectional(graph, None, start, end, context),
+            Algorithm::Fringe => Some(intern::fringe_search(graph, heuristic, start, end, context)),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Routes between two cells of an 8-connected Grid with this algorithm
+    ///
+    /// - grid: the Grid to search
+    /// - start: the start cell
+    /// - end: the end cell
+    ///
+    /// - return: the route (None if end cannot be reached) and the # of
+    ///   cells considered, or None if this algorithm searches any Graph
+    ///   rather than a Grid
+    ///
+    pub fn search_grid(self, grid: &Grid, start: Cell, end: Cell) -> Option<(Option<GridPath>, usize)>
+    {
+        match self
+        {
+            Algorithm::JumpPoint => Some(jps::jump_point_search(grid, start, end)),
+            Algorithm::ThetaStar => Some(theta_star::theta_star(grid, start, end)),
+            _ => None,
+        }
+    }
+}
+
+///
+/// The complete result of a single-source Dijkstra search
+///
+/// - dist: each reached node's distance from the source
+/// - prev: each reached node's predecessor on its shortest path
+///   back to the source (the source itself has no entry)
+///
+pub struct ShortestPathTree<'a>
+{
+    pub dist: HashMap<&'a str, u64>,
+    pub prev: HashMap<&'a str, &'a str>,
+}
+
+///
+/// Runs Dijkstra's algorithm from source to every reachable node
+/// in the Graph, with no early termination
+///
+/// - graph: the Graph to search
+/// - source: the node to search outward from
+///
+/// - return: the ShortestPathTree rooted at source
+///
+pub fn shortest_path_tree<'a>(graph: &RouteGraph<'a>, source: &'a str) -> ShortestPathTree<'a>
+{
+    let mut dist: HashMap<&'a str, u64> = HashMap::new();
+    let mut prev: HashMap<&'a str, &'a str> = HashMap::new();
+
+    let mut queue = PriorityQueue::new();
+    queue.push(source, Reverse(0));
+    dist.insert(source, 0);
+
+    while let Some((node, _)) = queue.pop()
+    {
+        for edge in graph.edges(node)
+        {
+            // Relax the edge if it offers a shorter distance
+            let alt_route = dist[node] + edge.2;
+            if !dist.contains_key(edge.1) || alt_route < dist[edge.1]
+            {
+                dist.insert(edge.1, alt_route);
+                prev.insert(edge.1, node);
+                queue.push(edge.1, Reverse(alt_route));
+            }
+        }
+    }
+
+    ShortestPathTree { dist, prev }
+}
+
+///
+/// Builds the route ending at end by following prev back to start
+///
+/// - prev: each reached node's previous node on its shortest path
+/// - start: the first node of the route
+/// - end: the last node of the route
+/// - cost: the route's total weight
+///
+pub(crate) fn build_route<'a, S: BuildHasher>(prev: &HashMap<&'a str, &'a str, S>, start: &'a str, end: &'a str, cost: u64) -> PathResult<'a>
+{
+    let mut nodes = vec![end];
+    let mut current = end;
+    while current != start
+    {
+        current = prev[current];
+        nodes.push(current);
+    }
+    nodes.reverse();
+
+    PathResult { nodes, cost }
+}
+
+///
+/// A min-priority queue of nodes keyed by 2-ples, supporting removal of
+/// arbitrary nodes, as required by the incremental searches. Removed or
+/// re-keyed entries are left in the heap and skipped when they surface
+///
+pub(crate) struct KeyedQueue<'a>
+{
+    heap: BinaryHeap<Reverse<((u64, u64), &'a str)>>,
+    keys: HashMap<&'a str, (u64, u64)>,
+}
+
+impl<'a> KeyedQueue<'a>
+{
+    pub(crate) fn new() -> Self
+    {
+        KeyedQueue { heap: BinaryHeap::new(), keys: HashMap::new() }
+    }
+
+    ///
+    /// Inserts node with the given key, replacing any existing key
+    ///
+    pub(crate) fn insert(&mut self, node: &'a str, key: (u64, u64))
+    {
+        self.keys.insert(node, key);
+        self.heap.push(Reverse((key, node)));
+    }
+
+    pub(crate) fn remove(&mut self, node: &'a str)
+    {
+        self.keys.remove(node);
+    }
+
+    ///
+    /// Returns the node with the smallest key, and its key, without
+    /// removing it. Discards any stale heap entries along the way
+    ///
+    pub(crate) fn top(&mut self) -> Option<((u64, u64), &'a str)>
+    {
+        while let Some(Reverse((key, node))) = self.heap.peek()
+        {
+            if self.keys.get(node) == Some(key) { return Some((*key, *node)); }
+            self.heap.pop();
+        }
+        None
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<((u64, u64), &'a str)>
+    {
+        let top = self.top()?;
+        self.heap.pop();
+        self.keys.remove(top.1);
+        Some(top)
+    }
+}
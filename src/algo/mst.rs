@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+use super::RouteGraph;
+
+///
+/// A minimum spanning tree (or forest, if the Graph is disconnected)
+///
+/// - edges: every edge in the tree, as (from, to, weight)
+/// - weight: the total weight of the tree
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanningTree<'a>
+{
+    pub edges: Vec<(&'a str, &'a str, u64)>,
+    pub weight: u64,
+}
+
+///
+/// Computes the minimum spanning tree of a Graph using Kruskal's algorithm:
+/// the cheapest set of edges connecting every node. If the Graph is
+/// disconnected, each connected component has its own tree
+///
+/// - graph: the Graph to span
+///
+/// - return: the SpanningTree, with edges in the order they were chosen
+///
+pub fn minimum_spanning_tree<'a>(graph: &RouteGraph<'a>) -> SpanningTree<'a>
+{
+    let mut edges = graph.all_edges()
+        .map(|(from, to, weight)| (from, to, *weight))
+        .collect::<Vec<(&'a str, &'a str, u64)>>();
+    edges.sort_by_key(|edge| edge.2);
+
+    // Each node's parent in a disjoint-set forest. Nodes sharing a root
+    // are already connected by the tree
+    let mut parent: HashMap<&'a str, &'a str> = graph.nodes().map(|node| (node, node)).collect();
+    fn root<'a>(parent: &mut HashMap<&'a str, &'a str>, mut node: &'a str) -> &'a str
+    {
+        while parent[node] != node
+        {
+            // Halve the path on the way up
+            let grandparent = parent[parent[node]];
+            parent.insert(node, grandparent);
+            node = grandparent;
+        }
+        node
+    }
+
+    let mut tree = SpanningTree { edges: Vec::new(), weight: 0 };
+    for (from, to, weight) in edges
+    {
+        let (from_root, to_root) = (root(&mut parent, from), root(&mut parent, to));
+        if from_root == to_root { continue; }
+
+        parent.insert(from_root, to_root);
+        tree.edges.push((from, to, weight));
+        tree.weight += weight;
+    }
+
+    tree
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use super::matching::max_weight_matching;
+use super::{build_route, shortest_path_tree, PathResult, RouteGraph};
+
+///
+/// Solves the route inspection (Chinese postman) problem: computes the
+/// shortest closed walk that travels every edge of the Graph at least once.
+///
+/// A walk using every edge exactly once exists only when every node has
+/// even degree. Otherwise, the odd-degree nodes are paired up so that the
+/// total distance between pairs is least (a minimum-weight perfect
+/// matching over their shortest paths), and the edges along each pair's
+/// shortest path are travelled twice. The resulting walk is then traced
+/// with Hierholzer's algorithm.
+///
+/// - graph: the Graph to cover
+/// - start: the node the walk begins and ends at
+///
+/// - return: the walk, or None if start does not exist or some edge
+///   cannot be reached from start
+///
+pub fn route_inspection<'a>(graph: &RouteGraph<'a>, start: &'a str) -> Option<PathResult<'a>>
+{
+    if !graph.contains_node(start) { return None; }
+
+    // Every edge to travel, as (from, to, weight)
+    let mut edges = graph.all_edges()
+        .map(|(from, to, weight)| (from, to, *weight))
+        .collect::<Vec<(&'a str, &'a str, u64)>>();
+
+    // Pair up the odd-degree nodes. Each matching weight is the largest
+    // distance less the pair's distance, so the heaviest perfect matching
+    // has the least total distance
+    let odd = graph.nodes().filter(|node| graph.edges(node).count() % 2 == 1).collect::<Vec<&'a str>>();
+    let trees = odd.iter().map(|node| shortest_path_tree(graph, node)).collect::<Vec<_>>();
+
+    let mut pairs = Vec::new();
+    for (i, tree) in trees.iter().enumerate()
+    {
+        for (j, other) in odd.iter().enumerate().skip(i + 1)
+        {
+            pairs.push((i, j, *tree.dist.get(other)?));
+        }
+    }
+    let longest = pairs.iter().map(|pair| pair.2).max().unwrap_or(0) as i64;
+    let weighted = pairs.iter().map(|&(i, j, dist)| (i, j, longest + 1 - dist as i64)).collect::<Vec<_>>();
+
+    let mates = max_weight_matching(odd.len(), &weighted, true);
+    for (i, mate) in mates.into_iter().enumerate()
+    {
+        // Each pair is handled once, from its lower index
+        let j = match mate
+        {
+            Some(j) if j > i => j,
+            _ => continue,
+        };
+
+        let path = build_route(&trees[i].prev, odd[i], odd[j], 0);
+        for leg in path.nodes.windows(2)
+        {
+            edges.push((leg[0], leg[1], *graph.edge_weight(leg[0], leg[1]).unwrap()));
+        }
+    }
+
+    // Hierholzer's algorithm: follow unused edges until stuck, then
+    // back up, splicing in the sub-walks found along the way
+    let mut incident: HashMap<&'a str, Vec<(usize, &'a str)>> = HashMap::new();
+    for (id, &(from, to, _)) in edges.iter().enumerate()
+    {
+        incident.entry(from).or_default().push((id, to));
+        incident.entry(to).or_default().push((id, from));
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut stack = vec![start];
+    let mut walk = Vec::new();
+    while let Some(&node) = stack.last()
+    {
+        let next = incident.get_mut(node).and_then(|list|
+        {
+            while let Some((id, next)) = list.pop()
+            {
+                if !used[id]
+                {
+                    used[id] = true;
+                    return Some(next);
+                }
+            }
+            None
+        });
+
+        match next
+        {
+            Some(next) => stack.push(next),
+            None => walk.push(stack.pop().unwrap()),
+        }
+    }
+
+    // Some edge lies in a part of the Graph start cannot reach
+    if used.contains(&false) { return None; }
+
+    walk.reverse();
+    let cost = edges.iter().map(|edge| edge.2).sum();
+    Some(PathResult { nodes: walk, cost })
+}
@@ -0,0 +1,312 @@
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
+
+use bumpalo::Bump;
+use petgraph::EdgeType;
+
+use super::{Heuristic, Network, PathResult};
+
+// The f-value of nodes which cannot lead to the end within the node cap
+const INFINITY: u64 = u64::MAX;
+
+///
+/// A single node of the search tree. Many tree nodes may share a
+/// location, as each represents a distinct route from the start. Its
+/// lists are allocated in the search's arena
+///
+struct TreeNode<'a, 'b>
+{
+    location: &'a str,
+    parent: Option<usize>,
+    g: u64,
+    f: u64,
+    depth: usize,
+
+    // Every location reachable without revisiting the route so far,
+    // and the edge weight to it
+    successors: &'b mut [(&'a str, u64)],
+
+    // For each successor, its tree node if held in memory, or the
+    // f-value it had when it was last forgotten
+    children: &'b mut [Option<usize>],
+    forgotten: &'b mut [Option<u64>],
+}
+
+///
+/// The search tree, with a free list so that pruned nodes' slots are reused,
+/// and their lists too by later nodes with as many successors
+///
+struct Tree<'a, 'b>
+{
+    nodes: Vec<Option<TreeNode<'a, 'b>>>,
+    free: Vec<usize>,
+    spare: Vec<Vec<TreeNode<'a, 'b>>>,
+
+    // Every node with successors not held in memory, ordered by
+    // f-value then deepest first
+    open: BTreeSet<(u64, Reverse<usize>, usize)>,
+}
+
+impl<'a, 'b> Tree<'a, 'b>
+{
+    fn node(&self, id: usize) -> &TreeNode<'a, 'b> { self.nodes[id].as_ref().unwrap() }
+    fn node_mut(&mut self, id: usize) -> &mut TreeNode<'a, 'b> { self.nodes[id].as_mut().unwrap() }
+
+    fn open_key(&self, id: usize) -> (u64, Reverse<usize>, usize)
+    {
+        (self.node(id).f, Reverse(self.node(id).depth), id)
+    }
+
+    fn add(&mut self, node: TreeNode<'a, 'b>) -> usize
+    {
+        match self.free.pop()
+        {
+            Some(id) => { self.nodes[id] = Some(node); id },
+            None => { self.nodes.push(Some(node)); self.nodes.len() - 1 },
+        }
+    }
+
+    ///
+    /// Sets a node's f-value, keeping its place in open up to date
+    ///
+    fn set_f(&mut self, id: usize, f: u64)
+    {
+        let was_open = self.open.remove(&self.open_key(id));
+        self.node_mut(id).f = f;
+        if was_open { self.open.insert(self.open_key(id)); }
+    }
+
+    ///
+    /// Once every successor of a node has been generated, its f-value
+    /// becomes the least f-value among them. Repeats up the tree for as
+    /// long as f-values change
+    ///
+    fn back_up(&mut self, mut id: usize)
+    {
+        loop
+        {
+            let node = self.node(id);
+            let mut best = INFINITY;
+            for (child, forgotten) in node.children.iter().zip(node.forgotten.iter())
+            {
+                match (child, forgotten)
+                {
+                    (Some(child), _) => best = best.min(self.node(*child).f),
+                    (None, Some(f)) => best = best.min(*f),
+                    (None, None) => return,
+                }
+            }
+
+            if best == node.f { return; }
+            self.set_f(id, best);
+
+            match self.node(id).parent
+            {
+                Some(parent) => id = parent,
+                None => return,
+            }
+        }
+    }
+
+    ///
+    /// Forgets the shallowest leaf with the highest f-value, other than
+    /// keep, remembering its f-value in its parent
+    ///
+    /// - return: whether a leaf could be pruned
+    ///
+    fn prune(&mut self, keep: usize) -> bool
+    {
+        let leaf = self.open.iter().rev()
+            .map(|entry| entry.2)
+            .find(|&id| id != keep &&
+                        self.node(id).parent.is_some() &&
+                        self.node(id).children.iter().all(Option::is_none));
+
+        let leaf = match leaf
+        {
+            Some(leaf) => leaf,
+            None => return false,
+        };
+
+        self.open.remove(&self.open_key(leaf));
+        let node = self.nodes[leaf].take().unwrap();
+        self.free.push(leaf);
+
+        let parent = node.parent.unwrap();
+        let index = self.node(parent).children.iter().position(|child| *child == Some(leaf)).unwrap();
+        self.node_mut(parent).children[index] = None;
+        self.node_mut(parent).forgotten[index] = Some(node.f);
+
+        let degree = node.successors.len();
+        if self.spare.len() <= degree { self.spare.resize_with(degree + 1, Vec::new); }
+        self.spare[degree].push(node);
+
+        // The parent has a successor to regenerate, so is open once more
+        let key = self.open_key(parent);
+        self.open.insert(key);
+
+        true
+    }
+}
+
+///
+/// Computes the shortest route between two nodes using Simplified
+/// Memory-Bounded A* (SMA*), which never holds more than max_nodes search
+/// tree nodes in memory at once.
+///
+/// SMA* expands like A* until the cap is reached. From then on, every new
+/// node forces out the shallowest, most costly leaf; its parent remembers
+/// the leaf's f-value, and regenerates it only if every other option turns
+/// out worse. The route found is the shortest if it fits within the cap,
+/// and otherwise the shortest that does.
+///
+/// Tree nodes' lists are allocated in the arena, and a pruned node's are
+/// reused by the next node with as many successors, so the arena grows
+/// only while the tree does.
+///
+/// - graph: the Graph to search
+/// - heuristic: estimates the distance between any two nodes
+/// - start: the start location on the Graph
+/// - end: the end location on the Graph
+/// - max_nodes: the most search tree nodes held in memory (at least 2)
+/// - arena: where tree nodes' lists are allocated. It is reset first, so
+///   that one arena's memory can be reused by every query
+///
+/// - return: the route (None if end cannot be reached within the cap),
+///   and the # of nodes considered
+///
+pub fn sma_star<'a, Ty: EdgeType>(graph: &Network<'a, Ty>, heuristic: &dyn Heuristic, start: &'a str, end: &'a str, max_nodes: usize, arena: &mut Bump) -> (Option<PathResult<'a>>, usize)
+{
+    arena.reset();
+    let arena = &*arena;
+    let max_nodes = max_nodes.max(2);
+    let mut tree = Tree { nodes: Vec::new(), free: Vec::new(), spare: Vec::new(), open: BTreeSet::new() };
+    let mut scratch = Scratch::default();
+
+    let root = new_node(graph, &mut tree, arena, &mut scratch, None, start, 0, heuristic.estimate(start, end));
+    let root = tree.add(root);
+    tree.open.insert(tree.open_key(root));
+
+    let mut node_counter = 0;
+    while let Some(&(f, _, best)) = tree.open.iter().next()
+    {
+        if f == INFINITY { break; }
+
+        node_counter += 1;
+        let node = tree.node(best);
+        if node.location == end
+        {
+            let cost = node.g;
+            let mut nodes = Vec::new();
+            let mut current = Some(best);
+            while let Some(id) = current
+            {
+                nodes.push(tree.node(id).location);
+                current = tree.node(id).parent;
+            }
+            nodes.reverse();
+
+            return (Some(PathResult { nodes, cost }), node_counter);
+        }
+
+        // A dead end can never reach the end
+        if node.successors.is_empty()
+        {
+            let parent = node.parent;
+            tree.set_f(best, INFINITY);
+            if let Some(parent) = parent { tree.back_up(parent); }
+            continue;
+        }
+
+        // Generate the most promising successor not held in memory,
+        // preferring those never generated over forgotten ones
+        let index = (0..node.successors.len())
+            .filter(|&i| node.children[i].is_none())
+            .min_by_key(|&i| node.forgotten[i].unwrap_or(0))
+            .unwrap();
+        let (location, weight) = node.successors[index];
+        let (g, depth) = (node.g + weight, node.depth + 1);
+
+        // A node at the cap's depth cannot be extended towards the end.
+        // Otherwise f never decreases along a route, nor below the
+        // f-value the successor had when forgotten
+        let child_f = if location != end && depth + 1 >= max_nodes { INFINITY }
+        else
+        {
+            (g + heuristic.estimate(location, end))
+                .max(node.f)
+                .max(node.forgotten[index].unwrap_or(0))
+        };
+
+        // Make room for the successor
+        if tree.nodes.len() - tree.free.len() >= max_nodes && !tree.prune(best) { break; }
+
+        let child = new_node(graph, &mut tree, arena, &mut scratch, Some(best), location, g, child_f);
+        let child = tree.add(child);
+        tree.node_mut(best).children[index] = Some(child);
+        tree.node_mut(best).forgotten[index] = None;
+        tree.open.insert(tree.open_key(child));
+
+        // With every successor in memory, the node need not be expanded again
+        if tree.node(best).children.iter().all(Option::is_some)
+        {
+            let key = tree.open_key(best);
+            tree.open.remove(&key);
+        }
+
+        tree.back_up(best);
+    }
+
+    (None, node_counter)
+}
+
+///
+/// Buffers reused by every call to new_node, holding the route to the
+/// new node and its successors before they are copied into the arena
+///
+#[derive(Default)]
+struct Scratch<'a>
+{
+    route: Vec<&'a str>,
+    successors: Vec<(&'a str, u64)>,
+}
+
+///
+/// Creates a tree node for location, listing the successors which do not
+/// revisit the route from the start. The lists of a pruned node with as
+/// many successors are reused, if there are any
+///
+#[allow(clippy::too_many_arguments)]
+fn new_node<'a, 'b, Ty: EdgeType>(graph: &Network<'a, Ty>, tree: &mut Tree<'a, 'b>, arena: &'b Bump, scratch: &mut Scratch<'a>, parent: Option<usize>, location: &'a str, g: u64, f: u64) -> TreeNode<'a, 'b>
+{
+    let Scratch { route, successors } = scratch;
+    route.clear();
+    let mut current = parent;
+    while let Some(id) = current
+    {
+        route.push(tree.node(id).location);
+        current = tree.node(id).parent;
+    }
+
+    successors.clear();
+    successors.extend(graph.edges(location)
+        .filter(|edge| !route.contains(&edge.1))
+        .map(|edge| (edge.1, *edge.2)));
+
+    if let Some(node) = tree.spare.get_mut(successors.len()).and_then(Vec::pop)
+    {
+        node.successors.copy_from_slice(successors);
+        node.children.fill(None);
+        node.forgotten.fill(None);
+        return TreeNode { location, parent, g, f, depth: route.len(), ..node };
+    }
+
+    TreeNode
+    {
+        location, parent, g, f,
+        depth: route.len(),
+        successors: arena.alloc_slice_copy(successors),
+        children: arena.alloc_slice_fill_copy(successors.len(), None),
+        forgotten: arena.alloc_slice_fill_copy(successors.len(), None),
+    }
+}
@@ -0,0 +1,82 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use priority_queue::PriorityQueue;
+
+use super::grid::{Cell, Connectivity, Grid, GridPath};
+
+///
+/// Computes a short any-angle route across a Grid using Theta*.
+///
+/// Theta* expands cells like A* over the 8-connected Grid, but whenever a
+/// cell's parent can see the neighbor being relaxed, the neighbor is linked
+/// straight to that parent instead. Routes therefore cut corners between
+/// cells at any angle, rather than zig-zagging along 45 degree moves.
+///
+/// - grid: the Grid to search
+/// - start: the start cell
+/// - end: the end cell
+///
+/// - return: the route (None if end cannot be reached), and the # of
+///   cells considered. Consecutive route cells are in line of sight of
+///   each other, but not necessarily adjacent
+///
+pub fn theta_star(grid: &Grid, start: Cell, end: Cell) -> (Option<GridPath>, usize)
+{
+    if !grid.is_passable(start) || !grid.is_passable(end) { return (None, 0); }
+
+    let mut dist: HashMap<Cell, u64> = HashMap::new();
+    let mut parent: HashMap<Cell, Cell> = HashMap::new();
+    let mut closed: HashSet<Cell> = HashSet::new();
+    dist.insert(start, 0);
+    parent.insert(start, start);
+
+    let mut open = PriorityQueue::new();
+    open.push(start, Reverse(grid.euclidean(start, end)));
+
+    let mut node_counter = 0;
+    while let Some((cell, _)) = open.pop()
+    {
+        node_counter += 1;
+        if cell == end
+        {
+            // Follow the parents back to start
+            let mut cells = vec![end];
+            let mut current = end;
+            while current != start
+            {
+                current = parent[&current];
+                cells.push(current);
+            }
+            cells.reverse();
+
+            return (Some(GridPath { cells, cost: dist[&end] }), node_counter);
+        }
+        closed.insert(cell);
+
+        for (neighbor, _) in grid.neighbors(cell, Connectivity::Eight)
+        {
+            if closed.contains(&neighbor) { continue; }
+
+            // Link straight to the grandparent if nothing is in the way
+            let grandparent = parent[&cell];
+            let (via, alt_route) = if grid.line_of_sight(grandparent, neighbor)
+            {
+                (grandparent, dist[&grandparent] + grid.euclidean(grandparent, neighbor))
+            }
+            else
+            {
+                (cell, dist[&cell] + grid.euclidean(cell, neighbor))
+            };
+
+            if dist.get(&neighbor).is_none_or(|d| alt_route < *d)
+            {
+                dist.insert(neighbor, alt_route);
+                parent.insert(neighbor, via);
+                open.push(neighbor, Reverse(alt_route + grid.euclidean(neighbor, end)));
+            }
+        }
+    }
+
+    (None, node_counter)
+}
@@ -0,0 +1,180 @@
+use std::collections::VecDeque;
+
+use petgraph::{Direction, EdgeType};
+
+use super::{search_map, search_set, Network, PathResult, SearchMap, SearchSet};
+
+///
+/// Computes the route between two nodes with the fewest hops (edges),
+/// ignoring edge weights, using breadth-first search
+///
+/// - graph: the Graph to search
+/// - start: the start location on the Graph
+/// - end: the end location on the Graph
+///
+/// - return: the route (None if end cannot be reached), and the # of
+///   nodes considered. The route's cost is its total distance, which
+///   need not be the shortest distance
+///
+pub fn bfs<'a, Ty: EdgeType>(graph: &Network<'a, Ty>, start: &'a str, end: &'a str) -> (Option<PathResult<'a>>, usize)
+{
+    let mut prev: SearchMap<&'a str, &'a str> = search_map(graph.node_count());
+    let mut visited: SearchSet<&'a str> = search_set(graph.node_count());
+    visited.insert(start);
+
+    let mut queue = VecDeque::new();
+    queue.push_back(start);
+
+    let mut node_counter = 0;
+    while let Some(node) = queue.pop_front()
+    {
+        node_counter += 1;
+        if node == end { return (Some(weighted_route(graph, &prev, start, end)), node_counter); }
+
+        for next in graph.neighbors(node)
+        {
+            if visited.insert(next)
+            {
+                prev.insert(next, node);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    (None, node_counter)
+}
+
+///
+/// Computes the route between two nodes with the fewest hops (edges) using
+/// bidirectional breadth-first search. Searches outward from both ends at
+/// once, always growing the smaller frontier by a whole level, and stops
+/// when they meet, so far fewer nodes are considered than by bfs
+///
+/// - graph: the Graph to search
+/// - start: the start location on the Graph
+/// - end: the end location on the Graph
+///
+/// - return: the route (None if end cannot be reached), and the # of
+///   nodes considered. The route's cost is its total distance, which
+///   need not be the shortest distance
+///
+pub fn bidirectional_bfs<'a, Ty: EdgeType>(graph: &Network<'a, Ty>, start: &'a str, end: &'a str) -> (Option<PathResult<'a>>, usize)
+{
+    if start == end { return (Some(PathResult { nodes: vec![start], cost: 0 }), 1); }
+
+    // Each side's hop count to every node it has reached, the node it
+    // was reached from, and its current frontier level
+    let mut hops: [SearchMap<&'a str, usize>; 2] = [search_map(graph.node_count()), search_map(graph.node_count())];
+    let mut prev: [SearchMap<&'a str, &'a str>; 2] = [search_map(graph.node_count()), search_map(graph.node_count())];
+    let mut frontier = [vec![start], vec![end]];
+    hops[0].insert(start, 0);
+    hops[1].insert(end, 0);
+
+    let mut node_counter = 0;
+    while !frontier[0].is_empty() && !frontier[1].is_empty()
+    {
+        let side = if frontier[0].len() <= frontier[1].len() { 0 } else { 1 };
+        let other = 1 - side;
+
+        // Expand the whole level, noting the best meeting point found
+        let mut meeting: Option<(usize, &'a str, &'a str)> = None;
+        let mut next_frontier = Vec::new();
+        for node in std::mem::take(&mut frontier[side])
+        {
+            node_counter += 1;
+            // The end's side searches backward, along edges into each node
+            let direction = if side == 0 { Direction::Outgoing } else { Direction::Incoming };
+            for next in graph.neighbors_directed(node, direction)
+            {
+                if let Some(other_hops) = hops[other].get(next)
+                {
+                    let total = hops[side][node] + 1 + other_hops;
+                    if meeting.is_none_or(|(best, _, _)| total < best) { meeting = Some((total, node, next)); }
+                }
+
+                if !hops[side].contains_key(next)
+                {
+                    hops[side].insert(next, hops[side][node] + 1);
+                    prev[side].insert(next, node);
+                    next_frontier.push(next);
+                }
+            }
+        }
+        frontier[side] = next_frontier;
+
+        if let Some((_, node, next)) = meeting
+        {
+            // Join the start's half of the route to the end's half
+            let (near, far) = if side == 0 { (node, next) } else { (next, node) };
+            let mut route = super::build_route(&prev[0], start, near, 0);
+            let mut current = far;
+            route.nodes.push(current);
+            while let Some(&prv) = prev[1].get(current)
+            {
+                current = prv;
+                route.nodes.push(current);
+            }
+            route.cost = route.nodes.windows(2)
+                .map(|pair| graph.edge_weight(pair[0], pair[1]).unwrap())
+                .sum();
+
+            return (Some(route), node_counter);
+        }
+    }
+
+    (None, node_counter)
+}
+
+///
+/// Finds any route between two nodes using depth-first search. The route
+/// found depends only on the order edges are stored in, and is usually
+/// far from the shortest
+///
+/// - graph: the Graph to search
+/// - start: the start location on the Graph
+/// - end: the end location on the Graph
+///
+/// - return: the route (None if end cannot be reached), and the # of
+///   nodes considered
+///
+pub fn dfs<'a, Ty: EdgeType>(graph: &Network<'a, Ty>, start: &'a str, end: &'a str) -> (Option<PathResult<'a>>, usize)
+{
+    let mut prev: SearchMap<&'a str, &'a str> = search_map(graph.node_count());
+    let mut visited: SearchSet<&'a str> = search_set(graph.node_count());
+
+    // Each stack entry is a node, and the node it was reached from
+    let mut stack = vec![(start, start)];
+
+    let mut node_counter = 0;
+    while let Some((node, from)) = stack.pop()
+    {
+        if !visited.insert(node) { continue; }
+        if node != from { prev.insert(node, from); }
+
+        node_counter += 1;
+        if node == end { return (Some(weighted_route(graph, &prev, start, end)), node_counter); }
+
+        // Push in reverse, so that edges are explored in stored order
+        let nexts = graph.neighbors(node).collect::<Vec<&'a str>>();
+        for next in nexts.into_iter().rev()
+        {
+            if !visited.contains(next) { stack.push((next, node)); }
+        }
+    }
+
+    (None, node_counter)
+}
+
+///
+/// Builds the route ending at end by following prev back to start,
+/// totalling the weights of the edges along it
+///
+fn weighted_route<'a, Ty: EdgeType>(graph: &Network<'a, Ty>, prev: &SearchMap<&'a str, &'a str>, start: &'a str, end: &'a str) -> PathResult<'a>
+{
+    let mut route = super::build_route(prev, start, end, 0);
+    route.cost = route.nodes.windows(2)
+        .map(|pair| graph.edge_weight(pair[0], pair[1]).unwrap())
+        .sum();
+
+    route
+}
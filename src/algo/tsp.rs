@@ -0,0 +1,103 @@
+use petgraph::EdgeType;
+
+use super::astar::{astar, TieBreak};
+use super::{Heuristic, Network, PathResult};
+
+///
+/// A closed tour through a set of waypoints
+///
+/// - stops: the waypoints in the order visited, beginning and ending
+///   at the first waypoint
+/// - route: every location along the tour, and its total distance
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tour<'a>
+{
+    pub stops: Vec<&'a str>,
+    pub route: PathResult<'a>,
+}
+
+///
+/// Computes a short closed tour visiting every waypoint, beginning and
+/// ending at the first. The shortest route between each pair of waypoints
+/// is found with A*, a tour is built by always visiting the nearest
+/// unvisited waypoint, and then 2-opt reverses sections of the tour for
+/// as long as doing so shortens it. The tour is not guaranteed to be the
+/// shortest, but is usually close
+///
+/// - graph: the Graph to search
+/// - heuristic: estimates the distance between any two nodes
+/// - waypoints: the locations to visit (duplicates are ignored)
+///
+/// - return: the Tour, or None if some waypoint cannot reach another
+///
+pub fn approximate_tour<'a, Ty: EdgeType>(graph: &Network<'a, Ty>, heuristic: &dyn Heuristic, waypoints: &[&'a str]) -> Option<Tour<'a>>
+{
+    let mut stops: Vec<&'a str> = Vec::new();
+    for &waypoint in waypoints
+    {
+        if !stops.contains(&waypoint) { stops.push(waypoint); }
+    }
+    if stops.is_empty() { return None; }
+
+    // The shortest route between every ordered pair of waypoints
+    let mut legs: Vec<Vec<PathResult<'a>>> = Vec::new();
+    for &from in &stops
+    {
+        let mut row = Vec::new();
+        for &to in &stops
+        {
+            row.push(astar(graph, Some(heuristic), from, to, TieBreak::default()).0?);
+        }
+        legs.push(row);
+    }
+    let dist = |a: usize, b: usize| legs[a][b].cost;
+    let tour_cost = |order: &[usize]| -> u64
+    {
+        order.iter().zip(order.iter().cycle().skip(1)).map(|(&a, &b)| dist(a, b)).sum()
+    };
+
+    // Nearest neighbor: start at the first waypoint, and repeatedly
+    // move to the closest waypoint not yet visited
+    let mut order = vec![0];
+    let mut unvisited = (1..stops.len()).collect::<Vec<usize>>();
+    while !unvisited.is_empty()
+    {
+        let current = order[order.len() - 1];
+        let (i, _) = unvisited.iter().enumerate().min_by_key(|(_, &next)| dist(current, next)).unwrap();
+        order.push(unvisited.remove(i));
+    }
+
+    // 2-opt: reverse any section of the tour (other than the fixed
+    // first waypoint) whose reversal shortens it, until none does
+    let mut improved = true;
+    while improved
+    {
+        improved = false;
+        for i in 1..order.len()
+        {
+            for j in i + 1..order.len()
+            {
+                let mut candidate = order.clone();
+                candidate[i..=j].reverse();
+                if tour_cost(&candidate) < tour_cost(&order)
+                {
+                    order = candidate;
+                    improved = true;
+                }
+            }
+        }
+    }
+
+    // Join the legs of the tour into a single route
+    order.push(0);
+    let mut route = PathResult { nodes: vec![stops[0]], cost: 0 };
+    for pair in order.windows(2)
+    {
+        let leg = &legs[pair[0]][pair[1]];
+        route.nodes.extend(&leg.nodes[1..]);
+        route.cost += leg.cost;
+    }
+
+    Some(Tour { stops: order.into_iter().map(|i| stops[i]).collect(), route })
+}
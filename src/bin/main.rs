@@ -1,19 +1,1428 @@
-use a_star::prog::Program;
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
-fn main() 
+use bumpalo::Bump;
+use clap::builder::PossibleValuesParser;
+use clap::{ArgAction, Args, CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use log::{warn, Level, LevelFilter, Log, Metadata, Record};
+use owo_colors::{OwoColorize, Stream};
+
+use a_star::algo::alt::{Landmarks, DEFAULT_LANDMARK_COUNT};
+use a_star::algo::astar::TieBreak;
+use a_star::algo::context::SearchContext;
+use a_star::algo::frontier::Frontier;
+use a_star::algo::geo::{Euclidean, GreatCircle, GridDistance};
+use a_star::algo::grid::Connectivity;
+use a_star::algo::intern::{InternedGraph, SearchGraph};
+use a_star::algo::{analysis, Algorithm, Heuristic, HeuristicTable, RouteGraph};
+use a_star::config::{Config, CONFIG_FILE};
+use a_star::export::{self, dot::to_dot};
+use a_star::load::movingai::{parse_map, parse_scenarios};
+use a_star::load::compiled::{self, CompiledGraph};
+use a_star::load::{sidecar, validate};
+use a_star::load::{binary, build_coordinates, build_heur_data, gtfs, load_routes, open_file, parse_queries, open_stdin, read_file, read_map, read_stdin, read_text, Duplicates, Format, LoadOptions, Routes, Unit};
+use a_star::history;
+use a_star::io::{is_plain, read_from_terminal, set_plain};
+use a_star::prog::{OutputFormat, Program, DEFAULT_PRECISION, EXIT_FOUND, EXIT_UNKNOWN_LOCATION, EXIT_UNREACHABLE};
+use a_star::report::{error_json, AlgorithmReport, Leg, RouteReport};
+use a_star::tui;
+
+/// The route files looked for, in order of preference
+const ROUTE_FILES: [&str; 3] = ["routes.txt", "routes.csv", "routes.json"];
+
+/// The route file name read as standard input
+const STDIN_PATH: &str = "-";
+
+/// The coordinates file looked for, if none is given
+const COORDINATES_FILE: &str = "coordinates.txt";
+
+/// The heuristic file looked for, if none is given
+const HEURISTIC_FILE: &str = "euclidian.txt";
+
+/// The # of pairs of locations named when a heuristic file is missing any
+const MISSING_PAIRS_SHOWN: usize = 5;
+
+/// The # of examples the validate command names of each kind of problem
+const EXAMPLES_SHOWN: usize = 5;
+
+/// The weight of one cell's step when running MovingAI scenarios, fine
+/// enough that diagonal steps are all but exactly √2 cells
+const SCENARIO_STRAIGHT_COST: u64 = 1_000_000;
+
+/// The # of random queries the bench command times, unless given
+const DEFAULT_BENCH_QUERIES: usize = 1000;
+
+/// The algorithms which can search a compiled graph
+const COMPILED_ALGORITHMS: [Algorithm; 3] = [Algorithm::AStar, Algorithm::Dijkstra, Algorithm::Fringe];
+
+/// The levels logged by default, and with each "-v" or "-q" given, from
+/// the quietest
+const LOG_LEVELS: [LevelFilter; 6] = [LevelFilter::Off, LevelFilter::Error, LevelFilter::Warn, LevelFilter::Info, LevelFilter::Debug, LevelFilter::Trace];
+const DEFAULT_LOG_LEVEL: usize = 2;
+
+///
+/// Writes log messages to standard error, each after its level
+///
+struct StderrLogger;
+
+impl Log for StderrLogger
+{
+    fn enabled(&self, metadata: &Metadata) -> bool
+    {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record)
+    {
+        if !self.enabled(record.metadata()) { return; }
+        let level = match record.level()
+        {
+            Level::Error => "error".if_supports_color(Stream::Stderr, |level| level.red()).to_string(),
+            Level::Warn => "warning".if_supports_color(Stream::Stderr, |level| level.yellow()).to_string(),
+            Level::Info => "info".if_supports_color(Stream::Stderr, |level| level.blue()).to_string(),
+            Level::Debug => "debug".if_supports_color(Stream::Stderr, |level| level.dimmed()).to_string(),
+            Level::Trace => "trace".if_supports_color(Stream::Stderr, |level| level.dimmed()).to_string(),
+        };
+        eprintln!("{}: {}", level, record.args());
+    }
+
+    fn flush(&self) {}
+}
+
+///
+/// Prints a message, and exits the program with an error code
+///
+fn fail(message: &str) -> !
+{
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
+
+
+///
+/// Looks for a file beside the route file, and then in the working
+/// directory
+///
+/// - route_path: the route file's path
+/// - name: the file's name
+///
+/// - return: the path of the file found, or None if there is none
+///
+fn find_beside(route_path: &str, name: &str) -> Option<PathBuf>
+{
+    let beside = Path::new(route_path).parent().map(|dir| dir.join(name));
+    beside.into_iter()
+        .chain(std::iter::once(PathBuf::from(name)))
+        .find(|path| path.is_file())
+}
+
+///
+/// Reads a delimiter, as a single character, or "tab" or "space"
+///
+fn parse_delimiter(name: &str) -> Option<char>
+{
+    match name
+    {
+        "tab" | "\\t" => Some('\t'),
+        "space" => Some(' '),
+        _ =>
+        {
+            let mut chars = name.chars();
+            match (chars.next(), chars.next())
+            {
+                (Some(delimiter), None) => Some(delimiter),
+                _ => None,
+            }
+        },
+    }
+}
+
+///
+/// Runs every problem of a MovingAI scenario file, printing each route's
+/// length beside its optimal length
+///
+/// - scenario_path: the scenario file's path
+/// - map_path: the map file's path. If None, the map named by the first
+///   scenario is looked for beside the scenario file
+/// - algorithms: the algorithms set, the first of which is routed with,
+///   or none for A*
+///
+fn run_scenarios(scenario_path: &Path, map_path: Option<&Path>, algorithms: Vec<Algorithm>)
+{
+    let scenarios = parse_scenarios(&read_text(scenario_path).unwrap_or_else(|e| fail(&e)))
+        .unwrap_or_else(|e| fail(&format!("{}: {}", scenario_path.display(), e)));
+
+    // Scenario files name their map relative to the benchmark's root
+    let map_path = match map_path
+    {
+        Some(map_path) => map_path.to_path_buf(),
+        None =>
+        {
+            let map = &scenarios.first().unwrap_or_else(|| fail(&format!("{}: no scenarios.", scenario_path.display()))).map;
+            let dir = scenario_path.parent().unwrap_or_else(|| Path::new(""));
+            Path::new(map).file_name().map(|name| dir.join(name)).into_iter()
+                .chain(vec![dir.join(map), PathBuf::from(map)])
+                .find(|path| path.is_file())
+                .unwrap_or_else(|| fail(&format!("Cannot find the map \"{}\"; give its path with --map.", map)))
+        },
+    };
+
+    let arena = Bump::new();
+    let map_text = read_text(&map_path).unwrap_or_else(|e| fail(&e));
+    let routes = parse_map(&map_text, SCENARIO_STRAIGHT_COST, &arena)
+        .unwrap_or_else(|e| fail(&format!("{}: {}", map_path.display(), e)));
+
+    let heuristic = GridDistance::new(routes.coordinates, Connectivity::Eight, SCENARIO_STRAIGHT_COST);
+    let mut prog = Program::from_graph(routes.graph, Box::new(heuristic));
+    prog.set_grid(routes.grid);
+    prog.set_algorithms(algorithms).unwrap_or_else(|e| fail(&e));
+    prog.run_scenarios(&scenarios, SCENARIO_STRAIGHT_COST);
+}
+
+/// Printed after the flags in every command's help
+const AFTER_HELP: &str = "\
+Without a route file, routes.txt, routes.csv or routes.json is read from the working \
+directory. Without coordinates or heuristic files, coordinates.txt or euclidian.txt is \
+looked for beside the first route file, and then in the working directory. Any file may \
+be gzipped, and its format is then named by the extension before \".gz\" (such as \
+routes.csv.gz). Settings may also be given in pathfinding.toml in the working directory, \
+or the file given with --config, which flags override.
+
+A compiled graph (.cgraph, written by convert) is searched where it lies in memory rather \
+than loaded, so the route command answers from it at once, however large it is. Its \
+locations must be named exactly, and queries are answered as with --script, or as JSON, \
+by A*, Djikstra or Fringe.
+
+Exits with 0, or 1 if the program could not start. The route command exits with 3 if any \
+query named an unknown location, or else 2 if any could not be completed.";
+
+///
+/// Finds and compares shortest routes between locations. Without a
+/// command, routes interactively
+///
+#[derive(Parser, Debug)]
+#[command(name = "pathfinding", version, args_conflicts_with_subcommands = true, after_help = AFTER_HELP)]
+struct Cli
+{
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    query: QueryArgs,
+
+    /// Log a summary of each search to standard error, or with -vv each
+    /// route relaxed as well
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Log only errors, or with -qq nothing, instead of errors and
+    /// warnings about the route data
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    quiet: u8,
+
+    /// Print without colour. Colour is also left out when output is not a
+    /// terminal, or NO_COLOR is set
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Print plain, linear text for screen readers and terminal logs: the
+    /// screen is never cleared, and there is no colour, progress spinner
+    /// or line editing. Also used when TERM is "dumb"
+    #[arg(long, global = true)]
+    plain: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command
+{
+    /// Route between locations typed at prompts, comparing algorithms (the
+    /// default)
+    #[command(after_help = AFTER_HELP)]
+    Interactive(QueryArgs),
+
+    /// Route in a full-screen terminal interface: search the locations,
+    /// choose the start and end, and switch algorithms with Tab, seeing
+    /// the route beside each algorithm's statistics
+    #[command(after_help = AFTER_HELP)]
+    Tui(QueryArgs),
+
+    /// Route from one location to another and exit, answer a file of
+    /// queries, or else answer queries read from standard input, one
+    /// "start<TAB>end" line each, without prompts
+    #[command(after_help = AFTER_HELP)]
+    Route(RouteArgs),
+
+    /// Load the routes, coordinates and heuristic, reporting self-loops,
+    /// routes of zero weight, duplicate routes, isolated locations and
+    /// locations only one of the files names, with examples
+    #[command(after_help = AFTER_HELP)]
+    Validate(DataArgs),
+
+    /// Check the heuristic against the shortest distance to every location
+    /// (or a random sample of them), reporting each estimate above it and
+    /// each route across which estimates fall by more than its length, as
+    /// either can make A* return routes longer than the shortest
+    #[command(after_help = AFTER_HELP)]
+    ValidateHeuristic(ValidateHeuristicArgs),
+
+    /// Time each algorithm over random queries between the routes'
+    /// locations, printing percentiles of the time taken per search, or
+    /// else run every problem of a MovingAI scenario file, comparing each
+    /// route's length with the optimal length
+    #[command(after_help = AFTER_HELP)]
+    Bench(BenchArgs),
+
+    /// Write the routes in another format, chosen by the file's extension
+    #[command(after_help = AFTER_HELP)]
+    Convert(ConvertArgs),
+
+    /// Print statistics about the routes
+    #[command(after_help = AFTER_HELP)]
+    Stats(DataArgs),
+
+    /// Print a shell completion script, completing the locations of any
+    /// route files given (or named in the configuration file) after
+    /// "route --from" and "--to"
+    Completions(CompletionsArgs),
+}
+
+///
+/// The flags choosing the route data to load
+///
+#[derive(Args, Debug)]
+struct DataArgs
+{
+    /// Route files, merged into one set of routes ("-" reads the routes
+    /// from standard input)
+    #[arg(value_name = "ROUTES")]
+    routes: Vec<PathBuf>,
+
+    /// The route files' format, overriding their extension: txt, csv,
+    /// json, graphml, dot, geojson, osm, pbf, gtfs, matrix, graph, cgraph,
+    /// grid, or map (MovingAI)
+    #[arg(long, value_name = "NAME")]
+    format: Option<String>,
+
+    /// The edge attribute holding distances
+    #[arg(long, value_name = "ATTRIBUTE")]
+    weight: Option<String>,
+
+    /// The area of an OpenStreetMap extract to keep roads within
+    #[arg(long, value_name = "MIN_LON,MIN_LAT,MAX_LON,MAX_LAT")]
+    bbox: Option<String>,
+
+    /// Read each route as one-way, from its first location to its second,
+    /// for formats which cannot mark direction
+    #[arg(long)]
+    directed: bool,
+
+    /// Let grid maps be crossed diagonally, as well as up, down, left and
+    /// right
+    #[arg(long)]
+    diagonal: bool,
+
+    /// How to merge routes listed more than once: keep-min (the default),
+    /// keep-max, sum, or error
+    #[arg(long, value_name = "POLICY")]
+    duplicates: Option<String>,
+
+    /// A file of each location's x and y in miles, from which
+    /// straight-line distances are estimated
+    #[arg(long, value_name = "PATH")]
+    coordinates: Option<PathBuf>,
+
+    /// Read the coordinates file's values as latitude and longitude in
+    /// degrees instead, estimating great-circle distances
+    #[arg(long)]
+    geographic: bool,
+
+    /// A file of straight-line distances between locations, as "from to
+    /// distance" lines
+    #[arg(long, value_name = "PATH")]
+    heuristic: Option<PathBuf>,
+
+    /// The straight-line distance estimated between locations the
+    /// heuristic file has no distance between, either way. Anything above
+    /// 0 (the default) may overestimate, so that A* misses the shortest
+    /// route
+    #[arg(long, value_name = "DISTANCE")]
+    heuristic_default: Option<f64>,
+
+    /// The character between fields in text, CSV and matrix route files,
+    /// and in coordinates and heuristic files, with "tab" and "space"
+    /// accepted by name
+    #[arg(long, value_name = "CHAR")]
+    delimiter: Option<String>,
+
+    /// What the route file's weights measure, with any of miles (mi),
+    /// kilometers (km), minutes, cells or raw (plain numbers, kept to
+    /// thousandths and printed without a unit), in place of what its
+    /// format implies. Lengths measured from GeoJSON and OpenStreetMap
+    /// maps are computed in it
+    #[arg(long, value_name = "UNIT")]
+    units: Option<String>,
+
+    /// Read the routes from standard input, as with "-"
+    #[arg(long)]
+    stdin: bool,
+
+    /// A configuration file giving any of these settings, which flags
+    /// override, instead of pathfinding.toml
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+}
+
+///
+/// The flags for routing, as well as loading the route data
+///
+#[derive(Args, Debug)]
+struct QueryArgs
+{
+    #[command(flatten)]
+    data: DataArgs,
+
+    /// Route with these algorithms, separated by commas (such as
+    /// "astar,dijkstra", or "all"), instead of comparing A*, Djikstra's
+    /// algorithm and fringe search (after "dag", routing in topological
+    /// order, if every route is one-way and none form a cycle). Grid maps
+    /// crossed diagonally may also be routed with "jps" (Jump Point
+    /// Search) or "theta" (Theta*, any-angle). Queries answered without
+    /// prompts use the first
+    #[arg(long, value_name = "NAME")]
+    algorithm: Option<String>,
+
+    /// The queue A* and Djikstra's algorithm keep their frontier in:
+    /// lazy-heap (the default), priority-queue, buckets (Dial's
+    /// algorithm, for routes no heavier than 6553.6, or 65.536 raw),
+    /// radix-heap, or pairing-heap. All find the same routes, at
+    /// different speeds
+    #[arg(long, value_name = "NAME")]
+    frontier: Option<String>,
+
+    /// Renumber the locations searched in reverse Cuthill-McKee order, so
+    /// that neighbouring locations lie close together in memory. Routes
+    /// are the same, but large Graphs may be searched faster
+    #[arg(long)]
+    reorder: bool,
+
+    /// The # of decimal places printed in distances, at most the places
+    /// weights are kept to: 1, or 3 for raw weights
+    #[arg(long, value_name = "DIGITS")]
+    precision: Option<usize>,
+
+    /// The # of threads a file of queries is searched on at once, or 0 for
+    /// one per CPU. Answers are still written in order
+    #[arg(long, value_name = "N")]
+    threads: Option<usize>,
+
+    /// Keep the last N searches in memory, so that a query asked again
+    /// with the same algorithm and options is answered without searching.
+    /// How often queries were answered this way is reported
+    #[arg(long, value_name = "N")]
+    cache: Option<usize>,
+
+    /// The speed routes are travelled at, in miles per hour (or kilometers
+    /// per hour, for routes in kilometers), printing the time taken to
+    /// reach each location along a route
+    #[arg(long, value_name = "SPEED")]
+    speed: Option<f64>,
+
+    /// How results are written: text (the default), json, which answers
+    /// each pair of start and end lines read with a line of JSON, or
+    /// script
+    #[arg(long, value_name = "FORMAT")]
+    output: Option<String>,
+
+    /// Write results as JSON, as with "--output json"
+    #[arg(long)]
+    json: bool,
+
+    /// Answer each "start<TAB>end" line read with one tab-separated line
+    /// ("found", the locations, the distance and the route; or
+    /// "unreachable" or "unknown" and the locations), with prompts and
+    /// messages on standard error, as with "--output script"
+    #[arg(long)]
+    script: bool,
+
+    /// Write each route shown to a GPX track file, for route files or
+    /// coordinates with latitude and longitude
+    #[arg(long, value_name = "PATH")]
+    gpx: Option<PathBuf>,
+
+    /// Append a row to a CSV file for each algorithm's search, of its
+    /// locations, algorithm, # of nodes expanded, cost and time taken
+    #[arg(long, value_name = "PATH")]
+    stats: Option<PathBuf>,
+
+    /// Append the nodes each A* and Djikstra search expands, in order,
+    /// with their g, h and f values and parents, as JSON (for .json or
+    /// .jsonl files) or else CSV
+    #[arg(long, value_name = "PATH")]
+    expansions: Option<PathBuf>,
+
+    /// The file every search is recorded in, for the "History" command to
+    /// list and re-run, instead of .pathfinding_history in the home
+    /// directory
+    #[arg(long, value_name = "PATH")]
+    history: Option<PathBuf>,
+
+    /// Write the loaded routes to a binary route file (.graph), which
+    /// loads far faster on later runs
+    #[arg(long, value_name = "PATH")]
+    save: Option<PathBuf>,
+}
+
+///
+/// The flags for routing without prompts
+///
+#[derive(Args, Debug)]
+struct RouteArgs
+{
+    #[command(flatten)]
+    query: QueryArgs,
+
+    /// The location to route from, printing the route as text (unless
+    /// another output format is given) and exiting
+    #[arg(long, value_name = "LOCATION", requires = "to")]
+    from: Option<String>,
+
+    /// The location to route to
+    #[arg(long, value_name = "LOCATION", requires = "from")]
+    to: Option<String>,
+
+    /// A file of queries to answer, one per line, as a start and an end
+    /// location separated by a tab or comma, followed by statistics over
+    /// them all
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["from", "to"])]
+    queries: Option<PathBuf>,
+}
+
+///
+/// The flags for running a MovingAI benchmark
+///
+#[derive(Args, Debug)]
+struct BenchArgs
+{
+    #[command(flatten)]
+    query: QueryArgs,
+
+    /// The # of random queries to time
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_BENCH_QUERIES)]
+    count: usize,
+
+    /// The seed random queries are drawn from. The same seed always draws
+    /// the same queries
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    seed: u64,
+
+    /// A MovingAI scenario file (.scen) to run, instead of random queries.
+    /// A route file ending in .scen is also run as a scenario file
+    #[arg(long, value_name = "PATH")]
+    scenarios: Option<PathBuf>,
+
+    /// The scenarios' map, instead of the map the scenario file names,
+    /// looked for beside it
+    #[arg(long, value_name = "PATH")]
+    map: Option<PathBuf>,
+}
+
+///
+/// The flags for checking the heuristic, as well as routing
+///
+#[derive(Args, Debug)]
+struct ValidateHeuristicArgs
+{
+    #[command(flatten)]
+    query: QueryArgs,
+
+    /// Check the estimates to this many random locations, rather than to
+    /// every location
+    #[arg(long, value_name = "N")]
+    sample: Option<usize>,
+
+    /// The seed the sample is drawn from. The same seed always draws the
+    /// same locations
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    seed: u64,
+}
+
+///
+/// The flags for converting route data to another format
+///
+#[derive(Args, Debug)]
+struct ConvertArgs
+{
+    #[command(flatten)]
+    data: DataArgs,
+
+    /// The file to write: a binary route file (.graph), which loads far
+    /// faster, a compiled graph (.cgraph), which is searched without
+    /// loading, with any heuristic table, or a Graphviz DOT file (.dot or
+    /// .gv)
+    #[arg(long, value_name = "PATH")]
+    to: PathBuf,
+}
+
+///
+/// The flags for printing a shell completion script
+///
+#[derive(Args, Debug)]
+struct CompletionsArgs
+{
+    /// The shell to complete commands in
+    #[arg(value_enum)]
+    shell: Shell,
+
+    #[command(flatten)]
+    data: DataArgs,
+}
+
+impl DataArgs
 {
-    // Import route data
-    let route_dat_text = std::fs::read_to_string("routes.txt")
-        .expect("Undefined io error when reading \"routes.txt\"");
+    ///
+    /// Combines these flags with the configuration file's settings
+    ///
+    /// - return: the settings, with flags overriding the configuration
+    ///   file's
+    ///
+    fn config(self) -> Config
+    {
+        let config_file = self.config.clone();
+        configure(self.flags(), config_file)
+    }
+
+    ///
+    /// - return: the settings given by these flags
+    ///
+    fn flags(self) -> Config
+    {
+        let mut routes = self.routes;
+        if self.stdin { routes.push(PathBuf::from(STDIN_PATH)); }
 
-    // Import heuristic data
-    let heur_dat_text = std::fs::read_to_string("euclidian.txt")
-        .expect("Undefined io error when reading \"euclidian.txt\"");
+        Config
+        {
+            routes,
+            format: self.format,
+            weight: self.weight,
+            bbox: self.bbox,
+            directed: self.directed.then_some(true),
+            diagonal: self.diagonal.then_some(true),
+            duplicates: self.duplicates,
+            delimiter: self.delimiter,
+            coordinates: self.coordinates,
+            geographic: self.geographic.then_some(true),
+            heuristic: self.heuristic,
+            heuristic_default: self.heuristic_default,
+            units: self.units,
+            ..Config::default()
+        }
+    }
+}
+
+impl QueryArgs
+{
+    ///
+    /// Combines these flags with the configuration file's settings
+    ///
+    /// - return: the settings, with flags overriding the configuration
+    ///   file's
+    ///
+    fn config(self) -> Config
+    {
+        let config_file = self.data.config.clone();
+        let output = match (self.json, self.script)
+        {
+            (true, _) => Some(String::from("json")),
+            (_, true) => Some(String::from("script")),
+            _ => self.output,
+        };
+
+        configure(Config
+        {
+            algorithm: self.algorithm,
+            frontier: self.frontier,
+            reorder: self.reorder.then_some(true),
+            precision: self.precision,
+            threads: self.threads,
+            cache: self.cache,
+            speed: self.speed,
+            output,
+            gpx: self.gpx,
+            stats: self.stats,
+            expansions: self.expansions,
+            history: self.history,
+            ..self.data.flags()
+        }, config_file)
+    }
+}
+
+///
+/// The files read before any routes are loaded, which the routes and
+/// heuristic borrow from
+///
+/// - route_files: each route file's path, as given
+/// - route_data: each route file's format, and its data if read whole
+/// - coordinates: the coordinates file's path and text, if any
+/// - heuristic: the heuristic file's path and text, if any
+/// - arena: holds any location names not borrowed from the files' data
+///
+struct Sources
+{
+    route_files: Vec<String>,
+    route_data: Vec<(Format, Option<Vec<u8>>)>,
+    coordinates: Option<(PathBuf, String)>,
+    heuristic: Option<(PathBuf, String)>,
+    arena: Bump,
+}
+
+///
+/// Combines flags with the configuration file's settings
+///
+/// - flags: the settings given by flags
+/// - config_file: the configuration file given, if any. Otherwise
+///   pathfinding.toml is read from the working directory, if it exists
+///
+/// - return: the settings, with flags overriding the configuration file's
+///
+fn configure(flags: Config, config_file: Option<PathBuf>) -> Config
+{
+    let config_file = config_file.or_else(|| Some(PathBuf::from(CONFIG_FILE)).filter(|path| path.is_file()));
+    match config_file
+    {
+        Some(path) => flags.or(Config::load(&path).unwrap_or_else(|e| fail(&e))),
+        None => flags,
+    }
+}
+
+///
+/// Reads how the route files are loaded from the settings
+///
+/// - return: the route files' format, if given, and the options to load
+///   them with
+///
+fn load_options(config: &Config) -> (Option<Format>, LoadOptions)
+{
+    let format = config.format.as_ref().map(|name| Format::from_name(name)
+        .unwrap_or_else(|| fail(&format!("Unknown route file format \"{}\".", name))));
+    let options = LoadOptions
+    {
+        weight: config.weight.clone(),
+        bbox: config.bbox.as_ref().map(|bbox| bbox.parse().unwrap_or_else(|e: String| fail(&e))),
+        directed: config.directed.unwrap_or(false),
+        duplicates: config.duplicates.as_ref().map_or_else(Duplicates::default, |name| Duplicates::from_name(name)
+            .unwrap_or_else(|| fail(&format!("Unknown duplicate route policy \"{}\".", name)))),
+        delimiter: config.delimiter.as_ref().map(|name| parse_delimiter(name)
+            .unwrap_or_else(|| fail(&format!("\"{}\" is not a delimiter; give a single character, \"tab\" or \"space\".", name)))),
+        diagonal: config.diagonal.unwrap_or(false),
+        unit: config.units.as_ref().map(|name| Unit::from_name(name)
+            .unwrap_or_else(|| fail(&format!("Unknown unit \"{}\".", name)))),
+    };
+
+    (format, options)
+}
+
+///
+/// Reads the route files, and any coordinates and heuristic files. Text
+/// route files are read a line at a time as they are loaded instead, as
+/// they may be too large to comfortably hold in memory, and directories
+/// hold unzipped GTFS feeds
+///
+/// - config: the settings naming the files
+/// - format: the route files' format, if given
+///
+/// - return: the files read
+///
+fn read_sources(config: &Config, format: Option<Format>) -> Sources
+{
+    // The given route files, or else the first route file found
+    let mut route_files = config.routes.iter().map(|path| path.display().to_string()).collect::<Vec<String>>();
+    if route_files.is_empty()
+    {
+        route_files.push(ROUTE_FILES.iter()
+            .find(|path| Path::new(path).is_file())
+            .map(|path| path.to_string())
+            .unwrap_or_else(|| fail("No route file given, and none of \"routes.txt\", \"routes.csv\" or \"routes.json\" found.")));
+    }
+
+    let route_data = route_files.iter()
+        .map(|path|
+        {
+            let format = format.unwrap_or_else(|| Format::from_path(path));
+            let data = match format
+            {
+                _ if Path::new(path).is_dir() => None,
+                Format::Text => None,
+                _ if from_stdin(path) => Some(read_stdin().unwrap_or_else(|e| fail(&e))),
+                _ => Some(read_file(Path::new(path)).unwrap_or_else(|e| fail(&e))),
+            };
+            (format, data)
+        })
+        .collect();
+
+    let read = |path: PathBuf| { let text = read_text(&path).unwrap_or_else(|e| fail(&e)); (path, text) };
+    let coordinates = config.coordinates.clone().or_else(|| find_beside(&route_files[0], COORDINATES_FILE)).map(read);
+    let heuristic = config.heuristic.clone().or_else(|| find_beside(&route_files[0], HEURISTIC_FILE)).map(read);
+
+    Sources { route_files, route_data, coordinates, heuristic, arena: Bump::new() }
+}
+
+///
+/// - return: whether a route file path names standard input
+///
+fn from_stdin(path: &str) -> bool
+{
+    path == STDIN_PATH
+}
+
+///
+/// - return: the name a route file is known by in messages
+///
+fn label(path: &str) -> String
+{
+    if from_stdin(path) { String::from("stdin") } else { path.to_string() }
+}
+
+///
+/// Loads each route file, merging them into one set of routes, and
+/// warning of any duplicate routes merged
+///
+/// - sources: the files read
+/// - options: how to load the route files
+///
+/// - return: the merged routes
+///
+fn load<'a>(sources: &'a Sources, options: &LoadOptions) -> Routes<'a>
+{
+    let mut merged: Option<Routes> = None;
+    for (path, (format, data)) in sources.route_files.iter().zip(&sources.route_data)
+    {
+        let loaded = match data
+        {
+            Some(data) => load_routes(data, *format, options, &sources.arena),
+            None if Path::new(path).is_dir() => gtfs::parse_dir(Path::new(path), &sources.arena),
+            None =>
+            {
+                let reader = if from_stdin(path) { open_stdin() } else { open_file(Path::new(path)) };
+                read_map(reader.unwrap_or_else(|e| fail(&e)), options.directed, options.duplicates, options.delimiter.unwrap_or(','), options.unit.unwrap_or_default(), &sources.arena)
+            },
+        };
+        // Weights measure the unit given, whatever their format implies
+        let loaded = loaded.and_then(|loaded| match options.unit
+        {
+            Some(unit) => loaded.read_as(unit),
+            None => Ok(loaded),
+        });
+        let loaded = loaded.unwrap_or_else(|e| fail(&format!("{}: {}", label(path), e)));
+        if !loaded.duplicates.is_empty()
+        {
+            warn!("{}: merged {} duplicate route(s), {}.", label(path), loaded.duplicates.len(), options.duplicates.description());
+        }
+
+        merged = Some(match merged
+        {
+            Some(routes) =>
+            {
+                let listed = routes.duplicates.len() + loaded.duplicates.len();
+                let routes = routes.merge(loaded, options.duplicates).unwrap_or_else(|e| fail(&format!("{}: {}", label(path), e)));
+                if routes.duplicates.len() > listed
+                {
+                    warn!("{}: merged {} route(s) listed in earlier route files, {}.", label(path), routes.duplicates.len() - listed, options.duplicates.description());
+                }
+                routes
+            },
+            None => loaded,
+        });
+    }
+
+    merged.unwrap()
+}
+
+///
+/// Estimates distances over the Earth's surface, in the unit the routes
+/// are measured in
+///
+/// - coordinates: each location's (longitude, latitude), in degrees
+/// - unit: miles or kilometers
+///
+fn great_circle<'a>(coordinates: HashMap<&'a str, (f64, f64)>, unit: Unit) -> GreatCircle<'a>
+{
+    match unit
+    {
+        Unit::Kilometers => GreatCircle::in_kilometres(coordinates),
+        _ => GreatCircle::new(coordinates),
+    }
+}
+
+///
+/// How the Program is run once the routes are loaded
+///
+enum Mode
+{
+    /// Prompting for queries, unless another output format is given
+    Interactive,
+    /// Answering queries read from standard input, without prompts
+    Queries,
+    /// Answering the one query given, and exiting
+    OneShot(String, String),
+    /// Answering every query in a file, and exiting
+    Batch(PathBuf),
+    /// Routing in the full-screen terminal interface
+    Tui,
+    /// Timing the algorithms over this many random queries, drawn from
+    /// this seed
+    Bench(usize, u64),
+    /// Checking the heuristic's estimates to every location, or to this
+    /// many drawn from this seed
+    ValidateHeuristic(Option<usize>, u64),
+}
+
+///
+/// Loads the routes and runs the Program over them
+///
+/// - args: the flags given
+/// - mode: how to run the Program
+///
+fn run_queries(args: QueryArgs, mode: Mode)
+{
+    let save_path = args.save.clone();
+    let config = args.config();
+    let (format, options) = load_options(&config);
+
+    let algorithms = config.algorithm.as_ref().map_or_else(|| Ok(Vec::new()), |names| Algorithm::from_names(names))
+        .unwrap_or_else(|e| fail(&e));
+    let frontier = config.frontier.as_ref().map_or_else(Frontier::default, |name| Frontier::from_name(name)
+        .unwrap_or_else(|| fail(&format!("Unknown frontier \"{}\".", name))));
+    let output = config.output.as_ref().map_or_else(OutputFormat::default, |name| OutputFormat::from_name(name)
+        .unwrap_or_else(|| fail(&format!("Unknown output format \"{}\".", name))));
+    // Queries read from standard input are never prompted for
+    let output = match mode
+    {
+        Mode::Queries if output == OutputFormat::Text => OutputFormat::Script,
+        _ => output,
+    };
+    // A compiled graph is searched where it lies, without loading routes
+    if let [path] = &config.routes[..]
+    {
+        if format.unwrap_or_else(|| Format::from_path(&path.display().to_string())) == Format::Compiled
+        {
+            std::process::exit(run_compiled(path, algorithms, output, config.precision.unwrap_or(DEFAULT_PRECISION), mode));
+        }
+    }
+    let geographic = config.geographic.unwrap_or(false);
+    let speed = config.speed.map(|speed| if speed > 0.0 { speed } else { fail("The speed must be more than 0 mph.") });
+    let history_file = config.history.clone().or_else(history::default_path);
+
+    let sources = read_sources(&config, format);
+    let routes = load(&sources, &options);
+
+    // With routes on standard input, the user's input must come from the
+    // terminal instead
+    let reads_input = matches!(mode, Mode::Interactive | Mode::Queries);
+    if reads_input && sources.route_files.iter().any(|path| from_stdin(path))
+    {
+        if let Err(e) = read_from_terminal() { warn!("{}", e); }
+    }
+    if let Some(save_path) = save_path
+    {
+        binary::save(&routes, &save_path).unwrap_or_else(|e| fail(&e));
+    }
+    let route_dat = routes.graph;
+    let unit = routes.unit;
+    if speed.is_some() && unit.speed().is_none()
+    {
+        fail(&format!("A speed cannot give travel times for routes measured in {}.", unit.name()));
+    }
+
+    // Grid maps and route files with latitude and longitude need no
+    // heuristic data, as the straight-line distance can be computed from
+    // them. Otherwise, straight-line distances are computed from a
+    // coordinates file, or else read from a heuristic file, if either
+    // exists. Without them (or for weights other than distance), the
+    // heuristic is computed from landmarks on the route data instead.
+    // Landmark distances ignore one-way routes, which can only shorten
+    // them, so estimates remain admissible
+    let coordinates = sources.coordinates.as_ref()
+        .map(|(path, text)| build_coordinates(text, options.delimiter)
+            .unwrap_or_else(|e| fail(&format!("{}: {}", path.display(), e))));
+    // Each location's position, for exports which draw or track routes,
+    // stored as (longitude, latitude) if geographic. GPX tracks need
+    // latitude and longitude
+    let positions = match &coordinates
+    {
+        _ if !routes.coordinates.is_empty() => Some((routes.coordinates.clone(), routes.geographic)),
+        Some(coordinates) if geographic => Some((coordinates.iter().map(|(&name, &(lat, lon))| (name, (lon, lat))).collect(), true)),
+        Some(coordinates) => Some((coordinates.clone(), false)),
+        None => None,
+    };
+    if config.gpx.is_some() && !positions.as_ref().is_some_and(|&(_, geographic)| geographic)
+    {
+        fail("GPX export needs latitude and longitude, from the route file or a coordinates file with --geographic.");
+    }
+    // Preprocessing is kept beside the route file, when it is a single
+    // file rather than standard input
+    let artifacts = match sources.route_files.as_slice()
+    {
+        [path] if !from_stdin(path) => Some(PathBuf::from(path)),
+        _ => None,
+    };
+    let landmarks = || match &artifacts
+    {
+        Some(path) => sidecar::load_or_build(&route_dat, path, DEFAULT_LANDMARK_COUNT as u64, || Landmarks::new(&route_dat, DEFAULT_LANDMARK_COUNT)),
+        None => Landmarks::new(&route_dat, DEFAULT_LANDMARK_COUNT),
+    };
+    let heuristic: Box<dyn Heuristic> = match (routes.grid.as_ref(), coordinates, &sources.heuristic)
+    {
+        (Some(grid), _, _) if unit == Unit::Cells => Box::new(GridDistance::new(routes.coordinates, grid.connectivity, grid.grid.straight_cost())),
+        _ if unit != Unit::Miles && unit != Unit::Kilometers => Box::new(landmarks()),
+        _ if routes.geographic => Box::new(great_circle(routes.coordinates, unit)),
+        // Stored as (longitude, latitude), as the coordinates are read as
+        // (latitude, longitude)
+        (_, Some(coordinates), _) if geographic => Box::new(great_circle(
+            coordinates.into_iter().map(|(name, (lat, lon))| (name, (lon, lat))).collect(), unit)),
+        (_, Some(coordinates), _) => Box::new(Euclidean::new(coordinates)),
+        (_, None, Some((heuristic_path, heur_dat_text))) => Box::new(heuristic_table(heuristic_path, heur_dat_text, &route_dat, unit, &config, &options)),
+        (_, None, None) => Box::new(landmarks()),
+    };
 
     // Create and run Program
-    let mut prog = Program::new(&route_dat_text, &heur_dat_text);
-    prog.run(); 
+    let mut prog = Program::from_graph(route_dat, heuristic);
+    prog.set_unit(unit);
+    if let Some(path) = artifacts { prog.set_artifacts(path); }
+    prog.set_one_way_routes(routes.arcs);
+    prog.set_grid(routes.grid);
+    if config.reorder.unwrap_or(false) { prog.set_reorder(true); }
+    prog.set_algorithms(algorithms).unwrap_or_else(|e| fail(&e));
+    prog.set_frontier(frontier);
+    prog.set_precision(config.precision.unwrap_or(DEFAULT_PRECISION));
+    prog.set_threads(config.threads.unwrap_or(1));
+    if let Some(capacity) = config.cache { prog.set_cache(capacity); }
+    prog.set_output(output);
+    prog.set_speed(speed);
+    // Progress is left out in quiet mode, and would disturb the full-screen
+    // interface
+    prog.set_progress(log::max_level() >= LevelFilter::Warn && !is_plain() && !matches!(mode, Mode::Tui | Mode::Bench(..) | Mode::ValidateHeuristic(..)));
+    if let Some((coordinates, geographic)) = positions { prog.set_coordinates(coordinates, geographic); }
+    if let Some(path) = config.gpx { prog.set_gpx_export(path); }
+    if let Some(path) = config.stats { prog.set_stats_export(path); }
+    if let Some(path) = config.expansions { prog.set_expansions_export(path); }
+    if let Some(path) = history_file { prog.set_history(path); }
+    match (mode, output)
+    {
+        (Mode::OneShot(from, to), _) => std::process::exit(prog.answer(&from, &to)),
+        (Mode::Batch(path), _) =>
+        {
+            let text = read_text(&path).unwrap_or_else(|e| fail(&e));
+            let queries = parse_queries(&text).unwrap_or_else(|e| fail(&format!("{}: {}", path.display(), e)));
+            std::process::exit(prog.run_batch(&queries));
+        },
+        (Mode::Tui, _) => tui::run(&mut prog).unwrap_or_else(|e| fail(&e)),
+        (Mode::Bench(count, seed), _) => prog.run_benchmark(count, seed),
+        (Mode::ValidateHeuristic(sample, seed), _) =>
+        {
+            if prog.run_heuristic_check(sample, seed) > 0 { std::process::exit(1); }
+        },
+        (_, OutputFormat::Script) => std::process::exit(prog.run_script()),
+        _ => prog.run(),
+    }
+}
+
+///
+/// Answers the queries of the route command over a compiled graph,
+/// searching it where it lies in memory, as --script output or else as
+/// JSON. Queries are routed with the first algorithm set, or else A*; as
+/// JSON, with every algorithm set
+///
+/// - path: the compiled graph file's path
+/// - algorithms: the algorithms set
+/// - output: the output format
+/// - precision: the # of decimal places distances are printed to
+/// - mode: which queries to answer
+///
+/// - return: the exit code for the queries' results, as for the route
+///   command
+///
+fn run_compiled(path: &Path, algorithms: Vec<Algorithm>, output: OutputFormat, precision: usize, mode: Mode) -> i32
+{
+    let data = compiled::map(path).unwrap_or_else(|e| fail(&e));
+    let graph = CompiledGraph::parse(&data).unwrap_or_else(|e| fail(&format!("{}: {}", path.display(), e)));
+    let algorithms = match (algorithms.first(), output)
+    {
+        (None, _) => vec![Algorithm::AStar],
+        (Some(_), OutputFormat::Json) => algorithms,
+        (Some(&algorithm), _) => vec![algorithm],
+    };
+    if let Some(algorithm) = algorithms.iter().find(|algorithm| !COMPILED_ALGORITHMS.contains(algorithm))
+    {
+        fail(&format!("A compiled graph cannot be searched with {}; choose A*, Djikstra or Fringe.", algorithm.name()));
+    }
+
+    let mut context = SearchContext::default();
+    let mut answer = |from: &str, to: &str| answer_compiled(&graph, &algorithms, output, precision, &mut context, from, to);
+    match mode
+    {
+        Mode::OneShot(from, to) => answer(&from, &to),
+        Mode::Batch(path) =>
+        {
+            let text = read_text(&path).unwrap_or_else(|e| fail(&e));
+            let queries = parse_queries(&text).unwrap_or_else(|e| fail(&format!("{}: {}", path.display(), e)));
+            queries.into_iter().map(|(from, to)| answer(from, to)).fold(EXIT_FOUND, i32::max)
+        },
+        Mode::Queries =>
+        {
+            let mut exit_code = EXIT_FOUND;
+            for line in std::io::stdin().lock().lines().map_while(Result::ok)
+            {
+                if line.trim().eq_ignore_ascii_case("quit") { break; }
+                match parse_queries(&line)
+                {
+                    Ok(queries) => for (from, to) in queries { exit_code = exit_code.max(answer(from, to)); },
+                    Err(_) => eprintln!("Expected a start and an end location, separated by a tab or comma."),
+                }
+            }
+            exit_code
+        },
+        _ => fail("A compiled graph can only answer queries with the route command."),
+    }
+}
+
+///
+/// Answers one query over a compiled graph, printing its line of
+/// --script output, or its RouteReport as JSON
+///
+/// - graph: the compiled graph
+/// - algorithms: the algorithms to route with
+/// - output: the output format
+/// - precision: the # of decimal places distances are printed to
+/// - context: the buffers to search in, reused across queries
+/// - from: the start location's name
+/// - to: the end location's name
+///
+/// - return: the exit code for the query's result
+///
+fn answer_compiled(graph: &CompiledGraph, algorithms: &[Algorithm], output: OutputFormat, precision: usize, context: &mut SearchContext, from: &str, to: &str) -> i32
+{
+    let (start, end) = match (graph.id(from), graph.id(to))
+    {
+        (Some(start), Some(end)) => (start, end),
+        (start, _) =>
+        {
+            let message = format!("Cannot route: \"{}\" does not exist.", if start.is_none() { from } else { to });
+            match output
+            {
+                OutputFormat::Json => println!("{}", error_json(from, to, &message)),
+                _ => { eprintln!("{}", message); println!("unknown\t{}\t{}", from, to); },
+            }
+            return EXIT_UNKNOWN_LOCATION;
+        },
+    };
+
+    let unit = graph.unit();
+    // Estimates are read from the graph's heuristic table, if it has one,
+    // and are otherwise 0
+    let no_estimates: HashMap<(&str, &str), u64> = HashMap::new();
+    let mut results = Vec::new();
+    for &algorithm in algorithms
+    {
+        let started = Instant::now();
+        let searched = algorithm.search_interned(graph, &no_estimates, from, to, TieBreak::default(), context, |_| ());
+        let micros = started.elapsed().as_micros();
+
+        let (route, nodes_considered) = match searched
+        {
+            Some((Some(route), nodes_considered)) => (route, nodes_considered),
+            _ =>
+            {
+                let message = "Route could not be completed!";
+                match output
+                {
+                    OutputFormat::Json => println!("{}", error_json(from, to, message)),
+                    _ => { eprintln!("{}", message); println!("unreachable\t{}\t{}", from, to); },
+                }
+                return EXIT_UNREACHABLE;
+            },
+        };
+        if output != OutputFormat::Json
+        {
+            println!("found\t{}\t{}\t{}\t{}", from, to, unit.number(unit.from_weight(route.cost), precision), route.nodes.join("\t"));
+            return EXIT_FOUND;
+        }
+
+        let mut cumulative = 0;
+        let legs = route.nodes.windows(2).enumerate()
+            .map(|(i, leg)|
+            {
+                let distance = graph.id(leg[0]).zip(graph.id(leg[1])).and_then(|(from, to)| graph.weight(from, to)).unwrap_or(0);
+                cumulative += distance;
+                Leg { index: i + 1, from: leg[0], to: leg[1], distance: unit.from_weight(distance), cumulative: unit.from_weight(cumulative) }
+            })
+            .collect();
+        results.push(AlgorithmReport { algorithm: algorithm.name(), total: unit.from_weight(route.cost), route: route.nodes, legs, nodes_considered,
+            counters: Some(context.counters()), micros });
+    }
+
+    println!("{}", RouteReport { start: graph.name(start), end: graph.name(end), unit: unit.name(), results }.to_json());
+    EXIT_FOUND
+}
+
+///
+/// Reads a heuristic file as a table, warning of the pairs of locations
+/// it has no distance between, which are estimated at the default
+///
+/// - path, text: the heuristic file's path and text
+/// - graph: the routes searched with the table
+/// - unit: what the routes' weights measure
+/// - config: the settings, giving the default estimate
+/// - options: how the files are read
+///
+/// - return: the table. Any problem reading it ends the program with an
+///   error
+///
+fn heuristic_table<'a>(path: &Path, text: &'a str, graph: &RouteGraph<'a>, unit: Unit, config: &Config, options: &LoadOptions) -> HeuristicTable<'a>
+{
+    let default = config.heuristic_default.unwrap_or(0.0);
+    if !default.is_finite() || default < 0.0 { fail(&format!("\"{}\" is not a distance.", default)); }
+
+    let estimates = build_heur_data(text, options.delimiter, unit).unwrap_or_else(|e| fail(&format!("{}: {}", path.display(), e)));
+    let table = HeuristicTable::new(estimates, unit.to_weight(default).round() as u64);
+    let (missing, examples) = table.missing(graph, MISSING_PAIRS_SHOWN);
+    if missing > 0
+    {
+        let examples = examples.iter().map(|(from, to)| format!("{} and {}", from, to)).collect::<Vec<String>>().join("; ");
+        warn!("{}: no distance either way for {} pair(s) of locations, which are estimated at {} (such as {}).",
+            path.display(), missing, default, examples);
+    }
+    table
 }
 
+///
+/// Loads the routes, and any coordinates and heuristic files, printing
+/// how much each holds, then how many routes and locations look like
+/// mistakes, with examples of each. Any problem loading them ends the
+/// program with an error, and any mistake found with exit code 1
+///
+/// - args: the flags given
+///
+fn validate(args: DataArgs)
+{
+    let config = args.config();
+    let (format, options) = load_options(&config);
+    let sources = read_sources(&config, format);
+    let routes = load(&sources, &options);
+
+    let files = sources.route_files.iter().map(|path| label(path)).collect::<Vec<String>>().join(", ");
+    match &routes.arcs
+    {
+        Some(arcs) => println!("{}: {} location(s), {} one-way route(s)", files, arcs.node_count(), arcs.edge_count()),
+        None => println!("{}: {} location(s), {} route(s)", files, routes.graph.node_count(), routes.graph.edge_count()),
+    }
+    if let Some((path, text)) = &sources.coordinates
+    {
+        let coordinates = build_coordinates(text, options.delimiter).unwrap_or_else(|e| fail(&format!("{}: {}", path.display(), e)));
+        println!("{}: coordinates of {} location(s)", path.display(), coordinates.len());
+    }
+    let heuristic = sources.heuristic.as_ref().map(|(path, text)|
+    {
+        let heuristic = build_heur_data(text, options.delimiter, routes.unit).unwrap_or_else(|e| fail(&format!("{}: {}", path.display(), e)));
+        println!("{}: {} straight-line distance(s)", path.display(), heuristic.len());
+        heuristic
+    });
+
+    let validation = validate::validate(&routes, heuristic.as_ref());
+    let names = |names: &[&str]| names.iter().take(EXAMPLES_SHOWN).cloned().collect::<Vec<&str>>().join(", ");
+    let pairs = |pairs: &mut dyn Iterator<Item = String>| pairs.take(EXAMPLES_SHOWN).collect::<Vec<String>>().join(", ");
+    let report = [
+        ("self-loop(s)", validation.self_loops.len(), names(&validation.self_loops)),
+        ("route(s) of weight 0", validation.zero_weights.len(),
+            pairs(&mut validation.zero_weights.iter().map(|(from, to)| format!("{} to {}", from, to)))),
+        ("duplicate route(s), merged", validation.duplicates.len(),
+            pairs(&mut validation.duplicates.iter().map(|(from, to)| format!("{} to {}", from, to)))),
+        ("isolated location(s), without routes", validation.isolated.len(), names(&validation.isolated)),
+        ("location(s) in the heuristic file but not the routes", validation.unrouted.len(), names(&validation.unrouted)),
+        ("location(s) in the routes but not the heuristic file", validation.unestimated.len(), names(&validation.unestimated)),
+    ];
+    for (problem, count, examples) in report
+    {
+        if count > 0 { println!("{} {}, such as {}", count, problem, examples); }
+    }
+    if let Some(heuristic) = heuristic
+    {
+        let (missing, _) = HeuristicTable::new(heuristic, 0).missing(&routes.graph, 0);
+        if missing > 0 { println!("{} pair(s) of locations without a straight-line distance either way", missing); }
+    }
+
+    match validation.problems()
+    {
+        0 => println!("No problems found."),
+        problems =>
+        {
+            println!("{} problem(s) found.", problems);
+            std::process::exit(1);
+        },
+    }
+}
+
+///
+/// Loads the routes and prints their statistics: how many locations and
+/// routes there are, how connected they are, and the routes' distances
+///
+/// - args: the flags given
+///
+fn print_stats(args: DataArgs)
+{
+    let config = args.config();
+    let (format, options) = load_options(&config);
+    let sources = read_sources(&config, format);
+    let routes = load(&sources, &options);
+    let unit = routes.unit;
 
+    let graph = &routes.graph;
+    let locations = graph.node_count();
+    println!("Locations: {}", locations);
+    match &routes.arcs
+    {
+        Some(arcs) => println!("Routes: {} one-way", arcs.edge_count()),
+        None => println!("Routes: {}", graph.edge_count()),
+    }
+    if locations == 0 { return; }
+
+    let components = analysis::analyze(graph).components;
+    let largest = components.iter().map(Vec::len).max().unwrap_or(0);
+    println!("Connected groups: {} (the largest of {} location(s))", components.len(), largest);
+
+    let degrees = graph.nodes().map(|node| graph.neighbors(node).count()).collect::<Vec<usize>>();
+    println!("Routes per location: {} to {}, {:.*} on average", degrees.iter().min().unwrap(), degrees.iter().max().unwrap(),
+        DEFAULT_PRECISION, degrees.iter().sum::<usize>() as f64 / locations as f64);
+
+    let weights = graph.all_edges().map(|(_, _, &weight)| weight).collect::<Vec<u64>>();
+    if let (Some(shortest), Some(longest)) = (weights.iter().min(), weights.iter().max())
+    {
+        let total = weights.iter().sum::<u64>();
+        let distance = |weight: u64| unit.format(unit.from_weight(weight), DEFAULT_PRECISION);
+        println!("Route {}: {} to {}, {} on average, {} in all", unit.measure(), distance(*shortest), distance(*longest),
+            distance(total / weights.len() as u64), distance(total));
+    }
+    if !routes.coordinates.is_empty()
+    {
+        println!("Locations with coordinates: {}", routes.coordinates.len());
+    }
+}
+
+///
+/// Loads the routes and writes them in the format named by the output
+/// file's extension
+///
+/// - args: the flags given
+///
+fn convert(args: ConvertArgs)
+{
+    let config = args.data.config();
+    let (format, options) = load_options(&config);
+    let sources = read_sources(&config, format);
+    let routes = load(&sources, &options);
 
+    let extension = args.to.extension().and_then(|extension| extension.to_str()).map(str::to_lowercase);
+    let written = match extension.as_deref()
+    {
+        Some("graph") => binary::save(&routes, &args.to),
+        Some("cgraph") =>
+        {
+            let mut interned = match &routes.arcs
+            {
+                Some(arcs) => InternedGraph::new(arcs),
+                None => InternedGraph::new(&routes.graph),
+            };
+            if let Some((path, text)) = &sources.heuristic
+            {
+                let heuristic = build_heur_data(text, options.delimiter, routes.unit).unwrap_or_else(|e| fail(&format!("{}: {}", path.display(), e)));
+                interned.set_heuristic(&heuristic);
+            }
+            compiled::save(&interned, routes.unit, &args.to)
+        },
+        Some("dot") | Some("gv") => export::write(&args.to, &to_dot(&routes.graph, routes.arcs.as_ref(), &[], routes.unit, DEFAULT_PRECISION)),
+        _ => fail(&format!("Cannot convert to \"{}\"; give a .graph, .cgraph or .dot file.", args.to.display())),
+    };
+    written.unwrap_or_else(|e| fail(&e));
+    println!("Routes written to {}.", args.to.display());
+}
+
+///
+/// Prints a shell completion script for the program, as it was run. With
+/// route files given, or named in the configuration file, their
+/// locations complete the locations routed between
+///
+/// - args: the flags given
+///
+fn completions(args: CompletionsArgs)
+{
+    let mut command = Cli::command();
+    let config = args.data.config();
+    if !config.routes.is_empty()
+    {
+        let (format, options) = load_options(&config);
+        let sources = read_sources(&config, format);
+        let routes = load(&sources, &options);
+
+        let mut locations = routes.graph.nodes().map(String::from).collect::<Vec<String>>();
+        locations.sort_unstable();
+        command = command.mut_subcommand("route", |route| ["from", "to"].iter()
+            .fold(route, |route, &arg| route.mut_arg(arg, |arg| arg.value_parser(PossibleValuesParser::new(locations.clone())))));
+    }
+
+    let name = std::env::args().next()
+        .and_then(|path| Path::new(&path).file_name().map(|name| name.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| command.get_name().to_string());
+    clap_complete::generate(args.shell, &mut command, name, &mut std::io::stdout());
+}
+
+fn main()
+{
+    let cli = Cli::parse();
+
+    // Library messages are logged, and may be routed elsewhere by other
+    // programs using it
+    let log_level = (DEFAULT_LOG_LEVEL + cli.verbose as usize).saturating_sub(cli.quiet as usize);
+    log::set_logger(&StderrLogger).expect("The logger is set once");
+    log::set_max_level(LOG_LEVELS[log_level.min(LOG_LEVELS.len() - 1)]);
+    let plain = cli.plain || std::env::var("TERM").is_ok_and(|term| term == "dumb");
+    set_plain(plain);
+    if cli.no_color || plain { owo_colors::set_override(false); }
+
+    match cli.command
+    {
+        None => run_queries(cli.query, Mode::Interactive),
+        Some(Command::Interactive(args)) => run_queries(args, Mode::Interactive),
+        Some(Command::Tui(_)) if plain => fail("The full-screen interface cannot be used with plain output; route with the interactive command instead."),
+        Some(Command::Tui(args)) => run_queries(args, Mode::Tui),
+        Some(Command::Route(RouteArgs { query, from: Some(from), to: Some(to), .. })) => run_queries(query, Mode::OneShot(from, to)),
+        Some(Command::Route(RouteArgs { query, queries: Some(path), .. })) => run_queries(query, Mode::Batch(path)),
+        Some(Command::Route(args)) => run_queries(args.query, Mode::Queries),
+        Some(Command::Validate(args)) => validate(args),
+        Some(Command::ValidateHeuristic(args)) => run_queries(args.query, Mode::ValidateHeuristic(args.sample, args.seed)),
+        Some(Command::Stats(args)) => print_stats(args),
+        Some(Command::Convert(args)) => convert(args),
+        Some(Command::Completions(args)) => completions(args),
+        Some(Command::Bench(mut args)) =>
+        {
+            // A lone .scen route file is taken as the scenario file
+            let routes = &mut args.query.data.routes;
+            if args.scenarios.is_none() && routes.len() == 1 && routes[0].extension().is_some_and(|extension| extension == "scen")
+            {
+                args.scenarios = routes.pop();
+            }
+
+            match args.scenarios
+            {
+                Some(scenarios) =>
+                {
+                    let algorithms = args.query.algorithm.as_ref().map_or_else(|| Ok(Vec::new()), |names| Algorithm::from_names(names))
+                        .unwrap_or_else(|e| fail(&e));
+                    run_scenarios(&scenarios, args.map.as_deref(), algorithms);
+                },
+                None => run_queries(args.query, Mode::Bench(args.count, args.seed)),
+            }
+        },
+    }
+}
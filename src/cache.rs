@@ -0,0 +1,134 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+///
+/// A cache holding the values most recently used, evicting the least
+/// recently used once full, and counting how often values were found
+///
+/// - capacity: the most values held
+/// - entries: each key's value, and the tick it was last used at
+/// - order: each held key by the tick it was last used at, oldest first
+/// - tick: the # of times a value has been used or inserted
+/// - hits: the # of lookups which found a value
+/// - misses: the # of lookups which found none
+///
+#[derive(Debug, Clone)]
+pub struct LruCache<K, V>
+{
+    capacity: usize,
+    entries: HashMap<K, (V, u64)>,
+    order: BTreeMap<u64, K>,
+    tick: u64,
+    hits: usize,
+    misses: usize,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V>
+{
+    ///
+    /// Creates an empty cache
+    ///
+    /// - capacity: the most values held. With 0, nothing is held
+    ///
+    pub fn new(capacity: usize) -> Self
+    {
+        LruCache { capacity, entries: HashMap::new(), order: BTreeMap::new(), tick: 0, hits: 0, misses: 0 }
+    }
+
+    ///
+    /// Looks up a key's value, marking it the most recently used
+    ///
+    /// - return: the value, or None if it is not held
+    ///
+    pub fn get(&mut self, key: &K) -> Option<&V>
+    {
+        match self.entries.get_mut(key)
+        {
+            Some((value, used)) =>
+            {
+                self.hits += 1;
+                self.tick += 1;
+                let key = self.order.remove(used).expect("Every held key is ordered");
+                self.order.insert(self.tick, key);
+                *used = self.tick;
+                Some(value)
+            },
+            None =>
+            {
+                self.misses += 1;
+                None
+            },
+        }
+    }
+
+    ///
+    /// Holds a key's value as the most recently used, replacing any it
+    /// had, and evicting the least recently used value if full
+    ///
+    pub fn insert(&mut self, key: K, value: V)
+    {
+        if self.capacity == 0 { return; }
+
+        self.tick += 1;
+        if let Some((_, used)) = self.entries.insert(key.clone(), (value, self.tick))
+        {
+            self.order.remove(&used);
+        }
+        self.order.insert(self.tick, key);
+
+        while self.entries.len() > self.capacity
+        {
+            let (_, oldest) = self.order.pop_first().expect("A full cache has keys to evict");
+            self.entries.remove(&oldest);
+        }
+    }
+
+    ///
+    /// Drops every value held, keeping the counts of hits and misses
+    ///
+    pub fn clear(&mut self)
+    {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    ///
+    /// - return: the # of values held
+    ///
+    pub fn len(&self) -> usize
+    {
+        self.entries.len()
+    }
+
+    ///
+    /// - return: true if no values are held
+    ///
+    pub fn is_empty(&self) -> bool
+    {
+        self.entries.is_empty()
+    }
+
+    ///
+    /// - return: the most values held
+    ///
+    pub fn capacity(&self) -> usize
+    {
+        self.capacity
+    }
+
+    ///
+    /// - return: the # of lookups which found a value
+    ///
+    pub fn hits(&self) -> usize
+    {
+        self.hits
+    }
+
+    ///
+    /// - return: the # of lookups which found none
+    ///
+    pub fn misses(&self) -> usize
+    {
+        self.misses
+    }
+}
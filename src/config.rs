@@ -0,0 +1,180 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Deserializer};
+
+/// The configuration file looked for in the working directory
+pub const CONFIG_FILE: &str = "pathfinding.toml";
+
+///
+/// Settings for running the program, read from a TOML configuration file
+/// such as:
+///
+/// ```toml
+/// routes = "data/oregon.csv"
+/// coordinates = "data/oregon-coordinates.txt"
+/// duplicates = "sum"
+/// algorithm = "astar"
+/// precision = 2
+/// ```
+///
+/// Every setting is optional, and is overridden by the matching
+/// command-line flag. Names (such as formats and algorithms) are kept as
+/// given, to be read in the same way as their flags
+///
+/// - routes: the route file (or GTFS feed directory), or a list of route
+///   files to merge
+/// - format: the route file's format, overriding its extension
+/// - weight: the edge attribute holding distances
+/// - bbox: the area of an OpenStreetMap extract to keep roads within
+/// - directed: whether each route is one-way
+/// - diagonal: whether grid maps may be crossed diagonally
+/// - duplicates: how to merge routes listed more than once
+/// - delimiter: the character between fields in route, coordinates and
+///   heuristic files
+/// - coordinates: the coordinates file
+/// - geographic: whether the coordinates are latitude and longitude
+/// - heuristic: the heuristic file of straight-line distances
+/// - heuristic_default: the distance estimated between locations the
+///   heuristic file has none between
+/// - algorithm: the algorithms to route with, separated by commas,
+///   instead of comparing several
+/// - frontier: the queue A* and Djikstra's algorithm keep their frontier in
+/// - reorder: whether locations are renumbered so neighbours are close in
+///   memory
+/// - units: what the route file's weights measure
+/// - precision: the # of decimal places printed in distances
+/// - threads: the # of threads batches of queries are searched on
+/// - cache: the # of recent searches kept to answer repeated queries
+/// - speed: the speed routes are travelled at, in miles per hour
+/// - output: how results are written, as text or JSON
+/// - gpx: the GPX file each route shown is written to
+/// - stats: the CSV file each search's statistics are appended to
+/// - expansions: the file each search's expanded nodes are appended to
+/// - history: the file every search is recorded in
+///
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config
+{
+    #[serde(deserialize_with = "one_or_many")]
+    pub routes: Vec<PathBuf>,
+    pub format: Option<String>,
+    pub weight: Option<String>,
+    pub bbox: Option<String>,
+    pub directed: Option<bool>,
+    pub diagonal: Option<bool>,
+    pub duplicates: Option<String>,
+    pub delimiter: Option<String>,
+    pub coordinates: Option<PathBuf>,
+    pub geographic: Option<bool>,
+    pub heuristic: Option<PathBuf>,
+    pub heuristic_default: Option<f64>,
+    pub algorithm: Option<String>,
+    pub frontier: Option<String>,
+    pub reorder: Option<bool>,
+    pub units: Option<String>,
+    pub precision: Option<usize>,
+    pub threads: Option<usize>,
+    pub cache: Option<usize>,
+    pub speed: Option<f64>,
+    pub output: Option<String>,
+    pub gpx: Option<PathBuf>,
+    pub stats: Option<PathBuf>,
+    pub expansions: Option<PathBuf>,
+    pub history: Option<PathBuf>,
+}
+
+impl Config
+{
+    ///
+    /// Reads a configuration file. Paths within it are relative to the
+    /// file's own directory
+    ///
+    /// - path: the configuration file's path
+    ///
+    /// - return: Either an Ok Result with the Config, or an Err with
+    ///   message explaining problem
+    ///
+    pub fn load(path: &Path) -> Result<Config, String>
+    {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read \"{}\": {}", path.display(), e))?;
+        let config: Config = toml::from_str(&text)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        // "-" names standard input, rather than a file
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let resolve = |file: PathBuf| if file == Path::new("-") { file } else { dir.join(file) };
+        Ok(Config
+        {
+            routes: config.routes.into_iter().map(resolve).collect(),
+            coordinates: config.coordinates.map(resolve),
+            heuristic: config.heuristic.map(resolve),
+            gpx: config.gpx.map(resolve),
+            stats: config.stats.map(resolve),
+            expansions: config.expansions.map(resolve),
+            history: config.history.map(resolve),
+            ..config
+        })
+    }
+
+    ///
+    /// Combines two sets of settings, such as command-line flags over a
+    /// configuration file
+    ///
+    /// - defaults: the settings used wherever these have none
+    ///
+    /// - return: these settings, with any missing taken from defaults
+    ///
+    pub fn or(self, defaults: Config) -> Config
+    {
+        Config
+        {
+            routes: if self.routes.is_empty() { defaults.routes } else { self.routes },
+            format: self.format.or(defaults.format),
+            weight: self.weight.or(defaults.weight),
+            bbox: self.bbox.or(defaults.bbox),
+            directed: self.directed.or(defaults.directed),
+            diagonal: self.diagonal.or(defaults.diagonal),
+            duplicates: self.duplicates.or(defaults.duplicates),
+            delimiter: self.delimiter.or(defaults.delimiter),
+            coordinates: self.coordinates.or(defaults.coordinates),
+            geographic: self.geographic.or(defaults.geographic),
+            heuristic: self.heuristic.or(defaults.heuristic),
+            heuristic_default: self.heuristic_default.or(defaults.heuristic_default),
+            algorithm: self.algorithm.or(defaults.algorithm),
+            frontier: self.frontier.or(defaults.frontier),
+            reorder: self.reorder.or(defaults.reorder),
+            units: self.units.or(defaults.units),
+            precision: self.precision.or(defaults.precision),
+            threads: self.threads.or(defaults.threads),
+            cache: self.cache.or(defaults.cache),
+            speed: self.speed.or(defaults.speed),
+            output: self.output.or(defaults.output),
+            gpx: self.gpx.or(defaults.gpx),
+            stats: self.stats.or(defaults.stats),
+            expansions: self.expansions.or(defaults.expansions),
+            history: self.history.or(defaults.history),
+        }
+    }
+}
+
+///
+/// Reads either a single path, or a list of paths
+///
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<PathBuf>, D::Error>
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Paths
+    {
+        One(PathBuf),
+        Many(Vec<PathBuf>),
+    }
+
+    Ok(match Paths::deserialize(deserializer)?
+    {
+        Paths::One(path) => vec![path],
+        Paths::Many(paths) => paths,
+    })
+}
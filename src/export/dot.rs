@@ -0,0 +1,77 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::algo::dag::DirectedRouteGraph;
+use crate::algo::RouteGraph;
+use crate::load::Unit;
+
+// How the route is drawn, over the rest of the graph
+const ROUTE_STYLE: &str = "color=red, penwidth=3";
+
+///
+/// Writes the routes as a Graphviz DOT file, such as:
+///
+/// ```text
+/// graph routes {
+///     "Portland" [color=red, penwidth=3];
+///     "Salem";
+///     "Portland" -- "Salem" [weight=47.3, label="47.3", color=red, penwidth=3];
+/// }
+/// ```
+///
+/// Two-way routes make a graph, while any one-way routes make a digraph,
+/// of every direction routes may be travelled. The locations and legs of
+/// the route given are drawn in bold red. Weights are written as each
+/// edge's "weight", so the file loads back as the same routes.
+///
+/// - graph: every route
+/// - arcs: the directions each route may be travelled, if any are one-way
+/// - route: the locations of the route to highlight, from start to end,
+///   or none
+/// - unit: what the weights measure
+/// - precision: the # of decimal places written in labels
+///
+/// - return: the DOT file's text
+///
+pub fn to_dot(graph: &RouteGraph, arcs: Option<&DirectedRouteGraph>, route: &[&str], unit: Unit, precision: usize) -> String
+{
+    let route_nodes = route.iter().cloned().collect::<HashSet<&str>>();
+    let legs = route.windows(2).map(|leg| (leg[0], leg[1])).collect::<HashSet<(&str, &str)>>();
+
+    let (keyword, edge_op) = if arcs.is_some() { ("digraph", "->") } else { ("graph", "--") };
+    let mut dot = format!("{} routes {{\n", keyword);
+    for node in graph.nodes()
+    {
+        match route_nodes.contains(node)
+        {
+            true => { let _ = writeln!(dot, "    {} [{}];", quote(node), ROUTE_STYLE); },
+            false => { let _ = writeln!(dot, "    {};", quote(node)); },
+        }
+    }
+
+    let edges = match arcs
+    {
+        Some(arcs) => arcs.all_edges().map(|(from, to, &weight)| (from, to, weight)).collect::<Vec<(&str, &str, u64)>>(),
+        None => graph.all_edges().map(|(from, to, &weight)| (from, to, weight)).collect(),
+    };
+    for (from, to, weight) in edges
+    {
+        // Two-way legs may be travelled in either direction
+        let on_route = legs.contains(&(from, to)) || (arcs.is_none() && legs.contains(&(to, from)));
+        let distance = unit.number(unit.from_weight(weight), precision);
+        let _ = write!(dot, "    {} {} {} [weight={}, label=\"{}\"", quote(from), edge_op, quote(to), distance, distance);
+        if on_route { let _ = write!(dot, ", {}", ROUTE_STYLE); }
+        dot.push_str("];\n");
+    }
+    dot.push_str("}\n");
+
+    dot
+}
+
+///
+/// Quotes a name as a DOT ID, escaping any quotes within it
+///
+fn quote(name: &str) -> String
+{
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,78 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::csv_field;
+
+// The first row of every CSV expansions file
+const HEADER: &str = "from,to,algorithm,order,node,parent,g,h,f\n";
+
+///
+/// A node a search expanded, with its costs when it was expanded
+///
+/// - order: the # of nodes expanded before it, from 0
+/// - node: the node expanded
+/// - parent: the node it was reached from, or None for the start
+/// - g: its distance from the start
+/// - h: its heuristic estimate to the end
+/// - f: g + h, by which the search ordered it
+///
+#[derive(Serialize, Debug, Clone)]
+pub struct ExpandedNode<'e>
+{
+    pub order: usize,
+    pub node: &'e str,
+    pub parent: Option<&'e str>,
+    pub g: f64,
+    pub h: f64,
+    pub f: f64,
+}
+
+///
+/// Appends the nodes one search expanded, in order, to a file for
+/// animating or comparing searches. Files named ".json" or ".jsonl" are
+/// given a line of JSON per search, such as:
+///
+/// ```text
+/// {"from":"A","to":"C","algorithm":"A*","expansions":[{"order":0,"node":"A","parent":null,"g":0.0,"h":4.5,"f":4.5},...]}
+/// ```
+///
+/// while any other file is given a CSV row per node expanded, after a
+/// header row, with the columns from, to, algorithm, order, node,
+/// parent, g, h and f.
+///
+/// - path: the file to append to
+/// - from, to: the search's start and end locations
+/// - algorithm: the algorithm's name
+/// - expansions: each node expanded, in order
+///
+/// - return: Either an empty Ok Result, or an Err with message explaining
+///   problem
+///
+pub fn append(path: &Path, from: &str, to: &str, algorithm: &str, expansions: &[ExpandedNode]) -> Result<(), String>
+{
+    #[derive(Serialize)]
+    struct Search<'s>
+    {
+        from: &'s str,
+        to: &'s str,
+        algorithm: &'s str,
+        expansions: &'s [ExpandedNode<'s>],
+    }
+
+    let json = path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json") || extension.eq_ignore_ascii_case("jsonl"));
+    if json
+    {
+        let line = serde_json::to_string(&Search { from, to, algorithm, expansions }).expect("Expansions are always valid JSON");
+        return super::append(path, "", &(line + "\n"));
+    }
+
+    let (from, to, algorithm) = (csv_field(from), csv_field(to), csv_field(algorithm));
+    let rows = expansions.iter()
+        .map(|expansion| format!("{},{},{},{},{},{},{},{},{}\n", from, to, algorithm, expansion.order, csv_field(expansion.node),
+            expansion.parent.map_or_else(String::new, csv_field), expansion.g, expansion.h, expansion.f))
+        .collect::<String>();
+    super::append(path, HEADER, &rows)
+}
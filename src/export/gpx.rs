@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::algo::PathResult;
+use crate::load::xml::escape;
+
+///
+/// Writes a route as a GPX track, which mapping apps and GPS devices can
+/// open, with a track point at each location along it:
+///
+/// ```xml
+/// <?xml version="1.0" encoding="UTF-8"?>
+/// <gpx version="1.1" creator="a_star" xmlns="http://www.topografix.com/GPX/1/1">
+///   <trk>
+///     <name>Portland to Seattle</name>
+///     <trkseg>
+///       <trkpt lat="45.5152" lon="-122.6784"><name>Portland</name></trkpt>
+///       ...
+/// ```
+///
+/// - route: the route to write
+/// - coordinates: each location's (longitude, latitude), in degrees
+///
+/// - return: Either an Ok Result with the GPX file's text, or an Err if
+///   a location on the route has no coordinates
+///
+pub fn to_gpx(route: &PathResult, coordinates: &HashMap<&str, (f64, f64)>) -> Result<String, String>
+{
+    let (start, end) = match (route.nodes.first(), route.nodes.last())
+    {
+        (Some(start), Some(end)) => (start, end),
+        _ => return Err(String::from("Cannot write an empty route as GPX.")),
+    };
+
+    let mut gpx = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    gpx.push_str("<gpx version=\"1.1\" creator=\"a_star\" xmlns=\"http://www.topografix.com/GPX/1/1\">\n");
+    gpx.push_str("  <trk>\n");
+    let _ = writeln!(gpx, "    <name>{} to {}</name>", escape(start), escape(end));
+    gpx.push_str("    <trkseg>\n");
+    for &node in &route.nodes
+    {
+        let &(lon, lat) = coordinates.get(node)
+            .ok_or_else(|| format!("Cannot write the route as GPX: \"{}\" has no coordinates.", node))?;
+        let _ = writeln!(gpx, "      <trkpt lat=\"{}\" lon=\"{}\"><name>{}</name></trkpt>", lat, lon, escape(node));
+    }
+    gpx.push_str("    </trkseg>\n  </trk>\n</gpx>\n");
+
+    Ok(gpx)
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use crate::algo::RouteGraph;
+
+/// The most locations laid out by force; larger Graphs are left on a
+/// circle, as each round of the layout takes time quadratic in their #
+pub const FORCE_LAYOUT_LIMIT: usize = 2000;
+
+// The # of rounds of force-directed layout
+const ITERATIONS: usize = 200;
+
+///
+/// Lays out the Graph's locations by force, for Graphs without
+/// coordinates (Fruchterman-Reingold). Every pair of locations repels,
+/// while routes pull their ends together, so connected locations settle
+/// near one another. Locations start evenly around a circle, so the
+/// layout is the same on every run.
+///
+/// - graph: the Graph to lay out
+///
+/// - return: each location's (x, y), within the unit square
+///
+pub fn force_directed<'a>(graph: &RouteGraph<'a>) -> HashMap<&'a str, (f64, f64)>
+{
+    let nodes = graph.nodes().collect::<Vec<&str>>();
+    let count = nodes.len();
+    let index = nodes.iter().enumerate().map(|(i, &node)| (node, i)).collect::<HashMap<&str, usize>>();
+
+    let mut positions = (0..count)
+        .map(|i| { let angle = 2.0 * PI * i as f64 / count as f64; (0.5 + 0.5 * angle.cos(), 0.5 + 0.5 * angle.sin()) })
+        .collect::<Vec<(f64, f64)>>();
+
+    // The ideal distance between locations, for them to fill the square
+    let k = (1.0 / count.max(1) as f64).sqrt();
+    let iterations = if count <= FORCE_LAYOUT_LIMIT { ITERATIONS } else { 0 };
+    for iteration in 0..iterations
+    {
+        let mut moves = vec![(0.0, 0.0); count];
+        for i in 0..count
+        {
+            for j in i + 1..count
+            {
+                let (dx, dy) = (positions[i].0 - positions[j].0, positions[i].1 - positions[j].1);
+                let distance = dx.hypot(dy).max(1e-6);
+                let force = k * k / distance;
+                moves[i].0 += dx / distance * force;
+                moves[i].1 += dy / distance * force;
+                moves[j].0 -= dx / distance * force;
+                moves[j].1 -= dy / distance * force;
+            }
+        }
+        for (from, to, _) in graph.all_edges()
+        {
+            let (i, j) = (index[from], index[to]);
+            let (dx, dy) = (positions[i].0 - positions[j].0, positions[i].1 - positions[j].1);
+            let distance = dx.hypot(dy).max(1e-6);
+            let force = distance * distance / k;
+            moves[i].0 -= dx / distance * force;
+            moves[i].1 -= dy / distance * force;
+            moves[j].0 += dx / distance * force;
+            moves[j].1 += dy / distance * force;
+        }
+
+        // Each location moves no further than the temperature, which
+        // cools as the layout settles
+        let temperature = 0.1 * (1.0 - iteration as f64 / iterations as f64);
+        for (position, (dx, dy)) in positions.iter_mut().zip(moves)
+        {
+            let distance = dx.hypot(dy).max(1e-6);
+            let step = distance.min(temperature);
+            position.0 += dx / distance * step;
+            position.1 += dy / distance * step;
+        }
+    }
+
+    nodes.into_iter().zip(positions).collect()
+}
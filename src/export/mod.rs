@@ -0,0 +1,60 @@
+pub mod dot;
+pub mod expansions;
+pub mod gpx;
+pub mod layout;
+pub mod stats;
+pub mod svg;
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+///
+/// Writes an exported file
+///
+/// - path: the file to write to
+/// - contents: the file's contents
+///
+/// - return: Either an empty Ok Result, or an Err with message explaining
+///   problem
+///
+pub fn write(path: &Path, contents: &str) -> Result<(), String>
+{
+    std::fs::write(path, contents).map_err(|e| format!("Cannot write \"{}\": {}", path.display(), e))
+}
+
+///
+/// Appends text to a file, creating it if needed
+///
+/// - path: the file to append to
+/// - header: written first if the file is new (or empty), such as a CSV
+///   file's header row
+/// - text: the text to append
+///
+/// - return: Either an empty Ok Result, or an Err with message explaining
+///   problem
+///
+pub(crate) fn append(path: &Path, header: &str, text: &str) -> Result<(), String>
+{
+    let error = |e: std::io::Error| format!("Cannot write \"{}\": {}", path.display(), e);
+    let mut file = OpenOptions::new().create(true).append(true).open(path).map_err(error)?;
+
+    if file.metadata().map_err(error)?.len() == 0
+    {
+        file.write_all(header.as_bytes()).map_err(error)?;
+    }
+    file.write_all(text.as_bytes()).map_err(error)
+}
+
+///
+/// Quotes a CSV field if it holds a comma, quote or line break, doubling
+/// any quotes within it
+///
+pub(crate) fn csv_field(text: &str) -> String
+{
+    match text.contains(&[',', '"', '\n', '\r'][..])
+    {
+        true => format!("\"{}\"", text.replace('"', "\"\"")),
+        false => text.to_string(),
+    }
+}
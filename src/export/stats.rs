@@ -0,0 +1,43 @@
+use std::path::Path;
+
+use super::csv_field;
+
+// The first row of every statistics file
+const HEADER: &str = "from,to,algorithm,nodes_expanded,cost,micros\n";
+
+///
+/// The statistics of one algorithm's search for one query
+///
+/// - from, to: the query's start and end locations
+/// - algorithm: the algorithm's name
+/// - nodes_expanded: the # of nodes the algorithm considered
+/// - cost: the route's total distance, or None if there is no route
+/// - micros: the time taken to search, in microseconds
+///
+pub struct SearchStats<'s>
+{
+    pub from: &'s str,
+    pub to: &'s str,
+    pub algorithm: &'s str,
+    pub nodes_expanded: usize,
+    pub cost: Option<f64>,
+    pub micros: u128,
+}
+
+///
+/// Appends a row of statistics to a CSV file, such as for comparing
+/// many runs in a spreadsheet. A new (or empty) file is first given its
+/// header row
+///
+/// - path: the CSV file
+/// - stats: the statistics to append
+///
+/// - return: Either an empty Ok Result, or an Err with message explaining
+///   problem
+///
+pub fn append(path: &Path, stats: &SearchStats) -> Result<(), String>
+{
+    let cost = stats.cost.map_or_else(String::new, |cost| cost.to_string());
+    let row = format!("{},{},{},{},{},{}\n", csv_field(stats.from), csv_field(stats.to), csv_field(stats.algorithm), stats.nodes_expanded, cost, stats.micros);
+    super::append(path, HEADER, &row)
+}
@@ -0,0 +1,91 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use crate::algo::RouteGraph;
+use crate::load::xml::escape;
+use crate::load::Unit;
+
+// The drawing's longest side, and its margin, in pixels
+const SIZE: f64 = 1000.0;
+const MARGIN: f64 = 40.0;
+
+// How routes and locations are drawn
+const EDGE_STYLE: &str = "stroke=\"#999\" stroke-width=\"1\"";
+const ROUTE_STYLE: &str = "stroke=\"red\" stroke-width=\"4\"";
+const NODE_RADIUS: f64 = 5.0;
+const NODE_FILL: &str = "white";
+const EXPANDED_FILL: &str = "#9cf";
+const ROUTE_FILL: &str = "red";
+
+///
+/// Draws the routes as an SVG image: each route as a line labelled with
+/// its distance, and each location as a named circle. The route given
+/// is drawn in bold red, and the locations a search expanded are shaded
+/// blue, showing how far it spread.
+///
+/// - graph: every route
+/// - positions: each location's (x, y) on the drawing, with y running
+///   downward. The drawing is scaled to fit them
+/// - route: the locations of the route to highlight, from start to end,
+///   or none
+/// - expanded: the locations to shade
+/// - unit: what the weights measure
+/// - precision: the # of decimal places written in labels
+///
+/// - return: the SVG file's text
+///
+pub fn to_svg(graph: &RouteGraph, positions: &HashMap<&str, (f64, f64)>, route: &[&str], expanded: &HashSet<&str>, unit: Unit, precision: usize) -> String
+{
+    let route_nodes = route.iter().cloned().collect::<HashSet<&str>>();
+    let legs = route.windows(2).flat_map(|leg| vec![(leg[0], leg[1]), (leg[1], leg[0])]).collect::<HashSet<(&str, &str)>>();
+
+    // Scale the locations to fit the drawing, keeping their proportions
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+    for &(x, y) in positions.values()
+    {
+        min_x = min_x.min(x);
+        min_y = min_y.min(y);
+        max_x = max_x.max(x);
+        max_y = max_y.max(y);
+    }
+    if positions.is_empty() { min_x = 0.0; min_y = 0.0; max_x = 0.0; max_y = 0.0; }
+    let span = (max_x - min_x).max(max_y - min_y);
+    let scale = if span > 0.0 { SIZE / span } else { 1.0 };
+    let point = |node: &str| positions.get(node).map(|&(x, y)| (MARGIN + (x - min_x) * scale, MARGIN + (y - min_y) * scale));
+
+    let (width, height) = ((max_x - min_x) * scale + 2.0 * MARGIN, (max_y - min_y) * scale + 2.0 * MARGIN);
+    let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0:.0}\" height=\"{1:.0}\" viewBox=\"0 0 {0:.0} {1:.0}\" font-family=\"sans-serif\" font-size=\"10\">\n", width, height);
+    let _ = writeln!(svg, "  <rect width=\"100%\" height=\"100%\" fill=\"white\"/>");
+
+    // Routes, with the route's legs drawn last, over the others
+    let mut edges = graph.all_edges().collect::<Vec<(&str, &str, &u64)>>();
+    edges.sort_by_key(|&(from, to, _)| legs.contains(&(from, to)));
+    for (from, to, &weight) in edges
+    {
+        let ((x1, y1), (x2, y2)) = match (point(from), point(to))
+        {
+            (Some(from), Some(to)) => (from, to),
+            _ => continue,
+        };
+        let style = if legs.contains(&(from, to)) { ROUTE_STYLE } else { EDGE_STYLE };
+        let _ = writeln!(svg, "  <line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" {}/>", x1, y1, x2, y2, style);
+        let _ = writeln!(svg, "  <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" fill=\"#555\">{}</text>", (x1 + x2) / 2.0, (y1 + y2) / 2.0 - 3.0, unit.number(unit.from_weight(weight), precision));
+    }
+
+    // Locations, over the routes
+    for node in graph.nodes()
+    {
+        let (x, y) = match point(node) { Some(point) => point, None => continue };
+        let fill = match node
+        {
+            node if route_nodes.contains(node) => ROUTE_FILL,
+            node if expanded.contains(node) => EXPANDED_FILL,
+            _ => NODE_FILL,
+        };
+        let _ = writeln!(svg, "  <circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{}\" fill=\"{}\" stroke=\"black\"/>", x, y, NODE_RADIUS, fill);
+        let _ = writeln!(svg, "  <text x=\"{:.1}\" y=\"{:.1}\">{}</text>", x + NODE_RADIUS + 2.0, y + NODE_RADIUS + 8.0, escape(node));
+    }
+    svg.push_str("</svg>\n");
+
+    svg
+}
@@ -0,0 +1,121 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// The history file kept in the user's home directory, if no other is
+/// given
+pub const HISTORY_FILE: &str = ".pathfinding_history";
+
+///
+/// One search run, as recorded in the history file
+///
+/// - time: when the search was run, in seconds since the Unix epoch
+/// - from, to: the query's start and end locations
+/// - algorithm: the algorithm's name
+/// - cost: the route's total distance, or None if there is no route
+/// - micros: the time taken to search, in microseconds
+///
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Query
+{
+    pub time: u64,
+    pub from: String,
+    pub to: String,
+    pub algorithm: String,
+    pub cost: Option<f64>,
+    pub micros: u128,
+}
+
+impl Query
+{
+    ///
+    /// Records a search run now
+    ///
+    /// - from, to: the query's start and end locations
+    /// - algorithm: the algorithm's name
+    /// - cost: the route's total distance, or None if there is no route
+    /// - micros: the time taken to search, in microseconds
+    ///
+    pub fn now(from: &str, to: &str, algorithm: &str, cost: Option<f64>, micros: u128) -> Query
+    {
+        let time = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+        Query { time, from: from.to_string(), to: to.to_string(), algorithm: algorithm.to_string(), cost, micros }
+    }
+}
+
+///
+/// Finds the history file in the user's home directory
+///
+/// - return: the history file's path, or None if there is no home
+///   directory
+///
+pub fn default_path() -> Option<PathBuf>
+{
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| Path::new(&home).join(HISTORY_FILE))
+}
+
+///
+/// Appends a query to the history file, as a line of JSON, creating the
+/// file if needed
+///
+/// - path: the history file
+/// - query: the query to record
+///
+/// - return: Either an empty Ok Result, or an Err with message explaining
+///   problem
+///
+pub fn append(path: &Path, query: &Query) -> Result<(), String>
+{
+    let line = serde_json::to_string(query).expect("Queries are always valid JSON");
+    super::export::append(path, "", &format!("{}\n", line))
+}
+
+///
+/// Reads every query recorded in the history file
+///
+/// - path: the history file
+///
+/// - return: Either an Ok Result with every Query, oldest first (none if
+///   there is no history file yet), or an Err with message explaining
+///   problem
+///
+pub fn read(path: &Path) -> Result<Vec<Query>, String>
+{
+    let text = match std::fs::read_to_string(path)
+    {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Cannot read \"{}\": {}", path.display(), e)),
+    };
+
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| serde_json::from_str(line)
+            .map_err(|e| format!("{}: Line {}: {}", path.display(), i + 1, e)))
+        .collect()
+}
+
+///
+/// Writes a time as a UTC date and time, such as "2021-03-14 15:09"
+///
+/// - time: the time, in seconds since the Unix epoch
+///
+pub fn format_time(time: u64) -> String
+{
+    // Counts days from 0000-03-01, so each leap day ends its year
+    let (days, seconds) = (time / 86_400 + 719_468, time % 86_400);
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = era * 400 + year_of_era + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, seconds / 3600, seconds % 3600 / 60)
+}
@@ -1,26 +1,188 @@
+use std::cell::RefCell;
+use std::fs::File;
 use std::io;
-use std::io::Write;
+use std::io::{BufRead, BufReader, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crossterm::cursor::MoveTo;
+use crossterm::terminal::{Clear, ClearType};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+
+// How long a search runs before its progress is shown, so quick searches
+// never flash a spinner
+const PROGRESS_DELAY: Duration = Duration::from_millis(250);
+
+// How many expansions pass between updates of a search's progress
+const PROGRESS_INTERVAL: usize = 1024;
+
+// Whether output is plain, linear text, for screen readers and logs
+static PLAIN: AtomicBool = AtomicBool::new(false);
+
+// The terminal, read from once standard input holds data instead
+static TERMINAL: OnceLock<Mutex<BufReader<File>>> = OnceLock::new();
+
+thread_local!
+{
+    // The line editor, used for input once completion is turned on
+    static EDITOR: RefCell<Option<Editor<NameCompleter, DefaultHistory>>> = const { RefCell::new(None) };
+}
+
+///
+/// Completes the name being typed at the prompt with Tab, from the names
+/// given. Within a list of names separated by commas, only the last is
+/// completed
+///
+/// - names: every name that can be completed
+///
+struct NameCompleter
+{
+    names: Vec<String>,
+}
+
+impl Completer for NameCompleter
+{
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)>
+    {
+        // Find where the last name begins, after any comma, spaces and quote
+        let typed = &line[..pos];
+        let mut start = typed.rfind(',').map_or(0, |comma| comma + 1);
+        start += typed[start..].len() - typed[start..].trim_start().len();
+        if typed[start..].starts_with('"') { start += 1; }
+
+        let prefix = typed[start..].to_lowercase();
+        let candidates = self.names.iter()
+            .filter(|name| name.to_lowercase().starts_with(&prefix))
+            .map(|name| Pair { display: name.clone(), replacement: name.clone() })
+            .collect();
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for NameCompleter
+{
+    type Hint = String;
+}
+
+impl Highlighter for NameCompleter {}
+
+impl Validator for NameCompleter {}
+
+impl Helper for NameCompleter {}
+
+#[cfg(windows)]
+const TERMINAL_PATH: &str = "CONIN$";
+#[cfg(not(windows))]
+const TERMINAL_PATH: &str = "/dev/tty";
+
+///
+/// Reads user input from the terminal rather than standard input, for
+/// when standard input was used for data, such as piped route data
+///
+/// - return: Either Ok, or an Err if there is no terminal to read from
+///
+pub fn read_from_terminal() -> Result<(), String>
+{
+    let terminal = File::open(TERMINAL_PATH).map_err(|e| format!("Cannot read input from the terminal: {}", e))?;
+    let _ = TERMINAL.set(Mutex::new(BufReader::new(terminal)));
+    Ok(())
+}
+
+///
+/// Sets whether output is plain, linear text, suited to screen readers
+/// and terminal logs: the screen is never cleared, and input is read
+/// without line editing
+///
+/// - plain: whether output is plain
+///
+pub fn set_plain(plain: bool)
+{
+    PLAIN.store(plain, Ordering::Relaxed);
+}
+
+///
+/// - return: whether output is plain, linear text
+///
+pub fn is_plain() -> bool
+{
+    PLAIN.load(Ordering::Relaxed)
+}
 
 ///
-/// Recieves user input and returns
+/// Turns on Tab completion of names at the prompt, along with the line
+/// editing and history of earlier input that come with it. Completion is
+/// only used when input is typed at a terminal
+///
+/// - names: every name that can be completed, such as each location and
+///   command
+///
+pub fn complete_names(names: Vec<String>)
+{
+    if is_plain() || TERMINAL.get().is_some() || !io::stdin().is_terminal() { return; }
+
+    if let Ok(mut editor) = Editor::new()
+    {
+        editor.set_helper(Some(NameCompleter { names }));
+        EDITOR.with(|cell| *cell.borrow_mut() = Some(editor));
+    }
+}
+
+///
+/// Recieves user input and returns. Once input has ended, "quit" is
+/// returned, so the program exits
 /// 
 pub fn input(same_line: bool) -> String
 {
-    // Print a input prompt;
-    if !same_line 
+    prompt(if same_line { "" } else { "  >> " })
+}
+
+///
+/// Recieves user input after a prompt on the same line, such as a
+/// question. Once input has ended, "quit" is returned, so the program
+/// exits
+///
+/// - text: the prompt
+///
+pub fn prompt(text: &str) -> String
+{
+    // Read with the line editor, if completion is turned on
+    let edited = EDITOR.with(|cell| cell.borrow_mut().as_mut().map(|editor| match editor.readline(text)
     {
-        print!("  >> "); 
-    }
+        Ok(line) =>
+        {
+            if !line.trim().is_empty() { let _ = editor.add_history_entry(line.as_str()); }
+            line
+        },
+        Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => String::from("quit"),
+        Err(e) => panic!("Failed to read input: {}", e),
+    }));
+    if let Some(line) = edited { return line; }
+
+    print!("{}", text);
     io::stdout().flush().unwrap();
 
     // Write input to String and return
     let mut inp = String::new();
-    io::stdin().read_line(&mut inp)
-        .unwrap();
+    let read = match TERMINAL.get()
+    {
+        Some(terminal) => terminal.lock().unwrap().read_line(&mut inp),
+        None => io::stdin().read_line(&mut inp),
+    };
+    if read.unwrap() == 0 { return String::from("quit"); }
 
     // Trim the '\n' off end of String (since
     // read_line writes end-line onto String)
-    return format!("{}", inp.trim_end_matches('\n'));
+    inp.trim_end_matches('\n').to_string()
 }
 
 ///
@@ -28,19 +190,75 @@ pub fn input(same_line: bool) -> String
 /// 
 pub fn wait_for_enter()
 {
-    print!("Press ENTER to continue...");
-    let _ = input(true);
+    let _ = prompt("Press ENTER to continue...");
 }
 
 ///
-/// Clears the console screen. Waits for
-/// terminal to call command before returning, so
-/// sequential lines are deleted in the lag
+/// Clears the console screen and moves the cursor to its top left, on
+/// any platform. Nothing is written when output isn't a terminal, or is
+/// plain
 /// 
 pub fn clear_screen()
 {
-    std::process::Command::new("clear").spawn()
-        .expect("Failed to clear screen!")
-        .wait()
-        .expect("Failed to sleep program!");
-}
\ No newline at end of file
+    let mut stdout = io::stdout();
+    if is_plain() || !stdout.is_terminal() { return; }
+
+    crossterm::execute!(stdout, Clear(ClearType::All), MoveTo(0, 0))
+        .expect("Failed to clear screen!");
+}
+
+///
+/// A spinner on standard error showing a long search's progress: the
+/// nodes expanded, how many per second, and how many are queued. It is
+/// only shown once the search has run a while, and only on a terminal
+///
+/// - bar: the spinner, hidden until shown
+/// - name: the algorithm's name
+/// - started: when the search began
+///
+pub struct SearchProgress
+{
+    bar: ProgressBar,
+    name: &'static str,
+    started: Instant,
+}
+
+impl SearchProgress
+{
+    ///
+    /// - name: the searching algorithm's name
+    ///
+    pub fn new(name: &'static str) -> Self
+    {
+        let bar = ProgressBar::hidden();
+        bar.set_style(ProgressStyle::with_template("{spinner} {msg}").expect("The template is valid"));
+        SearchProgress { bar, name, started: Instant::now() }
+    }
+
+    ///
+    /// Updates the spinner as a node is expanded, every so many nodes
+    ///
+    /// - expanded: the # of nodes expanded so far
+    /// - frontier: the # of nodes queued
+    ///
+    pub fn update(&self, expanded: usize, frontier: usize)
+    {
+        if !expanded.is_multiple_of(PROGRESS_INTERVAL) { return; }
+
+        let elapsed = self.started.elapsed();
+        if elapsed < PROGRESS_DELAY { return; }
+        if self.bar.is_hidden() { self.bar.set_draw_target(ProgressDrawTarget::stderr()); }
+
+        self.bar.set_message(format!("{}: {} nodes expanded ({:.0} per second), {} queued",
+            self.name, expanded, expanded as f64 / elapsed.as_secs_f64(), frontier));
+        self.bar.tick();
+    }
+
+    ///
+    /// Removes the spinner, once the search is done
+    ///
+    pub fn finish(&self)
+    {
+        self.bar.finish_and_clear();
+    }
+}
@@ -1,2 +1,11 @@
+pub mod algo;
+pub mod cache;
+pub mod config;
+pub mod export;
+pub mod history;
 pub mod io;
-pub mod prog;
\ No newline at end of file
+pub mod load;
+pub mod names;
+pub mod prog;
+pub mod report;
+pub mod tui;
\ No newline at end of file
@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::grid::GridMap;
+use super::{RouteAttributes, Routes, Unit};
+use crate::algo::dag::DirectedRouteGraph;
+use crate::algo::grid::{Connectivity, STRAIGHT_COST};
+use crate::algo::RouteGraph;
+
+// Marks the start of a binary route file, and its version. Version 1
+// files, without one-way routes, version 2 files, with only road
+// classes, version 3 files, without grid maps, and version 4 files, with
+// raw weights in tenths, may still be read
+const MAGIC: &[u8; 8] = b"ROUTEGR\0";
+const VERSION: u32 = 5;
+
+///
+/// Writes the routes to a binary route file, which loads far faster than
+/// re-parsing the text the routes came from
+///
+/// - routes: the Routes to write
+/// - path: the file to write to
+///
+/// - return: Either an empty Ok Result, or an Err with message explaining
+///   problem
+///
+pub fn save(routes: &Routes, path: &Path) -> Result<(), String>
+{
+    std::fs::write(path, to_bytes(routes)).map_err(|e| format!("Cannot write \"{}\": {}", path.display(), e))
+}
+
+///
+/// Encodes the routes as a binary route file: a header, then every
+/// location (its name and any coordinates), then every route (its ends'
+/// indices, weight, and any road class, speed limit and surface), then every direction routes may
+/// be travelled if any are one-way (its ends' indices and weight).
+/// Numbers are little-endian, and strings are prefixed by their length.
+///
+/// - routes: the Routes to encode
+///
+/// - return: the binary route file's contents
+///
+pub fn to_bytes(routes: &Routes) -> Vec<u8>
+{
+    fn string(bytes: &mut Vec<u8>, string: &str)
+    {
+        bytes.extend_from_slice(&(string.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(string.as_bytes());
+    }
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+    bytes.push(unit_code(routes.unit));
+    bytes.push(routes.geographic as u8);
+    bytes.push(match routes.grid.as_ref().map(|grid| grid.connectivity) { None => 0, Some(Connectivity::Four) => 4, Some(Connectivity::Eight) => 8 });
+
+    let mut indices = HashMap::new();
+    bytes.extend_from_slice(&(routes.graph.node_count() as u32).to_le_bytes());
+    for (i, node) in routes.graph.nodes().enumerate()
+    {
+        indices.insert(node, i as u32);
+        string(&mut bytes, node);
+        match routes.coordinates.get(node)
+        {
+            Some(&(x, y)) =>
+            {
+                bytes.push(1);
+                bytes.extend_from_slice(&x.to_le_bytes());
+                bytes.extend_from_slice(&y.to_le_bytes());
+            },
+            None => bytes.push(0),
+        }
+    }
+
+    bytes.extend_from_slice(&(routes.graph.edge_count() as u32).to_le_bytes());
+    for (from, to, &weight) in routes.graph.all_edges()
+    {
+        bytes.extend_from_slice(&indices[from].to_le_bytes());
+        bytes.extend_from_slice(&indices[to].to_le_bytes());
+        bytes.extend_from_slice(&weight.to_le_bytes());
+        let attributes = routes.attributes(from, to).unwrap_or_default();
+        string(&mut bytes, attributes.highway.unwrap_or(""));
+        match attributes.speed_limit
+        {
+            Some(speed_limit) =>
+            {
+                bytes.push(1);
+                bytes.extend_from_slice(&speed_limit.to_le_bytes());
+            },
+            None => bytes.push(0),
+        }
+        string(&mut bytes, attributes.surface.unwrap_or(""));
+    }
+
+    // The number of directions is 0 if every route is two-way
+    let arcs = routes.arcs.iter().flat_map(|arcs| arcs.all_edges()).collect::<Vec<(&str, &str, &u64)>>();
+    bytes.extend_from_slice(&(arcs.len() as u32).to_le_bytes());
+    for (from, to, &weight) in arcs
+    {
+        bytes.extend_from_slice(&indices[from].to_le_bytes());
+        bytes.extend_from_slice(&indices[to].to_le_bytes());
+        bytes.extend_from_slice(&weight.to_le_bytes());
+    }
+
+    bytes
+}
+
+///
+/// Reads the routes from a binary route file, borrowing every name from
+/// its contents
+///
+/// - data: the binary route file's contents
+///
+/// - return: Either an Ok Result with the Routes, or an Err with message
+///   explaining problem
+///
+pub fn parse(data: &[u8]) -> Result<Routes<'_>, String>
+{
+    let mut reader = Reader { data, pos: 0 };
+    if reader.take(MAGIC.len())? != MAGIC { return Err(String::from("Not a binary route file.")); }
+    let version = reader.u32()?;
+    if !(1..=VERSION).contains(&version) { return Err(format!("Binary route file version {} is not supported.", version)); }
+
+    let unit = unit_from_code(reader.take(1)?[0]).ok_or_else(|| String::from("Invalid binary route file: unknown unit."))?;
+    let geographic = reader.take(1)?[0] != 0;
+    let grid = match if version < 4 { 0 } else { reader.take(1)?[0] }
+    {
+        0 => None,
+        4 => Some(Connectivity::Four),
+        8 => Some(Connectivity::Eight),
+        _ => return Err(String::from("Invalid binary route file: unknown grid connectivity.")),
+    };
+
+    let node_count = reader.u32()? as usize;
+    let mut nodes = Vec::with_capacity(node_count);
+    let mut coordinates = HashMap::new();
+    for _ in 0..node_count
+    {
+        let node = reader.string()?;
+        if reader.take(1)?[0] != 0 { coordinates.insert(node, (reader.f64()?, reader.f64()?)); }
+        nodes.push(node);
+    }
+
+    let edge_count = reader.u32()? as usize;
+    let mut graph = RouteGraph::with_capacity(node_count, edge_count);
+    for &node in &nodes { graph.add_node(node); }
+
+    let node = |reader: &mut Reader<'_>| -> Result<&str, String>
+    {
+        let index = reader.u32()? as usize;
+        nodes.get(index).cloned().ok_or_else(|| String::from("Invalid binary route file: location out of range."))
+    };
+
+    let mut attributes = HashMap::new();
+    for _ in 0..edge_count
+    {
+        let (from, to) = (node(&mut reader)?, node(&mut reader)?);
+        graph.add_edge(from, to, reader.u64()?);
+
+        let highway = Some(reader.string()?).filter(|highway| !highway.is_empty());
+        let (speed_limit, surface) = match version
+        {
+            1 | 2 => (None, None),
+            _ =>
+            {
+                let speed_limit = if reader.take(1)?[0] != 0 { Some(reader.f64()?) } else { None };
+                (speed_limit, Some(reader.string()?).filter(|surface| !surface.is_empty()))
+            },
+        };
+        if highway.is_some() || speed_limit.is_some() || surface.is_some()
+        {
+            let route = RouteAttributes { highway, speed_limit, surface };
+            attributes.insert((from, to), route);
+            attributes.insert((to, from), route);
+        }
+    }
+
+    let arc_count = if version == 1 { 0 } else { reader.u32()? as usize };
+    let arcs = match arc_count
+    {
+        0 => None,
+        _ =>
+        {
+            let mut arcs = DirectedRouteGraph::with_capacity(node_count, arc_count);
+            for &node in &nodes { arcs.add_node(node); }
+            for _ in 0..arc_count
+            {
+                let (from, to) = (node(&mut reader)?, node(&mut reader)?);
+                arcs.add_edge(from, to, reader.u64()?);
+            }
+            Some(arcs)
+        },
+    };
+
+    if reader.pos != data.len() { return Err(String::from("Invalid binary route file: unexpected data at end.")); }
+
+    let mut routes = Routes { graph, arcs, coordinates, geographic, attributes, unit, duplicates: Vec::new(), grid: None };
+    // Raw weights were kept in tenths, as every unit's were, before
+    // version 5
+    if version < 5 && unit == Unit::Raw
+    {
+        routes.reweight(|_, _, weight, _| unit.to_weight(weight as f64 / 10.0).round() as u64);
+    }
+
+    // A grid map's Grid is rebuilt from its cells' coordinates, and its
+    // straight steps, the cheapest of its routes
+    let straight_cost = routes.graph.all_edges().map(|(_, _, &weight)| weight).min().unwrap_or(STRAIGHT_COST);
+    routes.grid = grid.map(|connectivity| GridMap::from_coordinates(&routes.coordinates, connectivity, straight_cost));
+    Ok(routes)
+}
+
+///
+/// - return: the byte a unit is stored as
+///
+pub(super) fn unit_code(unit: Unit) -> u8
+{
+    match unit { Unit::Miles => 0, Unit::Minutes => 1, Unit::Cells => 2, Unit::Kilometers => 3, Unit::Raw => 4 }
+}
+
+///
+/// - return: the unit stored as a byte, or None if it names no unit
+///
+pub(super) fn unit_from_code(code: u8) -> Option<Unit>
+{
+    match code
+    {
+        0 => Some(Unit::Miles),
+        1 => Some(Unit::Minutes),
+        2 => Some(Unit::Cells),
+        3 => Some(Unit::Kilometers),
+        4 => Some(Unit::Raw),
+        _ => None,
+    }
+}
+
+///
+/// Reads values from the start of a binary route file, in order
+///
+struct Reader<'d>
+{
+    data: &'d [u8],
+    pos: usize,
+}
+
+impl<'d> Reader<'d>
+{
+    fn take(&mut self, length: usize) -> Result<&'d [u8], String>
+    {
+        let bytes = self.data.get(self.pos..self.pos + length)
+            .ok_or_else(|| String::from("Invalid binary route file: truncated."))?;
+        self.pos += length;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, String>
+    {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn u64(&mut self) -> Result<u64, String>
+    {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn f64(&mut self) -> Result<f64, String>
+    {
+        self.u64().map(f64::from_bits)
+    }
+
+    fn string(&mut self) -> Result<&'d str, String>
+    {
+        let length = self.u32()? as usize;
+        std::str::from_utf8(self.take(length)?)
+            .map_err(|_| String::from("Invalid binary route file: name is not UTF-8."))
+    }
+}
@@ -0,0 +1,444 @@
+use std::cmp::{Ordering, Reverse};
+use std::convert::TryFrom;
+use std::fs::File;
+use std::path::Path;
+use std::slice::ChunksExact;
+
+use memmap2::Mmap;
+
+use super::binary::{unit_code, unit_from_code};
+use super::Unit;
+use crate::algo::intern::{Estimates, InternedGraph, SearchGraph};
+
+// Marks the start of a compiled graph file, and its version. Version 2
+// files may still be read, unless their weights are raw, which were kept
+// in tenths
+const MAGIC: &[u8; 8] = b"ROUTECSR";
+const VERSION: u32 = 3;
+
+// The bytes before the first section: the magic, version, unit, and the
+// # of locations, edges and goals, and the length of the names
+const HEADER_LENGTH: usize = 8 + 4 + 1 + 8 * 4;
+
+// The average # of names hashed to each bucket of the perfect hash. Fewer
+// make pilots quicker to find, but the file larger
+const NAMES_PER_BUCKET: usize = 4;
+
+///
+/// Writes an InternedGraph to a compiled graph file, which is searched
+/// where it lies in memory, without being loaded
+///
+/// - graph: the InternedGraph to write, with any heuristic table it holds
+/// - unit: the unit its weights measure
+/// - path: the file to write to
+///
+/// - return: Either an empty Ok Result, or an Err with message explaining
+///   problem
+///
+pub fn save(graph: &InternedGraph, unit: Unit, path: &Path) -> Result<(), String>
+{
+    std::fs::write(path, to_bytes(graph, unit)).map_err(|e| format!("Cannot write \"{}\": {}", path.display(), e))
+}
+
+///
+/// Encodes an InternedGraph as a compiled graph file: a header, then
+/// each location's name offset, the names, a minimal perfect hash of the
+/// names (each bucket's pilot, then the ID in each slot, for finding
+/// locations without searching), each location's edge offset,
+/// each edge's neighbour and weight, and last the goals of any heuristic
+/// table, each with every location's estimate to it. Numbers are
+/// little-endian, IDs are u32 and every other number is u64.
+///
+/// - graph: the InternedGraph to encode
+/// - unit: the unit its weights measure
+///
+/// - return: the compiled graph file's contents
+///
+pub fn to_bytes(graph: &InternedGraph, unit: Unit) -> Vec<u8>
+{
+    let nodes = graph.len() as u32;
+    let (pilots, slots) = perfect_hash(&(0..nodes).map(|id| graph.name(id)).collect::<Vec<&str>>());
+
+    let edges = (0..nodes).map(|id| graph.edges(id).count()).sum::<usize>();
+    let goals = graph.heuristic().map_or_else(Vec::new, |matrix| (0..nodes).filter(|&goal| matrix.to(goal).is_some()).collect());
+    let names = (0..nodes).map(|id| graph.name(id).len()).sum::<usize>();
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+    bytes.push(unit_code(unit));
+    for count in [nodes as usize, edges, goals.len(), names]
+    {
+        bytes.extend_from_slice(&(count as u64).to_le_bytes());
+    }
+
+    let mut offset = 0;
+    bytes.extend_from_slice(&0u64.to_le_bytes());
+    for id in 0..nodes
+    {
+        offset += graph.name(id).len() as u64;
+        bytes.extend_from_slice(&offset.to_le_bytes());
+    }
+    for id in 0..nodes { bytes.extend_from_slice(graph.name(id).as_bytes()); }
+    for pilot in pilots { bytes.extend_from_slice(&pilot.to_le_bytes()); }
+    for id in slots { bytes.extend_from_slice(&id.to_le_bytes()); }
+
+    let mut offset = 0;
+    bytes.extend_from_slice(&0u64.to_le_bytes());
+    for id in 0..nodes
+    {
+        offset += graph.edges(id).count() as u64;
+        bytes.extend_from_slice(&offset.to_le_bytes());
+    }
+    for id in 0..nodes
+    {
+        for (neighbour, _) in graph.edges(id) { bytes.extend_from_slice(&neighbour.to_le_bytes()); }
+    }
+    for id in 0..nodes
+    {
+        for (_, weight) in graph.edges(id) { bytes.extend_from_slice(&weight.to_le_bytes()); }
+    }
+
+    for &goal in &goals { bytes.extend_from_slice(&goal.to_le_bytes()); }
+    if let Some(matrix) = graph.heuristic()
+    {
+        for &goal in &goals
+        {
+            let estimates = Estimates::Ids(matrix.to(goal).unwrap_or(&[]));
+            for node in 0..nodes { bytes.extend_from_slice(&estimates.get(node).to_le_bytes()); }
+        }
+    }
+
+    bytes
+}
+
+///
+/// Maps a compiled graph file into memory, so that only the parts of it
+/// a search reads are ever read from disk
+///
+/// - path: the file's path
+///
+/// - return: Either an Ok Result with the mapped file, or an Err with
+///   message explaining problem
+///
+pub fn map(path: &Path) -> Result<Mmap, String>
+{
+    let file = File::open(path).map_err(|e| format!("Cannot read \"{}\": {}", path.display(), e))?;
+    // The mapping is only valid while the file is unchanged, as for any
+    // mapped file; compiled graphs are written once and then only read
+    unsafe { Mmap::map(&file) }.map_err(|e| format!("Cannot map \"{}\" into memory: {}", path.display(), e))
+}
+
+///
+/// A compiled graph file, searched where it lies without being loaded.
+/// Only the header is read, and the names checked, when it is opened;
+/// every other number is read as it is needed
+///
+/// - unit: the unit its weights measure
+/// - nodes: the # of locations
+/// - name_offsets, names: each location's name
+/// - pilots, slots: the minimal perfect hash finding each name's ID
+/// - offsets, neighbours, weights: each location's edges, in compressed
+///   sparse row form
+/// - goals, estimates: the goals of any heuristic table, in order, and
+///   every location's estimate to each
+///
+pub struct CompiledGraph<'d>
+{
+    unit: Unit,
+    nodes: usize,
+    name_offsets: &'d [u8],
+    names: &'d str,
+    pilots: &'d [u8],
+    slots: &'d [u8],
+    offsets: &'d [u8],
+    neighbours: &'d [u8],
+    weights: &'d [u8],
+    goals: &'d [u8],
+    estimates: &'d [u8],
+}
+
+impl<'d> CompiledGraph<'d>
+{
+    ///
+    /// Opens a compiled graph file's contents, checking that each of its
+    /// sections is as long as its header says
+    ///
+    /// - data: the file's contents, such as a mapped file
+    ///
+    /// - return: Either an Ok Result with the CompiledGraph, or an Err with
+    ///   message explaining problem
+    ///
+    pub fn parse(data: &'d [u8]) -> Result<Self, String>
+    {
+        let invalid = |problem: &str| format!("Invalid compiled graph file: {}.", problem);
+        if data.len() < HEADER_LENGTH || &data[..MAGIC.len()] != MAGIC { return Err(String::from("Not a compiled graph file.")); }
+
+        let version = u32_at(&data[8..12], 0);
+        let unit = unit_from_code(data[12]).ok_or_else(|| invalid("unknown unit"))?;
+        if !(2..=VERSION).contains(&version) || (version < 3 && unit == Unit::Raw)
+        {
+            return Err(format!("Compiled graph file version {} is not supported.", version));
+        }
+        let count = |index: usize| usize::try_from(u64_at(&data[13..HEADER_LENGTH], index)).map_err(|_| invalid("too large"));
+        let (nodes, edges, goals, names) = (count(0)?, count(1)?, count(2)?, count(3)?);
+
+        // Each section's length, in order
+        let lengths = [nodes.checked_add(1).and_then(|offsets| offsets.checked_mul(8)), Some(names), bucket_count(nodes).checked_mul(4), nodes.checked_mul(4),
+                       nodes.checked_add(1).and_then(|offsets| offsets.checked_mul(8)),
+                       edges.checked_mul(4), edges.checked_mul(8), goals.checked_mul(4), goals.checked_mul(nodes).and_then(|estimates| estimates.checked_mul(8))];
+        let mut sections = Vec::with_capacity(lengths.len());
+        let mut at = HEADER_LENGTH;
+        for length in lengths
+        {
+            let end = length.and_then(|length| at.checked_add(length)).ok_or_else(|| invalid("too large"))?;
+            sections.push(data.get(at..end).ok_or_else(|| invalid("truncated"))?);
+            at = end;
+        }
+        if at != data.len() { return Err(invalid("unexpected data at end")); }
+
+        let names = std::str::from_utf8(sections[1]).map_err(|_| invalid("names are not UTF-8"))?;
+        Ok(CompiledGraph
+        {
+            unit, nodes, names,
+            name_offsets: sections[0],
+            pilots: sections[2],
+            slots: sections[3],
+            offsets: sections[4],
+            neighbours: sections[5],
+            weights: sections[6],
+            goals: sections[7],
+            estimates: sections[8],
+        })
+    }
+
+    ///
+    /// - return: the unit the weights measure
+    ///
+    pub fn unit(&self) -> Unit
+    {
+        self.unit
+    }
+
+    ///
+    /// - return: the weight of the lightest edge from one location to
+    ///   another, or None if there is none
+    ///
+    pub fn weight(&self, from: u32, to: u32) -> Option<u64>
+    {
+        self.edges(from).filter(|&(neighbour, _)| neighbour == to).map(|(_, weight)| weight).min()
+    }
+
+    ///
+    /// - return: the # of goals in the heuristic table
+    ///
+    fn goal_count(&self) -> usize
+    {
+        self.goals.len() / 4
+    }
+}
+
+impl<'d> SearchGraph<'d> for CompiledGraph<'d>
+{
+    type Edges<'g> = CompiledEdges<'g> where Self: 'g;
+
+    fn id(&self, name: &str) -> Option<u32>
+    {
+        // The name's bucket gives the pilot placing it in its slot, which
+        // holds its ID, if it is on the Graph at all
+        if self.nodes == 0 { return None; }
+        let hash = name_hash(name);
+        let pilot = u32_at(self.pilots, (hash % bucket_count(self.nodes) as u64) as usize);
+        let id = u32_at(self.slots, slot(hash, pilot, self.nodes));
+        Some(id).filter(|&id| (id as usize) < self.nodes && self.name(id) == name)
+    }
+
+    fn name(&self, id: u32) -> &'d str
+    {
+        let (start, end) = (u64_at(self.name_offsets, id as usize) as usize, u64_at(self.name_offsets, id as usize + 1) as usize);
+        self.names.get(start..end).unwrap_or_default()
+    }
+
+    fn len(&self) -> usize
+    {
+        self.nodes
+    }
+
+    fn edges(&self, id: u32) -> Self::Edges<'_>
+    {
+        let (start, end) = (u64_at(self.offsets, id as usize) as usize, u64_at(self.offsets, id as usize + 1) as usize);
+        let neighbours = start.checked_mul(4).zip(end.checked_mul(4)).and_then(|(start, end)| self.neighbours.get(start..end));
+        let weights = start.checked_mul(8).zip(end.checked_mul(8)).and_then(|(start, end)| self.weights.get(start..end));
+        CompiledEdges
+        {
+            nodes: self.nodes,
+            neighbours: neighbours.unwrap_or_default().chunks_exact(4),
+            weights: weights.unwrap_or_default().chunks_exact(8),
+        }
+    }
+
+    fn estimates_to(&self, goal: u32) -> Option<Estimates<'_>>
+    {
+        if self.goal_count() == 0 { return None; }
+
+        // The goals are in order, so are searched by halves
+        let (mut low, mut high) = (0, self.goal_count());
+        while low < high
+        {
+            let middle = low + (high - low) / 2;
+            match u32_at(self.goals, middle).cmp(&goal)
+            {
+                Ordering::Less => low = middle + 1,
+                Ordering::Greater => high = middle,
+                Ordering::Equal => return Some(Estimates::LittleEndian(&self.estimates[middle * self.nodes * 8..(middle + 1) * self.nodes * 8])),
+            }
+        }
+        Some(Estimates::Ids(&[]))
+    }
+}
+
+///
+/// Each edge leaving a location of a CompiledGraph, as (neighbour ID,
+/// weight), read as it is taken. Edges to IDs past the last location are
+/// skipped
+///
+pub struct CompiledEdges<'g>
+{
+    nodes: usize,
+    neighbours: ChunksExact<'g, u8>,
+    weights: ChunksExact<'g, u8>,
+}
+
+impl Iterator for CompiledEdges<'_>
+{
+    type Item = (u32, u64);
+
+    fn next(&mut self) -> Option<(u32, u64)>
+    {
+        loop
+        {
+            let (neighbour, weight) = (u32_at(self.neighbours.next()?, 0), u64_at(self.weights.next()?, 0));
+            if (neighbour as usize) < self.nodes { return Some((neighbour, weight)); }
+        }
+    }
+}
+
+impl DoubleEndedIterator for CompiledEdges<'_>
+{
+    fn next_back(&mut self) -> Option<(u32, u64)>
+    {
+        loop
+        {
+            let (neighbour, weight) = (u32_at(self.neighbours.next_back()?, 0), u64_at(self.weights.next_back()?, 0));
+            if (neighbour as usize) < self.nodes { return Some((neighbour, weight)); }
+        }
+    }
+}
+
+///
+/// Builds a minimal perfect hash of the names, by hash and displace: the
+/// names are hashed into buckets, and then, largest bucket first, each
+/// bucket is given the first pilot placing all its names in free slots.
+/// Every name then has its own slot, and finding a name's ID takes one
+/// hash of the name and two reads
+///
+/// - names: each ID's name, all different
+///
+/// - return: each bucket's pilot, and the ID in each slot
+///
+fn perfect_hash(names: &[&str]) -> (Vec<u32>, Vec<u32>)
+{
+    let hashes = names.iter().map(|name| name_hash(name)).collect::<Vec<u64>>();
+    let mut buckets = vec![Vec::new(); bucket_count(names.len())];
+    for (id, &hash) in hashes.iter().enumerate()
+    {
+        let bucket = (hash % bucket_count(names.len()) as u64) as usize;
+        buckets[bucket].push(id as u32);
+    }
+    let mut by_size = (0..buckets.len()).collect::<Vec<usize>>();
+    by_size.sort_by_key(|&bucket| Reverse(buckets[bucket].len()));
+
+    let mut pilots = vec![0; buckets.len()];
+    let mut slots = vec![u32::MAX; names.len()];
+    let mut taken = Vec::new();
+    for bucket in by_size
+    {
+        if buckets[bucket].is_empty() { break; }
+
+        // The first pilot which places every name of the bucket in a
+        // slot of its own
+        for pilot in 0..
+        {
+            taken.clear();
+            taken.extend(buckets[bucket].iter().map(|&id| slot(hashes[id as usize], pilot, names.len())));
+            let free = taken.iter().enumerate().all(|(i, &slot)| slots[slot] == u32::MAX && !taken[..i].contains(&slot));
+            if free
+            {
+                for (&id, &slot) in buckets[bucket].iter().zip(taken.iter())
+                {
+                    slots[slot] = id;
+                }
+                pilots[bucket] = pilot;
+                break;
+            }
+        }
+    }
+
+    (pilots, slots)
+}
+
+///
+/// - return: the # of buckets the perfect hash of that many names has
+///
+fn bucket_count(names: usize) -> usize
+{
+    names.div_ceil(NAMES_PER_BUCKET).max(1)
+}
+
+///
+/// Hashes a name as FNV-1a, which is the same in every build, so that
+/// files compiled by one build are read by another
+///
+fn name_hash(name: &str) -> u64
+{
+    name.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+///
+/// - return: the slot a name with the given hash is placed in by the
+///   pilot, of that many slots
+///
+fn slot(hash: u64, pilot: u32, slots: usize) -> usize
+{
+    // SplitMix64's finalizer spreads the hash and pilot over every bit
+    let mut z = hash ^ (pilot as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    ((z ^ (z >> 31)) % slots as u64) as usize
+}
+
+///
+/// - return: the index'th little-endian u32 of bytes, or u32::MAX if
+///   bytes ends first
+///
+fn u32_at(bytes: &[u8], index: usize) -> u32
+{
+    match bytes.get(index * 4..index * 4 + 4)
+    {
+        Some(bytes) => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        None => u32::MAX,
+    }
+}
+
+///
+/// - return: the index'th little-endian u64 of bytes, or 0 if bytes ends
+///   first
+///
+fn u64_at(bytes: &[u8], index: usize) -> u64
+{
+    match bytes.get(index * 8..index * 8 + 8)
+    {
+        Some(bytes) => u64::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7]]),
+        None => 0,
+    }
+}
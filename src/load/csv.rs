@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+use super::{add_attributes, build_routes, Duplicates, RouteAttributes, Routes, Unit, DISTANCE_NAMES};
+
+// Accepted header names for each column, compared case-insensitively
+const FROM_NAMES: [&str; 5] = ["from", "source", "start", "origin", "a"];
+const TO_NAMES: [&str; 5] = ["to", "target", "end", "destination", "b"];
+const ONE_WAY_NAMES: [&str; 3] = ["one_way", "oneway", "directed"];
+
+// Values marking a route as one-way, compared case-insensitively
+const TRUE_VALUES: [&str; 4] = ["yes", "true", "1", "y"];
+
+///
+/// Builds a route Graph from CSV, one route per row, such as a spreadsheet
+/// export. If the first row is a header naming the columns (for instance
+/// "from,to,distance"), the columns may be in any order and any others are
+/// ignored. Otherwise, the first three columns are the two locations and
+/// the distance between them, in miles.
+///
+/// A header may also name a "one_way" column, marking routes which may
+/// only be travelled from their first location to their second with
+/// "yes", "true" or "1", and route attribute columns, such as "highway",
+/// "speed_limit" (in miles per hour, unless followed by "km/h") or
+/// "surface". Empty attribute fields are skipped.
+///
+/// Fields may be quoted to include commas. Blank lines are skipped.
+///
+/// - text: the CSV data
+/// - directed: whether every route is one-way
+/// - duplicates: how to merge routes listed more than once
+/// - delimiter: the character between fields, usually ','
+/// - unit: what the distances measure
+///
+/// - return: Either an Ok Result with the Routes, or an Err with message
+///   explaining problem
+///
+pub fn parse(text: &str, directed: bool, duplicates: Duplicates, delimiter: char, unit: Unit) -> Result<Routes<'_>, String>
+{
+    let mut rows = text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| split_row(line, delimiter).map(|fields| (i + 1, fields)))
+        .collect::<Result<Vec<(usize, Vec<&str>)>, String>>()?;
+
+    // A first row whose distance is not a number is a header
+    let mut columns = (0, 1, 2);
+    let mut one_way_column = None;
+    let mut header_names = Vec::new();
+    if let Some((_, header)) = rows.first()
+    {
+        if header.get(2).is_none_or(|field| field.parse::<f64>().is_err())
+        {
+            let find = |names: &[&str]| header.iter().position(|field| names.contains(&field.to_lowercase().as_str()));
+            columns = match (find(&FROM_NAMES), find(&TO_NAMES), find(&DISTANCE_NAMES))
+            {
+                (Some(from), Some(to), Some(distance)) => (from, to, distance),
+                _ => return Err(String::from("CSV header must name from, to, and distance columns.")),
+            };
+            one_way_column = find(&ONE_WAY_NAMES);
+            header_names = rows.remove(0).1;
+        }
+    }
+
+    let mut edges = Vec::new();
+    let mut attributes = HashMap::new();
+    for (line, fields) in rows
+    {
+        let field = |column: usize| fields.get(column)
+            .cloned()
+            .ok_or_else(|| format!("Line {}: expected at least {} columns.", line, column + 1));
+
+        let (from, to) = (field(columns.0)?, field(columns.1)?);
+        let distance = field(columns.2)?.parse::<f64>()
+            .map_err(|_| format!("Line {}: \"{}\" is not a distance.", line, fields[columns.2]))?;
+
+        let one_way = directed || one_way_column
+            .and_then(|column| fields.get(column))
+            .is_some_and(|field| TRUE_VALUES.contains(&field.to_lowercase().as_str()));
+
+        // Any other named column may be an attribute of the route
+        let mut route = RouteAttributes::default();
+        for (name, value) in header_names.iter().zip(&fields).filter(|(_, value)| !value.is_empty())
+        {
+            route.set(name, value).map_err(|message| format!("Line {}: {}", line, message))?;
+        }
+
+        // Round distance to nearest 10th
+        edges.push((from, to, unit.to_weight(distance).round() as i64, one_way));
+        add_attributes(&mut attributes, from, to, route);
+    }
+
+    Ok(Routes { attributes, ..build_routes(edges, duplicates, unit)? })
+}
+
+///
+/// Splits a CSV row into its trimmed fields, removing any quotes
+///
+/// - line: the row
+/// - delimiter: the character between fields, usually ','
+///
+pub(crate) fn split_row(line: &str, delimiter: char) -> Result<Vec<&str>, String>
+{
+    // Whitespace around fields is trimmed, unless it is the delimiter
+    let is_space = move |c: char| c.is_whitespace() && c != delimiter;
+
+    let mut fields = Vec::new();
+    let mut rest = line.trim_end_matches('\r');
+    loop
+    {
+        let trimmed = rest.trim_start_matches(is_space);
+        let (field, remainder) = if let Some(quoted) = trimmed.strip_prefix('"')
+        {
+            // A quoted field runs to the next quote
+            let end = quoted.find('"').ok_or_else(|| format!("Unterminated quote in \"{}\".", line))?;
+            let remainder = quoted[end + 1..].trim_start_matches(is_space);
+            if remainder.starts_with('"') { return Err(format!("Escaped quotes are not supported in \"{}\".", line)); }
+            (&quoted[..end], remainder)
+        }
+        else
+        {
+            let end = trimmed.find(delimiter).unwrap_or(trimmed.len());
+            (trimmed[..end].trim_end_matches(is_space), &trimmed[end..])
+        };
+
+        fields.push(field);
+        match remainder.strip_prefix(delimiter)
+        {
+            Some(remainder) => rest = remainder,
+            None if remainder.is_empty() => return Ok(fields),
+            None => return Err(format!("Unexpected text after quoted field in \"{}\".", line)),
+        }
+    }
+}
@@ -0,0 +1,501 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use bumpalo::Bump;
+
+use super::{add_attributes, build_routes, Duplicates, Names, RouteAttributes, Routes, Unit, DISTANCE_NAMES};
+
+type Attributes = HashMap<String, String>;
+
+///
+/// A DOT token, with IDs borrowed from the text where possible
+///
+#[derive(PartialEq, Debug)]
+enum Token<'t>
+{
+    Id(Cow<'t, str>),
+    EdgeOp,
+    Punct(char),
+}
+
+///
+/// The default node and edge attributes within a (sub)graph
+///
+#[derive(Clone, Default)]
+struct Defaults
+{
+    node: Attributes,
+    edge: Attributes,
+}
+
+///
+/// Builds the routes from a Graphviz DOT file, such as:
+///
+/// graph { A -- B [weight=3.2]; B -- C -- D [weight=1]; }
+///
+/// The edges of a graph are two-way routes, while those of a digraph are
+/// one-way, unless given "dir=both" or "dir=none".
+/// Subgraphs and default attributes ("edge [weight=2]") are supported;
+/// an edge to a subgraph is an edge to each of its nodes. Nodes with a
+/// "pos" attribute ("x,y") are given those coordinates. Edges may give
+/// the route attributes "highway", "speed_limit" and "surface".
+///
+/// - text: the DOT data
+/// - weight: the name of the edge attribute holding distances.
+///   If None, an attribute with a usual name for distance is used
+/// - duplicates: how to merge routes listed more than once
+/// - unit: what the distances measure
+/// - arena: holds the location names read
+///
+/// - return: Either an Ok Result with the Routes, or an Err with message
+///   explaining problem
+///
+pub fn parse<'a>(text: &str, weight: Option<&str>, duplicates: Duplicates, unit: Unit, arena: &'a Bump) -> Result<Routes<'a>, String>
+{
+    let mut parser = Parser { tokens: tokenize(text)?, pos: 0, directed: false, nodes: Vec::new(), edges: Vec::new() };
+    parser.graph()?;
+
+    let mut names = Names::new(arena);
+    let mut coordinates = HashMap::new();
+    let nodes = parser.nodes.iter()
+        .map(|(name, attributes)|
+        {
+            let name = names.get(name);
+            if let Some(pos) = attributes.get("pos")
+            {
+                // Positions pinned by neato end with '!'
+                let pos = pos.trim_end_matches('!').split(',').map(|v| v.trim().parse::<f64>()).collect::<Vec<_>>();
+                match pos.as_slice()
+                {
+                    [Ok(x), Ok(y), ..] => { coordinates.insert(name, (*x, *y)); },
+                    _ => return Err(format!("Location \"{}\" has an invalid pos.", name)),
+                }
+            }
+            Ok(name)
+        })
+        .collect::<Result<Vec<&'a str>, String>>()?;
+
+    let edge_op = if parser.directed { "->" } else { "--" };
+    let mut edges = Vec::new();
+    let mut route_attributes = HashMap::new();
+    for (from, to, attributes) in &parser.edges
+    {
+        let distance = match weight
+        {
+            Some(weight) => attributes.get(weight)
+                .ok_or_else(|| format!("Edge {} {} {} has no \"{}\".", from, edge_op, to, weight))?,
+            None => DISTANCE_NAMES.iter().find_map(|name| attributes.get(*name))
+                .ok_or_else(|| format!("Edge {} {} {} has no weight.", from, edge_op, to))?,
+        };
+        let distance = distance.parse::<f64>()
+            .map_err(|_| format!("Edge {} {} {}: \"{}\" is not a distance.", from, edge_op, to, distance))?;
+        let one_way = parser.directed && !matches!(attributes.get("dir").map(String::as_str), Some("both") | Some("none"));
+
+        let mut route = RouteAttributes::default();
+        for (name, value) in attributes
+        {
+            route.set(name, names.get(value)).map_err(|message| format!("Edge {} {} {}: {}", from, edge_op, to, message))?;
+        }
+
+        // Round distance to nearest 10th
+        let (from, to) = (names.get(from), names.get(to));
+        edges.push((from, to, unit.to_weight(distance).round() as i64, one_way));
+        add_attributes(&mut route_attributes, from, to, route);
+    }
+
+    let mut routes = build_routes(edges, duplicates, unit)?;
+    for node in nodes { routes.add_location(node); }
+
+    Ok(Routes { coordinates, attributes: route_attributes, ..routes })
+}
+
+///
+/// A recursive descent parser over DOT tokens, collecting every node and
+/// edge with their attributes
+///
+/// - tokens: every token, with the line it is on
+/// - pos: the index of the next token
+/// - directed: whether the graph is a digraph
+/// - nodes: every node, in the order first seen
+/// - edges: every edge, as (from, to, attributes)
+///
+struct Parser<'t>
+{
+    tokens: Vec<(Token<'t>, usize)>,
+    pos: usize,
+    directed: bool,
+    nodes: Vec<(String, Attributes)>,
+    edges: Vec<(String, String, Attributes)>,
+}
+
+impl<'t> Parser<'t>
+{
+    ///
+    /// graph : [ strict ] (graph | digraph) [ ID ] '{' stmt_list '}'
+    ///
+    fn graph(&mut self) -> Result<(), String>
+    {
+        if self.keyword("strict") { self.pos += 1; }
+        if !self.keyword("graph") && !self.keyword("digraph")
+        {
+            return Err(self.error("expected \"graph\" or \"digraph\""));
+        }
+        self.directed = self.keyword("digraph");
+        self.pos += 1;
+        if let Some(Token::Id(_)) = self.peek() { self.pos += 1; }
+
+        self.expect('{')?;
+        self.statements(&mut Defaults::default())?;
+        self.expect('}')?;
+
+        match self.tokens.get(self.pos)
+        {
+            Some(_) => Err(self.error("expected the end of the file")),
+            None => Ok(()),
+        }
+    }
+
+    ///
+    /// stmt_list : [ stmt [ ';' ] stmt_list ], up to a closing '}'
+    ///
+    /// - return: the names of every node within the statements
+    ///
+    fn statements(&mut self, defaults: &mut Defaults) -> Result<Vec<String>, String>
+    {
+        let mut members = Vec::new();
+        while self.peek().is_some() && self.peek() != Some(&Token::Punct('}'))
+        {
+            self.statement(defaults, &mut members)?;
+            if self.peek() == Some(&Token::Punct(';')) { self.pos += 1; }
+        }
+
+        Ok(members)
+    }
+
+    ///
+    /// stmt : node_stmt | edge_stmt | attr_stmt | ID '=' ID | subgraph
+    ///
+    fn statement(&mut self, defaults: &mut Defaults, members: &mut Vec<String>) -> Result<(), String>
+    {
+        // Default attributes, for the rest of this (sub)graph
+        for (keyword, target) in [("graph", None), ("node", Some(&mut defaults.node)), ("edge", Some(&mut defaults.edge))]
+        {
+            if self.keyword(keyword)
+            {
+                self.pos += 1;
+                let attributes = self.attributes()?;
+                if let Some(target) = target { target.extend(attributes); }
+                return Ok(());
+            }
+        }
+
+        // A graph attribute, which is ignored
+        if let (Some(Token::Id(_)), Some((Token::Punct('='), _))) = (self.peek(), self.tokens.get(self.pos + 1))
+        {
+            self.pos += 2;
+            return self.id().map(|_| ());
+        }
+
+        // A chain of one or more endpoints, joined by edges
+        let mut endpoints = vec![self.endpoint(defaults)?];
+        while self.peek() == Some(&Token::EdgeOp)
+        {
+            self.pos += 1;
+            endpoints.push(self.endpoint(defaults)?);
+        }
+        let attributes = self.attributes()?;
+
+        for endpoint in &endpoints
+        {
+            for node in endpoint { self.add_node(node, &defaults.node, None); }
+            members.extend(endpoint.iter().cloned());
+        }
+
+        if let [node] = endpoints.as_slice()
+        {
+            // A single node, with its own attributes
+            if let [node] = node.as_slice() { self.add_node(node, &defaults.node, Some(attributes)); }
+        }
+        else
+        {
+            for pair in endpoints.windows(2)
+            {
+                for from in &pair[0]
+                {
+                    for to in &pair[1]
+                    {
+                        let mut edge_attributes = defaults.edge.clone();
+                        edge_attributes.extend(attributes.clone());
+                        self.edges.push((from.clone(), to.clone(), edge_attributes));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// An edge's endpoint, either a node_id or a subgraph
+    ///
+    /// - return: the names of the nodes within the endpoint
+    ///
+    fn endpoint(&mut self, defaults: &Defaults) -> Result<Vec<String>, String>
+    {
+        // subgraph : [ subgraph [ ID ] ] '{' stmt_list '}'
+        if self.keyword("subgraph")
+        {
+            self.pos += 1;
+            if let Some(Token::Id(_)) = self.peek() { self.pos += 1; }
+        }
+        if self.peek() == Some(&Token::Punct('{'))
+        {
+            self.pos += 1;
+            let members = self.statements(&mut defaults.clone())?;
+            self.expect('}')?;
+            return Ok(members);
+        }
+
+        // node_id : ID [ ':' ID [ ':' ID ] ], where ports are ignored
+        let node = self.id()?;
+        for _ in 0..2
+        {
+            if self.peek() != Some(&Token::Punct(':')) { break; }
+            self.pos += 1;
+            self.id()?;
+        }
+
+        Ok(vec![node])
+    }
+
+    ///
+    /// attr_list : '[' [ a_list ] ']' [ attr_list ], where a missing
+    /// attr_list is empty
+    ///
+    fn attributes(&mut self) -> Result<Attributes, String>
+    {
+        let mut attributes = Attributes::new();
+        while self.peek() == Some(&Token::Punct('['))
+        {
+            self.pos += 1;
+            while self.peek() != Some(&Token::Punct(']'))
+            {
+                let key = self.id()?;
+                let value = if self.peek() == Some(&Token::Punct('='))
+                {
+                    self.pos += 1;
+                    self.id()?
+                }
+                else
+                {
+                    String::from("true")
+                };
+                attributes.insert(key, value);
+
+                if let Some(Token::Punct(',')) | Some(Token::Punct(';')) = self.peek() { self.pos += 1; }
+            }
+            self.pos += 1;
+        }
+
+        Ok(attributes)
+    }
+
+    ///
+    /// Records a node, with the default node attributes the first time
+    /// it is seen, and then any attributes given
+    ///
+    fn add_node(&mut self, name: &str, defaults: &Attributes, attributes: Option<Attributes>)
+    {
+        let index = match self.nodes.iter().position(|(node, _)| node == name)
+        {
+            Some(index) => index,
+            None =>
+            {
+                self.nodes.push((name.to_string(), defaults.clone()));
+                self.nodes.len() - 1
+            }
+        };
+        if let Some(attributes) = attributes { self.nodes[index].1.extend(attributes); }
+    }
+
+    fn peek(&self) -> Option<&Token<'t>>
+    {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    ///
+    /// Whether the next token is the given keyword, which in DOT is
+    /// case-insensitive
+    ///
+    fn keyword(&self, keyword: &str) -> bool
+    {
+        matches!(self.peek(), Some(Token::Id(Cow::Borrowed(id))) if id.eq_ignore_ascii_case(keyword))
+    }
+
+    fn id(&mut self) -> Result<String, String>
+    {
+        match self.peek()
+        {
+            Some(Token::Id(id)) =>
+            {
+                let id = id.to_string();
+                self.pos += 1;
+                Ok(id)
+            },
+            _ => Err(self.error("expected a name")),
+        }
+    }
+
+    fn expect(&mut self, punct: char) -> Result<(), String>
+    {
+        if self.peek() != Some(&Token::Punct(punct)) { return Err(self.error(&format!("expected '{}'", punct))); }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn error(&self, message: &str) -> String
+    {
+        match self.tokens.get(self.pos)
+        {
+            Some((_, line)) => format!("Invalid DOT on line {}: {}.", line, message),
+            None => format!("Invalid DOT at end of file: {}.", message),
+        }
+    }
+}
+
+///
+/// Splits DOT text into its tokens, skipping comments
+///
+/// - return: Either an Ok Result with every token and the line it is on,
+///   or an Err with message explaining problem
+///
+fn tokenize(text: &str) -> Result<Vec<(Token<'_>, usize)>, String>
+{
+    let mut tokens = Vec::new();
+    let mut rest = text;
+    let line = |rest: &str| text[..text.len() - rest.len()].matches('\n').count() + 1;
+    let error = |rest: &str, message: &str| format!("Invalid DOT on line {}: {}.", line(rest), message);
+
+    loop
+    {
+        rest = rest.trim_start();
+
+        // Comments, and lines of C preprocessor output
+        if rest.starts_with("//") || (rest.starts_with('#') && text[..text.len() - rest.len()].ends_with('\n'))
+        {
+            rest = rest.find('\n').map_or("", |end| &rest[end..]);
+            continue;
+        }
+        if let Some(comment) = rest.strip_prefix("/*")
+        {
+            let end = comment.find("*/").ok_or_else(|| error(rest, "unterminated comment"))?;
+            rest = &comment[end + 2..];
+            continue;
+        }
+
+        let c = match rest.chars().next()
+        {
+            Some(c) => c,
+            None => return Ok(tokens),
+        };
+        let token_line = line(rest);
+
+        let token = if rest.starts_with("--") || rest.starts_with("->")
+        {
+            rest = &rest[2..];
+            Token::EdgeOp
+        }
+        else if "{}[];,=:".contains(c)
+        {
+            rest = &rest[1..];
+            Token::Punct(c)
+        }
+        else if c == '"'
+        {
+            // Quoted strings, joined by any '+'
+            let mut id = String::new();
+            loop
+            {
+                let (quoted, after) = quoted_string(rest).ok_or_else(|| error(rest, "unterminated string"))?;
+                id.push_str(&quoted);
+                rest = after;
+
+                match rest.trim_start().strip_prefix('+')
+                {
+                    Some(joined) if joined.trim_start().starts_with('"') => rest = joined.trim_start(),
+                    _ => break,
+                }
+            }
+            Token::Id(Cow::Owned(id))
+        }
+        else if c == '<'
+        {
+            // HTML strings, within balanced angle brackets
+            let mut depth = 0;
+            let end = rest.char_indices()
+                .find(|&(_, c)|
+                {
+                    match c
+                    {
+                        '<' => depth += 1,
+                        '>' => depth -= 1,
+                        _ => (),
+                    }
+                    depth == 0
+                })
+                .map(|(end, _)| end)
+                .ok_or_else(|| error(rest, "unterminated HTML string"))?;
+            let id = &rest[1..end];
+            rest = &rest[end + 1..];
+            Token::Id(Cow::Borrowed(id))
+        }
+        else if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' || !c.is_ascii()
+        {
+            // Names and numerals
+            let end = rest.char_indices()
+                .skip(1)
+                .find(|&(_, c)| !(c.is_alphanumeric() || c == '_' || c == '.' || !c.is_ascii()))
+                .map_or(rest.len(), |(end, _)| end);
+            let id = &rest[..end];
+            rest = &rest[end..];
+            Token::Id(Cow::Borrowed(id))
+        }
+        else
+        {
+            return Err(error(rest, &format!("unexpected '{}'", c)));
+        };
+
+        tokens.push((token, token_line));
+    }
+}
+
+///
+/// Reads a quoted string at the start of text, where \" is a quote and a
+/// backslash before a newline continues the line
+///
+/// - return: the string's contents and the text after it, or None if the
+///   string is unterminated
+///
+fn quoted_string(text: &str) -> Option<(String, &str)>
+{
+    let mut contents = String::new();
+    let mut chars = text.char_indices().skip(1);
+    while let Some((i, c)) = chars.next()
+    {
+        match c
+        {
+            '"' => return Some((contents, &text[i + 1..])),
+            '\\' => match chars.next()
+            {
+                Some((_, '"')) => contents.push('"'),
+                Some((_, '\n')) => (),
+                Some((_, '\r')) => { chars.next(); },
+                Some((_, c)) => { contents.push('\\'); contents.push(c); },
+                None => return None,
+            },
+            c => contents.push(c),
+        }
+    }
+
+    None
+}
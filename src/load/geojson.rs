@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+use bumpalo::Bump;
+use serde_json::Value;
+
+use super::{build_routes, Duplicates, Names, Routes, Unit};
+use crate::algo::geo::haversine_miles;
+
+///
+/// Builds the routes from a GeoJSON FeatureCollection (or single
+/// Feature), in (longitude, latitude) as GeoJSON requires.
+///
+/// Each LineString (or part of a MultiLineString) is a route between its
+/// two endpoints, as long as the line's length along the Earth's surface.
+/// Points with a "name" property name the location at their position;
+/// other locations are named by their coordinates, as "lat,lon".
+///
+/// Lengths are rounded up to the next tenth of a mile, so that a
+/// straight-line heuristic never overestimates a route.
+///
+/// - text: the GeoJSON data
+/// - duplicates: how to merge lines joining the same locations
+/// - arena: holds the location names read
+///
+/// - return: Either an Ok Result with the Routes, with geographic
+///   coordinates, or an Err with message explaining problem
+///
+pub fn parse<'a>(text: &str, duplicates: Duplicates, arena: &'a Bump) -> Result<Routes<'a>, String>
+{
+    let root: Value = serde_json::from_str(text).map_err(|e| format!("Invalid GeoJSON route file: {}", e))?;
+
+    let features = match root["type"].as_str()
+    {
+        Some("FeatureCollection") => root["features"].as_array()
+            .ok_or_else(|| String::from("GeoJSON FeatureCollection has no features."))?
+            .iter()
+            .collect::<Vec<&Value>>(),
+        Some("Feature") => vec![&root],
+        _ => return Err(String::from("GeoJSON route file must be a FeatureCollection or Feature.")),
+    };
+
+    let mut points = Vec::new();
+    let mut lines = Vec::new();
+    for feature in features
+    {
+        let geometry = &feature["geometry"];
+        if geometry["type"] == "Point"
+        {
+            // Only Points name locations, by their name or else their id
+            let name = feature["properties"].as_object()
+                .and_then(|properties| properties.iter().find(|(key, _)| key.eq_ignore_ascii_case("name")))
+                .and_then(|(_, name)| name.as_str())
+                .or_else(|| feature["id"].as_str());
+            points.push((position(&geometry["coordinates"])?, name));
+        }
+        else
+        {
+            collect_geometry(geometry, &mut points, &mut lines)?;
+        }
+    }
+
+    // Locations, keyed by their exact position
+    let mut names = Names::new(arena);
+    let mut locations: HashMap<(u64, u64), &'a str> = HashMap::new();
+    let mut coordinates = HashMap::new();
+    let key = |(lon, lat): (f64, f64)| ((lon + 0.0).to_bits(), (lat + 0.0).to_bits());
+
+    for &(position, name) in &points
+    {
+        if let Some(name) = name
+        {
+            let name = names.get(name);
+            locations.entry(key(position)).or_insert(name);
+            coordinates.insert(name, position);
+        }
+    }
+
+    let mut edges = Vec::new();
+    for line in &lines
+    {
+        let (first, last) = (line[0], line[line.len() - 1]);
+        let mut location = |position: (f64, f64)| *locations.entry(key(position)).or_insert_with(||
+        {
+            let name = names.get(&format!("{},{}", position.1, position.0));
+            coordinates.insert(name, position);
+            name
+        });
+        let (from, to) = (location(first), location(last));
+        if from == to { continue; }
+
+        let length = line.windows(2).map(|pair| haversine_miles(pair[0], pair[1])).sum::<f64>();
+
+        // Round length up to the next 10th
+        edges.push((from, to, Unit::Miles.to_weight(length).ceil() as i64, false));
+    }
+
+    let mut routes = build_routes(edges, duplicates, Unit::Miles)?;
+    for &node in locations.values() { routes.add_location(node); }
+
+    Ok(Routes { coordinates, geographic: true, ..routes })
+}
+
+///
+/// Collects the Points and lines within a geometry, including those of
+/// Multi geometries and GeometryCollections. Other geometries (such as
+/// Polygons) are ignored.
+///
+/// - geometry: the GeoJSON geometry, which may be null
+/// - points: every Point, as its position and (unknown, so None) name
+/// - lines: every line, as its positions
+///
+fn collect_geometry<'v>(geometry: &'v Value, points: &mut Vec<((f64, f64), Option<&'v str>)>, lines: &mut Vec<Vec<(f64, f64)>>) -> Result<(), String>
+{
+    let coordinates = &geometry["coordinates"];
+    match geometry["type"].as_str()
+    {
+        Some("Point") => points.push((position(coordinates)?, None)),
+        Some("MultiPoint") => for point in array(coordinates)? { points.push((position(point)?, None)); },
+        Some("LineString") => lines.push(line(coordinates)?),
+        Some("MultiLineString") => for part in array(coordinates)? { lines.push(line(part)?); },
+        Some("GeometryCollection") => for geometry in array(&geometry["geometries"])?
+        {
+            collect_geometry(geometry, points, lines)?;
+        },
+        _ => (),
+    }
+
+    Ok(())
+}
+
+fn array(value: &Value) -> Result<&Vec<Value>, String>
+{
+    value.as_array().ok_or_else(|| format!("Invalid GeoJSON: expected an array, found {}.", value))
+}
+
+///
+/// Reads a GeoJSON position, as (longitude, latitude)
+///
+fn position(value: &Value) -> Result<(f64, f64), String>
+{
+    match array(value)?.as_slice()
+    {
+        [lon, lat, ..] => match (lon.as_f64(), lat.as_f64())
+        {
+            (Some(lon), Some(lat)) => Ok((lon, lat)),
+            _ => Err(format!("Invalid GeoJSON position {}.", value)),
+        },
+        _ => Err(format!("Invalid GeoJSON position {}.", value)),
+    }
+}
+
+///
+/// Reads a GeoJSON line, of at least two positions
+///
+fn line(value: &Value) -> Result<Vec<(f64, f64)>, String>
+{
+    let line = array(value)?.iter().map(position).collect::<Result<Vec<(f64, f64)>, String>>()?;
+    if line.len() < 2 { return Err(format!("Invalid GeoJSON line {}.", value)); }
+
+    Ok(line)
+}
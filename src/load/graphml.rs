@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+use bumpalo::Bump;
+
+use super::xml::{self, Element};
+use super::{add_attributes, build_routes, Duplicates, Names, RouteAttributes, Routes, Unit, DISTANCE_NAMES};
+
+// Attribute names commonly holding a node's name or coordinates,
+// compared case-insensitively
+const LABEL_NAMES: [&str; 2] = ["label", "name"];
+const X_NAMES: [&str; 4] = ["x", "lon", "lng", "longitude"];
+const Y_NAMES: [&str; 3] = ["y", "lat", "latitude"];
+
+///
+/// A GraphML attribute declaration (<key>)
+///
+struct Key<'x>
+{
+    id: &'x str,
+    name: &'x str,
+    domain: &'x str,
+    default: Option<&'x str>,
+}
+
+///
+/// Builds the routes from GraphML, as exported by yEd, Gephi, NetworkX
+/// and others. Edges are one-way routes if the graph's edgedefault is
+/// "directed", unless they are given directed="false", and likewise two-way
+/// otherwise. Nested graphs are ignored.
+///
+/// Each node is named by its "label" or "name" attribute, or yEd's label,
+/// or otherwise its id. Nodes with "x" and "y" (or "lon" and "lat")
+/// attributes are given those coordinates. Edges may give the route
+/// attributes "highway", "speed_limit" and "surface".
+///
+/// - text: the GraphML data
+/// - weight: the name (or id) of the edge attribute holding distances.
+///   If None, an attribute with a usual name for distance is used
+/// - duplicates: how to merge routes listed more than once
+/// - unit: what the distances measure
+/// - arena: holds the location names read
+///
+/// - return: Either an Ok Result with the Routes, or an Err with message
+///   explaining problem
+///
+pub fn parse<'a>(text: &str, weight: Option<&str>, duplicates: Duplicates, unit: Unit, arena: &'a Bump) -> Result<Routes<'a>, String>
+{
+    let root = xml::parse(text)?;
+    if root.name != "graphml" { return Err(String::from("GraphML file must have a <graphml> root element.")); }
+    let graph = root.children_named("graph").next()
+        .ok_or_else(|| String::from("GraphML file has no <graph>."))?;
+
+    let keys = root.children_named("key")
+        .map(|key| Key
+        {
+            id: key.attribute("id").unwrap_or(""),
+            name: key.attribute("attr.name").unwrap_or(""),
+            domain: key.attribute("for").unwrap_or("all"),
+            default: key.children_named("default").next().map(|default| default.text.trim()),
+        })
+        .collect::<Vec<Key>>();
+
+    // Finds the first key for the domain whose name is one of names
+    let find = |domain: &str, names: &[&str]| keys.iter()
+        .find(|key| (key.domain == domain || key.domain == "all") && names.contains(&key.name.to_lowercase().as_str()));
+
+    let weight_key = match weight
+    {
+        Some(weight) => keys.iter()
+            .find(|key| (key.domain == "edge" || key.domain == "all") && (key.name == weight || key.id == weight))
+            .ok_or_else(|| format!("GraphML file has no edge attribute \"{}\".", weight))?,
+        None => find("edge", &DISTANCE_NAMES)
+            .ok_or_else(|| String::from("GraphML file has no edge attribute for distance; name one to use as the weight."))?,
+    };
+    let (label_key, x_key, y_key) = (find("node", &LABEL_NAMES), find("node", &X_NAMES), find("node", &Y_NAMES));
+    let edge_keys = keys.iter()
+        .filter(|key| (key.domain == "edge" || key.domain == "all") && key.id != weight_key.id)
+        .collect::<Vec<&Key>>();
+
+    let mut names = Names::new(arena);
+    let mut nodes = HashMap::new();
+    let mut coordinates = HashMap::new();
+    for node in graph.children_named("node")
+    {
+        let id = node.attribute("id").ok_or_else(|| String::from("GraphML node has no id."))?;
+
+        // yEd keeps its labels within its own graphics data
+        let name = label_key.and_then(|key| value(node, key))
+            .or_else(|| node.descendant("NodeLabel").map(|label| label.text.trim()))
+            .filter(|name| !name.is_empty())
+            .unwrap_or(id);
+        let name = names.get(name);
+        nodes.insert(id, name);
+
+        if let (Some(x), Some(y)) = (x_key.and_then(|key| value(node, key)), y_key.and_then(|key| value(node, key)))
+        {
+            match (x.parse::<f64>(), y.parse::<f64>())
+            {
+                (Ok(x), Ok(y)) => { coordinates.insert(name, (x, y)); },
+                _ => return Err(format!("Location \"{}\" has invalid coordinates.", name)),
+            }
+        }
+    }
+
+    let directed = graph.attribute("edgedefault") == Some("directed");
+    let mut edges = Vec::new();
+    let mut attributes = HashMap::new();
+    for edge in graph.children_named("edge")
+    {
+        let (from, to) = match (edge.attribute("source"), edge.attribute("target"))
+        {
+            (Some(from), Some(to)) => (from, to),
+            _ => return Err(String::from("GraphML edge must have a source and target.")),
+        };
+
+        // Edges may name nodes never declared
+        let from = nodes.get(from).cloned().unwrap_or_else(|| names.get(from));
+        let to = nodes.get(to).cloned().unwrap_or_else(|| names.get(to));
+
+        let distance = value(edge, weight_key)
+            .ok_or_else(|| format!("Edge {} -> {} has no \"{}\".", from, to, weight_key.name))?;
+        let distance = distance.parse::<f64>()
+            .map_err(|_| format!("Edge {} -> {}: \"{}\" is not a distance.", from, to, distance))?;
+
+        let one_way = match edge.attribute("directed")
+        {
+            Some("true") => true,
+            Some("false") => false,
+            _ => directed,
+        };
+
+        let mut route = RouteAttributes::default();
+        for key in &edge_keys
+        {
+            if let Some(value) = value(edge, key)
+            {
+                route.set(key.name, names.get(value)).map_err(|message| format!("Edge {} -> {}: {}", from, to, message))?;
+            }
+        }
+
+        // Round distance to nearest 10th
+        edges.push((from, to, unit.to_weight(distance).round() as i64, one_way));
+        add_attributes(&mut attributes, from, to, route);
+    }
+
+    let mut routes = build_routes(edges, duplicates, unit)?;
+    for &node in nodes.values() { routes.add_location(node); }
+
+    Ok(Routes { coordinates, attributes, ..routes })
+}
+
+///
+/// Retrieves an element's value for a key, or else the key's default
+///
+fn value<'x>(element: &'x Element, key: &Key<'x>) -> Option<&'x str>
+{
+    element.children_named("data")
+        .find(|data| data.attribute("key") == Some(key.id))
+        .map(|data| data.text.trim())
+        .or(key.default)
+}
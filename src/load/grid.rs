@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+
+use bumpalo::Bump;
+
+use super::{build_routes, Duplicates, Routes, Unit};
+use crate::algo::grid::{Cell, Connectivity, Grid, STRAIGHT_COST};
+use crate::algo::{Algorithm, PathResult, Step};
+
+///
+/// A grid map: the Grid the grid algorithms search, and the name of each
+/// of its passable cells. Its Routes, for every other algorithm, are
+/// built from the Grid's own moves
+///
+/// - grid: the map's Grid
+/// - connectivity: which neighbours of each cell may be stepped to
+///
+#[derive(Debug, Clone)]
+pub struct GridMap<'a>
+{
+    pub grid: Grid,
+    pub connectivity: Connectivity,
+    names: HashMap<Cell, &'a str>,
+    cells: HashMap<&'a str, Cell>,
+}
+
+impl<'a> GridMap<'a>
+{
+    ///
+    /// Builds a grid map from its cells
+    ///
+    /// - rows: each row of cells, from the top, as each cell's name, or None
+    ///   for walls. Rows shorter than others are walled beyond their end
+    /// - connectivity: which neighbours of each cell may be stepped to
+    /// - straight_cost: the weight of a step between orthogonal neighbours
+    ///
+    pub(crate) fn from_rows(rows: &[Vec<Option<&'a str>>], connectivity: Connectivity, straight_cost: u64) -> Self
+    {
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let mut map = GridMap { grid: Grid::with_straight_cost(width, rows.len(), straight_cost), connectivity, names: HashMap::new(), cells: HashMap::new() };
+        for (y, row) in rows.iter().enumerate()
+        {
+            for x in 0..width
+            {
+                match row.get(x).cloned().flatten()
+                {
+                    Some(name) => map.name_cell((x, y), name),
+                    None => map.grid.set_blocked((x, y), true),
+                }
+            }
+        }
+
+        map
+    }
+
+    ///
+    /// Rebuilds a grid map from its cells' names and coordinates, as kept
+    /// by Routes which have lost their Grid, such as a binary route file's
+    ///
+    /// - coordinates: each passable cell's (column, row)
+    /// - connectivity: which neighbours of each cell may be stepped to
+    /// - straight_cost: the weight of a step between orthogonal neighbours
+    ///
+    pub(crate) fn from_coordinates(coordinates: &HashMap<&'a str, (f64, f64)>, connectivity: Connectivity, straight_cost: u64) -> Self
+    {
+        let cells = coordinates.iter().map(|(&name, &(x, y))| (name, (x as usize, y as usize))).collect::<Vec<(&str, Cell)>>();
+        let width = cells.iter().map(|&(_, (x, _))| x + 1).max().unwrap_or(0);
+        let height = cells.iter().map(|&(_, (_, y))| y + 1).max().unwrap_or(0);
+
+        let mut map = GridMap { grid: Grid::with_straight_cost(width, height, straight_cost), connectivity, names: HashMap::new(), cells: HashMap::new() };
+        for y in 0..height
+        {
+            for x in 0..width { map.grid.set_blocked((x, y), true); }
+        }
+        for (name, cell) in cells
+        {
+            map.grid.set_blocked(cell, false);
+            map.name_cell(cell, name);
+        }
+
+        map
+    }
+
+    fn name_cell(&mut self, cell: Cell, name: &'a str)
+    {
+        self.names.insert(cell, name);
+        self.cells.insert(name, cell);
+    }
+
+    ///
+    /// - return: the name of a passable cell's location, or None for walls
+    ///   and cells off the map
+    ///
+    pub fn name(&self, cell: Cell) -> Option<&'a str>
+    {
+        self.names.get(&cell).cloned()
+    }
+
+    ///
+    /// - return: the cell a location is at, or None if it is not on the map
+    ///
+    pub fn cell(&self, name: &str) -> Option<Cell>
+    {
+        self.cells.get(name).cloned()
+    }
+
+    ///
+    /// Routes between two cells with one of the algorithms which search
+    /// only a Grid, naming the route's cells
+    ///
+    /// - algorithm: the algorithm to route with
+    /// - start: the start cell's location
+    /// - end: the end cell's location
+    ///
+    /// - return: the route (None if end cannot be reached) and the # of
+    ///   cells considered, or None if the algorithm searches any Graph, if
+    ///   the map cannot be crossed diagonally, as these algorithms need,
+    ///   or if either location is not a cell
+    ///
+    pub fn search(&self, algorithm: Algorithm, start: &str, end: &str) -> Option<(Option<PathResult<'a>>, usize)>
+    {
+        if self.connectivity != Connectivity::Eight { return None; }
+
+        let (route, node_counter) = algorithm.search_grid(&self.grid, self.cell(start)?, self.cell(end)?)?;
+        let route = route.map(|route| PathResult { nodes: route.cells.iter().map(|cell| self.names[cell]).collect(), cost: route.cost });
+        Some((route, node_counter))
+    }
+
+    ///
+    /// Finds each Step of a route across the map, measuring each straight
+    /// across the Grid, as Theta*'s steps may join cells which are not
+    /// neighbours. A step between neighbours costs the same as its move
+    ///
+    /// - route: a route between the map's cells
+    ///
+    /// - return: each Step of the route, from start to end
+    ///
+    pub fn steps<'r>(&self, route: &PathResult<'r>) -> Vec<Step<'r>>
+    {
+        let mut cumulative = 0;
+        route.nodes.windows(2)
+            .enumerate()
+            .map(|(i, leg)|
+            {
+                let distance = match (self.cell(leg[0]), self.cell(leg[1]))
+                {
+                    (Some(from), Some(to)) => self.grid.euclidean(from, to),
+                    _ => 0,
+                };
+                cumulative += distance;
+                Step { index: i + 1, from: leg[0], to: leg[1], distance, cumulative }
+            })
+            .collect()
+    }
+
+    ///
+    /// Builds the routes between the map's cells, for the algorithms which
+    /// search any route Graph: one for each move Grid::neighbors allows,
+    /// weighted by its cost
+    ///
+    /// - return: Either an Ok Result with the Routes, with each cell's (x, y)
+    ///   as its coordinates and the map as their grid, or an Err with
+    ///   message explaining problem
+    ///
+    pub(crate) fn into_routes(self) -> Result<Routes<'a>, String>
+    {
+        // Each route once, from whichever of its cells is read first
+        let mut edges = Vec::new();
+        for y in 0..self.grid.height()
+        {
+            for x in 0..self.grid.width()
+            {
+                let name = match self.name((x, y)) { Some(name) => name, None => continue };
+                for ((nx, ny), cost) in self.grid.neighbors((x, y), self.connectivity)
+                {
+                    if (ny, nx) > (y, x) { edges.push((name, self.names[&(nx, ny)], cost as i64, false)); }
+                }
+            }
+        }
+
+        // Every step is a different route, so none are duplicates
+        let mut routes = build_routes(edges, Duplicates::default(), Unit::Cells)?;
+        let mut coordinates = HashMap::new();
+        for (&(x, y), &name) in &self.names
+        {
+            routes.add_location(name);
+            coordinates.insert(name, (x as f64, y as f64));
+        }
+
+        Ok(Routes { coordinates, grid: Some(self), ..routes })
+    }
+}
+
+///
+/// Builds the routes from an ASCII grid map, such as:
+///
+/// ```text
+/// S..#....
+/// .#.#.##.
+/// .#...#.G
+/// ```
+///
+/// Each '.' is a passable cell and each '#' a wall. Letters and digits
+/// are passable cells too, named by that character (so each may appear
+/// only once); every other cell is named by its column and row, from 0,
+/// as "x:y". Rows shorter than others are walled beyond their end.
+///
+/// Each cell has a route to each passable neighbour, of one cell, or √2
+/// for diagonal neighbours with eight-way connectivity. A diagonal step
+/// may not cut the corner of a wall.
+///
+/// - text: the grid map
+/// - connectivity: which neighbours of each cell may be stepped to
+/// - arena: holds the cells' names
+///
+/// - return: Either an Ok Result with the Routes, with each cell's (x, y)
+///   as its coordinates and the map as their grid, or an Err with
+///   message explaining problem
+///
+pub fn parse<'a>(text: &str, connectivity: Connectivity, arena: &'a Bump) -> Result<Routes<'a>, String>
+{
+    let mut rows = Vec::new();
+    let mut named = HashMap::new();
+    for (y, line) in text.lines().enumerate()
+    {
+        let mut row = Vec::new();
+        for (x, cell) in line.trim_end_matches('\r').chars().enumerate()
+        {
+            row.push(match cell
+            {
+                '#' => None,
+                '.' => Some(&*arena.alloc_str(&format!("{}:{}", x, y))),
+                cell if cell.is_alphanumeric() =>
+                {
+                    if named.insert(cell, (x, y)).is_some()
+                    {
+                        return Err(format!("Line {}: \"{}\" names more than one cell.", y + 1, cell));
+                    }
+                    Some(&*arena.alloc_str(cell.encode_utf8(&mut [0; 4])))
+                },
+                cell => return Err(format!("Line {}: '{}' is not a grid cell; use '.' for open cells and '#' for walls.", y + 1, cell)),
+            });
+        }
+        rows.push(row);
+    }
+
+    GridMap::from_rows(&rows, connectivity, STRAIGHT_COST).into_routes()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use bumpalo::Bump;
+
+    use super::parse;
+    use crate::algo::astar::{astar, TieBreak};
+    use crate::algo::grid::Connectivity;
+    use crate::algo::Algorithm;
+
+    const MAP: &str = "S..#....\n.#.#.##.\n.#...#.G\n........\n";
+
+    #[test]
+    fn grid_searches_match_the_routes()
+    {
+        let arena = Bump::new();
+        let routes = parse(MAP, Connectivity::Eight, &arena).unwrap();
+        let grid = routes.grid.as_ref().unwrap();
+
+        for &(start, end) in &[("S", "G"), ("G", "S"), ("S", "7:3"), ("4:0", "0:3")]
+        {
+            let optimal = astar(&routes.graph, None, start, end, TieBreak::default()).0.unwrap().cost;
+
+            let jps = grid.search(Algorithm::JumpPoint, start, end).unwrap().0.unwrap();
+            assert_eq!((jps.nodes[0], jps.nodes[jps.nodes.len() - 1], jps.cost), (start, end, optimal));
+            assert_eq!(grid.steps(&jps).last().map(|step| step.cumulative), Some(optimal));
+
+            // Theta* may cut across cells, but never takes a longer route
+            let theta = grid.search(Algorithm::ThetaStar, start, end).unwrap().0.unwrap();
+            assert!(theta.cost <= optimal);
+            assert_eq!(grid.steps(&theta).last().map(|step| step.cumulative), Some(theta.cost));
+
+            assert!(grid.search(Algorithm::AStar, start, end).is_none());
+        }
+    }
+
+    #[test]
+    fn four_connected_grids_are_not_searched()
+    {
+        let arena = Bump::new();
+        let routes = parse(MAP, Connectivity::Four, &arena).unwrap();
+        assert!(routes.grid.as_ref().unwrap().search(Algorithm::JumpPoint, "S", "G").is_none());
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use bumpalo::Bump;
+
+use super::csv::split_row;
+use super::{build_routes, zip, Duplicates, Names, Routes, Unit};
+
+/// A trip's visit to a station: (stop_sequence, station, arrival, departure)
+type Visit<'t> = (u32, &'t str, Option<u32>, Option<u32>);
+
+///
+/// A GTFS table, as its column names and rows
+///
+struct Table<'t>
+{
+    name: &'static str,
+    columns: Vec<&'t str>,
+    rows: Vec<(usize, Vec<&'t str>)>,
+}
+
+impl<'t> Table<'t>
+{
+    fn parse(name: &'static str, data: &'t [u8]) -> Result<Self, String>
+    {
+        let text = std::str::from_utf8(data).map_err(|_| format!("{} is not valid UTF-8 text.", name))?;
+        let mut rows = text.trim_start_matches('\u{feff}').lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| split_row(line, ',').map(|fields| (i + 1, fields)).map_err(|e| format!("{} line {}: {}", name, i + 1, e)))
+            .collect::<Result<Vec<(usize, Vec<&str>)>, String>>()?;
+        if rows.is_empty() { return Err(format!("{} is empty.", name)); }
+
+        let (_, columns) = rows.remove(0);
+        Ok(Table { name, columns, rows })
+    }
+
+    ///
+    /// Finds a column's index, which is an Err if the column is required
+    /// but missing, or None if it is optional and missing
+    ///
+    fn column(&self, column: &str, required: bool) -> Result<Option<usize>, String>
+    {
+        match self.columns.iter().position(|name| *name == column)
+        {
+            None if required => Err(format!("{} has no {} column.", self.name, column)),
+            index => Ok(index),
+        }
+    }
+}
+
+///
+/// Builds the routes from a zipped GTFS feed
+///
+/// - data: the zip archive
+/// - arena: holds the stop names read
+///
+/// - return: Either an Ok Result with the Routes, or an Err with message
+///   explaining problem
+///
+pub fn parse_zip<'a>(data: &[u8], arena: &'a Bump) -> Result<Routes<'a>, String>
+{
+    let files = zip::extract(data)?;
+    let file = |name: &str| files.get(name).ok_or_else(|| format!("GTFS feed has no {}.", name));
+
+    parse(file("stops.txt")?, file("stop_times.txt")?, arena)
+}
+
+///
+/// Builds the routes from a GTFS feed unzipped into a directory
+///
+/// - path: the directory
+/// - arena: holds the stop names read
+///
+/// - return: Either an Ok Result with the Routes, or an Err with message
+///   explaining problem
+///
+pub fn parse_dir<'a>(path: &Path, arena: &'a Bump) -> Result<Routes<'a>, String>
+{
+    let file = |name: &str| std::fs::read(path.join(name))
+        .map_err(|e| format!("Cannot read {} of GTFS feed: {}", name, e));
+
+    parse(&file("stops.txt")?, &file("stop_times.txt")?, arena)
+}
+
+///
+/// Builds the routes from a GTFS feed's stops and stop times. Each station
+/// is a location, with platforms and other stops within a station merged
+/// into it, and each pair of stations visited one after another by a
+/// trip is a route. A route's weight is the shortest scheduled time
+/// between the two stations, over every trip, in minutes.
+///
+/// Times missing from stops between timed stops are interpolated. Routes
+/// are two-way, taking the faster direction.
+///
+/// - stops: the contents of stops.txt
+/// - stop_times: the contents of stop_times.txt
+/// - arena: holds the stop names read
+///
+/// - return: Either an Ok Result with the Routes, with geographic
+///   coordinates and weights in minutes, or an Err with message
+///   explaining problem
+///
+fn parse<'a, 't>(stops: &'t [u8], stop_times: &'t [u8], arena: &'a Bump) -> Result<Routes<'a>, String>
+{
+    let stops = Table::parse("stops.txt", stops)?;
+    let (id_col, name_col) = (stops.column("stop_id", true)?.unwrap(), stops.column("stop_name", false)?);
+    let (lat_col, lon_col) = (stops.column("stop_lat", false)?, stops.column("stop_lon", false)?);
+    let parent_col = stops.column("parent_station", false)?;
+
+    let field = |row: &[&'t str], column: Option<usize>| column.and_then(|column| row.get(column).cloned()).filter(|value| !value.is_empty());
+
+    // Each stop's parent station, name, and position
+    let mut parents = HashMap::new();
+    let mut details = HashMap::new();
+    for (_, row) in &stops.rows
+    {
+        let id = field(row, Some(id_col)).ok_or_else(|| String::from("stops.txt has a stop with no stop_id."))?;
+        if let Some(parent) = field(row, parent_col) { parents.insert(id, parent); }
+
+        let position = match (field(row, lon_col).map(str::parse::<f64>), field(row, lat_col).map(str::parse::<f64>))
+        {
+            (Some(Ok(lon)), Some(Ok(lat))) => Some((lon, lat)),
+            _ => None,
+        };
+        details.insert(id, (field(row, name_col).unwrap_or(id), position));
+    }
+    let station = |mut stop: &'t str|
+    {
+        while let Some(&parent) = parents.get(stop) { stop = parent; }
+        stop
+    };
+
+    // Each trip's visits
+    let stop_times = Table::parse("stop_times.txt", stop_times)?;
+    let trip_col = stop_times.column("trip_id", true)?.unwrap();
+    let stop_col = stop_times.column("stop_id", true)?.unwrap();
+    let sequence_col = stop_times.column("stop_sequence", true)?.unwrap();
+    let (arrival_col, departure_col) = (stop_times.column("arrival_time", true)?, stop_times.column("departure_time", true)?);
+
+    let mut trips: HashMap<&str, Vec<Visit>> = HashMap::new();
+    for (line, row) in &stop_times.rows
+    {
+        let error = |message: &str| format!("stop_times.txt line {}: {}", line, message);
+
+        let trip = field(row, Some(trip_col)).ok_or_else(|| error("missing trip_id."))?;
+        let stop = field(row, Some(stop_col)).ok_or_else(|| error("missing stop_id."))?;
+        if !details.contains_key(stop) { return Err(error(&format!("unknown stop \"{}\".", stop))); }
+        let sequence = field(row, Some(sequence_col)).and_then(|sequence| sequence.parse::<u32>().ok())
+            .ok_or_else(|| error("invalid stop_sequence."))?;
+        let time = |column| field(row, column)
+            .map(|time| parse_time(time).ok_or_else(|| error(&format!("\"{}\" is not a time.", time))))
+            .transpose();
+
+        trips.entry(trip).or_default().push((sequence, station(stop), time(arrival_col)?, time(departure_col)?));
+    }
+
+    // The fastest time between each pair of stations, in seconds
+    let mut fastest: HashMap<(&str, &str), u32> = HashMap::new();
+    for (trip, mut visits) in trips
+    {
+        visits.sort_unstable_by_key(|&(sequence, ..)| sequence);
+        let times = interpolate(&visits.iter().map(|&(_, _, arrival, departure)| (arrival, departure)).collect::<Vec<_>>())
+            .ok_or_else(|| format!("Trip \"{}\" has no time at its first or last stop.", trip))?;
+
+        for (pair, times) in visits.windows(2).zip(times.windows(2))
+        {
+            let (from, to) = (pair[0].1, pair[1].1);
+            if from == to { continue; }
+
+            let key = if from < to { (from, to) } else { (to, from) };
+            let time = times[1].0.saturating_sub(times[0].1);
+            fastest.entry(key).and_modify(|fastest| *fastest = (*fastest).min(time)).or_insert(time);
+        }
+    }
+
+    // Name stations, telling apart stations sharing a name by their id
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    let mut stations = fastest.keys().flat_map(|&(from, to)| [from, to]).collect::<Vec<&str>>();
+    stations.sort_unstable();
+    stations.dedup();
+    for station in &stations { *counts.entry(details[station].0).or_insert(0) += 1; }
+
+    let mut names = Names::new(arena);
+    let mut locations = HashMap::new();
+    let mut coordinates = HashMap::new();
+    for station in stations
+    {
+        let (name, position) = details[station];
+        let name = if counts[name] > 1 { names.get(&format!("{} ({})", name, station)) } else { names.get(name) };
+        locations.insert(station, name);
+        if let Some(position) = position { coordinates.insert(name, position); }
+    }
+
+    // Round time to nearest 10th of a minute
+    let edges = fastest.into_iter()
+        .map(|((from, to), seconds)| (locations[from], locations[to], Unit::Minutes.to_weight(seconds as f64 / 60.0).round() as i64, false))
+        .collect();
+
+    // Each pair of stations is already a single route
+    let routes = build_routes(edges, Duplicates::default(), Unit::Minutes)?;
+
+    Ok(Routes { coordinates, geographic: true, ..routes })
+}
+
+///
+/// Reads a GTFS time, "H:MM:SS", which may be past 24:00:00 for trips
+/// running past midnight
+///
+/// - return: the time in seconds after midnight, or None if invalid
+///
+fn parse_time(time: &str) -> Option<u32>
+{
+    let parts = time.trim().split(':').map(|part| part.parse::<u32>().ok()).collect::<Option<Vec<u32>>>()?;
+    match parts.as_slice()
+    {
+        &[hours, minutes, seconds] if minutes < 60 && seconds < 60 => Some(hours * 3600 + minutes * 60 + seconds),
+        _ => None,
+    }
+}
+
+///
+/// Fills in each stop's missing arrival and departure times. A stop with
+/// only one is given it for both; a stop with neither is timed evenly
+/// between the timed stops around it.
+///
+/// - times: each stop's (arrival, departure), in order
+///
+/// - return: each stop's (arrival, departure), or None if the first or
+///   last stop has no time
+///
+fn interpolate(times: &[(Option<u32>, Option<u32>)]) -> Option<Vec<(u32, u32)>>
+{
+    let known = times.iter()
+        .map(|&(arrival, departure)| arrival.or(departure).map(|arrival| (arrival, departure.unwrap_or(arrival))))
+        .collect::<Vec<Option<(u32, u32)>>>();
+
+    let mut filled = Vec::with_capacity(times.len());
+    let mut previous = (0, known.first().cloned()??.1);
+    for (i, time) in known.iter().enumerate()
+    {
+        match time
+        {
+            Some(time) =>
+            {
+                filled.push(*time);
+                previous = (i, time.1);
+            },
+            None =>
+            {
+                let (next, next_time) = known.iter().enumerate().skip(i).find_map(|(j, time)| time.map(|time| (j, time.0)))?;
+                let (last, last_time) = previous;
+                let time = last_time + (next_time.saturating_sub(last_time)) * (i - last) as u32 / (next - last) as u32;
+                filled.push((time, time));
+            },
+        }
+    }
+
+    Some(filled)
+}
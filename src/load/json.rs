@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+use bumpalo::Bump;
+use serde::Deserialize;
+
+use super::{add_attributes, build_routes, Duplicates, Names, RouteAttributes, Routes, Unit};
+
+///
+/// A JSON route file, such as:
+///
+/// { "nodes": [ "Seattle", { "name": "Portland", "x": -122.7, "y": 45.5 } ],
+///   "edges": [ { "from": "Seattle", "to": "Portland", "distance": 173.2 } ] }
+///
+/// Nodes need only be listed to give them coordinates, or if they have
+/// no edges. With "directed": true, every edge is one-way, from "from" to
+/// "to", unless the edge itself gives "one_way": false
+///
+#[derive(Deserialize)]
+struct RouteFile
+{
+    #[serde(default)]
+    directed: bool,
+    #[serde(default)]
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Node
+{
+    Name(String),
+    Detailed
+    {
+        name: String,
+        #[serde(alias = "lon", alias = "lng")]
+        x: Option<f64>,
+        #[serde(alias = "lat")]
+        y: Option<f64>,
+    },
+}
+
+#[derive(Deserialize)]
+struct Edge
+{
+    #[serde(alias = "source")]
+    from: String,
+    #[serde(alias = "target")]
+    to: String,
+    #[serde(alias = "weight")]
+    distance: f64,
+    #[serde(default, alias = "oneway")]
+    one_way: Option<bool>,
+    #[serde(default, alias = "road_class")]
+    highway: Option<String>,
+    #[serde(default, alias = "maxspeed")]
+    speed_limit: Option<f64>,
+    #[serde(default)]
+    surface: Option<String>,
+}
+
+///
+/// Builds the routes from JSON, with an object holding a "nodes" list
+/// (optional) and an "edges" list. Each node is either a name, or an
+/// object with a "name" and, optionally, "x" and "y" (or "lon" and "lat")
+/// coordinates. Each edge has "from", "to" and "distance" (in miles),
+/// and optionally "one_way", and the attributes "highway", "speed_limit"
+/// (in miles per hour) and "surface"
+///
+/// - text: the JSON data
+/// - directed: whether edges are one-way unless they say otherwise, as
+///   if the file gave "directed": true
+/// - duplicates: how to merge routes listed more than once
+/// - unit: what the distances measure
+/// - arena: holds the location names read
+///
+/// - return: Either an Ok Result with the Routes, or an Err with message
+///   explaining problem
+///
+pub fn parse<'a>(text: &str, directed: bool, duplicates: Duplicates, unit: Unit, arena: &'a Bump) -> Result<Routes<'a>, String>
+{
+    let file: RouteFile = serde_json::from_str(text).map_err(|e| format!("Invalid JSON route file: {}", e))?;
+
+    // Names are parsed as owned Strings, as they may contain escapes
+    let mut names = Names::new(arena);
+
+    let mut nodes = Vec::new();
+    let mut coordinates = HashMap::new();
+    for node in file.nodes
+    {
+        match node
+        {
+            Node::Name(node) => nodes.push(names.get(&node)),
+            Node::Detailed { name: node, x, y } =>
+            {
+                let node = names.get(&node);
+                nodes.push(node);
+                match (x, y)
+                {
+                    (Some(x), Some(y)) => { coordinates.insert(node, (x, y)); },
+                    (None, None) => (),
+                    _ => return Err(format!("Location \"{}\" has only one coordinate.", node)),
+                }
+            },
+        }
+    }
+
+    // Round distance to nearest 10th
+    let directed = directed || file.directed;
+    let mut edges = Vec::new();
+    let mut attributes = HashMap::new();
+    for edge in file.edges
+    {
+        let (from, to) = (names.get(&edge.from), names.get(&edge.to));
+        edges.push((from, to, unit.to_weight(edge.distance).round() as i64, edge.one_way.unwrap_or(directed)));
+
+        let route = RouteAttributes
+        {
+            highway: edge.highway.map(|highway| names.get(&highway)),
+            speed_limit: edge.speed_limit,
+            surface: edge.surface.map(|surface| names.get(&surface)),
+        };
+        add_attributes(&mut attributes, from, to, route);
+    }
+
+    let mut routes = build_routes(edges, duplicates, unit)?;
+    for node in nodes { routes.add_location(node); }
+
+    Ok(Routes { coordinates, attributes, ..routes })
+}
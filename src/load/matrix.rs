@@ -0,0 +1,125 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::csv::split_row;
+use super::{build_routes, Duplicates, Routes, Unit};
+
+// Cells marking that no route exists, besides blank cells
+const NO_ROUTE: [&str; 5] = ["-", "x", "inf", "infinity", "∞"];
+
+///
+/// Builds a route Graph from a square distance matrix, such as:
+///
+/// ```text
+///        ,Salem ,Eugene,Bend
+/// Salem  ,      ,65.2  ,
+/// Eugene ,65.2  ,      ,128.4
+/// Bend   ,      ,128.4 ,
+/// ```
+///
+/// The first row and column name the locations; the corner may be blank
+/// or left out. Each cell is the distance in miles between its row's and
+/// column's locations, or blank (or "-", "x", "inf") if no route joins
+/// them. Cells are separated by commas, tabs, or else whitespace, as
+/// found in the first row (without commas or tabs, no cell may be blank). Routes are two-way, so the matrix must be
+/// symmetric, though one triangle may be left blank. The diagonal is
+/// ignored.
+///
+/// If directed, each cell is instead a one-way route from its row's
+/// location to its column's, and the matrix need not be symmetric.
+///
+/// - text: the matrix data
+/// - directed: whether each cell is a one-way route
+/// - delimiter: the character between cells. If None, it is found from
+///   the first row
+/// - unit: what the distances measure
+///
+/// - return: Either an Ok Result with the Routes, or an Err with message
+///   explaining problem
+///
+pub fn parse(text: &str, directed: bool, delimiter: Option<char>, unit: Unit) -> Result<Routes<'_>, String>
+{
+    let mut lines = text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| (i + 1, line));
+
+    let (header_line, header) = lines.next().ok_or_else(|| String::from("Distance matrix is empty."))?;
+    let delimiter = delimiter.or_else(|| ['\t', ','].iter().find(|&&delimiter| header.contains(delimiter)).cloned());
+
+    let rows = lines.map(|(line, row)| split(row, delimiter).map(|cells| (line, cells)).map_err(|e| format!("Line {}: {}", line, e)))
+        .collect::<Result<Vec<(usize, Vec<&str>)>, String>>()?;
+
+    // The header names every column, after any corner cell
+    let mut names = split(header, delimiter).map_err(|e| format!("Line {}: {}", header_line, e))?;
+    if names.len() == rows.len() + 1 { names.remove(0); }
+    if names.len() != rows.len()
+    {
+        return Err(format!("Distance matrix has {} columns but {} rows; it must be square.", names.len(), rows.len()));
+    }
+
+    let mut columns = HashMap::new();
+    for (i, &name) in names.iter().enumerate()
+    {
+        if name.is_empty() { return Err(format!("Column {} of the distance matrix has no name.", i + 1)); }
+        if columns.insert(name, i).is_some() { return Err(format!("Distance matrix names \"{}\" more than once.", name)); }
+    }
+
+    // Each cell's weight, by (row, column) index
+    let mut distances = BTreeMap::new();
+    let mut seen = vec![false; names.len()];
+    for (line, cells) in &rows
+    {
+        let (&name, cells) = cells.split_first().ok_or_else(|| format!("Line {}: missing location name.", line))?;
+        let row = *columns.get(name).ok_or_else(|| format!("Line {}: \"{}\" is not named in the first row.", line, name))?;
+        if std::mem::replace(&mut seen[row], true) { return Err(format!("Line {}: \"{}\" has more than one row.", line, name)); }
+
+        // Trailing blank cells may be left out
+        if cells.len() > names.len() { return Err(format!("Line {}: expected at most {} distances.", line, names.len())); }
+        for (column, cell) in cells.iter().enumerate()
+        {
+            if column == row || cell.is_empty() || NO_ROUTE.contains(&cell.to_lowercase().as_str()) { continue; }
+
+            let distance = cell.parse::<f64>()
+                .map_err(|_| format!("Line {}: \"{}\" is not a distance.", line, cell))?;
+
+            // Round distance to nearest 10th
+            distances.insert((row, column), (unit.to_weight(distance).round() as i64, *line));
+        }
+    }
+
+    let mut edges = Vec::new();
+    for (&(row, column), &(distance, line)) in &distances
+    {
+        match distances.get(&(column, row))
+        {
+            _ if directed => edges.push((names[row], names[column], distance, true)),
+            Some(&(reverse, _)) if reverse != distance => return Err(format!(
+                "Line {}: the distance from {} to {} differs from {} to {}; the matrix must be symmetric.",
+                line, names[row], names[column], names[column], names[row])),
+            // Each route once, from its lower-indexed end
+            Some(_) if row > column => (),
+            _ => edges.push((names[row], names[column], distance, false)),
+        }
+    }
+
+    // Every cell is a different route, so none are duplicates
+    let mut routes = build_routes(edges, Duplicates::default(), unit)?;
+    for name in names { routes.add_location(name); }
+
+    Ok(routes)
+}
+
+///
+/// Splits a row of the matrix into its cells
+///
+/// - line: the row
+/// - delimiter: the character between cells, or None for whitespace
+///
+fn split(line: &str, delimiter: Option<char>) -> Result<Vec<&str>, String>
+{
+    match delimiter
+    {
+        Some(' ') | None => Ok(line.split_whitespace().collect()),
+        Some(delimiter) => split_row(line, delimiter),
+    }
+}
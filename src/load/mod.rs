@@ -0,0 +1,1506 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use bumpalo::Bump;
+use flate2::read::MultiGzDecoder;
+
+use super::algo::bellman_ford::find_negative_cycle;
+use super::algo::dag::DirectedRouteGraph;
+use super::algo::grid::{Connectivity, STRAIGHT_COST};
+use super::algo::RouteGraph;
+use grid::GridMap;
+use osm::BoundingBox;
+
+pub mod binary;
+pub mod compiled;
+pub mod csv;
+pub mod dot;
+pub mod geojson;
+pub mod graphml;
+pub mod grid;
+pub mod gtfs;
+pub mod json;
+pub mod matrix;
+pub mod movingai;
+pub mod osm;
+mod pbf;
+pub mod sidecar;
+pub mod validate;
+pub mod xml;
+mod zip;
+
+// Names commonly given to a distance column or attribute, compared
+// case-insensitively
+pub(crate) const DISTANCE_NAMES: [&str; 6] = ["distance", "dist", "weight", "cost", "miles", "length"];
+
+// Names commonly given to each route attribute, compared case-insensitively
+const HIGHWAY_NAMES: [&str; 3] = ["highway", "road_class", "class"];
+const SPEED_LIMIT_NAMES: [&str; 4] = ["speed_limit", "maxspeed", "max_speed", "speed"];
+const SURFACE_NAMES: [&str; 1] = ["surface"];
+
+// Kilometres in a mile, for speed limits given in km/h
+const KILOMETRES_PER_MILE: f64 = 1.609344;
+
+// The first bytes of every gzip file
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+///
+/// The contents of a route file
+///
+/// - graph: the route Graph, with every route, regardless of direction
+/// - arcs: the directions each route may be travelled, if any route is
+///   one-way. Searches should follow these rather than graph
+/// - coordinates: the (x, y) position of each location, if the file's
+///   format supplies them
+/// - geographic: whether the coordinates are (longitude, latitude) in
+///   degrees, rather than positions on a plane
+/// - attributes: each route's attributes beyond its weight, in both
+///   directions, for routes the file's format gives any
+/// - unit: what the graph's weights measure
+/// - duplicates: each extra listing of a route listed more than once, and
+///   merged, as (from, to)
+/// - grid: the grid map, if the routes are a grid map's, with each
+///   cell's coordinates its column and row
+///
+pub struct Routes<'a>
+{
+    pub graph: RouteGraph<'a>,
+    pub arcs: Option<DirectedRouteGraph<'a>>,
+    pub coordinates: HashMap<&'a str, (f64, f64)>,
+    pub geographic: bool,
+    pub attributes: HashMap<(&'a str, &'a str), RouteAttributes<'a>>,
+    pub unit: Unit,
+    pub duplicates: Vec<(&'a str, &'a str)>,
+    pub grid: Option<GridMap<'a>>,
+}
+
+impl<'a> From<RouteGraph<'a>> for Routes<'a>
+{
+    fn from(graph: RouteGraph<'a>) -> Self
+    {
+        Routes { graph, arcs: None, coordinates: HashMap::new(), geographic: false, attributes: HashMap::new(), unit: Unit::Miles, duplicates: Vec::new(), grid: None }
+    }
+}
+
+impl<'a> Routes<'a>
+{
+    ///
+    /// Adds a location, which may have no routes
+    ///
+    /// - name: the location's name
+    ///
+    pub(crate) fn add_location(&mut self, name: &'a str)
+    {
+        self.graph.add_node(name);
+        if let Some(arcs) = &mut self.arcs { arcs.add_node(name); }
+    }
+
+    ///
+    /// Combines these routes with those of another route file, such as a
+    /// neighbouring region's, into one set of routes. Routes listed in
+    /// both are merged by the Duplicates policy. Where both give a
+    /// location's coordinates or a route's attributes, these are kept
+    ///
+    /// - other: the other route file's routes
+    /// - duplicates: how to merge routes listed in both
+    ///
+    /// - return: Either an Ok Result with the combined Routes, or an Err
+    ///   if their weights or coordinates differ in kind, or if merging
+    ///   them fails as in build_routes
+    ///
+    pub fn merge(self, other: Routes<'a>, duplicates: Duplicates) -> Result<Routes<'a>, String>
+    {
+        if self.unit != other.unit
+        {
+            return Err(format!("Cannot merge routes measured in {} with routes measured in {}.", self.unit.name(), other.unit.name()));
+        }
+        if !self.coordinates.is_empty() && !other.coordinates.is_empty() && self.geographic != other.geographic
+        {
+            return Err(String::from("Cannot merge routes with geographic coordinates and routes with planar coordinates."));
+        }
+        if self.grid.is_some() || other.grid.is_some()
+        {
+            return Err(String::from("Cannot merge grid maps with other routes."));
+        }
+
+        let mut routes = build_routes(self.routes().into_iter().chain(other.routes()).collect(), duplicates, self.unit)?;
+        for node in self.graph.nodes().chain(other.graph.nodes()) { routes.add_location(node); }
+
+        let geographic = if self.coordinates.is_empty() { other.geographic } else { self.geographic };
+        let (mut coordinates, mut attributes) = (self.coordinates, self.attributes);
+        for (node, position) in other.coordinates { coordinates.entry(node).or_insert(position); }
+        for (route, route_attributes) in other.attributes { attributes.entry(route).or_insert(route_attributes); }
+
+        let duplicates = self.duplicates.into_iter().chain(other.duplicates).chain(routes.duplicates).collect();
+        Ok(Routes { coordinates, geographic, attributes, duplicates, ..routes })
+    }
+
+    ///
+    /// - return: every route, as (from, to, weight, one_way), with a pair
+    ///   of one-way routes of equal weight joined into a two-way route
+    ///
+    fn routes(&self) -> Vec<(&'a str, &'a str, i64, bool)>
+    {
+        match &self.arcs
+        {
+            Some(arcs) => arcs.all_edges()
+                .filter_map(|(from, to, &weight)| match arcs.edge_weight(to, from)
+                {
+                    Some(&back) if back == weight => Some((from, to, weight as i64, false)).filter(|_| from <= to),
+                    _ => Some((from, to, weight as i64, true)),
+                })
+                .collect(),
+            None => self.graph.all_edges().map(|(from, to, &weight)| (from, to, weight as i64, false)).collect(),
+        }
+    }
+
+    ///
+    /// - from: the route's first location
+    /// - to: the route's second location
+    ///
+    /// - return: the route's attributes, or None if it has none
+    ///
+    pub fn attributes(&self, from: &str, to: &str) -> Option<RouteAttributes<'a>>
+    {
+        // Shorten the key lifetimes so that the map can be
+        // indexed with any borrowed names
+        let attributes: &HashMap<(&str, &str), RouteAttributes<'a>> = &self.attributes;
+        attributes.get(&(from, to)).copied()
+    }
+
+    ///
+    /// Removes every route for which keep returns false, such as to avoid
+    /// unpaved roads. Routes are removed in each direction separately, if
+    /// any are one-way
+    ///
+    /// - keep: given each route's (from, to, weight, attributes), whether
+    ///   to keep it
+    ///
+    pub fn retain_routes<F>(&mut self, mut keep: F)
+        where F: FnMut(&str, &str, u64, Option<&RouteAttributes>) -> bool
+    {
+        let removed = self.graph.all_edges()
+            .filter(|&(from, to, &weight)| !keep(from, to, weight, self.attributes(from, to).as_ref()))
+            .map(|(from, to, _)| (from, to))
+            .collect::<Vec<(&'a str, &'a str)>>();
+        for (from, to) in removed { self.graph.remove_edge(from, to); }
+
+        if let Some(arcs) = &self.arcs
+        {
+            let removed = arcs.all_edges()
+                .filter(|&(from, to, &weight)| !keep(from, to, weight, self.attributes(from, to).as_ref()))
+                .map(|(from, to, _)| (from, to))
+                .collect::<Vec<(&'a str, &'a str)>>();
+            let arcs = self.arcs.as_mut().unwrap();
+            for (from, to) in removed { arcs.remove_edge(from, to); }
+        }
+    }
+
+    ///
+    /// Replaces the weight of every route, such as with its travel time at
+    /// the speed limit. If any route is one-way, each direction is
+    /// reweighted, and a route's weight regardless of direction is the
+    /// least of its directions'
+    ///
+    /// - cost: given each route's (from, to, weight, attributes), its new
+    ///   weight
+    ///
+    pub fn reweight<F>(&mut self, mut cost: F)
+        where F: FnMut(&str, &str, u64, Option<&RouteAttributes>) -> u64
+    {
+        match &self.arcs
+        {
+            Some(arcs) =>
+            {
+                let weights = arcs.all_edges()
+                    .map(|(from, to, &weight)| (from, to, cost(from, to, weight, self.attributes(from, to).as_ref())))
+                    .collect::<Vec<(&'a str, &'a str, u64)>>();
+                let arcs = self.arcs.as_mut().unwrap();
+                for &(from, to, weight) in &weights { arcs.add_edge(from, to, weight); }
+
+                let mut graph = RouteGraph::new();
+                for node in self.graph.nodes() { graph.add_node(node); }
+                for (from, to, weight) in weights
+                {
+                    let weight = graph.edge_weight(from, to).map_or(weight, |&existing| existing.min(weight));
+                    graph.add_edge(from, to, weight);
+                }
+                self.graph = graph;
+            },
+            None =>
+            {
+                let weights = self.graph.all_edges()
+                    .map(|(from, to, &weight)| (from, to, cost(from, to, weight, self.attributes(from, to).as_ref())))
+                    .collect::<Vec<(&'a str, &'a str, u64)>>();
+                for (from, to, weight) in weights { self.graph.add_edge(from, to, weight); }
+            },
+        }
+    }
+
+    ///
+    /// Reads the routes' weights as measuring another unit, keeping each
+    /// weight's value, so that 12.5 miles become 12.5 minutes. Weights
+    /// are rescaled if the units' scales differ
+    ///
+    /// - unit: the unit the weights measure
+    ///
+    /// - return: Either an Ok Result with the routes, or an Err if they
+    ///   are a grid map's, and the unit would rescale them
+    ///
+    pub fn read_as(mut self, unit: Unit) -> Result<Routes<'a>, String>
+    {
+        if unit.scale() != self.unit.scale()
+        {
+            if self.grid.is_some() { return Err(format!("A grid map cannot be measured in {}.", unit.name())); }
+            let measured = self.unit;
+            self.reweight(|_, _, weight, _| unit.to_weight(measured.from_weight(weight)).round() as u64);
+        }
+        self.unit = unit;
+        Ok(self)
+    }
+}
+
+///
+/// A route's attributes, beyond its weight
+///
+/// - highway: the road class (such as "primary" or "residential")
+/// - speed_limit: the speed limit, in miles per hour
+/// - surface: the road surface (such as "asphalt" or "gravel")
+///
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct RouteAttributes<'a>
+{
+    pub highway: Option<&'a str>,
+    pub speed_limit: Option<f64>,
+    pub surface: Option<&'a str>,
+}
+
+impl<'a> RouteAttributes<'a>
+{
+    ///
+    /// - return: whether the route has no attributes
+    ///
+    pub fn is_empty(&self) -> bool
+    {
+        *self == RouteAttributes::default()
+    }
+
+    ///
+    /// Sets the attribute with the given name, if it is one. Speed limits
+    /// are in miles per hour, unless followed by "km/h"
+    ///
+    /// - name: the attribute's name, which may be any usual name for it
+    /// - value: the attribute's value
+    ///
+    /// - return: Either an Ok Result with whether name is a route
+    ///   attribute, or an Err if its value is invalid
+    ///
+    pub(crate) fn set(&mut self, name: &str, value: &'a str) -> Result<bool, String>
+    {
+        let name = name.to_lowercase();
+        if HIGHWAY_NAMES.contains(&name.as_str()) { self.highway = Some(value); }
+        else if SURFACE_NAMES.contains(&name.as_str()) { self.surface = Some(value); }
+        else if SPEED_LIMIT_NAMES.contains(&name.as_str())
+        {
+            self.speed_limit = Some(parse_speed(value, true).ok_or_else(|| format!("\"{}\" is not a speed limit.", value))?);
+        }
+        else { return Ok(false); }
+
+        Ok(true)
+    }
+}
+
+///
+/// Reads a speed limit, such as "45", "45 mph" or "70 km/h"
+///
+/// - value: the speed limit
+/// - bare_in_mph: whether a speed with no unit is in miles per hour,
+///   rather than kilometres per hour
+///
+/// - return: the speed limit in miles per hour, or None if invalid
+///
+pub(crate) fn parse_speed(value: &str, bare_in_mph: bool) -> Option<f64>
+{
+    let value = value.trim().to_lowercase();
+    let (number, in_mph) = match value.strip_suffix("mph")
+    {
+        Some(number) => (number, true),
+        None => match ["km/h", "kmh", "kph"].iter().find_map(|unit| value.strip_suffix(unit))
+        {
+            Some(number) => (number, false),
+            None => (value.as_str(), bare_in_mph),
+        },
+    };
+
+    let speed = number.trim().parse::<f64>().ok().filter(|speed| speed.is_finite() && *speed > 0.0)?;
+    Some(if in_mph { speed } else { speed / KILOMETRES_PER_MILE })
+}
+
+///
+/// Records a route's attributes in both directions, unless it has none.
+/// A one-way route's attributes replace any of a route the other way
+/// only in its own direction
+///
+/// - attributes: every route's attributes
+/// - from: the route's first location
+/// - to: the route's second location
+/// - route: the route's attributes
+///
+pub(crate) fn add_attributes<'a>(attributes: &mut HashMap<(&'a str, &'a str), RouteAttributes<'a>>, from: &'a str, to: &'a str, route: RouteAttributes<'a>)
+{
+    if route.is_empty() { return; }
+    attributes.insert((from, to), route);
+    attributes.entry((to, from)).or_insert(route);
+}
+
+///
+/// What a Graph's weights measure. Weights are whole numbers of a
+/// fraction of the unit, its scale: tenths of most units, and
+/// thousandths of raw numbers
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Unit
+{
+    /// Distance, in miles
+    #[default]
+    Miles,
+    /// Distance, in kilometers
+    Kilometers,
+    /// Travel time, in minutes
+    Minutes,
+    /// Steps across a grid map, in cells
+    Cells,
+    /// Plain numbers, printed without a unit
+    Raw,
+}
+
+impl Unit
+{
+    ///
+    /// Finds the unit with the given name, such as "miles"
+    ///
+    pub fn from_name(name: &str) -> Option<Unit>
+    {
+        match name.to_lowercase().as_str()
+        {
+            "miles" | "mi" => Some(Unit::Miles),
+            "kilometers" | "kilometres" | "km" => Some(Unit::Kilometers),
+            "minutes" | "min" => Some(Unit::Minutes),
+            "cells" => Some(Unit::Cells),
+            "raw" => Some(Unit::Raw),
+            _ => None,
+        }
+    }
+
+    ///
+    /// The unit's name, such as "miles"
+    ///
+    pub fn name(self) -> &'static str
+    {
+        match self
+        {
+            Unit::Miles => "miles",
+            Unit::Kilometers => "kilometers",
+            Unit::Minutes => "minutes",
+            Unit::Cells => "cells",
+            Unit::Raw => "raw",
+        }
+    }
+
+    ///
+    /// The unit's abbreviation, as printed after a weight
+    ///
+    pub fn abbreviation(self) -> &'static str
+    {
+        match self
+        {
+            Unit::Miles => "mi.",
+            Unit::Kilometers => "km",
+            Unit::Minutes => "min.",
+            Unit::Cells => "cells",
+            Unit::Raw => "",
+        }
+    }
+
+    ///
+    /// What the unit measures, as printed before a total
+    ///
+    pub fn measure(self) -> &'static str
+    {
+        match self
+        {
+            Unit::Miles => "distance",
+            Unit::Kilometers => "distance",
+            Unit::Minutes => "time",
+            Unit::Cells => "distance",
+            Unit::Raw => "cost",
+        }
+    }
+
+    ///
+    /// The unit speeds are given in for distances in this unit, such as
+    /// "mph", or None if the unit is not a distance along roads
+    ///
+    pub fn speed(self) -> Option<&'static str>
+    {
+        match self
+        {
+            Unit::Miles => Some("mph"),
+            Unit::Kilometers => Some("km/h"),
+            _ => None,
+        }
+    }
+
+    ///
+    /// The # of weights in one of the unit
+    ///
+    pub fn scale(self) -> f64
+    {
+        match self
+        {
+            Unit::Raw => 1000.0,
+            _ => 10.0,
+        }
+    }
+
+    ///
+    /// The # of decimal places weights in the unit are kept to
+    ///
+    pub fn decimals(self) -> usize
+    {
+        match self
+        {
+            Unit::Raw => 3,
+            _ => 1,
+        }
+    }
+
+    ///
+    /// Converts a value in the unit into a weight, left for the caller
+    /// to round
+    ///
+    /// - value: the value, in whole units
+    ///
+    pub fn to_weight(self, value: f64) -> f64
+    {
+        value * self.scale()
+    }
+
+    ///
+    /// Converts a weight into a value in the unit
+    ///
+    /// - weight: the weight
+    ///
+    pub fn from_weight(self, weight: u64) -> f64
+    {
+        weight as f64 / self.scale()
+    }
+
+    ///
+    /// Writes a value in the unit as just the number, to no more decimal
+    /// places than weights are kept to
+    ///
+    /// - value: the value, in whole units
+    /// - precision: the # of decimal places to write, at most
+    ///
+    pub fn number(self, value: f64, precision: usize) -> String
+    {
+        format!("{:.*}", precision.min(self.decimals()), value)
+    }
+
+    ///
+    /// Writes a value in the unit, such as "12.5 mi.", or just the
+    /// number for raw weights
+    ///
+    /// - value: the value, in whole units
+    /// - precision: the # of decimal places to write, at most
+    ///
+    pub fn format(self, value: f64, precision: usize) -> String
+    {
+        match self
+        {
+            Unit::Raw => self.number(value, precision),
+            unit => format!("{} {}", unit.number(value, precision), unit.abbreviation()),
+        }
+    }
+}
+
+///
+/// How to merge a route listed more than once, between the same locations
+/// (and in the same direction, if one-way)
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Duplicates
+{
+    /// Keep the shortest weight
+    #[default]
+    KeepMin,
+    /// Keep the longest weight
+    KeepMax,
+    /// Add the weights together
+    Sum,
+    /// Refuse to load the routes
+    Error,
+}
+
+impl Duplicates
+{
+    ///
+    /// Finds the policy with the given name, such as "keep-min"
+    ///
+    pub fn from_name(name: &str) -> Option<Duplicates>
+    {
+        match name.to_lowercase().as_str()
+        {
+            "keep-min" | "min" => Some(Duplicates::KeepMin),
+            "keep-max" | "max" => Some(Duplicates::KeepMax),
+            "sum" => Some(Duplicates::Sum),
+            "error" => Some(Duplicates::Error),
+            _ => None,
+        }
+    }
+
+    ///
+    /// The policy's description, as printed after merging duplicates
+    ///
+    pub fn description(self) -> &'static str
+    {
+        match self
+        {
+            Duplicates::KeepMin => "keeping the shortest",
+            Duplicates::KeepMax => "keeping the longest",
+            Duplicates::Sum => "adding their weights",
+            Duplicates::Error => "refusing them",
+        }
+    }
+
+    ///
+    /// Merges weights sharing a key, keeping the order keys are first seen
+    ///
+    /// - items: every (key, weight)
+    /// - describe: names a key, for the message if duplicates are an error
+    ///
+    /// - return: Either an Ok Result with each key's merged weight and the
+    ///   key of each duplicate merged, or an Err if duplicates are an error
+    ///
+    fn merge<K, I, D>(self, items: I, describe: D) -> Result<Merged<K>, String>
+        where K: Copy + Eq + Hash, I: Iterator<Item = (K, i64)>, D: Fn(K) -> String
+    {
+        let mut merged: Vec<(K, i64)> = Vec::new();
+        let mut indices = HashMap::new();
+        let mut duplicates = Vec::new();
+        for (key, weight) in items
+        {
+            let index = match indices.get(&key)
+            {
+                Some(&index) => index,
+                None =>
+                {
+                    indices.insert(key, merged.len());
+                    merged.push((key, weight));
+                    continue;
+                },
+            };
+
+            duplicates.push(key);
+            let existing = &mut merged[index].1;
+            *existing = match self
+            {
+                Duplicates::KeepMin => (*existing).min(weight),
+                Duplicates::KeepMax => (*existing).max(weight),
+                Duplicates::Sum => *existing + weight,
+                Duplicates::Error => return Err(format!("Route {} is listed more than once.", describe(key))),
+            };
+        }
+
+        Ok((merged, duplicates))
+    }
+}
+
+///
+/// Weights merged by Duplicates::merge: each key's merged weight, in the
+/// order keys are first seen, and the key of each duplicate merged
+///
+type Merged<K> = (Vec<(K, i64)>, Vec<K>);
+
+///
+/// Settings for loading a route file, beyond its Format
+///
+/// - weight: the name of the edge attribute holding each route's
+///   distance, for formats with named edge attributes. If None, an
+///   attribute with a usual name for distance is used
+/// - bbox: the area to keep roads within, for OpenStreetMap extracts. If
+///   None, every road is kept
+/// - directed: whether each route is one-way, from its first location to
+///   its second, for formats which cannot mark direction themselves
+/// - duplicates: how to merge routes listed more than once
+/// - delimiter: the character between fields, for text, CSV and matrix
+///   route files. If None, each format's usual delimiter is used
+/// - diagonal: whether grid map cells may be stepped between diagonally,
+///   as well as up, down, left and right
+/// - unit: what the route file's weights measure, if given, or else
+///   miles. Lengths measured from the map itself, for GeoJSON and
+///   OpenStreetMap files, are computed in miles, or else kilometers if
+///   given
+///
+#[derive(Default)]
+pub struct LoadOptions
+{
+    pub weight: Option<String>,
+    pub bbox: Option<BoundingBox>,
+    pub directed: bool,
+    pub duplicates: Duplicates,
+    pub delimiter: Option<char>,
+    pub diagonal: bool,
+    pub unit: Option<Unit>,
+}
+
+///
+/// Location names copied into an arena, so they may outlive the
+/// parsed text they were read from. Each name is copied only once.
+///
+pub(crate) struct Names<'a>
+{
+    arena: &'a Bump,
+    names: HashMap<String, &'a str>,
+}
+
+impl<'a> Names<'a>
+{
+    pub(crate) fn new(arena: &'a Bump) -> Self
+    {
+        Names { arena, names: HashMap::new() }
+    }
+
+    ///
+    /// Retrieves the arena's copy of a name, copying it in if needed
+    ///
+    pub(crate) fn get(&mut self, name: &str) -> &'a str
+    {
+        if let Some(&name) = self.names.get(name) { return name; }
+
+        let copy = self.arena.alloc_str(name);
+        self.names.insert(name.to_string(), copy);
+        copy
+    }
+}
+
+///
+/// The formats a route file may be written in
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Format
+{
+    /// "(A, B, dist)" lines
+    Text,
+    Csv,
+    Json,
+    GraphMl,
+    Dot,
+    GeoJson,
+    /// OpenStreetMap XML
+    Osm,
+    /// OpenStreetMap PBF
+    OsmPbf,
+    /// A zipped GTFS transit feed
+    Gtfs,
+    /// A square distance matrix
+    Matrix,
+    /// A binary route file, as saved by binary::save
+    Binary,
+    /// A compiled graph file, as saved by compiled::save, which is searched
+    /// in place rather than loaded
+    Compiled,
+    /// An ASCII grid map of open cells and walls
+    Grid,
+    /// A MovingAI benchmark map
+    MovingAi,
+}
+
+impl Format
+{
+    ///
+    /// Finds the Format with the given name, such as "csv"
+    ///
+    pub fn from_name(name: &str) -> Option<Format>
+    {
+        match name.to_lowercase().as_str()
+        {
+            "txt" | "text" => Some(Format::Text),
+            "csv" => Some(Format::Csv),
+            "json" => Some(Format::Json),
+            "graphml" => Some(Format::GraphMl),
+            "dot" | "gv" => Some(Format::Dot),
+            "geojson" => Some(Format::GeoJson),
+            "osm" => Some(Format::Osm),
+            "pbf" => Some(Format::OsmPbf),
+            "gtfs" | "zip" => Some(Format::Gtfs),
+            "matrix" => Some(Format::Matrix),
+            "graph" | "binary" => Some(Format::Binary),
+            "cgraph" | "compiled" => Some(Format::Compiled),
+            "grid" => Some(Format::Grid),
+            "map" | "movingai" => Some(Format::MovingAi),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Chooses the Format of a route file by its extension, with any
+    /// unknown extension read as Text
+    ///
+    pub fn from_path(path: &str) -> Format
+    {
+        // A gzipped file's format is named before its ".gz"
+        let path = Path::new(path);
+        let path = match path.extension().and_then(|extension| extension.to_str())
+        {
+            Some(extension) if extension.eq_ignore_ascii_case("gz") => Path::new(path.file_stem().unwrap()),
+            _ => path,
+        };
+
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(Format::from_name)
+            .unwrap_or(Format::Text)
+    }
+}
+
+///
+/// Decompresses gzipped data, found by its leading bytes rather than the
+/// file's name. Any other data is returned as it is
+///
+/// - data: the data, which may be gzipped
+///
+/// - return: Either an Ok Result with the decompressed data, or an Err
+///   if gzipped data is invalid
+///
+pub fn decompress(data: Vec<u8>) -> Result<Vec<u8>, String>
+{
+    if !data.starts_with(&GZIP_MAGIC) { return Ok(data); }
+
+    let mut decompressed = Vec::new();
+    MultiGzDecoder::new(data.as_slice()).read_to_end(&mut decompressed)
+        .map_err(|e| format!("Invalid gzip file: {}", e))?;
+    Ok(decompressed)
+}
+
+///
+/// Reads a file, decompressing it if it is gzipped
+///
+/// - path: the file's path
+///
+/// - return: Either an Ok Result with the file's (decompressed)
+///   contents, or an Err with message explaining problem
+///
+pub fn read_file(path: &Path) -> Result<Vec<u8>, String>
+{
+    let data = std::fs::read(path).map_err(|e| format!("Cannot read \"{}\": {}", path.display(), e))?;
+    decompress(data).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+///
+/// Reads a text file, decompressing it if it is gzipped
+///
+/// - path: the file's path
+///
+/// - return: Either an Ok Result with the file's text, or an Err with
+///   message explaining problem
+///
+pub fn read_text(path: &Path) -> Result<String, String>
+{
+    String::from_utf8(read_file(path)?)
+        .map_err(|e| format!("{}: not valid UTF-8 text: {}", path.display(), e))
+}
+
+///
+/// Loads the routes from the contents of a route file
+///
+/// - data: the route file's contents, which must be UTF-8 text for all
+///   but binary formats
+/// - format: the Format the route file is written in
+/// - options: any further settings for loading
+/// - arena: holds any location names which cannot be borrowed from data
+///
+/// - return: Either an Ok Result with the Routes, or an Err with
+///   message explaining problem
+///
+pub fn load_routes<'a>(data: &'a [u8], format: Format, options: &LoadOptions, arena: &'a Bump) -> Result<Routes<'a>, String>
+{
+    // Binary formats
+    match format
+    {
+        Format::OsmPbf => return osm::parse_pbf(data, options.bbox, options.duplicates, arena).map(|routes| measured_in(routes, options.unit)),
+        Format::Gtfs => return gtfs::parse_zip(data, arena),
+        Format::Binary => return binary::parse(data),
+        Format::Compiled => return Err(String::from("A compiled graph can only answer queries with the route command.")),
+        _ => (),
+    }
+
+    let text = std::str::from_utf8(data)
+        .map_err(|e| format!("Route file is not valid UTF-8 text: {}", e))?;
+    // The unit written weights are read in
+    let unit = options.unit.unwrap_or_default();
+    match format
+    {
+        Format::Text => build_map(text, options.directed, options.duplicates, options.delimiter.unwrap_or(','), unit),
+        Format::Csv => csv::parse(text, options.directed, options.duplicates, options.delimiter.unwrap_or(','), unit),
+        Format::Json => json::parse(text, options.directed, options.duplicates, unit, arena),
+        Format::GraphMl => graphml::parse(text, options.weight.as_deref(), options.duplicates, unit, arena),
+        Format::Dot => dot::parse(text, options.weight.as_deref(), options.duplicates, unit, arena),
+        Format::GeoJson => geojson::parse(text, options.duplicates, arena).map(|routes| measured_in(routes, options.unit)),
+        Format::Osm => osm::parse_xml(text, options.bbox, options.duplicates, arena).map(|routes| measured_in(routes, options.unit)),
+        Format::Matrix => matrix::parse(text, options.directed, options.delimiter, unit),
+        Format::Grid => grid::parse(text, if options.diagonal { Connectivity::Eight } else { Connectivity::Four }, arena),
+        Format::MovingAi => movingai::parse_map(text, STRAIGHT_COST, arena),
+        Format::OsmPbf | Format::Gtfs | Format::Binary | Format::Compiled => unreachable!(),
+    }
+}
+
+///
+/// Converts routes whose lengths were measured from the map, in miles,
+/// into kilometers if asked. Lengths are rounded up, so they are never
+/// shorter than the great circle between their ends
+///
+/// - routes: the routes measured, in miles
+/// - unit: the unit asked for, if any
+///
+/// - return: the routes, in kilometers if asked, or else as they were
+///
+fn measured_in(mut routes: Routes<'_>, unit: Option<Unit>) -> Routes<'_>
+{
+    if unit == Some(Unit::Kilometers) && routes.unit == Unit::Miles
+    {
+        routes.reweight(|_, _, weight, _| Unit::Kilometers.to_weight(Unit::Miles.from_weight(weight) * KILOMETRES_PER_MILE).ceil() as u64);
+        routes.unit = Unit::Kilometers;
+    }
+    routes
+}
+
+///
+/// Builds the routes from every route read, each as (from, to, weight,
+/// one_way). Routes listed more than once are merged by the Duplicates
+/// policy
+///
+/// - routes: every route
+/// - duplicates: how to merge routes listed more than once
+/// - unit: what the weights measure
+///
+/// - return: Either an Ok Result with the Routes, holding only their
+///   Graphs and the duplicates merged, or an Err if duplicates are
+///   an error, or if the routes contain a negative-weight cycle or any
+///   other negative weight
+///
+pub(crate) fn build_routes<'a>(routes: Vec<(&'a str, &'a str, i64, bool)>, duplicates: Duplicates, unit: Unit) -> Result<Routes<'a>, String>
+{
+    // Two-way routes are the same whichever end is listed first
+    let (routes, merged) = duplicates.merge(
+        routes.into_iter().map(|(from, to, weight, one_way)| match one_way || from <= to
+        {
+            true => ((from, to, one_way), weight),
+            false => ((to, from, one_way), weight),
+        }),
+        |(from, to, one_way)| format!("{} {} {}", from, if one_way { "->" } else { "--" }, to))?;
+
+    // Every direction each route may be travelled. A one-way route may
+    // also parallel a two-way route, which is merged the same way
+    let any_one_way = routes.iter().any(|&((.., one_way), _)| one_way);
+    let (arcs, parallel) = duplicates.merge(
+        routes.iter().flat_map(|&((from, to, one_way), weight)| match one_way
+        {
+            true => vec![((from, to), weight)],
+            false => vec![((from, to), weight), ((to, from), weight)],
+        }),
+        |(from, to)| format!("{} -> {}", from, to))?;
+    let mut merged = merged.into_iter().map(|(from, to, _)| (from, to)).collect::<Vec<(&'a str, &'a str)>>();
+    if any_one_way { merged.extend(parallel); }
+
+    // Shortest routes are meaningless around a negative cycle, as
+    // each trip around it would shorten the route further
+    let cycle_arcs = arcs.iter().map(|&((from, to), weight)| (from, to, weight)).collect::<Vec<(&'a str, &'a str, i64)>>();
+    if let Some(cycle) = find_negative_cycle(&cycle_arcs)
+    {
+        return Err(format!("Cannot load routes: negative-weight cycle {}", cycle.join(" -> ")));
+    }
+
+    // Weights are held unsigned, and every search adds them up, so a
+    // negative weight off any cycle is refused too
+    if let Some(&((from, to), weight)) = arcs.iter().find(|&&(_, weight)| weight < 0)
+    {
+        return Err(format!("Cannot load routes: the route from {} to {} has a negative weight, {}; weights may not be negative",
+            from, to, -unit.from_weight(weight.unsigned_abs())));
+    }
+
+    // Routes joining the same locations both ways keep the shorter weight,
+    // so that distances ignoring direction never exceed those following it
+    let mut graph = RouteGraph::new();
+    for ((from, to, _), weight) in routes
+    {
+        let weight = graph.edge_weight(from, to).map_or(weight as u64, |&existing| existing.min(weight as u64));
+        graph.add_edge(from, to, weight);
+    }
+
+    let directed = if any_one_way
+    {
+        let mut directed = DirectedRouteGraph::new();
+        for ((from, to), weight) in arcs { directed.add_edge(from, to, weight as u64); }
+        Some(directed)
+    }
+    else { None };
+
+    Ok(Routes { arcs: directed, duplicates: merged, unit, ..Routes::from(graph) })
+}
+
+///
+/// Iterates over the lines of a text file holding data, skipping blank
+/// lines and comments (lines starting with '#'). Lines may end in "\r\n",
+/// as files saved on Windows do, and any byte order mark before the
+/// first line is skipped, so neither is left on a name
+///
+/// - input: the file's contents
+///
+/// - return: each data line, trimmed, with its line number
+///
+pub(crate) fn data_lines(input: &str) -> impl Iterator<Item = (usize, &str)>
+{
+    input.trim_start_matches('\u{feff}').lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+}
+
+///
+/// Explains that a field of a data line holds the wrong kind of value,
+/// citing the line and the column the field starts at
+///
+/// - text: the text the line was read from
+/// - line_num: the line's number, counting from 1
+/// - line: the line, trimmed
+/// - field: the field, which must be part of line
+/// - expected: what the field should hold, such as "a distance"
+///
+/// - return: the message
+///
+pub(crate) fn field_error(text: &str, line_num: usize, line: &str, field: &str, expected: &str) -> String
+{
+    let offset = field.as_ptr() as usize - text.as_ptr() as usize;
+    let start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let column = text[start..offset].trim_start_matches('\u{feff}').chars().count() + 1;
+    format!("Line {}, column {}: expected {}, but found \"{}\" (\"{}\")", line_num, column, expected, field, line)
+}
+
+///
+/// Splits a line into its fields wherever is_delimiter matches outside of
+/// double quotes, so that quoted names may hold delimiters. Fields are
+/// trimmed, but keep their quotes
+///
+/// - line: the line
+/// - is_delimiter: whether a character separates fields
+///
+/// - return: Either an Ok Result with each field, or an Err if a quote
+///   is never closed
+///
+pub(crate) fn split_quoted<F: Fn(char) -> bool>(line: &str, is_delimiter: F) -> Result<Vec<&str>, String>
+{
+    let mut fields = Vec::new();
+    let (mut start, mut quoted) = (0, false);
+    for (i, c) in line.char_indices()
+    {
+        if c == '"' { quoted = !quoted; }
+        else if !quoted && is_delimiter(c)
+        {
+            fields.push(line[start..i].trim());
+            start = i + c.len_utf8();
+        }
+    }
+    if quoted { return Err(String::from("unterminated quote")); }
+
+    fields.push(line[start..].trim());
+    Ok(fields)
+}
+
+///
+/// Splits a field in two at the first place pattern appears outside of
+/// double quotes
+///
+/// - return: the text before and after pattern, or None if it is absent
+///
+fn split_once_quoted<'t>(field: &'t str, pattern: &str) -> Option<(&'t str, &'t str)>
+{
+    let mut quoted = false;
+    for (i, c) in field.char_indices()
+    {
+        if c == '"' { quoted = !quoted; }
+        else if !quoted && field[i..].starts_with(pattern) { return Some((&field[..i], &field[i + pattern.len()..])); }
+    }
+    None
+}
+
+///
+/// Removes the double quotes around a field, if it has them
+///
+pub fn unquote(field: &str) -> &str
+{
+    let field = field.trim();
+    field.strip_prefix('"')
+        .and_then(|field| field.strip_suffix('"'))
+        .unwrap_or(field)
+}
+
+///
+/// Splits a list of names separated by commas, such as several cities
+/// entered at once. Names holding commas may be quoted
+///
+/// - input: the list
+///
+/// - return: each name, without quotes
+///
+pub fn split_names(input: &str) -> Vec<&str>
+{
+    // An unclosed quote runs to the end of the input
+    let input = input.trim();
+    split_quoted(input, |c| c == ',')
+        .unwrap_or_else(|_| vec![input])
+        .into_iter()
+        .map(|name| unquote(name).trim_matches('"'))
+        .collect()
+}
+
+///
+/// Reads a file of queries, one per line, as a start and an end location
+/// separated by a tab, or else a comma. Names holding commas may be
+/// quoted. Blank lines, and lines starting with '#', are skipped
+///
+/// - text: the file's text
+///
+/// - return: Either an Ok Result with each query's start and end, in
+///   order, or an Err citing the line which could not be read
+///
+pub fn parse_queries(text: &str) -> Result<Vec<(&str, &str)>, String>
+{
+    let mut queries = Vec::new();
+    for (i, line) in text.lines().enumerate()
+    {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') { continue; }
+
+        let names = match line.split_once('\t')
+        {
+            Some((from, to)) => vec![unquote(from.trim()), unquote(to.trim())],
+            None => split_names(line),
+        };
+        match names[..]
+        {
+            [from, to] if !from.is_empty() && !to.is_empty() => queries.push((from, to)),
+            _ => return Err(format!("Line {}: expected a start and an end location, separated by a tab or comma.", i + 1)),
+        }
+    }
+
+    Ok(queries)
+}
+
+/// 
+/// Build the route Graphs off of the supplied input, with one route per
+/// line, as "(from, to, distance)" (the parentheses are optional). A
+/// route written "(from -> to, distance)" may only be travelled from
+/// its first location to its second, while "(from -- to, distance)" is
+/// two-way, like the first form. Names holding commas may be quoted, as
+/// in "(\"Coeur d'Alene, ID\", Spokane, 33.4)". Either form may be
+/// followed by "key=value" attributes, such as "highway=primary",
+/// "speed_limit=45" or "surface=paved"
+/// 
+/// - route_dat: the input data, as a borrowed String
+/// - directed: whether routes in the first form are one-way too
+/// - duplicates: how to merge routes listed more than once
+/// - delimiter: the character between fields, usually ','. Runs of a
+///   whitespace delimiter count as one
+/// - unit: what the distances measure
+/// 
+/// - return: Either an Ok Result with the Routes, with u64 weight edges,
+///   or an Err citing the line (and column of any field) which could not
+///   be read, or if the routes are listed more than once when duplicates
+///   are an error, or any is negative. The float value provided from
+///   route_dat is rounded to the unit's scale, to maintain precision, but
+///   allow complete ordering
+/// 
+pub fn build_map<'a>(route_dat: &'a str, directed: bool, duplicates: Duplicates, delimiter: char, unit: Unit) -> Result<Routes<'a>, String>
+{
+    // Every route, with its signed weight, and whether it is one-way
+    let mut edges = Vec::new();
+    // The attributes of the routes which list any
+    let mut attributes = HashMap::new();
+
+    // For each line, add two Nodes and
+    // Edge into the graph
+    for (line_num, line) in data_lines(route_dat)
+    {
+        let (route_from, route_to, weight, one_way, route) = parse_route(route_dat, line_num, line, directed, delimiter, unit)?;
+        edges.push((route_from, route_to, weight, one_way));
+        add_attributes(&mut attributes, route_from, route_to, route);
+    }
+
+    Ok(Routes { attributes, ..build_routes(edges, duplicates, unit)? })
+}
+
+///
+/// Builds the route Graphs as build_map does, but reading the input one
+/// line at a time, so that the whole of a very large route file need
+/// never be held in memory. Location names are copied into the arena
+/// instead of borrowed from the input
+///
+/// - reader: the input data
+/// - directed: whether routes in the first form are one-way too
+/// - duplicates: how to merge routes listed more than once
+/// - delimiter: the character between fields, usually ','
+/// - unit: what the distances measure
+/// - arena: holds the location names read
+///
+/// - return: Either an Ok Result with the Routes, or an Err as from
+///   build_map, or if the input could not be read
+///
+pub fn read_map<'a, R: BufRead>(reader: R, directed: bool, duplicates: Duplicates, delimiter: char, unit: Unit, arena: &'a Bump) -> Result<Routes<'a>, String>
+{
+    let mut names = Names::new(arena);
+    let mut edges = Vec::new();
+    let mut attributes = HashMap::new();
+
+    for (i, line) in reader.lines().enumerate()
+    {
+        let text = line.map_err(|e| format!("Line {}: cannot be read: {}", i + 1, e))?;
+        let line = text.trim_start_matches('\u{feff}').trim();
+        if line.is_empty() || line.starts_with('#') { continue; }
+
+        let (route_from, route_to, weight, one_way, route) = parse_route(&text, i + 1, line, directed, delimiter, unit)?;
+        let (route_from, route_to) = (names.get(route_from), names.get(route_to));
+        let route = RouteAttributes
+        {
+            highway: route.highway.map(|highway| names.get(highway)),
+            surface: route.surface.map(|surface| names.get(surface)),
+            ..route
+        };
+        edges.push((route_from, route_to, weight, one_way));
+        add_attributes(&mut attributes, route_from, route_to, route);
+    }
+
+    Ok(Routes { attributes, ..build_routes(edges, duplicates, unit)? })
+}
+
+///
+/// Reads one route from a line of a route file, in either form accepted
+/// by build_map
+///
+/// - text: the text the line was read from
+/// - line_num: the line's number, counting from 1
+/// - line: the line, trimmed
+/// - directed: whether routes in the first form are one-way
+/// - delimiter: the character between fields
+/// - unit: what the distance measures
+///
+/// - return: Either an Ok Result with the route's (from, to, weight,
+///   one_way, attributes), or an Err citing the line, and the column of
+///   any field which could not be read
+///
+fn parse_route<'t>(text: &str, line_num: usize, line: &'t str, directed: bool, delimiter: char, unit: Unit) -> Result<(&'t str, &'t str, i64, bool, RouteAttributes<'t>), String>
+{
+    let error = |message: String| format!("Line {}: {} (\"{}\")", line_num, message, line);
+
+    // Trim parens, and split by the delimiter, outside of quotes
+    let data = split_quoted(line.trim_matches(|c| { c == '(' || c == ')' }), |c| c == delimiter).map_err(error)?
+        .into_iter()
+        .filter(|val| { !val.is_empty() || !delimiter.is_whitespace() })
+        .collect::<Vec<&str>>();
+
+    // Any trailing "key=value" items are the route's attributes
+    let fields = data.iter().take_while(|val| split_once_quoted(val, "=").is_none()).count();
+    let (data, attribute_fields) = data.split_at(fields);
+    let mut route = RouteAttributes::default();
+    for field in attribute_fields
+    {
+        let (name, value) = split_once_quoted(field, "=")
+            .map(|(name, value)| (name.trim(), unquote(value)))
+            .ok_or_else(|| field_error(text, line_num, line, field, "a \"key=value\" attribute"))?;
+        if !route.set(name, value).map_err(|message| error(message.trim_end_matches('.').to_string()))?
+        {
+            return Err(error(format!("unknown attribute \"{}\"", name)));
+        }
+    }
+
+    // 1st item - the starting node
+    // 2nd item - the ending node
+    // 3rd item - the distance
+    // or else the two nodes, joined by an arrow, then the distance.
+    // Names may be quoted
+    let (route_from, route_to, dist, one_way) = match *data
+    {
+        [from, to, dist] => (unquote(from), unquote(to), dist, directed),
+        [ends, dist] => match (split_once_quoted(ends, "->"), split_once_quoted(ends, "--"))
+        {
+            (Some((from, to)), _) => (unquote(from), unquote(to), dist, true),
+            (None, Some((from, to))) => (unquote(from), unquote(to), dist, false),
+            (None, None) => return Err(field_error(text, line_num, line, ends, "\"->\" or \"--\" between the locations")),
+        },
+        _ => return Err(error(format!("expected 3 fields, \"from, to, distance\", but found {}", data.len()))),
+    };
+    if route_from.is_empty() || route_to.is_empty() { return Err(error(String::from("missing location name"))); }
+
+    // Round weight to the unit's scale
+    let weight = dist.parse::<f64>()
+        .ok()
+        .filter(|dist| dist.is_finite())
+        .ok_or_else(|| field_error(text, line_num, line, dist, &format!("a distance in field {}", data.len())))?;
+    Ok((route_from, route_to, unit.to_weight(weight).round() as i64, one_way, route))
+}
+
+///
+/// Opens a file to be read a line at a time, decompressing it as it is
+/// read if it is gzipped
+///
+/// - path: the file's path
+///
+/// - return: Either an Ok Result with the file's reader, or an Err with
+///   message explaining problem
+///
+pub fn open_file(path: &Path) -> Result<Box<dyn BufRead>, String>
+{
+    let error = |e: std::io::Error| format!("Cannot read \"{}\": {}", path.display(), e);
+    buffered(File::open(path).map_err(error)?).map_err(error)
+}
+
+///
+/// Opens standard input to be read a line at a time, decompressing it as
+/// it is read if it is gzipped
+///
+/// - return: Either an Ok Result with standard input's reader, or an Err
+///   with message explaining problem
+///
+pub fn open_stdin() -> Result<Box<dyn BufRead>, String>
+{
+    buffered(std::io::stdin()).map_err(|e| format!("Cannot read standard input: {}", e))
+}
+
+///
+/// Reads the whole of standard input, decompressing it if it is gzipped
+///
+/// - return: Either an Ok Result with standard input's (decompressed)
+///   contents, or an Err with message explaining problem
+///
+pub fn read_stdin() -> Result<Vec<u8>, String>
+{
+    let mut data = Vec::new();
+    std::io::stdin().read_to_end(&mut data).map_err(|e| format!("Cannot read standard input: {}", e))?;
+    decompress(data)
+}
+
+///
+/// Buffers a reader, decompressing it as it is read if it is gzipped
+///
+fn buffered<R: Read + 'static>(reader: R) -> std::io::Result<Box<dyn BufRead>>
+{
+    let mut reader = BufReader::new(reader);
+    if reader.fill_buf()?.starts_with(&GZIP_MAGIC)
+    {
+        return Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))));
+    }
+
+    Ok(Box::new(reader))
+}
+
+///
+/// Retrieves all Heuristic data from euclidian.txt, with one pair per
+/// line, as "from to distance" (names holding spaces may be quoted)
+/// Returns as a HashMap, with key values being the 2-ple of the
+/// two borrowed String slices, and the value being the distance between.
+/// 
+/// - input: the input-data, as a borrowed String
+/// - delimiter: the character between values, or None for whitespace
+/// - unit: what the distances measure
+/// 
+/// - return: Either an Ok Result with the generated HashMap, with u64 type
+///   values, or an Err citing the line (and column of any field) which
+///   could not be read. The float value provided from input is rounded
+///   to the unit's scale, to maintain precision, but allow complete
+///   ordering
+/// 
+pub fn build_heur_data(input: &str, delimiter: Option<char>, unit: Unit) -> Result<HashMap<(&str, &str), u64>, String>
+{
+    // HashMap of data - returned value
+    let mut dist_dat = HashMap::new();
+
+    // For each line of input
+    for (line_num, line) in data_lines(input)
+    {
+        let error = |message: &str| format!("Line {}: {} (\"{}\")", line_num, message, line);
+
+        // Collect the data, seperated by spaces or the delimiter, outside
+        // of quoted names
+        let data = split_quoted(line, |c| delimiter.map_or(c.is_whitespace(), |delimiter| c == delimiter))
+            .map_err(|message| error(&message))?
+            .into_iter()
+            .filter(|val| delimiter.is_some() || !val.is_empty())
+            .collect::<Vec<&str>>();
+
+        // Assign from and to node (edge) to vars
+        let (from, to, dist) = match data.as_slice()
+        {
+            &[from, to, dist] => (unquote(from), unquote(to), dist),
+            _ => return Err(error(&format!("expected 3 fields, \"from to distance\", but found {}", data.len()))),
+        };
+
+        // Round distance to the unit's scale and convert to u64. Heuristic
+        // distances can't be negative
+        let dist = dist.parse::<f64>()
+            .ok()
+            .filter(|dist| dist.is_finite() && *dist >= 0.0)
+            .ok_or_else(|| field_error(input, line_num, line, dist, "a distance of at least 0 in field 3"))?;
+
+        // Insert data
+        dist_dat.insert((from, to), unit.to_weight(dist).round() as u64);
+    }
+
+    Ok(dist_dat)
+}
+
+///
+/// Retrieves each location's planar coordinates from a coordinates file,
+/// with one location per line, as its name then its x and y in miles:
+///
+/// ```text
+/// Salem 0.0 0.0
+/// Eugene 12.5 -63.9
+/// ```
+///
+/// Names may hold spaces, as the last two values on each line are the
+/// coordinates, and may be quoted. Blank lines and comments are skipped. Geographic coordinates may be
+/// given in the same way, as latitude then longitude in degrees.
+///
+/// - input: the coordinates file's contents
+/// - delimiter: the character between values, or None for whitespace
+///
+/// - return: Either an Ok Result with each location's (x, y), or an Err
+///   with message explaining problem
+///
+pub fn build_coordinates(input: &str, delimiter: Option<char>) -> Result<HashMap<&str, (f64, f64)>, String>
+{
+    let mut coordinates = HashMap::new();
+    for (line_num, line) in data_lines(input)
+    {
+        // Split off the last two values, leaving the name
+        let values = match delimiter
+        {
+            Some(delimiter) => line.rsplitn(3, delimiter).map(str::trim).collect::<Vec<&str>>(),
+            None => line.rsplitn(3, char::is_whitespace).collect::<Vec<&str>>(),
+        };
+        let (y, x, name) = match *values.as_slice()
+        {
+            [y, x, name] if !unquote(name).is_empty() => (y, x, unquote(name)),
+            _ => return Err(format!("Line {}: expected a name, then x and y coordinates (\"{}\")", line_num, line)),
+        };
+
+        let parse = |value: &str, expected: &str| value.parse::<f64>().map_err(|_| field_error(input, line_num, line, value, expected));
+        if coordinates.insert(name, (parse(x, "an x coordinate")?, parse(y, "a y coordinate")?)).is_some()
+        {
+            return Err(format!("Line {}: \"{}\" has more than one position (\"{}\")", line_num, name, line));
+        }
+    }
+
+    Ok(coordinates)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::io::Cursor;
+
+    use bumpalo::Bump;
+
+    use super::{build_heur_data, build_map, load_routes, open_file, read_map, read_text, Duplicates, Format, LoadOptions, Unit};
+
+    #[test]
+    fn comments_and_blank_lines_are_skipped()
+    {
+        let input = "# Oregon\n\n(Salem, Eugene, 63.9)\n   \n  # Salem to Portland\nSalem, Portland, 47.1\n\n";
+        let routes = build_map(input, false, Duplicates::default(), ',', Unit::default()).unwrap();
+        assert_eq!((routes.graph.node_count(), routes.graph.edge_count()), (3, 2));
+        assert_eq!(routes.graph.edge_weight("Salem", "Eugene"), Some(&639));
+        assert_eq!(routes.graph.edge_weight("Portland", "Salem"), Some(&471));
+
+        let arena = Bump::new();
+        let read = read_map(Cursor::new(input), false, Duplicates::default(), ',', Unit::default(), &arena).unwrap();
+        assert_eq!((read.graph.node_count(), read.graph.edge_count()), (3, 2));
+
+        let heuristic = build_heur_data("# Straight lines\n\nSalem Eugene 60\n\n# Back\nEugene Salem 60\n", None, Unit::default()).unwrap();
+        assert_eq!(heuristic.len(), 2);
+        assert_eq!(heuristic[&("Salem", "Eugene")], 600);
+    }
+
+    #[test]
+    fn errors_cite_the_line_in_the_file()
+    {
+        let input = "# Oregon\n\nSalem, Eugene, 63.9\n\nSalem\n";
+        let expected = "Line 5: expected 3 fields, \"from, to, distance\", but found 1 (\"Salem\")";
+        assert_eq!(build_map(input, false, Duplicates::default(), ',', Unit::default()).err().as_deref(), Some(expected));
+
+        let arena = Bump::new();
+        assert_eq!(read_map(Cursor::new(input), false, Duplicates::default(), ',', Unit::default(), &arena).err().as_deref(), Some(expected));
+
+        assert_eq!(
+            build_heur_data("# Straight lines\nSalem Eugene 60\n\nSalem Eugene\n", None, Unit::default()).err().as_deref(),
+            Some("Line 4: expected 3 fields, \"from to distance\", but found 2 (\"Salem Eugene\")"));
+    }
+
+    #[test]
+    fn malformed_route_files_cite_the_column()
+    {
+        let path = std::env::temp_dir().join(format!("malformed-routes-{}.txt", std::process::id()));
+        std::fs::write(&path, "Salem, Eugene, 63.9\n  (Salem, Portland, far)\n").unwrap();
+        let expected = "Line 2, column 21: expected a distance in field 3, but found \"far\" (\"(Salem, Portland, far)\")";
+
+        let text = read_text(&path).unwrap();
+        let arena = Bump::new();
+        assert_eq!(load_routes(text.as_bytes(), Format::Text, &LoadOptions::default(), &arena).err().as_deref(), Some(expected));
+        assert_eq!(read_map(open_file(&path).unwrap(), false, Duplicates::default(), ',', Unit::default(), &arena).err().as_deref(), Some(expected));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn windows_line_endings_leave_no_carriage_returns()
+    {
+        let input = "Salem, Eugene, 63.9\r\nSalem, Portland, 47.1\r\n\r\n";
+        let routes = build_map(input, false, Duplicates::default(), ',', Unit::default()).unwrap();
+        let mut names = routes.graph.nodes().collect::<Vec<&str>>();
+        names.sort_unstable();
+        assert_eq!(names, vec!["Eugene", "Portland", "Salem"]);
+        assert_eq!(routes.graph.edge_weight("Salem", "Portland"), Some(&471));
+
+        let arena = Bump::new();
+        let read = read_map(Cursor::new(input), false, Duplicates::default(), ',', Unit::default(), &arena).unwrap();
+        let mut names = read.graph.nodes().collect::<Vec<&str>>();
+        names.sort_unstable();
+        assert_eq!(names, vec!["Eugene", "Portland", "Salem"]);
+
+        let heuristic = build_heur_data("Salem Eugene 60\r\nEugene Portland 100\r\n\r\n", None, Unit::default()).unwrap();
+        let mut pairs = heuristic.into_iter().collect::<Vec<((&str, &str), u64)>>();
+        pairs.sort_unstable();
+        assert_eq!(pairs, vec![(("Eugene", "Portland"), 1000), (("Salem", "Eugene"), 600)]);
+    }
+
+    #[test]
+    fn raw_weights_keep_thousandths()
+    {
+        let routes = build_map("A, B, 0.04\nB, C, 12.3456\n", false, Duplicates::default(), ',', Unit::Raw).unwrap();
+        assert_eq!(routes.graph.edge_weight("A", "B"), Some(&40));
+        assert_eq!(routes.graph.edge_weight("B", "C"), Some(&12346));
+        assert_eq!(build_heur_data("A C 12.3", None, Unit::Raw).unwrap()[&("A", "C")], 12300);
+
+        // No more decimal places are written than weights are kept to
+        assert_eq!(Unit::Raw.format(Unit::Raw.from_weight(40), 5), "0.040");
+        assert_eq!(Unit::Miles.format(Unit::Miles.from_weight(639), 3), "63.9 mi.");
+
+        // Read as miles, the weights keep their values to a tenth
+        let miles = routes.read_as(Unit::Miles).unwrap();
+        assert_eq!((miles.unit, miles.graph.edge_weight("A", "B"), miles.graph.edge_weight("B", "C")), (Unit::Miles, Some(&0), Some(&123)));
+    }
+}
@@ -0,0 +1,173 @@
+use bumpalo::Bump;
+
+use super::grid::GridMap;
+use crate::algo::grid::Connectivity;
+use super::Routes;
+
+// The terrain which may be crossed: ground, either '.' or 'G', and swamp.
+// Out of bounds ('@', 'O'), trees and water may not
+const PASSABLE: [char; 3] = ['.', 'G', 'S'];
+const IMPASSABLE: [char; 4] = ['@', 'O', 'T', 'W'];
+
+///
+/// One search problem from a MovingAI scenario file
+///
+/// - bucket: the scenario's group, of problems of similar length
+/// - map: the map file the scenario is set on, as written in the file
+/// - width, height: the map's size, in cells
+/// - start, goal: the (column, row) of the start and goal cells
+/// - optimal: the shortest route's length, in cells
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scenario
+{
+    pub bucket: usize,
+    pub map: String,
+    pub width: usize,
+    pub height: usize,
+    pub start: (usize, usize),
+    pub goal: (usize, usize),
+    pub optimal: f64,
+}
+
+///
+/// Names a cell of a MovingAI map
+///
+/// - cell: the (column, row) of a cell on a MovingAI map
+///
+/// - return: the name of the cell's location, as "x:y"
+///
+pub fn cell_name(cell: (usize, usize)) -> String
+{
+    format!("{}:{}", cell.0, cell.1)
+}
+
+///
+/// Builds the routes from a MovingAI benchmark map, such as:
+///
+/// ```text
+/// type octile
+/// height 3
+/// width 8
+/// map
+/// ...@....
+/// .T.@.@@.
+/// .....@..
+/// ```
+///
+/// Ground ('.' or 'G') and swamp ('S') may be crossed; out of bounds ('@'
+/// or 'O'), trees ('T') and water ('W') may not. As in the benchmarks,
+/// cells are eight-connected, diagonal steps cost √2 and may not cut the
+/// corner of an impassable cell. Every cell is named by its column and
+/// row, from 0, as "x:y".
+///
+/// - text: the map file's text
+/// - straight_cost: the weight of a step between orthogonal neighbours
+/// - arena: holds the cells' names
+///
+/// - return: Either an Ok Result with the Routes, with each cell's (x, y)
+///   as its coordinates and the map as their grid, or an Err with
+///   message explaining problem
+///
+pub fn parse_map<'a>(text: &str, straight_cost: u64, arena: &'a Bump) -> Result<Routes<'a>, String>
+{
+    let mut lines = text.lines().map(|line| line.trim_end_matches('\r')).enumerate().map(|(i, line)| (i + 1, line));
+
+    // The header, up to the "map" line
+    let (mut width, mut height) = (None, None);
+    loop
+    {
+        let (line, header) = lines.next().ok_or_else(|| String::from("MovingAI map has no \"map\" line."))?;
+        let mut fields = header.split_whitespace();
+        match (fields.next(), fields.next())
+        {
+            (Some("map"), None) => break,
+            (Some("type"), Some(_)) | (None, _) => (),
+            (Some(field @ "width"), Some(value)) | (Some(field @ "height"), Some(value)) =>
+            {
+                let value = value.parse::<usize>().map_err(|_| format!("Line {}: \"{}\" is not a {}.", line, value, field))?;
+                if field == "width" { width = Some(value); } else { height = Some(value); }
+            },
+            _ => return Err(format!("Line {}: unknown MovingAI map header \"{}\".", line, header)),
+        }
+    }
+    let width = width.ok_or_else(|| String::from("MovingAI map has no width."))?;
+    let height = height.ok_or_else(|| String::from("MovingAI map has no height."))?;
+
+    let mut rows = Vec::with_capacity(height);
+    for (line, text) in lines
+    {
+        if rows.len() == height
+        {
+            if text.trim().is_empty() { continue; }
+            return Err(format!("Line {}: MovingAI map has more than its height of {} rows.", line, height));
+        }
+
+        let y = rows.len();
+        let mut row = Vec::with_capacity(width);
+        for (x, cell) in text.chars().enumerate()
+        {
+            row.push(match cell
+            {
+                cell if PASSABLE.contains(&cell) => Some(&*arena.alloc_str(&cell_name((x, y)))),
+                cell if IMPASSABLE.contains(&cell) => None,
+                cell => return Err(format!("Line {}: '{}' is not MovingAI terrain.", line, cell)),
+            });
+        }
+        if row.len() != width { return Err(format!("Line {}: expected a row of {} cells, found {}.", line, width, row.len())); }
+        rows.push(row);
+    }
+    if rows.len() != height { return Err(format!("MovingAI map has {} rows, but a height of {}.", rows.len(), height)); }
+
+    GridMap::from_rows(&rows, Connectivity::Eight, straight_cost).into_routes()
+}
+
+///
+/// Reads the problems in a MovingAI scenario file, such as:
+///
+/// ```text
+/// version 1
+/// 0   maps/dao/arena.map  49  49  1   11  1   12  1
+/// 1   maps/dao/arena.map  49  49  1   13  4   12  3.41421356
+/// ```
+///
+/// After the version line, each line is a problem, as its bucket, map
+/// file, map width and height, start column and row, goal column and
+/// row, and optimal length, separated by tabs (shown here as spaces).
+///
+/// - text: the scenario file's text
+///
+/// - return: Either an Ok Result with every Scenario, in order, or an Err
+///   with message explaining problem
+///
+pub fn parse_scenarios(text: &str) -> Result<Vec<Scenario>, String>
+{
+    let mut scenarios = Vec::new();
+    for (i, line) in text.lines().enumerate()
+    {
+        let line_number = i + 1;
+        let fields = line.split('\t').map(str::trim).collect::<Vec<&str>>();
+        if line.trim().is_empty() || (i == 0 && fields[0].starts_with("version")) { continue; }
+
+        if fields.len() != 9
+        {
+            return Err(format!("Line {}: expected 9 tab-separated fields, found {}.", line_number, fields.len()));
+        }
+        let number = |field: usize, name: &str| fields[field].parse::<usize>()
+            .map_err(|_| format!("Line {}: \"{}\" is not a {}.", line_number, fields[field], name));
+
+        scenarios.push(Scenario
+        {
+            bucket: number(0, "bucket")?,
+            map: fields[1].to_string(),
+            width: number(2, "width")?,
+            height: number(3, "height")?,
+            start: (number(4, "column")?, number(5, "row")?),
+            goal: (number(6, "column")?, number(7, "row")?),
+            optimal: fields[8].parse::<f64>()
+                .map_err(|_| format!("Line {}: \"{}\" is not a length.", line_number, fields[8]))?,
+        });
+    }
+
+    Ok(scenarios)
+}
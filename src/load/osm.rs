@@ -0,0 +1,315 @@
+use std::collections::{BTreeSet, HashMap};
+use std::str::FromStr;
+
+use bumpalo::Bump;
+
+use super::xml;
+use super::{add_attributes, build_routes, parse_speed, pbf, Duplicates, Names, RouteAttributes, Routes, Unit};
+use crate::algo::geo::haversine_miles;
+
+// The highway classes of ways which are roads, rather than paths,
+// tracks, or features
+const ROAD_CLASSES: [&str; 15] =
+[
+    "motorway", "motorway_link", "trunk", "trunk_link", "primary", "primary_link",
+    "secondary", "secondary_link", "tertiary", "tertiary_link", "unclassified",
+    "residential", "living_street", "service", "road",
+];
+
+///
+/// An area of the Earth, bounded by longitude and latitude
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BoundingBox
+{
+    pub min_lon: f64,
+    pub min_lat: f64,
+    pub max_lon: f64,
+    pub max_lat: f64,
+}
+
+impl BoundingBox
+{
+    ///
+    /// Whether a (longitude, latitude) position lies within the box
+    ///
+    pub fn contains(&self, (lon, lat): (f64, f64)) -> bool
+    {
+        lon >= self.min_lon && lon <= self.max_lon && lat >= self.min_lat && lat <= self.max_lat
+    }
+}
+
+impl FromStr for BoundingBox
+{
+    type Err = String;
+
+    ///
+    /// Reads a BoundingBox as "min_lon,min_lat,max_lon,max_lat", the
+    /// order used by OSM tools
+    ///
+    fn from_str(text: &str) -> Result<Self, String>
+    {
+        let values = text.split(',')
+            .map(|value| value.trim().parse::<f64>())
+            .collect::<Result<Vec<f64>, _>>()
+            .map_err(|_| format!("\"{}\" is not a bounding box.", text))?;
+
+        match values.as_slice()
+        {
+            &[min_lon, min_lat, max_lon, max_lat] if min_lon <= max_lon && min_lat <= max_lat =>
+                Ok(BoundingBox { min_lon, min_lat, max_lon, max_lat }),
+            _ => Err(format!("\"{}\" is not a bounding box; expected min_lon,min_lat,max_lon,max_lat.", text)),
+        }
+    }
+}
+
+///
+/// The parts of an OSM extract needed to build its road network
+///
+/// - positions: every node's (longitude, latitude)
+/// - names: the name of every node which has one
+/// - ways: every road
+///
+#[derive(Default)]
+pub(crate) struct Extract
+{
+    pub(crate) positions: HashMap<i64, (f64, f64)>,
+    pub(crate) names: HashMap<i64, String>,
+    pub(crate) ways: Vec<Way>,
+}
+
+///
+/// A road, as the nodes along it, in the direction it may be travelled
+/// if it is one-way
+///
+pub(crate) struct Way
+{
+    refs: Vec<i64>,
+    highway: String,
+    name: Option<String>,
+    one_way: bool,
+    speed_limit: Option<f64>,
+    surface: Option<String>,
+}
+
+impl Extract
+{
+    ///
+    /// Records a way, if it is a road. Roads tagged oneway, roundabouts,
+    /// and motorways are one-way. Speed limits with no unit are in km/h,
+    /// and those which cannot be read (such as "signals") are ignored
+    ///
+    /// - refs: the ids of the nodes along the way
+    /// - tags: the way's tags, as (key, value)
+    ///
+    pub(crate) fn add_way<'t>(&mut self, mut refs: Vec<i64>, tags: impl Iterator<Item = (&'t str, &'t str)>)
+    {
+        let (mut highway, mut name, mut oneway, mut roundabout) = (None, None, None, false);
+        let (mut speed_limit, mut surface) = (None, None);
+        for (key, value) in tags
+        {
+            match key
+            {
+                "highway" => highway = Some(value),
+                "name" => name = Some(value.to_string()),
+                "oneway" => oneway = Some(value),
+                "junction" => roundabout = value == "roundabout",
+                "maxspeed" => speed_limit = parse_speed(value, false),
+                "surface" => surface = Some(value.to_string()),
+                _ => (),
+            }
+        }
+
+        if let Some(highway) = highway.filter(|highway| ROAD_CLASSES.contains(highway))
+        {
+            let one_way = match oneway
+            {
+                Some("yes") | Some("true") | Some("1") => true,
+                // Travelled against the order of its nodes
+                Some("-1") | Some("reverse") =>
+                {
+                    refs.reverse();
+                    true
+                },
+                Some(_) => false,
+                None => roundabout || highway == "motorway" || highway == "motorway_link",
+            };
+            self.ways.push(Way { refs, highway: highway.to_string(), name, one_way, speed_limit, surface });
+        }
+    }
+}
+
+///
+/// Builds the road network from an OSM XML extract (.osm)
+///
+/// - text: the OSM XML data
+/// - bbox: the area to keep roads within, or None to keep every road
+/// - duplicates: how to merge roads joining the same junctions
+/// - arena: holds the location names read
+///
+/// - return: Either an Ok Result with the Routes, or an Err with message
+///   explaining problem
+///
+pub fn parse_xml<'a>(text: &str, bbox: Option<BoundingBox>, duplicates: Duplicates, arena: &'a Bump) -> Result<Routes<'a>, String>
+{
+    let root = xml::parse(text)?;
+    if root.name != "osm" { return Err(String::from("OSM file must have an <osm> root element.")); }
+
+    let id = |element: &xml::Element| element.attribute("id")
+        .and_then(|id| id.parse::<i64>().ok())
+        .ok_or_else(|| format!("OSM {} has an invalid id.", element.name));
+    fn tags(element: &xml::Element) -> Vec<(&str, &str)>
+    {
+        element.children_named("tag")
+            .filter_map(|tag| Some((tag.attribute("k")?, tag.attribute("v")?)))
+            .collect()
+    }
+
+    let mut extract = Extract::default();
+    for element in &root.children
+    {
+        match element.name.as_str()
+        {
+            "node" =>
+            {
+                let id = id(element)?;
+                let lon = element.attribute("lon").and_then(|lon| lon.parse::<f64>().ok());
+                let lat = element.attribute("lat").and_then(|lat| lat.parse::<f64>().ok());
+                match (lon, lat)
+                {
+                    (Some(lon), Some(lat)) => { extract.positions.insert(id, (lon, lat)); },
+                    _ => return Err(format!("OSM node {} has an invalid position.", id)),
+                }
+
+                if let Some(&(_, name)) = tags(element).iter().find(|(key, _)| *key == "name")
+                {
+                    extract.names.insert(id, name.to_string());
+                }
+            },
+            "way" =>
+            {
+                let refs = element.children_named("nd")
+                    .map(|nd| nd.attribute("ref").and_then(|id| id.parse::<i64>().ok()))
+                    .collect::<Option<Vec<i64>>>()
+                    .ok_or_else(|| format!("OSM way {} has an invalid node reference.", id(element).unwrap_or(0)))?;
+                extract.add_way(refs, tags(element).into_iter());
+            },
+            _ => (),
+        }
+    }
+
+    build(extract, bbox, duplicates, arena)
+}
+
+///
+/// Builds the road network from an OSM PBF extract (.osm.pbf)
+///
+/// - data: the PBF data
+/// - bbox: the area to keep roads within, or None to keep every road
+/// - duplicates: how to merge roads joining the same junctions
+/// - arena: holds the location names read
+///
+/// - return: Either an Ok Result with the Routes, or an Err with message
+///   explaining problem
+///
+pub fn parse_pbf<'a>(data: &[u8], bbox: Option<BoundingBox>, duplicates: Duplicates, arena: &'a Bump) -> Result<Routes<'a>, String>
+{
+    build(pbf::read(data)?, bbox, duplicates, arena)
+}
+
+///
+/// Builds the road network from an extract. Each junction (where roads
+/// meet or end) is a location, and each stretch of road between
+/// junctions is a route, as long as the road along the Earth's surface.
+/// Routes along one-way roads are one-way.
+///
+/// Junctions are named by their own name, or else the roads meeting
+/// there ("Main Street & 2nd Avenue"), or else their node id. Names
+/// shared by several junctions are followed by the node id, to tell them
+/// apart.
+///
+fn build<'a>(extract: Extract, bbox: Option<BoundingBox>, duplicates: Duplicates, arena: &'a Bump) -> Result<Routes<'a>, String>
+{
+    let kept = |id: &i64| extract.positions.get(id).is_some_and(|&position| bbox.is_none_or(|bbox| bbox.contains(position)));
+
+    // Each way, split wherever it leaves the box (or the extract)
+    let stretches = extract.ways.iter()
+        .flat_map(|way| way.refs.split(|id| !kept(id)).map(move |refs| (way, refs)))
+        .filter(|(_, refs)| refs.len() >= 2)
+        .collect::<Vec<(&Way, &[i64])>>();
+    if stretches.is_empty() { return Err(String::from("OSM extract has no roads within the area.")); }
+
+    // Junctions are the ends of each stretch, and nodes on several
+    let mut uses: HashMap<i64, usize> = HashMap::new();
+    let mut roads: HashMap<i64, BTreeSet<&str>> = HashMap::new();
+    for &(way, refs) in &stretches
+    {
+        for id in refs { *uses.entry(*id).or_insert(0) += 1; }
+        for id in [refs[0], refs[refs.len() - 1]] { *uses.entry(id).or_insert(0) += 1; }
+        for id in refs
+        {
+            let streets = roads.entry(*id).or_default();
+            if let Some(name) = &way.name { streets.insert(name); }
+        }
+    }
+    let mut junctions = uses.iter()
+        .filter(|&(_, &uses)| uses >= 2)
+        .map(|(&id, _)| id)
+        .collect::<Vec<i64>>();
+    junctions.sort_unstable();
+
+    // Name each junction by its own name, or else its roads
+    let mut names = Names::new(arena);
+    let mut named: HashMap<i64, String> = HashMap::new();
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for &id in &junctions
+    {
+        let name = extract.names.get(&id).cloned()
+            .or_else(|| Some(roads[&id].iter().cloned().collect::<Vec<&str>>().join(" & ")).filter(|name| !name.is_empty()))
+            .unwrap_or_else(|| format!("Node {}", id));
+        *counts.entry(name.clone()).or_insert(0) += 1;
+        named.insert(id, name);
+    }
+
+    let mut locations: HashMap<i64, &'a str> = HashMap::new();
+    let mut coordinates = HashMap::new();
+    for &id in &junctions
+    {
+        let name = &named[&id];
+        let name = if counts[name] > 1 { names.get(&format!("{} #{}", name, id)) } else { names.get(name) };
+        locations.insert(id, name);
+        coordinates.insert(name, extract.positions[&id]);
+    }
+
+    let mut edges = Vec::new();
+    let mut attributes = HashMap::new();
+    for &(way, refs) in &stretches
+    {
+        let route = RouteAttributes
+        {
+            highway: Some(names.get(&way.highway)),
+            speed_limit: way.speed_limit,
+            surface: way.surface.as_ref().map(|surface| names.get(surface)),
+        };
+        let (mut from, mut length) = (locations[&refs[0]], 0.0);
+        for pair in refs.windows(2)
+        {
+            length += haversine_miles(extract.positions[&pair[0]], extract.positions[&pair[1]]);
+            if let Some(&to) = locations.get(&pair[1])
+            {
+                if to != from
+                {
+                    // Round length up to the next 10th
+                    edges.push((from, to, Unit::Miles.to_weight(length).ceil() as i64, way.one_way));
+                    add_attributes(&mut attributes, from, to, route);
+                }
+                from = to;
+                length = 0.0;
+            }
+        }
+    }
+
+    let routes = build_routes(edges, duplicates, Unit::Miles)?;
+
+    Ok(Routes { coordinates, geographic: true, attributes, ..routes })
+}
@@ -0,0 +1,359 @@
+use std::borrow::Cow;
+use std::io::Read;
+
+use flate2::read::ZlibDecoder;
+
+use super::osm::Extract;
+
+// The only optional features a PBF file may require and still be read
+const SUPPORTED_FEATURES: [&str; 2] = ["OsmSchema-V0.6", "DenseNodes"];
+
+///
+/// A protocol buffer field's value
+///
+enum Value<'d>
+{
+    Varint(u64),
+    Bytes(&'d [u8]),
+    Fixed,
+}
+
+///
+/// Reads protocol buffer fields, in order
+///
+struct Reader<'d>
+{
+    data: &'d [u8],
+    pos: usize,
+}
+
+impl<'d> Reader<'d>
+{
+    fn new(data: &'d [u8]) -> Self
+    {
+        Reader { data, pos: 0 }
+    }
+
+    fn varint(&mut self) -> Result<u64, String>
+    {
+        let mut value = 0;
+        for shift in (0..64).step_by(7)
+        {
+            let byte = *self.data.get(self.pos).ok_or_else(|| String::from("Invalid PBF: truncated number."))?;
+            self.pos += 1;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 { return Ok(value); }
+        }
+
+        Err(String::from("Invalid PBF: number too long."))
+    }
+
+    ///
+    /// Reads the next field
+    ///
+    /// - return: Either an Ok Result with the field's number and value, or
+    ///   None at the end of the data, or an Err if the data is invalid
+    ///
+    fn field(&mut self) -> Result<Option<(u64, Value<'d>)>, String>
+    {
+        if self.pos >= self.data.len() { return Ok(None); }
+
+        let key = self.varint()?;
+        let skip = |reader: &mut Self, length: usize| -> Result<&'d [u8], String>
+        {
+            let bytes = reader.data.get(reader.pos..reader.pos + length)
+                .ok_or_else(|| String::from("Invalid PBF: truncated field."))?;
+            reader.pos += length;
+            Ok(bytes)
+        };
+
+        let value = match key & 7
+        {
+            0 => Value::Varint(self.varint()?),
+            1 => { skip(self, 8)?; Value::Fixed },
+            2 =>
+            {
+                let length = self.varint()? as usize;
+                Value::Bytes(skip(self, length)?)
+            },
+            5 => { skip(self, 4)?; Value::Fixed },
+            wire_type => return Err(format!("Invalid PBF: unknown field type {}.", wire_type)),
+        };
+
+        Ok(Some((key >> 3, value)))
+    }
+}
+
+///
+/// Reads a repeated number field's values, which are usually packed
+///
+fn numbers(value: Value, numbers: &mut Vec<u64>) -> Result<(), String>
+{
+    match value
+    {
+        Value::Varint(number) => numbers.push(number),
+        Value::Bytes(bytes) =>
+        {
+            let mut reader = Reader::new(bytes);
+            while reader.pos < bytes.len() { numbers.push(reader.varint()?); }
+        },
+        Value::Fixed => return Err(String::from("Invalid PBF: expected a number.")),
+    }
+
+    Ok(())
+}
+
+///
+/// Decodes a signed (zigzag-encoded) number
+///
+fn signed(number: u64) -> i64
+{
+    ((number >> 1) as i64) ^ -((number & 1) as i64)
+}
+
+///
+/// Sums delta-encoded signed numbers into their values
+///
+fn undelta(numbers: &[u64]) -> Vec<i64>
+{
+    numbers.iter()
+        .scan(0, |total, &number| { *total += signed(number); Some(*total) })
+        .collect()
+}
+
+///
+/// Reads the nodes and roads of an OSM PBF file: a sequence of blobs,
+/// each a header and a (usually zlib-compressed) block
+///
+/// - data: the PBF data
+///
+/// - return: Either an Ok Result with the Extract, or an Err with message
+///   explaining problem
+///
+pub(crate) fn read(data: &[u8]) -> Result<Extract, String>
+{
+    let mut extract = Extract::default();
+    let mut pos = 0;
+    while pos < data.len()
+    {
+        let truncated = || String::from("Invalid PBF: truncated blob.");
+
+        // BlobHeader: type (1), datasize (3), after its 4-byte length
+        let length = data.get(pos..pos + 4).ok_or_else(truncated)?;
+        let length = u32::from_be_bytes([length[0], length[1], length[2], length[3]]) as usize;
+        let mut header = Reader::new(data.get(pos + 4..pos + 4 + length).ok_or_else(truncated)?);
+        pos += 4 + length;
+
+        let (mut blob_type, mut size) = (&b""[..], 0);
+        while let Some((field, value)) = header.field()?
+        {
+            match (field, value)
+            {
+                (1, Value::Bytes(bytes)) => blob_type = bytes,
+                (3, Value::Varint(number)) => size = number as usize,
+                _ => (),
+            }
+        }
+        let blob = data.get(pos..pos + size).ok_or_else(truncated)?;
+        pos += size;
+
+        match blob_type
+        {
+            b"OSMHeader" => check_header(&decompress(blob)?)?,
+            b"OSMData" => read_block(&decompress(blob)?, &mut extract)?,
+            _ => (),
+        }
+    }
+
+    Ok(extract)
+}
+
+///
+/// Retrieves a Blob's contents: raw (1) or zlib_data (3)
+///
+fn decompress(blob: &[u8]) -> Result<Cow<'_, [u8]>, String>
+{
+    let mut reader = Reader::new(blob);
+    while let Some((field, value)) = reader.field()?
+    {
+        match (field, value)
+        {
+            (1, Value::Bytes(raw)) => return Ok(Cow::Borrowed(raw)),
+            (3, Value::Bytes(compressed)) =>
+            {
+                let mut contents = Vec::new();
+                ZlibDecoder::new(compressed).read_to_end(&mut contents)
+                    .map_err(|e| format!("Invalid PBF: {}", e))?;
+                return Ok(Cow::Owned(contents));
+            },
+            (4..=7, _) => return Err(String::from("PBF blob uses an unsupported compression; only zlib is supported.")),
+            _ => (),
+        }
+    }
+
+    Err(String::from("Invalid PBF: blob has no data."))
+}
+
+///
+/// Ensures a HeaderBlock requires (4) no features beyond those supported
+///
+fn check_header(block: &[u8]) -> Result<(), String>
+{
+    let mut reader = Reader::new(block);
+    while let Some((field, value)) = reader.field()?
+    {
+        if let (4, Value::Bytes(feature)) = (field, value)
+        {
+            let feature = String::from_utf8_lossy(feature);
+            if !SUPPORTED_FEATURES.contains(&feature.as_ref())
+            {
+                return Err(format!("PBF file requires unsupported feature \"{}\".", feature));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+///
+/// Reads a PrimitiveBlock's nodes and roads: its stringtable (1),
+/// primitivegroups (2), and coordinate granularity (17) and offsets
+/// (19, 20)
+///
+fn read_block(block: &[u8], extract: &mut Extract) -> Result<(), String>
+{
+    let mut strings = Vec::new();
+    let mut groups = Vec::new();
+    let (mut granularity, mut lat_offset, mut lon_offset) = (100, 0, 0);
+
+    let mut reader = Reader::new(block);
+    while let Some((field, value)) = reader.field()?
+    {
+        match (field, value)
+        {
+            (1, Value::Bytes(table)) =>
+            {
+                let mut table = Reader::new(table);
+                while let Some((field, value)) = table.field()?
+                {
+                    if let (1, Value::Bytes(string)) = (field, value) { strings.push(String::from_utf8_lossy(string)); }
+                }
+            },
+            (2, Value::Bytes(group)) => groups.push(group),
+            (17, Value::Varint(number)) => granularity = number as i64,
+            (19, Value::Varint(number)) => lat_offset = number as i64,
+            (20, Value::Varint(number)) => lon_offset = number as i64,
+            _ => (),
+        }
+    }
+
+    let string = |index: u64| strings.get(index as usize)
+        .map(|string| string.as_ref())
+        .ok_or_else(|| String::from("Invalid PBF: string out of range."));
+    let position = |lon: i64, lat: i64|
+        (1e-9 * (lon_offset + granularity * lon) as f64, 1e-9 * (lat_offset + granularity * lat) as f64);
+
+    for group in groups
+    {
+        // PrimitiveGroup: nodes (1), dense (2), ways (3)
+        let mut reader = Reader::new(group);
+        while let Some((field, value)) = reader.field()?
+        {
+            let message = match value
+            {
+                Value::Bytes(message) => message,
+                _ => continue,
+            };
+
+            match field
+            {
+                1 =>
+                {
+                    // Node: id (1), keys (2), vals (3), lat (8), lon (9)
+                    let (mut id, mut keys, mut vals, mut lat, mut lon) = (0, Vec::new(), Vec::new(), 0, 0);
+                    let mut node = Reader::new(message);
+                    while let Some((field, value)) = node.field()?
+                    {
+                        match (field, value)
+                        {
+                            (1, Value::Varint(number)) => id = signed(number),
+                            (2, value) => numbers(value, &mut keys)?,
+                            (3, value) => numbers(value, &mut vals)?,
+                            (8, Value::Varint(number)) => lat = signed(number),
+                            (9, Value::Varint(number)) => lon = signed(number),
+                            _ => (),
+                        }
+                    }
+
+                    extract.positions.insert(id, position(lon, lat));
+                    for (&key, &val) in keys.iter().zip(&vals)
+                    {
+                        if string(key)? == "name" { extract.names.insert(id, string(val)?.to_string()); }
+                    }
+                },
+                2 =>
+                {
+                    // DenseNodes: ids (1), lats (8) and lons (9), each
+                    // delta-encoded, and keys_vals (10), with each node's
+                    // tags ended by a 0
+                    let (mut ids, mut lats, mut lons, mut keys_vals) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+                    let mut dense = Reader::new(message);
+                    while let Some((field, value)) = dense.field()?
+                    {
+                        match field
+                        {
+                            1 => numbers(value, &mut ids)?,
+                            8 => numbers(value, &mut lats)?,
+                            9 => numbers(value, &mut lons)?,
+                            10 => numbers(value, &mut keys_vals)?,
+                            _ => (),
+                        }
+                    }
+
+                    let (ids, lats, lons) = (undelta(&ids), undelta(&lats), undelta(&lons));
+                    if lats.len() != ids.len() || lons.len() != ids.len()
+                    {
+                        return Err(String::from("Invalid PBF: dense nodes are missing positions."));
+                    }
+
+                    let mut tags = keys_vals.split(|&key| key == 0);
+                    for (i, &id) in ids.iter().enumerate()
+                    {
+                        extract.positions.insert(id, position(lons[i], lats[i]));
+                        for pair in tags.next().unwrap_or(&[]).chunks(2)
+                        {
+                            if let [key, val] = *pair
+                            {
+                                if string(key)? == "name" { extract.names.insert(id, string(val)?.to_string()); }
+                            }
+                        }
+                    }
+                },
+                3 =>
+                {
+                    // Way: keys (2), vals (3), delta-encoded refs (8)
+                    let (mut keys, mut vals, mut refs) = (Vec::new(), Vec::new(), Vec::new());
+                    let mut way = Reader::new(message);
+                    while let Some((field, value)) = way.field()?
+                    {
+                        match field
+                        {
+                            2 => numbers(value, &mut keys)?,
+                            3 => numbers(value, &mut vals)?,
+                            8 => numbers(value, &mut refs)?,
+                            _ => (),
+                        }
+                    }
+
+                    let tags = keys.iter().zip(&vals)
+                        .map(|(&key, &val)| Ok((string(key)?, string(val)?)))
+                        .collect::<Result<Vec<(&str, &str)>, String>>()?;
+                    extract.add_way(undelta(&refs), tags.into_iter());
+                },
+                _ => (),
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,186 @@
+use std::path::{Path, PathBuf};
+
+use log::info;
+
+use crate::algo::RouteGraph;
+
+// Marks the start of a sidecar file, and its version
+const MAGIC: &[u8; 8] = b"ROUTEART";
+const VERSION: u32 = 1;
+
+// The bytes before an artifact's own: the magic, version, kind, graph
+// hash and parameters
+const HEADER_LEN: usize = 8 + 4 + 8 + 8 + 8;
+
+///
+/// Preprocessing computed from a route Graph which is worth keeping in a
+/// sidecar file beside the routes, so that it is computed once rather
+/// than every time the routes are loaded
+///
+pub trait Artifact<'a>: Sized
+{
+    /// Names the kind of artifact in the sidecar's header
+    const KIND: &'static [u8; 8];
+
+    /// The sidecar file's extension, added after the route file's name
+    const EXTENSION: &'static str;
+
+    ///
+    /// Encodes the artifact, with locations given by their index in the
+    /// Graph's node order
+    ///
+    /// - graph: the Graph the artifact was computed from
+    ///
+    fn to_bytes(&self, graph: &RouteGraph<'a>) -> Vec<u8>;
+
+    ///
+    /// Decodes an artifact written by to_bytes for the same Graph
+    ///
+    /// - graph: the Graph the artifact was computed from
+    /// - bytes: the encoded artifact
+    ///
+    /// - return: the artifact, or None if the bytes do not hold one
+    ///
+    fn from_bytes(graph: &RouteGraph<'a>, bytes: &[u8]) -> Option<Self>;
+}
+
+///
+/// Hashes a Graph's locations, in node order, and its routes, as FNV-1a,
+/// which unlike the standard library's hasher is the same in every build
+///
+/// - graph: the Graph to hash
+///
+/// - return: the hash, which differs for any change to the routes
+///
+pub fn graph_hash(graph: &RouteGraph) -> u64
+{
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    let mut feed = |bytes: &[u8]| for &byte in bytes
+    {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    };
+
+    feed(&(graph.node_count() as u64).to_le_bytes());
+    for node in graph.nodes()
+    {
+        feed(&(node.len() as u64).to_le_bytes());
+        feed(node.as_bytes());
+    }
+    feed(&(graph.edge_count() as u64).to_le_bytes());
+    for (from, to, weight) in graph.all_edges()
+    {
+        for name in [from, to]
+        {
+            feed(&(name.len() as u64).to_le_bytes());
+            feed(name.as_bytes());
+        }
+        feed(&weight.to_le_bytes());
+    }
+
+    hash
+}
+
+///
+/// - return: the sidecar file an artifact of the given extension is kept
+///   in, beside the route file, such as "routes.txt.alt"
+///
+pub fn sidecar_path(routes: &Path, extension: &str) -> PathBuf
+{
+    let mut name = routes.as_os_str().to_owned();
+    name.push(".");
+    name.push(extension);
+    PathBuf::from(name)
+}
+
+///
+/// Reads an artifact from its sidecar file beside the routes, if the file
+/// holds one computed from the same Graph with the same parameters, or
+/// else computes it and writes the sidecar for the next time. A sidecar
+/// which cannot be written is noted in the log, and is otherwise no
+/// problem
+///
+/// - graph: the Graph the artifact is computed from
+/// - routes: the route file the Graph was loaded from
+/// - parameters: whatever else the artifact depends on, such as the #
+///   of landmarks chosen
+/// - build: computes the artifact
+///
+/// - return: the artifact
+///
+pub fn load_or_build<'a, A: Artifact<'a>, F: FnOnce() -> A>(graph: &RouteGraph<'a>, routes: &Path, parameters: u64, build: F) -> A
+{
+    let path = sidecar_path(routes, A::EXTENSION);
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(MAGIC);
+    header.extend_from_slice(&VERSION.to_le_bytes());
+    header.extend_from_slice(A::KIND);
+    header.extend_from_slice(&graph_hash(graph).to_le_bytes());
+    header.extend_from_slice(&parameters.to_le_bytes());
+
+    if let Ok(bytes) = std::fs::read(&path)
+    {
+        match bytes.strip_prefix(header.as_slice()).and_then(|bytes| A::from_bytes(graph, bytes))
+        {
+            Some(artifact) =>
+            {
+                info!("Read the preprocessed {} from \"{}\"", A::EXTENSION, path.display());
+                return artifact;
+            },
+            None => info!("\"{}\" was computed from other routes, so is computed again", path.display()),
+        }
+    }
+
+    let artifact = build();
+    header.extend_from_slice(&artifact.to_bytes(graph));
+    match std::fs::write(&path, header)
+    {
+        Ok(()) => info!("Wrote the preprocessed {} to \"{}\"", A::EXTENSION, path.display()),
+        Err(e) => info!("Cannot write \"{}\": {}", path.display(), e),
+    }
+    artifact
+}
+
+///
+/// Reads little-endian numbers from the front of a sidecar's bytes
+///
+pub(crate) struct Reader<'b>
+{
+    bytes: &'b [u8],
+}
+
+impl<'b> Reader<'b>
+{
+    pub(crate) fn new(bytes: &'b [u8]) -> Self
+    {
+        Reader { bytes }
+    }
+
+    ///
+    /// - return: the next u32, or None if the bytes end first
+    ///
+    pub(crate) fn u32(&mut self) -> Option<u32>
+    {
+        let (number, rest) = self.bytes.split_first_chunk::<4>()?;
+        self.bytes = rest;
+        Some(u32::from_le_bytes(*number))
+    }
+
+    ///
+    /// - return: the next u64, or None if the bytes end first
+    ///
+    pub(crate) fn u64(&mut self) -> Option<u64>
+    {
+        let (number, rest) = self.bytes.split_first_chunk::<8>()?;
+        self.bytes = rest;
+        Some(u64::from_le_bytes(*number))
+    }
+
+    ///
+    /// - return: true if every byte has been read
+    ///
+    pub(crate) fn is_done(&self) -> bool
+    {
+        self.bytes.is_empty()
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::{BTreeSet, HashMap};
+
+use super::Routes;
+
+///
+/// The problems found in a set of routes, and between them and their
+/// heuristic file, each list sorted by name
+///
+/// - self_loops: each location with a route back to itself
+/// - zero_weights: each route of weight 0, as (from, to)
+/// - duplicates: each route listed more than once, as (from, to), once
+///   for each extra listing
+/// - isolated: each location without a route to any other location
+/// - unrouted: each location the heuristic file names which no route does
+/// - unestimated: each location with routes which the heuristic file
+///   never names
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Validation<'a>
+{
+    pub self_loops: Vec<&'a str>,
+    pub zero_weights: Vec<(&'a str, &'a str)>,
+    pub duplicates: Vec<(&'a str, &'a str)>,
+    pub isolated: Vec<&'a str>,
+    pub unrouted: Vec<&'a str>,
+    pub unestimated: Vec<&'a str>,
+}
+
+impl Validation<'_>
+{
+    ///
+    /// - return: the # of problems found, of every kind
+    ///
+    pub fn problems(&self) -> usize
+    {
+        self.self_loops.len() + self.zero_weights.len() + self.duplicates.len()
+            + self.isolated.len() + self.unrouted.len() + self.unestimated.len()
+    }
+}
+
+///
+/// Checks loaded routes for routes which are likely mistakes, and
+/// locations which no route reaches or which the heuristic file and the
+/// routes disagree on
+///
+/// - routes: the routes loaded
+/// - heuristic: the heuristic file's table, if any
+///
+/// - return: the Validation
+///
+pub fn validate<'a>(routes: &Routes<'a>, heuristic: Option<&HashMap<(&'a str, &'a str), u64>>) -> Validation<'a>
+{
+    let graph = &routes.graph;
+    let mut validation = Validation { duplicates: routes.duplicates.clone(), ..Validation::default() };
+
+    let weights = match &routes.arcs
+    {
+        Some(arcs) => arcs.all_edges().map(|(from, to, &weight)| (from, to, weight)).collect::<Vec<(&'a str, &'a str, u64)>>(),
+        None => graph.all_edges().map(|(from, to, &weight)| (from, to, weight)).collect(),
+    };
+    for (from, to, weight) in weights
+    {
+        if from == to { validation.self_loops.push(from); }
+        if weight == 0 { validation.zero_weights.push((from, to)); }
+    }
+    validation.isolated = graph.nodes().filter(|&node| graph.neighbors(node).all(|neighbor| neighbor == node)).collect();
+
+    if let Some(heuristic) = heuristic
+    {
+        let named = heuristic.keys().flat_map(|&(from, to)| [from, to]).collect::<BTreeSet<&'a str>>();
+        validation.unrouted = named.iter().cloned().filter(|&name| !graph.contains_node(name)).collect();
+        validation.unestimated = graph.nodes().filter(|node| !named.contains(node)).collect();
+    }
+
+    validation.self_loops.sort_unstable();
+    validation.self_loops.dedup();
+    validation.zero_weights.sort_unstable();
+    validation.duplicates.sort_unstable();
+    validation.isolated.sort_unstable();
+    validation.unestimated.sort_unstable();
+    validation
+}
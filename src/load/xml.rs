@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+
+///
+/// An XML element, with its attributes, child elements, and text
+///
+/// - name: the element's name, without any namespace prefix
+/// - attributes: the element's attributes, by name without any
+///   namespace prefix
+/// - children: the element's child elements, in order
+/// - text: all text directly within the element, joined
+///
+pub struct Element
+{
+    pub name: String,
+    pub attributes: HashMap<String, String>,
+    pub children: Vec<Element>,
+    pub text: String,
+}
+
+impl Element
+{
+    ///
+    /// Retrieves the value of one of the Element's attributes
+    ///
+    pub fn attribute(&self, name: &str) -> Option<&str>
+    {
+        self.attributes.get(name).map(String::as_str)
+    }
+
+    ///
+    /// Retrieves the Element's children with the given name
+    ///
+    pub fn children_named<'e>(&'e self, name: &'e str) -> impl Iterator<Item = &'e Element>
+    {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    ///
+    /// Finds the first Element with the given name among the Element's
+    /// descendants, searching depth-first
+    ///
+    pub fn descendant(&self, name: &str) -> Option<&Element>
+    {
+        self.children.iter()
+            .find_map(|child| if child.name == name { Some(child) } else { child.descendant(name) })
+    }
+}
+
+///
+/// Parses an XML document into its root Element. Comments, processing
+/// instructions and DOCTYPEs are skipped, and CDATA is read as text.
+/// Namespaces are not resolved; prefixes are simply removed from names.
+///
+/// - text: the XML document
+///
+/// - return: Either an Ok Result with the root Element, or an Err with
+///   message explaining problem
+///
+pub fn parse(text: &str) -> Result<Element, String>
+{
+    let error = |pos: usize, message: &str|
+        format!("Invalid XML on line {}: {}", text[..pos].matches('\n').count() + 1, message);
+
+    // Open Elements, innermost last
+    let mut open: Vec<Element> = Vec::new();
+    let mut root = None;
+    let mut pos = 0;
+
+    while pos < text.len()
+    {
+        let rest = &text[pos..];
+        if !rest.starts_with('<')
+        {
+            // Text runs to the next tag
+            let end = rest.find('<').unwrap_or(rest.len());
+            match open.last_mut()
+            {
+                Some(element) => element.text.push_str(&unescape(&rest[..end]).map_err(|e| error(pos, &e))?),
+                None if rest[..end].trim().is_empty() => (),
+                None => return Err(error(pos, "text outside of the root element.")),
+            }
+            pos += end;
+        }
+        else if let Some(cdata) = rest.strip_prefix("<![CDATA[")
+        {
+            let end = cdata.find("]]>").ok_or_else(|| error(pos, "unterminated CDATA."))?;
+            if let Some(element) = open.last_mut() { element.text.push_str(&cdata[..end]); }
+            pos += "<![CDATA[".len() + end + "]]>".len();
+        }
+        else if rest.starts_with("<!--")
+        {
+            pos += rest.find("-->").ok_or_else(|| error(pos, "unterminated comment."))? + "-->".len();
+        }
+        else if rest.starts_with("<?")
+        {
+            pos += rest.find("?>").ok_or_else(|| error(pos, "unterminated processing instruction."))? + "?>".len();
+        }
+        else if rest.starts_with("<!")
+        {
+            pos += rest.find('>').ok_or_else(|| error(pos, "unterminated declaration."))? + 1;
+        }
+        else if let Some(closing) = rest.strip_prefix("</")
+        {
+            let end = closing.find('>').ok_or_else(|| error(pos, "unterminated closing tag."))?;
+            let name = local_name(closing[..end].trim());
+            let element = open.pop()
+                .filter(|element| element.name == name)
+                .ok_or_else(|| error(pos, &format!("unexpected closing tag \"{}\".", name)))?;
+
+            match open.last_mut()
+            {
+                Some(parent) => parent.children.push(element),
+                None => root = Some(element),
+            }
+            pos += "</".len() + end + 1;
+        }
+        else
+        {
+            if root.is_some() { return Err(error(pos, "more than one root element.")); }
+
+            let (element, empty, length) = parse_tag(rest).map_err(|e| error(pos, &e))?;
+            if empty
+            {
+                match open.last_mut()
+                {
+                    Some(parent) => parent.children.push(element),
+                    None => root = Some(element),
+                }
+            }
+            else
+            {
+                open.push(element);
+            }
+            pos += length;
+        }
+    }
+
+    if let Some(element) = open.last()
+    {
+        return Err(error(text.len(), &format!("unclosed element \"{}\".", element.name)));
+    }
+    root.ok_or_else(|| error(text.len(), "no root element."))
+}
+
+///
+/// Parses an opening tag, at the start of text
+///
+/// - return: the Element opened, whether the tag is self-closing (and so
+///   has no content), and the length of the tag
+///
+fn parse_tag(text: &str) -> Result<(Element, bool, usize), String>
+{
+    let name_end = |s: &str| s.find(|c: char| c.is_whitespace() || c == '/' || c == '>' || c == '=').unwrap_or(s.len());
+
+    let mut rest = &text[1..];
+    let end = name_end(rest);
+    if end == 0 { return Err(String::from("expected an element name.")); }
+
+    let mut element = Element
+    {
+        name: local_name(&rest[..end]).to_string(),
+        attributes: HashMap::new(),
+        children: Vec::new(),
+        text: String::new(),
+    };
+    rest = &rest[end..];
+
+    loop
+    {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix("/>")
+        {
+            return Ok((element, true, text.len() - after.len()));
+        }
+        if let Some(after) = rest.strip_prefix('>')
+        {
+            return Ok((element, false, text.len() - after.len()));
+        }
+
+        // An attribute, as name="value" or name='value'
+        let end = name_end(rest);
+        if end == 0 { return Err(format!("unterminated tag \"{}\".", element.name)); }
+        let name = local_name(&rest[..end]);
+
+        rest = rest[end..].trim_start().strip_prefix('=')
+            .ok_or_else(|| format!("attribute \"{}\" has no value.", name))?
+            .trim_start();
+        let quote = rest.chars().next()
+            .filter(|&c| c == '"' || c == '\'')
+            .ok_or_else(|| format!("attribute \"{}\" is not quoted.", name))?;
+        let end = rest[1..].find(quote).ok_or_else(|| format!("attribute \"{}\" is unterminated.", name))?;
+
+        element.attributes.insert(name.to_string(), unescape(&rest[1..end + 1])?);
+        rest = &rest[end + 2..];
+    }
+}
+
+///
+/// Removes any namespace prefix from a name
+///
+fn local_name(name: &str) -> &str
+{
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+///
+/// Replaces the character and entity references in text with the
+/// characters they stand for
+///
+fn unescape(text: &str) -> Result<String, String>
+{
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&')
+    {
+        unescaped.push_str(&rest[..start]);
+        let end = rest[start..].find(';').ok_or_else(|| String::from("unterminated reference."))?;
+        let reference = &rest[start + 1..start + end];
+
+        let c = match reference
+        {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match reference.strip_prefix("#x").or_else(|| reference.strip_prefix("#X"))
+            {
+                Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                None => reference.strip_prefix('#')
+                    .and_then(|dec| dec.parse::<u32>().ok())
+                    .and_then(char::from_u32),
+            },
+        };
+        unescaped.push(c.ok_or_else(|| format!("unknown reference \"&{};\".", reference))?);
+        rest = &rest[start + end + 1..];
+    }
+    unescaped.push_str(rest);
+
+    Ok(unescaped)
+}
+
+///
+/// Replaces the characters in text which cannot appear as they are in
+/// XML text or attribute values with their entity references
+///
+pub fn escape(text: &str) -> String
+{
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars()
+    {
+        match c
+        {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
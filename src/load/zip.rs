@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::io::Read;
+
+use flate2::read::DeflateDecoder;
+
+// Signatures of the zip records read
+const END_OF_DIRECTORY: u32 = 0x0605_4b50;
+const DIRECTORY_ENTRY: u32 = 0x0201_4b50;
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+
+fn u16_at(data: &[u8], pos: usize) -> Result<usize, String>
+{
+    data.get(pos..pos + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]) as usize)
+        .ok_or_else(|| String::from("Invalid zip file: truncated record."))
+}
+
+fn u32_at(data: &[u8], pos: usize) -> Result<u32, String>
+{
+    data.get(pos..pos + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or_else(|| String::from("Invalid zip file: truncated record."))
+}
+
+///
+/// Extracts the files of a zip archive, stored or deflated, through its
+/// central directory. Directories are skipped, and each file is keyed by
+/// its name alone, without its folder.
+///
+/// - data: the zip archive
+///
+/// - return: Either an Ok Result with the contents of each file, or an
+///   Err with message explaining problem
+///
+pub(crate) fn extract(data: &[u8]) -> Result<HashMap<String, Vec<u8>>, String>
+{
+    // The end of directory record is last, before a comment of at most
+    // 65535 bytes
+    let search_start = data.len().saturating_sub(22 + 0xffff);
+    let end = (search_start..data.len().saturating_sub(21)).rev()
+        .find(|&pos| u32_at(data, pos) == Ok(END_OF_DIRECTORY))
+        .ok_or_else(|| String::from("Invalid zip file: no central directory."))?;
+
+    let count = u16_at(data, end + 10)?;
+    let mut pos = u32_at(data, end + 16)? as usize;
+    if count == 0xffff || pos == 0xffff_ffff { return Err(String::from("Zip64 archives are not supported.")); }
+
+    let mut files = HashMap::new();
+    for _ in 0..count
+    {
+        if u32_at(data, pos)? != DIRECTORY_ENTRY { return Err(String::from("Invalid zip file: bad directory entry.")); }
+        let method = u16_at(data, pos + 10)?;
+        let compressed_size = u32_at(data, pos + 20)? as usize;
+        let size = u32_at(data, pos + 24)? as usize;
+        let (name_length, extra_length, comment_length) = (u16_at(data, pos + 28)?, u16_at(data, pos + 30)?, u16_at(data, pos + 32)?);
+        let header = u32_at(data, pos + 42)? as usize;
+        let name = data.get(pos + 46..pos + 46 + name_length)
+            .map(String::from_utf8_lossy)
+            .ok_or_else(|| String::from("Invalid zip file: truncated record."))?
+            .to_string();
+        pos += 46 + name_length + extra_length + comment_length;
+
+        if name.ends_with('/') { continue; }
+
+        // The file's data follows its local header
+        if u32_at(data, header)? != LOCAL_HEADER { return Err(format!("Invalid zip file: bad header for \"{}\".", name)); }
+        let start = header + 30 + u16_at(data, header + 26)? + u16_at(data, header + 28)?;
+        let compressed = data.get(start..start + compressed_size)
+            .ok_or_else(|| format!("Invalid zip file: \"{}\" is truncated.", name))?;
+
+        let contents = match method
+        {
+            0 => compressed.to_vec(),
+            8 =>
+            {
+                let mut contents = Vec::with_capacity(size);
+                DeflateDecoder::new(compressed).read_to_end(&mut contents)
+                    .map_err(|e| format!("Invalid zip file: \"{}\": {}", name, e))?;
+                contents
+            },
+            _ => return Err(format!("Zip file \"{}\" uses an unsupported compression method.", name)),
+        };
+
+        let file_name = name.rsplit('/').next().unwrap_or(&name).to_string();
+        files.insert(file_name, contents);
+    }
+
+    Ok(files)
+}
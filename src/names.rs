@@ -0,0 +1,125 @@
+use std::collections::{HashMap, HashSet};
+
+///
+/// Finds locations by their names as typed: exactly, or else ignoring
+/// case and spacing, so "  new   york city" finds "New York City"
+///
+/// - exact: every name
+/// - normalized: each normalized name, with the name it was normalized
+///   from, or None if several names normalize alike
+///
+#[derive(Debug, Clone)]
+pub struct NameIndex<'a>
+{
+    exact: HashSet<&'a str>,
+    normalized: HashMap<String, Option<&'a str>>,
+}
+
+impl<'a> NameIndex<'a>
+{
+    ///
+    /// Indexes a set of names
+    ///
+    /// - names: every name to find
+    ///
+    pub fn new<I: IntoIterator<Item = &'a str>>(names: I) -> Self
+    {
+        let exact = names.into_iter().collect::<HashSet<&'a str>>();
+        let mut normalized = HashMap::new();
+        for &name in &exact
+        {
+            normalized.entry(normalize(name))
+                .and_modify(|found: &mut Option<&'a str>| *found = None)
+                .or_insert(Some(name));
+        }
+
+        NameIndex { exact, normalized }
+    }
+
+    ///
+    /// Finds a name as typed
+    ///
+    /// - name: the name, as typed
+    ///
+    /// - return: the name it matches exactly, or else the one name it
+    ///   matches ignoring case and spacing, if any
+    ///
+    pub fn find(&self, name: &str) -> Option<&'a str>
+    {
+        match self.exact.get(name)
+        {
+            Some(&name) => Some(name),
+            None => self.normalized.get(&normalize(name)).cloned().flatten(),
+        }
+    }
+}
+
+///
+/// Normalizes a name for matching, trimming it, collapsing any runs of
+/// whitespace within it to single spaces, and folding its case
+///
+/// - name: the name
+///
+/// - return: the normalized name
+///
+pub fn normalize(name: &str) -> String
+{
+    name.split_whitespace().collect::<Vec<&str>>().join(" ").to_lowercase()
+}
+
+///
+/// Counts the single-character insertions, deletions and substitutions
+/// needed to turn one name into another (their Levenshtein distance),
+/// ignoring case
+///
+/// - a: the first name
+/// - b: the second name
+///
+/// - return: the edit distance between the names
+///
+pub fn edit_distance(a: &str, b: &str) -> usize
+{
+    let a = a.to_lowercase().chars().collect::<Vec<char>>();
+    let b = b.to_lowercase().chars().collect::<Vec<char>>();
+
+    // Each row holds the distances from a prefix of a to every prefix of b
+    let mut row = (0..=b.len()).collect::<Vec<usize>>();
+    for (i, &a_char) in a.iter().enumerate()
+    {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &b_char) in b.iter().enumerate()
+        {
+            let substitution = diagonal + if a_char == b_char { 0 } else { 1 };
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+///
+/// Finds the names closest to a mistyped name, allowing about one edit
+/// for every three characters
+///
+/// - name: the name as typed
+/// - candidates: every name it might have meant
+/// - count: the most names to return
+///
+/// - return: up to count of the closest candidates, closest first, or
+///   none if no candidate is close enough (or the name is blank)
+///
+pub fn closest<'n, I: IntoIterator<Item = &'n str>>(name: &str, candidates: I, count: usize) -> Vec<&'n str>
+{
+    if name.trim().is_empty() { return Vec::new(); }
+
+    let allowed = (name.chars().count() / 3).max(1);
+    let mut close = candidates.into_iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|&(distance, _)| distance <= allowed)
+        .collect::<Vec<(usize, &str)>>();
+    close.sort();
+
+    close.into_iter().take(count).map(|(_, candidate)| candidate).collect()
+}
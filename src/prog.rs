@@ -1,16 +1,257 @@
-use std::collections::HashMap;
-use std::cmp::Reverse;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
 
-use petgraph::graphmap::UnGraphMap;
-use priority_queue::PriorityQueue;
+use bumpalo::Bump;
+use log::{debug, info, warn};
+use rayon::prelude::*;
+use owo_colors::{OwoColorize, Stream};
+use petgraph::Direction::{Incoming, Outgoing};
 use stopwatch::Stopwatch;
 
+use super::algo::admissibility::{check_heuristic, HeuristicCheck};
+use super::algo::alt::Landmarks;
+use super::algo::analysis::{self, Analysis, Components};
+use super::algo::ara_star::{self, ara_star};
+use super::algo::astar::{self, Expansion, TieBreak};
+use super::algo::ch::ContractionHierarchy;
+use super::algo::dag::{topological_order, DirectedRouteGraph};
+use super::algo::dstar_lite::DStarLite;
+use super::algo::context::{Counters, SearchContext};
+use super::algo::frontier::{Frontier, MAX_BUCKET_WEIGHT};
+use super::algo::grid::Connectivity;
+use super::algo::hpa::Hierarchy;
+use super::algo::incremental::IncrementalSearch;
+use super::algo::intern::{InternedGraph, SearchGraph};
+use super::algo::ksp::KspBackend;
+use super::algo::lpa_star::LpaStar;
+use super::algo::mst::{self, SpanningTree};
+use super::algo::tsp::{self, Tour};
+use super::algo::{focal, ksp, postman, sma_star, Algorithm, Heuristic, PathResult, RouteGraph, Step};
+use super::cache::LruCache;
+use super::export::expansions::{self, ExpandedNode};
+use super::export::stats::{self, SearchStats};
+use super::export::{self, dot::to_dot, gpx::to_gpx, layout::force_directed, svg::to_svg};
+use super::history::{self, format_time, Query};
+use super::names::{self, NameIndex};
 use super::io::*;
+use super::report::{error_json, AlgorithmReport, Leg, RouteReport};
+use super::load::grid::GridMap;
+use super::load::movingai::{cell_name, Scenario};
+use super::load::sidecar;
+use super::load::{build_heur_data, build_map, split_names, unquote, Duplicates, Unit};
+
+/// The algorithms compared on every query, in the order they are run
+const COMPARED_ALGORITHMS: [Algorithm; 3] = [Algorithm::AStar, Algorithm::Dijkstra, Algorithm::Fringe];
+
+/// The # of decimal places printed in distances, unless set otherwise
+pub const DEFAULT_PRECISION: usize = 1;
+
+/// How far a scenario's route length may be from its optimal length, in
+/// cells, and still match it
+const SCENARIO_TOLERANCE: f64 = 1e-4;
+
+/// The # of names suggested for a location which does not exist
+const SUGGESTION_COUNT: usize = 3;
+
+/// The # of locations named from each side when two locations are in
+/// separate connected groups
+const COMPONENT_MEMBERS_SHOWN: usize = 3;
+
+/// The # of the most recent searches the "History" command lists
+const HISTORY_SHOWN: usize = 20;
+
+/// The # of the largest inadmissible and inconsistent estimates printed
+/// by run_heuristic_check
+const PROBLEMS_SHOWN: usize = 20;
+
+/// The # of locations listed on each page above the prompt
+const LOCATIONS_PER_PAGE: usize = 50;
+
+/// The exit code when scripted queries all found routes
+pub const EXIT_FOUND: i32 = 0;
+
+/// The exit code when some scripted query's end could not be reached
+pub const EXIT_UNREACHABLE: i32 = 2;
+
+/// The exit code when some scripted query named an unknown location
+pub const EXIT_UNKNOWN_LOCATION: i32 = 3;
+
+///
+/// How the Program writes the results of each query
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum OutputFormat
+{
+    /// Console text, guiding the user through prompts
+    #[default]
+    Text,
+    /// One line of JSON per query, read as start and end lines without
+    /// prompts, for other tools to consume
+    Json,
+    /// One tab-separated line per query, with prompts and messages on
+    /// standard error and an exit code for the results, for shell scripts
+    Script,
+}
+
+impl OutputFormat
+{
+    ///
+    /// Finds the OutputFormat with the given name, such as "json"
+    ///
+    pub fn from_name(name: &str) -> Option<OutputFormat>
+    {
+        match name.to_lowercase().as_str()
+        {
+            "text" => Some(OutputFormat::Text),
+            "json" => Some(OutputFormat::Json),
+            "script" | "tsv" => Some(OutputFormat::Script),
+            _ => None,
+        }
+    }
+}
+
+///
+/// A search's result: the route found, if any, the # of nodes considered,
+/// the time taken to search, in microseconds, and the search's Counters,
+/// if it searched the interned Graph
+///
+type Search<'a> = (Option<PathResult<'a>>, usize, u128, Option<Counters>);
+
+///
+/// What a cached search is looked up by: its start and end locations' IDs
+/// on the interned Graph, and the algorithm, tie-breaking and frontier it
+/// was searched with
+///
+type QueryKey = (u32, u32, Algorithm, TieBreak, Frontier);
+
+///
+/// A cached search, as a Search with its route's locations held by ID on
+/// the interned Graph and without the time taken
+///
+type CachedSearch = (Option<(Vec<u32>, u64)>, usize, Option<Counters>);
+
+///
+/// The searches recently run, shared between the threads searching
+///
+type SearchCache = Mutex<LruCache<QueryKey, CachedSearch>>;
+
+///
+/// Everything a search reads from a Program, borrowed apart from the
+/// Program's per-query state, so that searches can share it between
+/// threads
+///
+struct Searcher<'p, 'a>
+{
+    route_dat: &'p RouteGraph<'a>,
+    arcs: &'p Option<DirectedRouteGraph<'a>>,
+    grid: Option<&'p GridMap<'a>>,
+    interned: &'p InternedGraph<'a>,
+    heuristic: &'p dyn Heuristic,
+    tie_break: TieBreak,
+    cache: Option<&'p SearchCache>,
+}
+
+impl<'p, 'a> Searcher<'p, 'a>
+{
+    ///
+    /// Routes between two nodes on the Graph using the given algorithm,
+    /// following one-way routes if any, or across the grid map's Grid for
+    /// the algorithms which search only a Grid
+    ///
+    /// - start: the start location on the Graph
+    /// - end: the end location on the Graph
+    /// - algorithm: the algorithm to route with
+    /// - context: the buffers to search in
+    /// - on_expand: called with each node A* and Djikstra's algorithm take
+    ///   from their queue
+    ///
+    fn search<F: FnMut(Expansion<'a>)>(&self, start: &'a str, end: &'a str, algorithm: Algorithm, context: &mut SearchContext, on_expand: F) -> Search<'a>
+    {
+        let mut sw = Stopwatch::new();
+        sw.start();
+        let gridded = self.grid.and_then(|grid| grid.search(algorithm, start, end));
+        let interned = match gridded
+        {
+            Some(_) => None,
+            None => algorithm.search_interned(self.interned, self.heuristic, start, end, self.tie_break, context, on_expand),
+        };
+        let counters = interned.is_some().then(|| context.counters());
+        let (route, node_counter) = match (gridded.or(interned), self.arcs)
+        {
+            (Some(found), _) => found,
+            (None, Some(arcs)) => algorithm.search(arcs, self.heuristic, start, end, self.tie_break),
+            (None, None) => algorithm.search(self.route_dat, self.heuristic, start, end, self.tie_break),
+        };
+        sw.stop();
+
+        (route, node_counter, sw.elapsed().as_micros(), counters)
+    }
+
+    ///
+    /// Routes between two nodes as search does, first looking for the
+    /// same search in the cache, if any. A search found there is returned
+    /// with the # of nodes considered and Counters of the search which
+    /// ran it, but the time taken to find it in the cache. Searches not
+    /// found are run and then cached
+    ///
+    fn search_cached<F: FnMut(Expansion<'a>)>(&self, start: &'a str, end: &'a str, algorithm: Algorithm, context: &mut SearchContext, on_expand: F) -> Search<'a>
+    {
+        let (cache, from, to) = match (self.cache, self.interned.id(start), self.interned.id(end))
+        {
+            (Some(cache), Some(from), Some(to)) => (cache, from, to),
+            _ => return self.search(start, end, algorithm, context, on_expand),
+        };
+
+        let key = (from, to, algorithm, self.tie_break, context.frontier());
+        let sw = Stopwatch::start_new();
+        if let Some((route, node_counter, counters)) = cache.lock().unwrap().get(&key).cloned()
+        {
+            debug!("Found the {} search from {} to {} in the cache", algorithm.name(), start, end);
+            let route = route.map(|(nodes, cost)| PathResult { nodes: nodes.into_iter().map(|id| self.interned.name(id)).collect(), cost });
+            return (route, node_counter, sw.elapsed().as_micros(), counters);
+        }
+
+        let (route, node_counter, micros, counters) = self.search(start, end, algorithm, context, on_expand);
+        let ids = route.as_ref().map(|route| (route.nodes.iter().filter_map(|&node| self.interned.id(node)).collect(), route.cost));
+        cache.lock().unwrap().insert(key, (ids, node_counter, counters));
+        (route, node_counter, micros, counters)
+    }
+}
 
 pub struct Program<'a>
 {
-    route_dat: UnGraphMap<&'a str, u64>,
-    heur_map: HashMap<(&'a str, &'a str), u64>,
+    route_dat: RouteGraph<'a>,
+    names: NameIndex<'a>,
+    arcs: Option<DirectedRouteGraph<'a>>,
+    acyclic: bool,
+    grid: Option<GridMap<'a>>,
+    interned: InternedGraph<'a>,
+    reorder: bool,
+    components: Components<'a>,
+    heuristic: Box<dyn Heuristic + 'a>,
+    tie_break: TieBreak,
+    context: RefCell<SearchContext>,
+    arena: RefCell<Bump>,
+    unit: Unit,
+    algorithms: Vec<Algorithm>,
+    precision: usize,
+    output: OutputFormat,
+    coordinates: HashMap<&'a str, (f64, f64)>,
+    geographic: bool,
+    gpx: Option<PathBuf>,
+    stats: Option<PathBuf>,
+    expansions: Option<PathBuf>,
+    history: Option<PathBuf>,
+    speed: Option<f64>,
+    progress: bool,
+    threads: usize,
+    cache: Option<SearchCache>,
+    artifacts: Option<PathBuf>,
+    incremental: Option<IncrementalSearch<'a>>,
+    last_route: Vec<String>,
 }
 
 impl<'a> Program<'a>
@@ -25,295 +266,2708 @@ impl<'a> Program<'a>
     /// heur_file_txt: the heuristic information, by which the
     ///     heuristic HashMap will be built
     /// 
-    pub fn new(route_file_txt: &'a String, heur_file_txt: &'a String) -> Self
+    /// - Return: Either an Ok Result with the Program, or an Err with
+    ///   message explaining why the routes or heuristic data could not
+    ///   be loaded
+    /// 
+    pub fn new(route_file_txt: &'a str, heur_file_txt: &'a str) -> Result<Self, String>
+    {
+        let routes = build_map(route_file_txt, false, Duplicates::default(), ',', Unit::default())?;
+        let heuristic = build_heur_data(heur_file_txt, None, Unit::default())?;
+        Ok(Program::base(routes.graph, routes.arcs, Box::new(heuristic)))
+    }
+
+    ///
+    /// Creates a new program from an already-loaded Graph and heuristic
+    ///
+    /// route_dat: the Graph to route over
+    ///
+    /// heuristic: estimates the distance between any two locations
+    ///
+    pub fn from_graph(route_dat: RouteGraph<'a>, heuristic: Box<dyn Heuristic + 'a>) -> Self
+    {
+        Program::base(route_dat, None, heuristic)
+    }
+
+    ///
+    /// Creates a new program from route information alone. Instead of
+    /// reading heuristic data, landmarks are selected on the Graph and
+    /// an ALT heuristic is computed from their exact distances
+    ///
+    /// route_file_txt: the route information, by which the
+    ///     Graph will be built
+    ///
+    /// landmark_count: the # of landmarks to select
+    ///
+    /// - Return: Either an Ok Result with the Program, or an Err with
+    ///   message explaining why the routes could not be loaded
+    ///
+    pub fn with_landmarks(route_file_txt: &'a str, landmark_count: usize) -> Result<Self, String>
+    {
+        let routes = build_map(route_file_txt, false, Duplicates::default(), ',', Unit::default())?;
+        let landmarks = Landmarks::new(&routes.graph, landmark_count);
+        Ok(Program::base(routes.graph, routes.arcs, Box::new(landmarks)))
+    }
+
+    ///
+    /// Creates a program over loaded routes with every setting at its
+    /// default, as each constructor does
+    ///
+    /// route_dat: the Graph to route over
+    ///
+    /// arcs: every direction routes may be travelled, or None if every
+    ///     route is two-way
+    ///
+    /// heuristic: estimates the distance between any two locations
+    ///
+    fn base(route_dat: RouteGraph<'a>, arcs: Option<DirectedRouteGraph<'a>>, heuristic: Box<dyn Heuristic + 'a>) -> Self
+    {
+        Program
+        {
+            names: NameIndex::new(route_dat.nodes()),
+            interned: interned(&route_dat, &arcs, &*heuristic, false),
+            reorder: false,
+            components: Components::new(&route_dat),
+            route_dat,
+            acyclic: is_acyclic(&arcs),
+            arcs,
+            grid: None,
+            heuristic,
+            tie_break: TieBreak::default(),
+            context: RefCell::default(),
+            arena: RefCell::default(),
+            unit: Unit::default(),
+            algorithms: Vec::new(),
+            precision: DEFAULT_PRECISION,
+            output: OutputFormat::default(),
+            coordinates: HashMap::new(),
+            geographic: false,
+            gpx: None,
+            stats: None,
+            expansions: None,
+            history: None,
+            speed: None,
+            progress: false,
+            threads: 1,
+            cache: None,
+            artifacts: None,
+            incremental: None,
+            last_route: Vec::new(),
+        }
+    }
+
+    ///
+    /// Sets what the Graph's weights measure, for printing
+    ///
+    /// - unit: the Unit of every weight
+    ///
+    pub fn set_unit(&mut self, unit: Unit)
+    {
+        self.unit = unit;
+    }
+
+    ///
+    /// - return: the Unit of every weight
+    ///
+    pub fn unit(&self) -> Unit
+    {
+        self.unit
+    }
+
+    ///
+    /// - return: every location's name, in alphabetical order
+    ///
+    pub fn locations(&self) -> Vec<&'a str>
+    {
+        let mut locations = self.route_dat.nodes().collect::<Vec<&'a str>>();
+        locations.sort_unstable();
+        locations
+    }
+
+    ///
+    /// Sets the directions each route may be travelled, when some are
+    /// one-way. Routes are then only searched in those directions, and
+    /// features which cannot follow them report an error
+    ///
+    /// - arcs: every direction routes may be travelled, or None if every
+    ///   route is two-way
+    ///
+    pub fn set_one_way_routes(&mut self, arcs: Option<DirectedRouteGraph<'a>>)
+    {
+        self.interned = interned(&self.route_dat, &arcs, &*self.heuristic, self.reorder);
+        self.acyclic = is_acyclic(&arcs);
+        self.arcs = arcs;
+        if let Some(cache) = &self.cache { cache.lock().unwrap().clear(); }
+    }
+
+    ///
+    /// Sets the grid map the routes were built from, if they were. Jump
+    /// Point Search and Theta* then route across its Grid, rather than
+    /// searching the Graph as A*
+    ///
+    /// - grid: the grid map, or None if the routes are not a grid map's
+    ///
+    pub fn set_grid(&mut self, grid: Option<GridMap<'a>>)
+    {
+        self.grid = grid;
+        if let Some(cache) = &self.cache { cache.lock().unwrap().clear(); }
+    }
+
+    ///
+    /// Sets whether the locations searched are renumbered so that
+    /// neighbouring locations are close together in memory, which can
+    /// speed up searches on large Graphs. Routes are the same either way
+    ///
+    /// - reorder: whether to renumber the locations
+    ///
+    pub fn set_reorder(&mut self, reorder: bool)
+    {
+        self.reorder = reorder;
+        self.interned = interned(&self.route_dat, &self.arcs, &*self.heuristic, reorder);
+        if let Some(cache) = &self.cache { cache.lock().unwrap().clear(); }
+    }
+
+    ///
+    /// Sets how A* and Djikstra's algorithm choose between nodes with equal
+    /// f-values. Routes are equally short under every strategy, but the
+    /// # of nodes considered can differ greatly
+    ///
+    /// - tie_break: the strategy to use
+    ///
+    pub fn set_tie_break(&mut self, tie_break: TieBreak)
+    {
+        self.tie_break = tie_break;
+    }
+
+    ///
+    /// - return: how A* and Djikstra's algorithm currently break ties
+    ///
+    pub fn tie_break(&self) -> TieBreak
+    {
+        self.tie_break
+    }
+
+    ///
+    /// Sets the queue A* and Djikstra's algorithm keep their frontier in.
+    /// Routes and the # of nodes considered are the same with any, but
+    /// the time taken differs. Buckets are only used for routes no heavier
+    /// than MAX_BUCKET_WEIGHT, and the lazy heap otherwise
+    ///
+    /// - frontier: the queue to use
+    ///
+    pub fn set_frontier(&mut self, frontier: Frontier)
+    {
+        let max_weight = self.interned.max_weight();
+        let frontier = match frontier
+        {
+            Frontier::Buckets if max_weight > MAX_BUCKET_WEIGHT =>
+            {
+                warn!("Routes as long as {} are too long for the bucket queue; using the lazy heap instead.", self.distance(max_weight));
+                Frontier::LazyHeap
+            },
+            _ => frontier,
+        };
+        self.context = RefCell::new(SearchContext::new(frontier));
+    }
+
+    ///
+    /// Sets the algorithms each query is routed with, compared in the
+    /// order given. Features which route with one algorithm use the first
+    ///
+    /// - algorithms: the algorithms to route with, or none to compare A*,
+    ///   Djikstra's algorithm and fringe search, after routing in
+    ///   topological order if every route is one-way and none form a cycle
+    ///
+    /// - Return: Either an empty Ok Result, or an Err if an algorithm
+    ///   searches only a Grid and no grid map crossed diagonally is set,
+    ///   leaving the algorithms as they were
+    ///
+    pub fn set_algorithms(&mut self, algorithms: Vec<Algorithm>) -> Result<(), String>
+    {
+        let diagonal_grid = self.grid.as_ref().is_some_and(|grid| grid.connectivity == Connectivity::Eight);
+        if let Some(algorithm) = algorithms.iter().find(|algorithm| Algorithm::GRID.contains(algorithm) && !diagonal_grid)
+        {
+            return Err(format!("{} only searches grid maps crossed diagonally; load a grid map with --diagonal, or a MovingAI map.", algorithm.name()));
+        }
+
+        self.algorithms = algorithms;
+        Ok(())
+    }
+
+    ///
+    /// - return: the algorithms each query is routed with, or none if A*,
+    ///   Djikstra's algorithm and fringe search are compared
+    ///
+    pub fn algorithms(&self) -> &[Algorithm]
+    {
+        &self.algorithms
+    }
+
+    ///
+    /// Sets how many decimal places are printed in distances
+    ///
+    /// - precision: the # of decimal places
+    ///
+    pub fn set_precision(&mut self, precision: usize)
+    {
+        self.precision = precision;
+    }
+
+    ///
+    /// - return: the # of decimal places printed in distances
+    ///
+    pub fn precision(&self) -> usize
+    {
+        self.precision
+    }
+
+    ///
+    /// Sets how the results of each query are written
+    ///
+    /// - output: the OutputFormat to write
+    ///
+    pub fn set_output(&mut self, output: OutputFormat)
+    {
+        self.output = output;
+    }
+
+    ///
+    /// Sets the position of each location, for exports which draw or
+    /// track routes
+    ///
+    /// - coordinates: each location's (x, y), or else (longitude, latitude)
+    ///   in degrees
+    /// - geographic: whether the coordinates are longitude and latitude
+    ///
+    pub fn set_coordinates(&mut self, coordinates: HashMap<&'a str, (f64, f64)>, geographic: bool)
+    {
+        self.coordinates = coordinates;
+        self.geographic = geographic;
+    }
+
+    ///
+    /// Sets a GPX file to write each route shown to, as a track. The
+    /// coordinates set must be geographic
+    ///
+    /// - path: the GPX file to write
+    ///
+    pub fn set_gpx_export(&mut self, path: PathBuf)
+    {
+        self.gpx = Some(path);
+    }
+
+    ///
+    /// Sets a file to append the nodes expanded by each A* and Djikstra
+    /// search to, in order, with their costs and parents. Files named
+    /// ".json" or ".jsonl" are written as JSON, and others as CSV
+    ///
+    /// - path: the file to append to
+    ///
+    pub fn set_expansions_export(&mut self, path: PathBuf)
+    {
+        self.expansions = Some(path);
+    }
+
+    ///
+    /// Sets a file to record every search in, which the "History" command
+    /// lists and re-runs searches from
+    ///
+    /// - path: the history file
+    ///
+    pub fn set_history(&mut self, path: PathBuf)
+    {
+        self.history = Some(path);
+    }
+
+    ///
+    /// Sets the speed routes are travelled at, so the time taken to reach
+    /// each location is printed with the route's directions. Only used
+    /// for distances in miles
+    ///
+    /// - speed: the speed, in miles per hour, or None to print no times
+    ///
+    pub fn set_speed(&mut self, speed: Option<f64>)
+    {
+        self.speed = speed;
+    }
+
+    ///
+    /// Sets whether a spinner on standard error shows the progress of
+    /// searches which run a while
+    ///
+    /// - progress: whether to show progress
+    ///
+    pub fn set_progress(&mut self, progress: bool)
+    {
+        self.progress = progress;
+    }
+
+    ///
+    /// Sets the # of threads batches of queries are searched on at once
+    ///
+    /// - threads: the # of threads, or 0 for one per CPU
+    ///
+    pub fn set_threads(&mut self, threads: usize)
+    {
+        self.threads = threads;
+    }
+
+    ///
+    /// Sets how many searches are kept in memory, so that repeating a
+    /// query answers it without searching again. Once full, the search
+    /// least recently asked for is dropped
+    ///
+    /// - capacity: the most searches kept, or 0 to keep none
+    ///
+    pub fn set_cache(&mut self, capacity: usize)
+    {
+        self.cache = (capacity > 0).then(|| Mutex::new(LruCache::new(capacity)));
+    }
+
+    ///
+    /// Sets the route file which preprocessing, such as a contraction
+    /// hierarchy, is kept beside, so that it is computed once for the
+    /// routes rather than every time they are loaded
+    ///
+    /// - routes: the route file the Graph was loaded from
+    ///
+    pub fn set_artifacts(&mut self, routes: PathBuf)
+    {
+        self.artifacts = Some(routes);
+    }
+
+    ///
+    /// - Return: how often queries were answered from the cache, and how
+    ///   full it is, or None if no cache is set
+    ///
+    fn cache_summary(&self) -> Option<String>
+    {
+        let cache = self.cache.as_ref()?.lock().unwrap();
+        let lookups = cache.hits() + cache.misses();
+        Some(format!("Cache: {} hit(s) and {} miss(es) ({:.1}% hit), holding {} of at most {} searches.",
+            cache.hits(), cache.misses(), if lookups == 0 { 0.0 } else { cache.hits() as f64 * 100.0 / lookups as f64 },
+            cache.len(), cache.capacity()))
+    }
+
+    ///
+    /// Sets a CSV file to append each search's statistics to, as a row of
+    /// its locations, algorithm, # of nodes expanded, cost and time taken
+    ///
+    /// - path: the CSV file to append to
+    ///
+    pub fn set_stats_export(&mut self, path: PathBuf)
+    {
+        self.stats = Some(path);
+    }
+
+    ///
+    /// Runs the Program, guiding the user through a loop until they
+    /// enter "quit". Asks user to provide a starting point and destination,
+    /// then calling the find_shortest_route method to traverse from start
+    /// to finish using A*, Djikstra's and fringe search (comparing the three),
+    /// or else the algorithms set. With JSON output, queries are read and
+    /// answered without prompts instead
+    /// 
+    pub fn run(&mut self)
     {
-        Program 
-        { 
-            route_dat: build_map(route_file_txt), 
-            heur_map: build_heur_data(heur_file_txt)
+        match self.output
+        {
+            OutputFormat::Json => return self.run_json(),
+            OutputFormat::Script => { self.run_script(); return; },
+            OutputFormat::Text => (),
+        }
+
+        // Complete locations and commands with Tab
+        let commands = ["Quit", "MST", "Tour", "Postman", "Analyze", "Export-DOT", "Export-SVG", "History", "List", "Neighbors", "Info", "Next", "Previous", "Show", "Algorithms", "Itinerary", "Edit"];
+        complete_names(self.route_dat.nodes().chain(commands).map(String::from).collect());
+
+        // The page of locations shown, of those holding the filter text
+        let (mut filter, mut page) = (String::new(), 0);
+
+        // Loop until user quites
+        loop
+        {
+            // Clear the screen and print a page of the possible locations
+            clear_screen();            
+            println!("Your Locations:\n");
+            let pages = self.print_location_page(&filter, page);
+            page = page.min(pages - 1);
+
+            // Prompt for and retrieve start and finish location(s)
+            println!("--\nWhat city are you starting at? (Separate several with commas, quoting names which hold commas)");
+            println!("Type \"Quit\" at any time to exit.");
+            println!("Type \"MST\" for the cheapest network connecting every city.");
+            println!("Type \"Tour\" for a short round trip through several cities.");
+            println!("Type \"Itinerary\" to route through several cities in the order given.");
+            println!("Type \"Postman\" for the shortest round trip travelling every route.");
+            println!("Type \"Edit\" to change, add or remove the route between two cities, then re-run the last search.");
+            println!("Type \"Analyze\" to see which single route or city failures would disconnect cities.");
+            println!("Type \"Export-DOT\" to write the routes as a Graphviz DOT file, with the last route shown highlighted.");
+            println!("Type \"Export-SVG\" to draw the routes as an SVG image, with the last route shown highlighted.");
+            println!("Type \"History\" to list and re-run earlier searches.");
+            println!("Type \"Algorithms\" to choose which algorithms each search runs (now {}).",
+                self.compared_algorithms().iter().map(|algorithm| algorithm.name()).collect::<Vec<&str>>().join(", "));
+            println!("Type \"List\" to list every city, \"Neighbors <city>\" for the routes leaving a city, or \"Info <city>\" for a city's details.");
+            let from = input(false);
+            if from.to_lowercase() == "quit" { break; }
+
+            // Commands given a city, unless a city is named alike
+            let (command, argument) = match from.trim().split_once(char::is_whitespace)
+            {
+                Some((command, argument)) => (command.to_lowercase(), argument.trim()),
+                None => (from.trim().to_lowercase(), ""),
+            };
+            let is_location = self.find_node(&from).is_some();
+
+            if !is_location && (command == "next" || command == "previous")
+            {
+                page = match command.as_str()
+                {
+                    "next" => (page + 1) % pages,
+                    _ => (page + pages - 1) % pages,
+                };
+                continue;
+            }
+
+            if !is_location && command == "show"
+            {
+                filter = unquote(argument).to_string();
+                page = 0;
+                continue;
+            }
+
+            if !is_location && command == "algorithms"
+            {
+                println!("Which algorithms should each search run? (Separate with commas: astar, dijkstra, bidirectional-astar,");
+                println!("bidirectional-dijkstra, fringe, focal, bfs, bidirectional-bfs, dfs or dag, or jps or theta on grid maps;");
+                println!("\"all\" for every one; or press ENTER to compare A*, Djikstra and Fringe, and DAG if the one-way routes");
+                println!("form no cycle)");
+                let names = input(false);
+                if names.to_lowercase() == "quit" { break; }
+
+                let set = match names.trim()
+                {
+                    "" => self.set_algorithms(Vec::new()),
+                    names => Algorithm::from_names(names).and_then(|algorithms| self.set_algorithms(algorithms)),
+                };
+                if let Err(e) = set { println!("{}", e); wait_for_enter(); }
+                continue;
+            }
+
+            if !is_location && command == "list"
+            {
+                clear_screen();
+                self.print_locations();
+                wait_for_enter();
+                continue;
+            }
+
+            if !is_location && (command == "neighbors" || command == "info")
+            {
+                let location = match argument
+                {
+                    "" =>
+                    {
+                        println!("Which city?");
+                        input(false)
+                    },
+                    argument => argument.to_string(),
+                };
+                if location.to_lowercase() == "quit" { break; }
+
+                if let Some(location) = self.confirm_location(unquote(location.trim()))
+                {
+                    clear_screen();
+                    match command.as_str()
+                    {
+                        "neighbors" => self.print_neighbors(location),
+                        _ => self.print_info(location),
+                    }
+                }
+
+                wait_for_enter();
+                continue;
+            }
+
+            if from.to_lowercase() == "mst"
+            {
+                clear_screen();
+                self.print_spanning_tree(&self.minimum_spanning_tree());
+                wait_for_enter();
+                continue;
+            }
+
+            if from.to_lowercase() == "tour"
+            {
+                println!("Which cities should the tour visit? (Separate with commas, starting city first)");
+                let cities = input(false);
+                if cities.to_lowercase() == "quit" { break; }
+
+                clear_screen();
+                let cities = split_names(&cities);
+                match self.tour(&cities)
+                {
+                    Err(e) => println!("{}", e),
+                    Ok(tour) =>
+                    {
+                        println!("Tour order: {}\n", tour.stops.join(" -> "));
+                        self.print_route(&tour.route);
+                        self.remember_route(&tour.route);
+                    }
+                }
+
+                wait_for_enter();
+                continue;
+            }
+
+            if from.to_lowercase() == "itinerary"
+            {
+                println!("Which cities should the itinerary visit? (Separate with commas, in the order visited)");
+                let cities = input(false);
+                if cities.to_lowercase() == "quit" { break; }
+
+                clear_screen();
+                let cities = split_names(&cities);
+                match self.itinerary(&cities)
+                {
+                    Err(e) => println!("{}", e),
+                    Ok(legs) => self.print_itinerary(&legs),
+                }
+
+                wait_for_enter();
+                continue;
+            }
+
+            if from.to_lowercase() == "edit"
+            {
+                println!("Which two cities does the route join? (Separate with commas)");
+                let cities = input(false);
+                if cities.to_lowercase() == "quit" { break; }
+                println!("How long should the route be? (Press ENTER to remove it)");
+                let length = input(false);
+                if length.to_lowercase() == "quit" { break; }
+
+                clear_screen();
+                if let Err(e) = self.edit_and_reroute(&split_names(&cities), length.trim()) { println!("{}", e); }
+
+                wait_for_enter();
+                continue;
+            }
+
+            if from.to_lowercase() == "analyze"
+            {
+                clear_screen();
+                self.print_analysis(&self.analyze());
+                wait_for_enter();
+                continue;
+            }
+
+            if from.to_lowercase() == "postman"
+            {
+                println!("Which city should the round trip begin and end at?");
+                let start = input(false);
+                if start.to_lowercase() == "quit" { break; }
+
+                clear_screen();
+                match self.route_inspection(unquote(&start))
+                {
+                    Err(e) => println!("{}", e),
+                    Ok(route) =>
+                    {
+                        self.print_route(&route);
+                        self.remember_route(&route);
+                    }
+                }
+
+                wait_for_enter();
+                continue;
+            }
+
+            if from.to_lowercase() == "history"
+            {
+                clear_screen();
+                let queries = match &self.history
+                {
+                    None => Err(String::from("No history is being kept.")),
+                    Some(path) => history::read(path),
+                };
+                match queries
+                {
+                    Err(e) => println!("{}", e),
+                    Ok(queries) if queries.is_empty() => println!("No searches have been run yet."),
+                    Ok(queries) =>
+                    {
+                        self.print_history(&queries);
+                        println!("Which search should be run again? (Type its #, or press ENTER to go back)");
+                        let choice = input(false);
+                        if choice.to_lowercase() == "quit" { break; }
+
+                        if !choice.trim().is_empty()
+                        {
+                            let query = choice.trim().parse::<usize>().ok()
+                                .and_then(|number| queries.get(number.wrapping_sub(1)));
+                            match query
+                            {
+                                None => println!("There is no search #{}.", choice.trim()),
+                                Some(query) => match self.rerun(query)
+                                {
+                                    Err(e) => println!("{}", e),
+                                    Ok(route) => self.remember_route(&route),
+                                },
+                            }
+                        }
+                    },
+                }
+
+                wait_for_enter();
+                continue;
+            }
+
+            if from.to_lowercase() == "export-dot"
+            {
+                println!("Which file should the graph be written to?");
+                let path = input(false);
+                if path.to_lowercase() == "quit" { break; }
+
+                match self.export_dot(Path::new(path.trim()))
+                {
+                    Err(e) => println!("{}", e),
+                    Ok(()) => println!("Graph written to {}; render it with \"dot -Tpng {} -o route.png\".", path.trim(), path.trim()),
+                }
+
+                wait_for_enter();
+                continue;
+            }
+
+            if from.to_lowercase() == "export-svg"
+            {
+                println!("Which file should the drawing be written to?");
+                let path = input(false);
+                if path.to_lowercase() == "quit" { break; }
+
+                match self.export_svg(Path::new(path.trim()))
+                {
+                    Err(e) => println!("{}", e),
+                    Ok(()) => println!("Drawing written to {}.", path.trim()),
+                }
+
+                wait_for_enter();
+                continue;
+            }
+
+            println!("What city are you going to?");
+            let to = input(false);
+            if to.to_lowercase() == "quit" { break; }
+
+            // Offer the closest names for any location which does not
+            // exist. Names holding commas may be quoted
+            let locations = split_names(&from).into_iter()
+                .chain(std::iter::once(unquote(&to)))
+                .map(|location| self.confirm_location(location))
+                .collect::<Option<Vec<&'a str>>>();
+            let (to, starts) = match locations.as_ref().and_then(|locations| locations.split_last())
+            {
+                Some((&to, starts)) => (to, starts),
+                None => { wait_for_enter(); continue; },
+            };
+          
+            clear_screen();
+
+            // With several start locations, route from the nearest of them
+            if starts.len() > 1
+            {
+                println!("\nRunning multi-source A* Algorithm...");
+                match self.route_from_nearest(starts, to)
+                {
+                    Err(e) => println!("{}", e),
+                    Ok(route) =>
+                    {
+                        println!("Nearest start location: {}", route.nodes[0]);
+                        self.print_route(&route);
+                        self.remember_route(&route);
+                    }
+                }
+
+                wait_for_enter();
+                continue;
+            }
+
+            if let Err(e) = self.compare_algorithms(starts[0], to) { println!("{}", e); }
+           
+            // Wait for ENTER as user looks over results
+            wait_for_enter();
+        }        
+    }
+
+    ///
+    /// Routes between two locations with each compared algorithm in turn,
+    /// A* first, or else the algorithms chosen, printing the first
+    /// algorithm's route and then the time each took
+    ///
+    /// - from: the start location's name
+    /// - to: the end location's name
+    ///
+    /// - Return: Either an empty Ok Result, or an Err with message
+    ///   explaining problem
+    ///
+    fn compare_algorithms(&mut self, from: &str, to: &str) -> Result<(), String>
+    {
+        let (start, end) = match (self.find_node(from), self.find_node(to))
+        {
+            (Some(start), Some(end)) => (start, end),
+            _ => return Err(self.unknown_location(&[from, to])),
+        };
+
+        // Track the time taken for each to complete and display at finish
+        let algorithms = self.compared_algorithms();
+        let mut times = Vec::new();
+        for (i, &algorithm) in algorithms.iter().enumerate()
+        {
+            println!("\nRunning {} Algorithm...", algorithm.name());
+            let (elapsed, route) = self.find_shortest_route(start, end, algorithm, i == 0)?;
+            if i == 0 { self.remember_route(&route); }
+            times.push((algorithm, elapsed));
+        }
+
+        // The fastest algorithm is highlighted, when several are compared
+        println!("--");
+        let fastest = times.iter().map(|&(_, elapsed)| elapsed).min().filter(|_| times.len() > 1);
+        for (algorithm, elapsed) in times
+        {
+            let line = format!("{} time to compute: {} micros.", algorithm.name(), elapsed);
+            match Some(elapsed) == fastest
+            {
+                true => println!("{}", line.if_supports_color(Stream::Stdout, |line| line.green())),
+                false => println!("{}", line),
+            }
+        }
+        if let Some(summary) = self.cache_summary() { println!("{}", summary); }
+        println!();
+        Ok(())
+    }
+
+    ///
+    /// - Return: the algorithms each query is compared with: those set, or
+    ///   else A*, Djikstra's algorithm and fringe search, led by routing in
+    ///   topological order when the one-way routes form no cycle
+    ///
+    fn compared_algorithms(&self) -> Vec<Algorithm>
+    {
+        match (self.algorithms.is_empty(), self.acyclic)
+        {
+            (true, true) => std::iter::once(Algorithm::Dag).chain(COMPARED_ALGORITHMS).collect(),
+            (true, false) => COMPARED_ALGORITHMS.to_vec(),
+            (false, _) => self.algorithms.clone(),
+        }
+    }
+
+    ///
+    /// - Return: the algorithm features which route with one algorithm
+    ///   use: the first set, or else routing in topological order when the
+    ///   one-way routes form no cycle, or else A*
+    ///
+    fn first_algorithm(&self) -> Algorithm
+    {
+        match (self.algorithms.first(), self.acyclic)
+        {
+            (Some(&algorithm), _) => algorithm,
+            (None, true) => Algorithm::Dag,
+            (None, false) => Algorithm::AStar,
+        }
+    }
+
+    ///
+    /// Routes between two nodes on the Graph using the given algorithm,
+    /// printing the # of nodes considered (and the route itself, if asked)
+    ///
+    /// - start: the start location on the Graph
+    /// - end: the end location on the Graph
+    /// - algorithm: the algorithm to route with
+    /// - show_route: whether to print the route found
+    ///
+    /// - Return: Either an Ok Result with the amount of time taken to compute path,
+    ///   and the route, or an Err with message explaining problem
+    ///
+    fn find_shortest_route(&self, start: &'a str, end: &'a str, algorithm: Algorithm, show_route: bool) -> Result<(u128, PathResult<'a>), String>
+    {
+        // If provided start or end node does not exist, prompt the
+        // user of this, and return Err
+        let (start, end) = (self.resolve(start)?, self.resolve(end)?);
+
+        let (route, node_counter, elapsed, counters) = self.timed_search(start, end, algorithm);
+
+        // Print # of nodes considered, what the search did beyond that if
+        // counted, and how ties were broken if relevant
+        println!("{} nodes considered", node_counter);
+        if let Some(counters) = counters
+        {
+            println!("{} pushes, {} pops ({} stale), {} relaxations, {} duplicate expansions, and at most {} nodes queued",
+                counters.pushes, counters.pops, counters.stale_pops, counters.relaxations, counters.duplicate_expansions, counters.peak_frontier);
+        }
+        if let Algorithm::AStar | Algorithm::Dijkstra = algorithm
+        {
+            println!("Ties broken by {}", self.tie_break.name());
+        }
+
+        match route
+        {
+            Some(route) =>
+            {
+                // Print shortest route (if asked)
+                if show_route { self.print_route(&route); }
+
+                // Return time taken to compute (in microseconds)
+                Ok((elapsed, route))
+            },
+
+            // If no route exists, return Err - destination could not be reached
+            None => Err(self.unreachable(start, end))
+        }
+    }
+
+    ///
+    /// Routes between two nodes on the Graph using the given algorithm,
+    /// following one-way routes if any, and appends the search's
+    /// statistics and expansions to any files set, and the search to the
+    /// history
+    ///
+    /// - Return: the route found, if any, the # of nodes considered, the
+    ///   time taken to search, in microseconds, and the search's Counters,
+    ///   if counted
+    ///
+    fn timed_search(&self, start: &'a str, end: &'a str, algorithm: Algorithm) -> Search<'a>
+    {
+        let progress = self.progress.then(|| SearchProgress::new(algorithm.name()));
+        // The nodes expanded, if they are to be written
+        let mut expanded = Vec::new();
+        let on_expand = |expansion: Expansion<'a>|
+        {
+            if let Some(progress) = &progress { progress.update(expansion.order + 1, expansion.frontier); }
+            if self.expansions.is_some() { expanded.push(expansion); }
+        };
+        // A search found in the cache expands nothing, so is run again if
+        // its expansions are to be written
+        let searcher = self.searcher();
+        let (route, node_counter, micros, counters) = match self.expansions
+        {
+            Some(_) => searcher.search(start, end, algorithm, &mut self.context.borrow_mut(), on_expand),
+            None => searcher.search_cached(start, end, algorithm, &mut self.context.borrow_mut(), on_expand),
+        };
+        if let Some(progress) = progress { progress.finish(); }
+
+        self.record_search(start, end, algorithm, route.as_ref(), node_counter, micros, Some(&expanded));
+        (route, node_counter, micros, counters)
+    }
+
+    ///
+    /// - Return: a Searcher over the Program's routes and heuristic, which
+    ///   can be shared between threads
+    ///
+    fn searcher(&self) -> Searcher<'_, 'a>
+    {
+        Searcher
+        {
+            route_dat: &self.route_dat,
+            arcs: &self.arcs,
+            grid: self.grid.as_ref(),
+            interned: &self.interned,
+            heuristic: &*self.heuristic,
+            tie_break: self.tie_break,
+            cache: self.cache.as_ref(),
+        }
+    }
+
+    ///
+    /// Logs a search, and appends its statistics and expansions to any
+    /// files set, and the search to the history
+    ///
+    /// - start: the start location on the Graph
+    /// - end: the end location on the Graph
+    /// - algorithm: the algorithm routed with
+    /// - route: the route found, if any
+    /// - node_counter: the # of nodes considered
+    /// - micros: the time taken to search, in microseconds
+    /// - expanded: each node the search expanded, in order, or None if
+    ///   the search was not observed
+    ///
+    #[allow(clippy::too_many_arguments)]
+    fn record_search(&self, start: &'a str, end: &'a str, algorithm: Algorithm, route: Option<&PathResult>, node_counter: usize, micros: u128, expanded: Option<&[Expansion<'a>]>)
+    {
+        match route
+        {
+            Some(route) => info!("{} routed {} to {} in {}, expanding {} node(s) in {} µs", algorithm.name(), start, end,
+                self.distance(route.cost), node_counter, micros),
+            None => info!("{} found no route from {} to {}, expanding {} node(s) in {} µs", algorithm.name(), start, end, node_counter, micros),
+        }
+
+        if let Some(path) = &self.stats
+        {
+            let stats = SearchStats
+            {
+                from: start,
+                to: end,
+                algorithm: algorithm.name(),
+                nodes_expanded: node_counter,
+                cost: route.map(|route| self.unit.from_weight(route.cost)),
+                micros,
+            };
+            if let Err(e) = stats::append(path, &stats) { warn!("{}", e); }
+        }
+        if let Some(path) = &self.expansions
+        {
+            if let Err(e) = self.export_expansions(path, start, end, algorithm, expanded) { warn!("{}", e); }
+        }
+        if let Some(path) = &self.history
+        {
+            let query = Query::now(start, end, algorithm.name(), route.map(|route| self.unit.from_weight(route.cost)), micros);
+            if let Err(e) = history::append(path, &query) { warn!("{}", e); }
+        }
+    }
+
+    ///
+    /// Routes every query with each of the algorithms on a pool of
+    /// threads, each reusing its own SearchContext. Nothing is recorded,
+    /// so that the searches can be recorded in order once all are done
+    ///
+    /// - queries: each query's start and end locations on the Graph
+    /// - algorithms: the algorithms to route each query with
+    ///
+    /// - Return: Either an Ok Result with each query's searches, one per
+    ///   algorithm, as timed_search returns them, or an Err with message
+    ///   explaining why the threads could not be started
+    ///
+    fn search_parallel(&self, queries: &[(&'a str, &'a str)], algorithms: &[Algorithm]) -> Result<Vec<Vec<Search<'a>>>, String>
+    {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.threads)
+            .build()
+            .map_err(|e| format!("Cannot start the search threads: {}", e))?;
+        let searcher = self.searcher();
+        let frontier = self.context.borrow().frontier();
+
+        Ok(pool.install(|| queries.par_iter()
+            .map_init(|| SearchContext::new(frontier), |context, &(start, end)| algorithms.iter()
+                .map(|&algorithm| searcher.search_cached(start, end, algorithm, context, |_| ()))
+                .collect())
+            .collect()))
+    }
+
+    ///
+    /// Routes between two nodes on the Graph with each compared algorithm,
+    /// or else the algorithms set, collecting every route found with its
+    /// legs, # of nodes considered and time taken, as other tools read
+    /// them
+    ///
+    /// - start: the start location on the Graph
+    /// - end: the end location on the Graph
+    ///
+    /// - Return: Either an Ok Result with the RouteReport, or an Err with
+    ///   message explaining problem
+    ///
+    pub fn report(&self, start: &'a str, end: &'a str) -> Result<RouteReport<'a>, String>
+    {
+        let (start, end) = (self.resolve(start)?, self.resolve(end)?);
+        let searches = self.compared_algorithms().into_iter()
+            .map(|algorithm| (algorithm, self.timed_search(start, end, algorithm)));
+
+        self.build_report(start, end, searches)
+    }
+
+    ///
+    /// Collects searches between two nodes into a RouteReport
+    ///
+    /// - start: the start location on the Graph
+    /// - end: the end location on the Graph
+    /// - searches: each algorithm with its search, run as they are taken
+    ///
+    /// - Return: Either an Ok Result with the RouteReport, or an Err with
+    ///   message explaining problem. Searches after any which found no
+    ///   route are never taken
+    ///
+    fn build_report<I: Iterator<Item = (Algorithm, Search<'a>)>>(&self, start: &'a str, end: &'a str, searches: I) -> Result<RouteReport<'a>, String>
+    {
+        let mut results = Vec::new();
+        for (algorithm, (route, nodes_considered, micros, counters)) in searches
+        {
+            let route = route.ok_or_else(|| self.unreachable(start, end))?;
+
+            let legs = self.route_steps(&route).into_iter()
+                .map(|step| Leg
+                {
+                    index: step.index,
+                    from: step.from,
+                    to: step.to,
+                    distance: self.unit.from_weight(step.distance),
+                    cumulative: self.unit.from_weight(step.cumulative),
+                })
+                .collect();
+            results.push(AlgorithmReport
+            {
+                algorithm: algorithm.name(),
+                total: self.unit.from_weight(route.cost),
+                route: route.nodes,
+                legs,
+                nodes_considered,
+                counters,
+                micros,
+            });
+        }
+
+        Ok(RouteReport { start, end, unit: self.unit.name(), results })
+    }
+
+    ///
+    /// Answers queries as JSON, reading each as a start location line and
+    /// then an end location line, until input ends or "quit" is entered.
+    /// Each query's RouteReport is printed as one line, or else an object
+    /// holding its error
+    ///
+    fn run_json(&self)
+    {
+        loop
+        {
+            let from = input(true);
+            if from.to_lowercase() == "quit" { break; }
+            let to = input(true);
+            if to.to_lowercase() == "quit" { break; }
+
+            let (from, to) = (unquote(from.trim()), unquote(to.trim()));
+            match self.report(from, to)
+            {
+                Ok(report) => println!("{}", report.to_json()),
+                Err(e) => println!("{}", error_json(from, to, &e)),
+            }
+        }
+
+        if let Some(summary) = self.cache_summary() { eprintln!("{}", summary); }
+    }
+
+    ///
+    /// Answers queries for shell scripts, reading each as a line holding
+    /// the start and end locations separated by a tab (or else as a start
+    /// line and then an end line), until input ends or "quit" is entered.
+    /// Each query is answered on standard output with one tab-separated
+    /// line: "found", the locations, the route's distance and each
+    /// location along it; or "unreachable" or "unknown" and the locations.
+    /// Prompts and messages are written to standard error. Queries are
+    /// routed with the first algorithm set, or else A*
+    ///
+    /// - Return: the exit code for the queries' results: EXIT_UNKNOWN_LOCATION
+    ///   if any named an unknown location, or else EXIT_UNREACHABLE if any
+    ///   end could not be reached, or else EXIT_FOUND
+    ///
+    pub fn run_script(&self) -> i32
+    {
+        let mut exit_code = EXIT_FOUND;
+        loop
+        {
+            eprint!("Start and end locations: ");
+            let query = input(true);
+            if query.to_lowercase() == "quit" { break; }
+            let query = match query.split_once('\t')
+            {
+                Some((from, to)) => (from.to_string(), to.to_string()),
+                None =>
+                {
+                    eprint!("End location: ");
+                    let to = input(true);
+                    if to.to_lowercase() == "quit" { break; }
+                    (query, to)
+                },
+            };
+            let (from, to) = (unquote(query.0.trim()), unquote(query.1.trim()));
+
+            exit_code = match (exit_code, self.answer_script(from, to))
+            {
+                (EXIT_UNKNOWN_LOCATION, _) | (_, EXIT_UNKNOWN_LOCATION) => EXIT_UNKNOWN_LOCATION,
+                (EXIT_UNREACHABLE, _) | (_, EXIT_UNREACHABLE) => EXIT_UNREACHABLE,
+                _ => EXIT_FOUND,
+            };
+        }
+
+        if let Some(summary) = self.cache_summary() { eprintln!("{}", summary); }
+        exit_code
+    }
+
+    ///
+    /// Answers one query for shell scripts, with the first algorithm set, or
+    /// else A*, as a line of run_script's output
+    ///
+    /// - from: the start location's name
+    /// - to: the end location's name
+    ///
+    /// - Return: the exit code for the query's result
+    ///
+    fn answer_script(&self, from: &str, to: &str) -> i32
+    {
+        let (start, end) = match (self.find_node(from), self.find_node(to))
+        {
+            (Some(start), Some(end)) => (start, end),
+            _ =>
+            {
+                eprintln!("{}", self.unknown_location(&[from, to]));
+                println!("unknown\t{}\t{}", from, to);
+                return EXIT_UNKNOWN_LOCATION;
+            },
+        };
+
+        let route = self.timed_search(start, end, self.first_algorithm()).0;
+        self.print_script_line(start, end, route.as_ref())
+    }
+
+    ///
+    /// Prints a routed query's line of run_script's output
+    ///
+    /// - start: the start location
+    /// - end: the end location
+    /// - route: the route found, if any
+    ///
+    /// - Return: the exit code for the query's result
+    ///
+    fn print_script_line(&self, start: &str, end: &str, route: Option<&PathResult>) -> i32
+    {
+        match route
+        {
+            Some(route) =>
+            {
+                println!("found\t{}\t{}\t{}\t{}", start, end, self.unit.number(self.unit.from_weight(route.cost), self.precision), route.nodes.join("\t"));
+                EXIT_FOUND
+            },
+            None =>
+            {
+                eprintln!("{}", self.unreachable(start, end));
+                println!("unreachable\t{}\t{}", start, end);
+                EXIT_UNREACHABLE
+            },
+        }
+    }
+
+    ///
+    /// Answers a batch of queries in the output format set, without
+    /// prompts, and then prints statistics over the whole batch: how many
+    /// routes were found, and the nodes considered and time taken by the
+    /// searches. Each query is routed with the first algorithm set, or else A*,
+    /// but as JSON compares algorithms as run_json does. Statistics are
+    /// written to standard error, unless writing text. With several threads
+    /// set, every query is searched at once before any is answered, and
+    /// the answers are then written in order
+    ///
+    /// - queries: each query's start and end location names
+    ///
+    /// - Return: the exit code for the queries' results: EXIT_UNKNOWN_LOCATION
+    ///   if any named an unknown location, or else EXIT_UNREACHABLE if any
+    ///   end could not be reached, or else EXIT_FOUND
+    ///
+    pub fn run_batch(&self, queries: &[(&str, &str)]) -> i32
+    {
+        let algorithm = self.first_algorithm();
+        let algorithms = match self.output
+        {
+            OutputFormat::Json => self.compared_algorithms(),
+            _ => vec![algorithm],
+        };
+        let (mut found, mut unreachable, mut unknown) = (0, 0, 0);
+        // The # of nodes considered and time taken by each search
+        let mut searches: Vec<(usize, u128)> = Vec::new();
+
+        let mut sw = Stopwatch::new();
+        sw.start();
+        let resolved = queries.iter()
+            .map(|&(from, to)| self.find_node(from).zip(self.find_node(to)))
+            .collect::<Vec<Option<(&'a str, &'a str)>>>();
+        let mut searched = match self.threads
+        {
+            1 => None,
+            _ if self.expansions.is_some() =>
+            {
+                warn!("Searching the queries one at a time, so that the nodes each search expands can be recorded.");
+                None
+            },
+            _ => match self.search_parallel(&resolved.iter().flatten().cloned().collect::<Vec<(&str, &str)>>(), &algorithms)
+            {
+                Ok(searched) => Some(searched.into_iter()),
+                Err(e) => { warn!("{}", e); None },
+            },
+        };
+        let threads = match (&searched, self.threads)
+        {
+            (None, _) => 1,
+            (Some(_), 0) => rayon::current_num_threads(),
+            (Some(_), threads) => threads,
+        };
+
+        for (i, (&(from, to), resolved)) in queries.iter().zip(resolved).enumerate()
+        {
+            let (start, end) = match resolved
+            {
+                Some((start, end)) => (start, end),
+                None =>
+                {
+                    unknown += 1;
+                    match self.output
+                    {
+                        OutputFormat::Json => println!("{}", error_json(from, to, &self.unknown_location(&[from, to]))),
+                        OutputFormat::Script => println!("unknown\t{}\t{}", from, to),
+                        OutputFormat::Text => println!("{}. {} to {}: {}", i + 1, from, to, self.unknown_location(&[from, to])),
+                    }
+                    continue;
+                },
+            };
+
+            // The query's searches, if already run on the threads, which are
+            // recorded as if just run
+            let precomputed = searched.as_mut().and_then(Iterator::next).map(|query_searches| algorithms.iter().cloned()
+                .zip(query_searches)
+                .inspect(|(algorithm, (route, node_counter, micros, _))| self.record_search(start, end, *algorithm, route.as_ref(), *node_counter, *micros, None))
+                .collect::<Vec<(Algorithm, Search<'a>)>>());
+
+            if self.output == OutputFormat::Json
+            {
+                let report = match precomputed
+                {
+                    Some(precomputed) => self.build_report(start, end, precomputed.into_iter()),
+                    None => self.report(start, end),
+                };
+                match report
+                {
+                    Ok(report) =>
+                    {
+                        found += 1;
+                        searches.extend(report.results.iter().map(|result| (result.nodes_considered, result.micros)));
+                        println!("{}", report.to_json());
+                    },
+                    Err(e) => { unreachable += 1; println!("{}", error_json(start, end, &e)); },
+                }
+                continue;
+            }
+
+            let (route, node_counter, micros, _) = match precomputed
+            {
+                Some(mut precomputed) => precomputed.remove(0).1,
+                None => self.timed_search(start, end, algorithm),
+            };
+            searches.push((node_counter, micros));
+            if route.is_some() { found += 1; } else { unreachable += 1; }
+            match (route, self.output)
+            {
+                (route, OutputFormat::Script) => { self.print_script_line(start, end, route.as_ref()); },
+                (Some(route), _) => println!("{}. {} to {}: {} through {} location(s), {} nodes considered in {} micros", i + 1, start, end,
+                    self.distance(route.cost), route.nodes.len(), node_counter, micros),
+                (None, _) => println!("{}. {} to {}: route could not be completed, {} nodes considered in {} micros", i + 1, start, end, node_counter, micros),
+            }
+        }
+        sw.stop();
+
+        let nodes = searches.iter().map(|&(nodes, _)| nodes).sum::<usize>();
+        let micros = searches.iter().map(|&(_, micros)| micros).sum::<u128>();
+        let average = |total: f64| if searches.is_empty() { 0.0 } else { total / searches.len() as f64 };
+        let mut summary = format!("--\n{} queries: {} routed, {} could not be completed, {} named unknown locations.\n\
+            {} searches considered {} nodes ({:.1} on average) in {} micros ({:.1} on average).\n\
+            Answered in {} ms on {} thread(s), {:.1} queries per second.\n",
+            queries.len(), found, unreachable, unknown,
+            searches.len(), nodes, average(nodes as f64), micros, average(micros as f64),
+            sw.elapsed_ms(), threads,
+            queries.len() as f64 / sw.elapsed().as_secs_f64().max(f64::EPSILON));
+        if let Some(cache) = self.cache_summary() { summary += &format!("{}\n", cache); }
+        match self.output
+        {
+            OutputFormat::Text => print!("{}", summary),
+            _ => eprint!("{}", summary),
+        }
+
+        if unknown > 0 { EXIT_UNKNOWN_LOCATION } else if unreachable > 0 { EXIT_UNREACHABLE } else { EXIT_FOUND }
+    }
+
+    ///
+    /// Answers a single query in the output format set, without prompts
+    /// or clearing the screen: as text, with the route found and the time
+    /// each compared algorithm took; as JSON, with its RouteReport; or as
+    /// one line of run_script's output
+    ///
+    /// - from: the start location's name
+    /// - to: the end location's name
+    ///
+    /// - Return: the exit code for the query's result: EXIT_UNKNOWN_LOCATION
+    ///   if either location is unknown, or else EXIT_UNREACHABLE if the
+    ///   end could not be reached, or else EXIT_FOUND
+    ///
+    pub fn answer(&mut self, from: &str, to: &str) -> i32
+    {
+        // A query of known locations fails only if the end is unreachable
+        let known = self.find_node(from).is_some() && self.find_node(to).is_some();
+        let failed = |known: bool| if known { EXIT_UNREACHABLE } else { EXIT_UNKNOWN_LOCATION };
+        match self.output
+        {
+            OutputFormat::Script => self.answer_script(from, to),
+            OutputFormat::Json => match self.report(from, to)
+            {
+                Ok(report) => { println!("{}", report.to_json()); EXIT_FOUND },
+                Err(e) => { println!("{}", error_json(from, to, &e)); failed(known) },
+            },
+            OutputFormat::Text => match self.compare_algorithms(from, to)
+            {
+                Ok(()) => EXIT_FOUND,
+                Err(e) => { eprintln!("{}", e); failed(known) },
+            },
+        }
+    }
+
+    ///
+    /// Routes between two nodes on the Graph using the given algorithm
+    ///
+    /// - algorithm: the algorithm to route with
+    /// - start: the start location on the Graph
+    /// - end: the end location on the Graph
+    ///
+    /// - Return: Either an Ok Result with the route, or an Err with
+    ///   message explaining problem
+    ///
+    pub fn route(&self, algorithm: Algorithm, start: &'a str, end: &'a str) -> Result<PathResult<'a>, String>
+    {
+        let (start, end) = (self.resolve(start)?, self.resolve(end)?);
+
+        let route = match (self.grid.as_ref().and_then(|grid| grid.search(algorithm, start, end)), &self.arcs)
+        {
+            (Some((route, _)), _) => route,
+            (None, Some(arcs)) => algorithm.search(arcs, &*self.heuristic, start, end, self.tie_break).0,
+            (None, None) => algorithm.search(&self.route_dat, &*self.heuristic, start, end, self.tie_break).0,
+        };
+        route.ok_or_else(|| self.unreachable(start, end))
+    }
+
+    ///
+    /// Computes the shortest route from a node to whichever of several
+    /// destinations is nearest (any airport, say), using A*
+    ///
+    /// - start: the start location on the Graph
+    /// - goals: the acceptable end locations on the Graph
+    ///
+    /// - Return: Either an Ok Result with the route, whose last node is the
+    ///   destination chosen, or an Err with message explaining problem
+    ///
+    pub fn route_to_nearest(&self, start: &'a str, goals: &[&'a str]) -> Result<PathResult<'a>, String>
+    {
+        let start = self.resolve(start)?;
+        let goals = &self.resolve_all(goals)?[..];
+
+        let route = match &self.arcs
+        {
+            Some(arcs) => astar::astar_to_any(arcs, Some(&*self.heuristic), start, goals, self.tie_break).0,
+            None => astar::astar_to_any(&self.route_dat, Some(&*self.heuristic), start, goals, self.tie_break).0,
+        };
+        route.ok_or_else(|| String::from("Route could not be completed!"))
+    }
+
+    ///
+    /// Computes the shortest route to a destination from whichever of
+    /// several start locations is nearest (any of three depots, say), using A*
+    ///
+    /// - starts: the acceptable start locations on the Graph
+    /// - end: the end location on the Graph
+    ///
+    /// - Return: Either an Ok Result with the route, whose first node is the
+    ///   start location chosen, or an Err with message explaining problem
+    ///
+    pub fn route_from_nearest(&self, starts: &[&'a str], end: &'a str) -> Result<PathResult<'a>, String>
+    {
+        let starts = &self.resolve_all(starts)?[..];
+        let end = self.resolve(end)?;
+
+        let route = match &self.arcs
+        {
+            Some(arcs) => astar::astar_multi(arcs, Some(&*self.heuristic), starts, &[end], self.tie_break).0,
+            None => astar::astar_multi(&self.route_dat, Some(&*self.heuristic), starts, &[end], self.tie_break).0,
+        };
+        route.ok_or_else(|| String::from("Route could not be completed!"))
+    }
+
+    ///
+    /// Computes a route between two nodes on the Graph that is at most
+    /// (1 + epsilon) times the shortest, using focal search. Larger values
+    /// of epsilon trade route length for fewer nodes considered
+    ///
+    /// - start: the start location on the Graph
+    /// - end: the end location on the Graph
+    /// - epsilon: the allowed suboptimality (0 for the shortest route)
+    ///
+    /// - Return: Either an Ok Result with the route, or an Err with
+    ///   message explaining problem
+    ///
+    pub fn bounded_route(&self, start: &'a str, end: &'a str, epsilon: f64) -> Result<PathResult<'a>, String>
+    {
+        let (start, end) = (self.resolve(start)?, self.resolve(end)?);
+
+        let route = match &self.arcs
+        {
+            Some(arcs) => focal::focal_search(arcs, &*self.heuristic, start, end, epsilon).0,
+            None => focal::focal_search(&self.route_dat, &*self.heuristic, start, end, epsilon).0,
+        };
+        route.ok_or_else(|| self.unreachable(start, end))
+    }
+
+    ///
+    /// Computes the shortest route between two nodes on the Graph using
+    /// SMA*, holding no more than max_nodes search nodes in memory. If the
+    /// shortest route cannot fit within the cap, the shortest that can is
+    /// returned instead
+    ///
+    /// - start: the start location on the Graph
+    /// - end: the end location on the Graph
+    /// - max_nodes: the most search nodes held in memory at once
+    ///
+    /// - Return: Either an Ok Result with the route, or an Err with
+    ///   message explaining problem
+    ///
+    pub fn memory_bounded_route(&self, start: &'a str, end: &'a str, max_nodes: usize) -> Result<PathResult<'a>, String>
+    {
+        let (start, end) = (self.resolve(start)?, self.resolve(end)?);
+
+        let route = match &self.arcs
+        {
+            Some(arcs) => sma_star::sma_star(arcs, &*self.heuristic, start, end, max_nodes, &mut self.arena.borrow_mut()).0,
+            None => sma_star::sma_star(&self.route_dat, &*self.heuristic, start, end, max_nodes, &mut self.arena.borrow_mut()).0,
+        };
+        route.ok_or_else(|| self.unreachable(start, end))
+    }
+
+    ///
+    /// Computes the minimum spanning tree of the Graph: the cheapest
+    /// network of routes connecting every location, treating every route
+    /// as two-way
+    ///
+    /// - Return: the tree's routes and total distance
+    ///
+    pub fn minimum_spanning_tree(&self) -> SpanningTree<'a>
+    {
+        mst::minimum_spanning_tree(&self.route_dat)
+    }
+
+    ///
+    /// Computes a short round trip visiting every one of several locations,
+    /// beginning and ending at the first, using nearest-neighbor and 2-opt
+    /// over the A* distances between them
+    ///
+    /// - waypoints: the locations to visit, starting location first
+    ///
+    /// - Return: Either an Ok Result with the tour, or an Err with
+    ///   message explaining problem
+    ///
+    pub fn tour(&self, waypoints: &[&'a str]) -> Result<Tour<'a>, String>
+    {
+        let waypoints = &self.resolve_all(waypoints)?[..];
+
+        let tour = match &self.arcs
+        {
+            Some(arcs) => tsp::approximate_tour(arcs, &*self.heuristic, waypoints),
+            None => tsp::approximate_tour(&self.route_dat, &*self.heuristic, waypoints),
+        };
+        tour.ok_or_else(|| String::from("Route could not be completed!"))
+    }
+
+    ///
+    /// Routes through several locations in the order given, chaining the
+    /// shortest route between each one and the next, found with the first
+    /// algorithm set (or else A*)
+    ///
+    /// - stops: the locations to visit, in order
+    ///
+    /// - Return: Either an Ok Result with each leg's route, in order, or
+    ///   an Err with message explaining problem
+    ///
+    pub fn itinerary(&self, stops: &[&str]) -> Result<Vec<PathResult<'a>>, String>
+    {
+        let stops = self.resolve_all(stops)?;
+        if stops.len() < 2 { return Err(String::from("An itinerary needs at least two cities.")); }
+
+        stops.windows(2)
+            .map(|pair| self.route(self.first_algorithm(), pair[0], pair[1])
+                .map_err(|_| format!("No route could be found from {} to {}.", pair[0], pair[1])))
+            .collect()
+    }
+
+    ///
+    /// Computes the shortest round trip which travels every route in the
+    /// Graph at least once (the Chinese postman problem), as a snow plow
+    /// or delivery van covering the whole network would
+    ///
+    /// - start: the location the round trip begins and ends at
+    ///
+    /// - Return: Either an Ok Result with the round trip, or an Err with
+    ///   message explaining problem
+    ///
+    pub fn route_inspection(&self, start: &'a str) -> Result<PathResult<'a>, String>
+    {
+        let start = self.resolve(start)?;
+        self.check_two_way("The round trip")?;
+
+        postman::route_inspection(&self.route_dat, start)
+            .ok_or_else(|| String::from("Route could not be completed!"))
+    }
+
+    ///
+    /// Analyzes the structure of the Graph: its connected groups of
+    /// locations, and the single routes (bridges) and locations
+    /// (articulation points) whose failure would disconnect others,
+    /// treating every route as two-way
+    ///
+    /// - Return: the Analysis
+    ///
+    pub fn analyze(&self) -> Analysis<'a>
+    {
+        analysis::analyze(&self.route_dat)
+    }
+
+    ///
+    /// Computes up to k of the shortest routes between two nodes on the Graph,
+    /// in order of increasing distance
+    ///
+    /// - start: the start location on the Graph
+    /// - end: the end location on the Graph
+    /// - k: the maximum number of routes to compute
+    /// - backend: the algorithm to compute them with: Yen's, for routes
+    ///   which never revisit a location, or Eppstein's, for many routes
+    ///
+    /// - Return: Either an Ok Result with the computed routes (empty if end
+    ///   cannot be reached), or an Err with message explaining problem
+    ///
+    pub fn k_shortest_routes(&self, start: &'a str, end: &'a str, k: usize, backend: KspBackend) -> Result<Vec<PathResult<'a>>, String>
+    {
+        let (start, end) = (self.resolve(start)?, self.resolve(end)?);
+        self.check_two_way("Finding the k shortest routes")?;
+
+        Ok(ksp::k_shortest(&self.route_dat, start, end, k, backend, &mut self.arena.borrow_mut()))
+    }
+
+    ///
+    /// Computes progressively shorter routes between two nodes on the Graph
+    /// using Anytime Repairing A*, handing each one to on_solution as it is
+    /// found. A good route is available almost immediately, and better ones
+    /// follow for as long as time allows
+    ///
+    /// - start: the start location on the Graph
+    /// - end: the end location on the Graph
+    /// - deadline: when to stop improving, or None to continue until optimal
+    /// - on_solution: called with every improved route, and the factor by
+    ///   which it may exceed the shortest route
+    ///
+    /// - Return: Either an Ok Result with the best route found (None if end
+    ///   cannot be reached), or an Err with message explaining problem
+    ///
+    pub fn anytime_route<F>(&self, start: &'a str, end: &'a str, deadline: Option<Instant>, mut on_solution: F) -> Result<Option<PathResult<'a>>, String>
+        where F: FnMut(&PathResult<'a>, f64)
+    {
+        let (start, end) = (self.resolve(start)?, self.resolve(end)?);
+        self.check_two_way("Anytime Repairing A*")?;
+
+        ara_star(&self.route_dat, &*self.heuristic, start, end,
+                 ara_star::DEFAULT_INITIAL_WEIGHT, ara_star::DEFAULT_WEIGHT_STEP,
+                 deadline, &mut on_solution)
+    }
+
+    ///
+    /// Creates a D* Lite planner between two nodes on the Graph, using the
+    /// Program's heuristic. The planner keeps its search state, so
+    /// routes can be repaired cheaply as edge weights change
+    ///
+    /// - start: the start location on the Graph
+    /// - end: the end location on the Graph
+    ///
+    /// - Return: Either an Ok Result with the planner, or an Err with
+    ///   message explaining problem
+    ///
+    pub fn dstar_lite(&self, start: &'a str, end: &'a str) -> Result<DStarLite<'a, '_>, String>
+    {
+        let (start, end) = (self.resolve(start)?, self.resolve(end)?);
+        self.check_two_way("D* Lite")?;
+
+        Ok(DStarLite::new(&self.route_dat, &*self.heuristic, start, end))
+    }
+
+    ///
+    /// Creates a Lifelong Planning A* search between two nodes on the Graph,
+    /// using the Program's heuristic. Repeated queries after small edge
+    /// updates reuse the previous search effort
+    ///
+    /// - start: the start location on the Graph
+    /// - end: the end location on the Graph
+    ///
+    /// - Return: Either an Ok Result with the search, or an Err with
+    ///   message explaining problem
+    ///
+    pub fn lpa_star(&self, start: &'a str, end: &'a str) -> Result<LpaStar<'a, '_>, String>
+    {
+        let (start, end) = (self.resolve(start)?, self.resolve(end)?);
+        self.check_two_way("Lifelong Planning A*")?;
+
+        Ok(LpaStar::new(&self.route_dat, &*self.heuristic, start, end))
+    }
+
+    ///
+    /// Computes the shortest route between two nodes on the Graph with
+    /// Djikstra's algorithm, keeping every distance from start. After a
+    /// route is changed with edit_route, the kept distances are repaired
+    /// rather than searched again, so re-querying from the same start is
+    /// cheap
+    ///
+    /// - start: the start location on the Graph
+    /// - end: the end location on the Graph
+    ///
+    /// - Return: Either an Ok Result with the route, or an Err with
+    ///   message explaining problem
+    ///
+    pub fn reroute(&mut self, start: &str, end: &str) -> Result<PathResult<'a>, String>
+    {
+        let (start, end) = (self.resolve(start)?, self.resolve(end)?);
+        self.check_two_way("Incremental search")?;
+
+        let search = match self.incremental.take()
+        {
+            Some(search) if search.start() == start => search,
+            _ => IncrementalSearch::new(&self.route_dat, start),
+        };
+        let route = search.route(end);
+        self.incremental = Some(search);
+        route.ok_or_else(|| String::from("Route could not be completed!"))
+    }
+
+    ///
+    /// Changes the weight of the route between two locations, adding the
+    /// route if there is none, or removes it. Every later search follows
+    /// the changed Graph, and the search kept by reroute is repaired. The
+    /// heuristic is not recomputed, so a shortened route may leave A*'s
+    /// routes longer than the shortest
+    ///
+    /// - from, to: the route's end locations
+    /// - weight: the route's new weight, or None to remove it
+    ///
+    /// - Return: Either an Ok Result with the # of nodes settled repairing
+    ///   the kept search, or None if none is kept, or an Err with message
+    ///   explaining problem
+    ///
+    pub fn edit_route(&mut self, from: &str, to: &str, weight: Option<u64>) -> Result<Option<usize>, String>
+    {
+        let (from, to) = (self.resolve(from)?, self.resolve(to)?);
+        self.check_two_way("Editing routes")?;
+        if from == to { return Err(String::from("A route must join two different locations.")); }
+
+        match weight
+        {
+            Some(weight) => { self.route_dat.add_edge(from, to, weight); },
+            None => if self.route_dat.remove_edge(from, to).is_none()
+            {
+                return Err(format!("There is no route between {} and {}.", from, to));
+            },
+        }
+
+        // Everything derived from the Graph's routes is brought up to date,
+        // and the grid map, and the algorithms searching only it, no longer
+        // match them
+        self.grid = None;
+        if self.algorithms.iter().any(|algorithm| Algorithm::GRID.contains(algorithm))
+        {
+            warn!("The grid map no longer matches the edited routes, so Jump Point Search and Theta* are no longer routed with.");
+            self.algorithms.retain(|algorithm| !Algorithm::GRID.contains(algorithm));
+        }
+        self.interned = interned(&self.route_dat, &self.arcs, &*self.heuristic, self.reorder);
+        self.components = Components::new(&self.route_dat);
+        if let Some(cache) = &self.cache { cache.lock().unwrap().clear(); }
+        let frontier = self.context.borrow().frontier();
+        if frontier == Frontier::Buckets { self.set_frontier(frontier); }
+
+        let route_dat = &self.route_dat;
+        Ok(self.incremental.as_mut().map(|search|
+        {
+            search.update_edge(route_dat, from, to);
+            search.expanded()
+        }))
+    }
+
+    ///
+    /// Preprocesses the Graph into a contraction hierarchy, which can then
+    /// answer any number of shortest route queries in a fraction of the
+    /// time of a flat search. Every route is treated as two-way
+    ///
+    pub fn contraction_hierarchy(&self) -> ContractionHierarchy<'a>
+    {
+        match &self.artifacts
+        {
+            Some(routes) => sidecar::load_or_build(&self.route_dat, routes, 0, || ContractionHierarchy::new(&self.route_dat)),
+            None => ContractionHierarchy::new(&self.route_dat),
+        }
+    }
+
+    ///
+    /// Partitions the Graph into clusters with precomputed paths across
+    /// each, so that routes can be found over the much smaller Graph of
+    /// cluster border nodes and then refined. Every route is treated as
+    /// two-way
+    ///
+    /// - cluster_size: the most nodes any one cluster may hold
+    ///
+    pub fn hierarchy(&self, cluster_size: usize) -> Hierarchy<'a>
+    {
+        Hierarchy::new(&self.route_dat, cluster_size)
+    }
+
+    ///
+    /// Writes the routes as a Graphviz DOT file, with the last route
+    /// shown highlighted, to be drawn with "dot -Tpng"
+    ///
+    /// - path: the DOT file to write
+    ///
+    /// - Return: Either an empty Ok Result, or an Err with message
+    ///   explaining problem
+    ///
+    pub fn export_dot(&self, path: &Path) -> Result<(), String>
+    {
+        let route = self.last_route.iter().map(String::as_str).collect::<Vec<&str>>();
+        export::write(path, &to_dot(&self.route_dat, self.arcs.as_ref(), &route, self.unit, self.precision))
+    }
+
+    ///
+    /// Draws the routes as an SVG image, with the last route shown
+    /// highlighted and the locations A* expanded to find it shaded (or
+    /// Djikstra's algorithm, if set first). Locations are placed by their
+    /// coordinates, or else laid out by force
+    ///
+    /// - path: the SVG file to write
+    ///
+    /// - Return: Either an empty Ok Result, or an Err with message
+    ///   explaining problem
+    ///
+    pub fn export_svg(&self, path: &Path) -> Result<(), String>
+    {
+        let route = self.last_route.iter().map(String::as_str).collect::<Vec<&str>>();
+
+        // Repeat the last route's search, to find the locations expanded
+        let mut expanded = HashSet::new();
+        let ends = (route.first().and_then(|&start| self.find_node(start)), route.last().and_then(|&end| self.find_node(end)));
+        if let (Some(start), Some(end)) = ends
+        {
+            let heuristic = match self.first_algorithm()
+            {
+                Algorithm::Dijkstra => None,
+                _ => Some(&*self.heuristic),
+            };
+            let on_expand = |expansion: Expansion<'a>| { expanded.insert(expansion.node); };
+            match &self.arcs
+            {
+                Some(arcs) => astar::astar_observed(arcs, heuristic, &[start], &[end], self.tie_break, on_expand),
+                None => astar::astar_observed(&self.route_dat, heuristic, &[start], &[end], self.tie_break, on_expand),
+            };
+        }
+
+        export::write(path, &to_svg(&self.route_dat, &self.drawing_positions(), &route, &expanded, self.unit, self.precision))
+    }
+
+    ///
+    /// Appends each node an A* or Djikstra search expanded to a file.
+    /// Other algorithms cannot be observed, so are not written
+    ///
+    /// - path: the file to append to
+    /// - start: the start location on the Graph
+    /// - end: the end location on the Graph
+    /// - algorithm: the algorithm searched with
+    /// - expanded: each node the search expanded, in order, or None if
+    ///   the search was not observed
+    ///
+    /// - Return: Either an empty Ok Result, or an Err with message
+    ///   explaining problem, or why the search was not written
+    ///
+    fn export_expansions(&self, path: &Path, start: &'a str, end: &'a str, algorithm: Algorithm, expanded: Option<&[Expansion<'a>]>) -> Result<(), String>
+    {
+        let heuristic = match (algorithm, expanded)
+        {
+            (Algorithm::AStar, Some(_)) => Some(&*self.heuristic),
+            (Algorithm::Dijkstra, Some(_)) => None,
+            _ => return Err(format!("The nodes {} expanded from {} to {} cannot be observed, so were not written to \"{}\".",
+                algorithm.name(), start, end, path.display())),
+        };
+
+        let expansions = expanded.unwrap_or_default().iter().map(|expansion|
+        {
+            let h = heuristic.map_or(0, |heuristic| heuristic.estimate(expansion.node, end));
+            ExpandedNode
+            {
+                order: expansion.order,
+                node: expansion.node,
+                parent: expansion.parent,
+                g: self.unit.from_weight(expansion.g),
+                h: self.unit.from_weight(h),
+                f: self.unit.from_weight(expansion.g + h),
+            }
+        }).collect::<Vec<ExpandedNode>>();
+
+        expansions::append(path, start, end, algorithm.name(), &expansions)
+    }
+
+    ///
+    /// Finds a location on the Graph by its name, exactly, or else
+    /// ignoring case and spacing
+    ///
+    /// - Return: the Graph's copy of a location's name, if it exists
+    ///
+    fn find_node(&self, name: &str) -> Option<&'a str>
+    {
+        self.names.find(name)
+    }
+
+    ///
+    /// Places each location for drawing, with y running downward: by its
+    /// coordinates with north up, or by force without coordinates
+    ///
+    fn drawing_positions(&self) -> HashMap<&'a str, (f64, f64)>
+    {
+        if self.coordinates.is_empty() { return force_directed(&self.route_dat); }
+
+        match (self.geographic, self.unit)
+        {
+            // Degrees of longitude narrow away from the equator
+            (true, _) =>
+            {
+                let mean_lat = self.coordinates.values().map(|&(_, lat)| lat).sum::<f64>() / self.coordinates.len() as f64;
+                let narrowing = mean_lat.to_radians().cos();
+                self.coordinates.iter().map(|(&node, &(lon, lat))| (node, (lon * narrowing, -lat))).collect()
+            },
+
+            // Grid maps' rows already run downward
+            (false, Unit::Cells) => self.coordinates.clone(),
+            (false, _) => self.coordinates.iter().map(|(&node, &(x, y))| (node, (x, -y))).collect(),
+        }
+    }
+
+    ///
+    /// Keeps the locations of a route shown, for exports to highlight
+    ///
+    fn remember_route(&mut self, route: &PathResult)
+    {
+        self.last_route = route.nodes.iter().map(|node| node.to_string()).collect();
+    }
+
+    ///
+    /// Runs a search from the history again, printing its route
+    ///
+    /// - query: the search to run
+    ///
+    /// - Return: Either an Ok Result with the route, or an Err with message
+    ///   explaining problem
+    ///
+    fn rerun(&self, query: &Query) -> Result<PathResult<'a>, String>
+    {
+        let algorithm = Algorithm::from_name(&query.algorithm)
+            .ok_or_else(|| format!("Unknown algorithm \"{}\".", query.algorithm))?;
+        let (start, end) = match (self.find_node(&query.from), self.find_node(&query.to))
+        {
+            (Some(start), Some(end)) => (start, end),
+            _ => return Err(self.unknown_location(&[&query.from, &query.to])),
+        };
+
+        println!("Running {} Algorithm from {} to {}...", algorithm.name(), start, end);
+        let (elapsed, route) = self.find_shortest_route(start, end, algorithm, true)?;
+        println!("--\n{} time to compute: {} micros.", algorithm.name(), elapsed);
+        Ok(route)
+    }
+
+    ///
+    /// Runs every problem of a MovingAI scenario file over the map's
+    /// Graph, with the first algorithm set (or else A*), printing the length of
+    /// each route found beside its optimal length, and then how many
+    /// matched
+    ///
+    /// - scenarios: the problems to run
+    /// - straight_cost: the weight of one cell's step on the Graph
+    ///
+    /// - Return: the # of problems whose route did not match its optimal
+    ///   length, or could not be completed
+    ///
+    pub fn run_scenarios(&self, scenarios: &[Scenario], straight_cost: u64) -> usize
+    {
+        let algorithm = self.first_algorithm();
+        println!("Running {} scenario(s) with the {} Algorithm...\n", scenarios.len(), algorithm.name());
+
+        // Each cell's location, by name
+        let cells = self.route_dat.nodes().map(|node| (node, node)).collect::<HashMap<&str, &'a str>>();
+
+        let (mut matched, mut nodes, mut sw) = (0, 0, Stopwatch::new());
+        for (i, scenario) in scenarios.iter().enumerate()
+        {
+            let (start, goal) = (cell_name(scenario.start), cell_name(scenario.goal));
+            let (start, goal) = match (cells.get(start.as_str()), cells.get(goal.as_str()))
+            {
+                (Some(&start), Some(&goal)) => (start, goal),
+                _ =>
+                {
+                    println!("{}: ({}) to ({}): start or goal is not a passable cell", i + 1, start, goal);
+                    continue;
+                },
+            };
+
+            sw.start();
+            let (route, node_counter) = self.grid.as_ref().and_then(|grid| grid.search(algorithm, start, goal))
+                .unwrap_or_else(|| algorithm.search(&self.route_dat, &*self.heuristic, start, goal, self.tie_break));
+            sw.stop();
+            nodes += node_counter;
+
+            let length = match route
+            {
+                Some(route) => route.cost as f64 / straight_cost as f64,
+                None =>
+                {
+                    println!("{}: ({}) to ({}): route could not be completed (optimal {:.4})", i + 1, start, goal, scenario.optimal);
+                    continue;
+                },
+            };
+            let status = if (length - scenario.optimal).abs() <= SCENARIO_TOLERANCE { matched += 1; "optimal" } else { "DIFFERS" };
+            println!("{}: ({}) to ({}): {:.4} (optimal {:.4}) {}", i + 1, start, goal, length, scenario.optimal, status);
+        }
+
+        println!("--\n{} of {} scenario(s) matched their optimal length.", matched, scenarios.len());
+        println!("{} nodes considered in {} micros.", nodes, sw.elapsed().as_micros());
+        scenarios.len() - matched
+    }
+
+    ///
+    /// Benchmarks each compared algorithm, or else the algorithms set, over
+    /// the same random queries between the Graph's locations, printing for
+    /// each how many routes were found, the nodes considered, and
+    /// percentiles of the time taken per search. Nothing is recorded, and
+    /// the same seed always draws the same queries
+    ///
+    /// - count: the # of random queries
+    /// - seed: the seed the queries are drawn from
+    ///
+    pub fn run_benchmark(&self, count: usize, seed: u64)
+    {
+        let locations = self.locations();
+        if locations.len() < 2 { return println!("Benchmarking needs at least two locations."); }
+
+        let mut state = seed;
+        let mut pick = || locations[(split_mix(&mut state) % locations.len() as u64) as usize];
+        let queries = (0..count).map(|_| (pick(), pick())).collect::<Vec<(&'a str, &'a str)>>();
+
+        let algorithms = self.compared_algorithms();
+        println!("Running {} random queries between {} locations (seed {})...\n", count, locations.len(), seed);
+        println!("{0:<28}{1:>8}{2:>12}{3:>10}{4:>10}{5:>10}{6:>10}{7:>10}", "Algorithm", "Routed", "Nodes", "Mean", "p50", "p90", "p99", "Max");
+
+        let searcher = self.searcher();
+        let mut context = SearchContext::new(self.context.borrow().frontier());
+        for algorithm in algorithms
+        {
+            let (mut routed, mut nodes, mut micros) = (0, 0, Vec::with_capacity(count));
+            for &(start, end) in &queries
+            {
+                let (route, node_counter, elapsed, _) = searcher.search(start, end, algorithm, &mut context, |_| ());
+                if route.is_some() { routed += 1; }
+                nodes += node_counter;
+                micros.push(elapsed);
+            }
+            micros.sort_unstable();
+
+            let mean = micros.iter().sum::<u128>() as f64 / count.max(1) as f64;
+            println!("{0:<28}{1:>8}{2:>12.1}{3:>10.1}{4:>10}{5:>10}{6:>10}{7:>10}", algorithm.name(), routed,
+                nodes as f64 / count.max(1) as f64, mean,
+                percentile(&micros, 50.0), percentile(&micros, 90.0), percentile(&micros, 99.0), micros.last().cloned().unwrap_or(0));
+        }
+        println!("--\nNodes are the mean considered per search, and times are in micros per search.");
+    }
+
+    ///
+    /// Checks the heuristic against the true shortest distance to every
+    /// location, or to a random sample of them, printing the estimates
+    /// which overestimate it (so are inadmissible) and the routes across
+    /// which estimates fall by more than the route's length (so are
+    /// inconsistent), largest first. One-way routes are only followed in
+    /// their direction
+    ///
+    /// - sample: the # of goals to check, chosen at random, or None for
+    ///   every location
+    /// - seed: the seed the sample is drawn from
+    ///
+    /// - Return: the # of inadmissible and inconsistent estimates found
+    ///
+    pub fn run_heuristic_check(&self, sample: Option<usize>, seed: u64) -> usize
+    {
+        let mut goals = self.locations();
+        if let Some(sample) = sample.filter(|&sample| sample < goals.len())
+        {
+            // Shuffle the first sample locations into place
+            let mut state = seed;
+            for i in 0..sample
+            {
+                let j = i + (split_mix(&mut state) % (goals.len() - i) as u64) as usize;
+                goals.swap(i, j);
+            }
+            goals.truncate(sample);
+        }
+
+        println!("Checking the heuristic's estimates to {} location(s)...\n", goals.len());
+        let check = match &self.arcs
+        {
+            Some(arcs) => check_heuristic(arcs, &*self.heuristic, &goals),
+            None => check_heuristic(&self.route_dat, &*self.heuristic, &goals),
+        };
+        self.print_heuristic_check(&check);
+        check.overestimates.len() + check.inconsistencies.len()
+    }
+
+    ///
+    /// Finds a location on the Graph, as find_node does
+    ///
+    /// - Return: Either an Ok Result with the Graph's copy of the
+    ///   location's name, or an Err with message explaining problem if it
+    ///   does not exist
+    ///
+    fn resolve(&self, location: &str) -> Result<&'a str, String>
+    {
+        self.find_node(location).ok_or_else(|| self.unknown_location(&[location]))
+    }
+
+    ///
+    /// Finds every given location on the Graph, as find_node does
+    ///
+    /// - Return: Either an Ok Result with the Graph's copy of each
+    ///   location's name, in order, or an Err with message explaining
+    ///   problem if any does not exist
+    ///
+    fn resolve_all(&self, locations: &[&str]) -> Result<Vec<&'a str>, String>
+    {
+        locations.iter().map(|location| self.resolve(location)).collect()
+    }
+
+    ///
+    /// Explains that a location does not exist on the Graph, suggesting
+    /// the closest names to it
+    ///
+    /// - locations: the locations asked for, of which any may not exist
+    ///
+    /// - Return: the message, naming the first location which does not
+    ///   exist
+    ///
+    fn unknown_location(&self, locations: &[&str]) -> String
+    {
+        let location = match locations.iter().find(|location| self.find_node(location).is_none())
+        {
+            Some(location) => location,
+            None => return String::from("Cannot route: one or more locations do not exist."),
+        };
+
+        match self.suggest(location).as_slice()
+        {
+            [] => format!("Cannot route: \"{}\" does not exist.", location),
+            [suggestion] => format!("Cannot route: \"{}\" does not exist. Did you mean {}?", location, suggestion),
+            [suggestions @ .., last] => format!("Cannot route: \"{}\" does not exist. Did you mean {} or {}?", location, suggestions.join(", "), last),
         }
     }
 
     ///
-    /// Runs the Program, guiding the user through a loop until they
-    /// enter "quit". Asks user to provide a starting point and destination,
-    /// then calling the find_shortest_route method to traverse from start
-    /// to finish using A* and Djikstra's (comparing the two)
-    /// 
-    pub fn run(&mut self)
+    /// Explains why no route was found between two locations: when they
+    /// are in separate connected groups, a few of the locations in each,
+    /// or else that one-way routes keep end out of reach
+    ///
+    /// - start, end: the locations no route was found between
+    ///
+    /// - Return: the message
+    ///
+    fn unreachable(&self, start: &str, end: &str) -> String
     {
-        // Loop until user quites
-        loop
+        if self.components.connected(start, end)
         {
-            // Clear the screen and print all possible locations in Graph
-            clear_screen();            
-            println!("Your Locations:\n");
-            for (i, node) in self.route_dat.nodes().enumerate()
+            return match self.arcs
+            {
+                Some(_) => format!("Route could not be completed! The routes joining {} to {} cannot all be travelled that way.", start, end),
+                None => String::from("Route could not be completed!"),
+            };
+        }
+
+        // Describes the connected group a location is in, by a few of the
+        // other locations in it
+        let group = |location: &str|
+        {
+            let members = match self.components.component(location)
+            {
+                Some(component) => self.components.members(component),
+                None => &[],
+            };
+            let others = members.iter().filter(|&&member| member != location).cloned().collect::<Vec<&str>>();
+            match others.len()
             {
-                print!("{0:<15}", node); 
-                if i % 5 == 4 { println!(); }
+                0 => format!("{} has no routes to any other location", location),
+                count if count <= COMPONENT_MEMBERS_SHOWN => format!("{} is connected only to {}", location, others.join(", ")),
+                count => format!("{} is connected to {} location(s), such as {}", location, count,
+                    others[..COMPONENT_MEMBERS_SHOWN].join(", ")),
             }
+        };
 
-            // Prompt for and retrieve start and finish location(s)
-            println!("--\nWhat city are you starting at?");
-            println!("Type \"Quit\" at any time to exit.");
-            let from = input(false);
-            if from.to_lowercase() == "quit" { break; }
+        format!("Route could not be completed! {} and {} are in separate groups of locations, of {} in all: {}, and {}.",
+            start, end, self.components.count(), group(start), group(end))
+    }
 
-            println!("What city are you going to?");
-            let to = input(false);
-            if to.to_lowercase() == "quit" { break; }
-          
-            clear_screen();
+    ///
+    /// - Return: the names on the Graph closest to a mistyped location,
+    ///   closest first
+    ///
+    fn suggest(&self, location: &str) -> Vec<&'a str>
+    {
+        names::closest(location, self.route_dat.nodes(), SUGGESTION_COUNT)
+    }
+
+    ///
+    /// Finds a location typed at a prompt on the Graph, or else offers the
+    /// closest names to it to choose from
+    ///
+    /// - location: the location's name, as typed
+    ///
+    /// - Return: the location on the Graph, or None if there is none, or
+    ///   none was chosen
+    ///
+    fn confirm_location(&self, location: &str) -> Option<&'a str>
+    {
+        if let Some(node) = self.find_node(location) { return Some(node); }
 
-            // Run the method, first with the A* heuristic, then with
-            // Djikstra. Track the time taken for both to complete and display at
-            // finish
-            println!("\nRunning A* Algorithm...");
-            match self.find_shortest_route(&from, &to, true)
+        let suggestions = self.suggest(location);
+        match suggestions.as_slice()
+        {
+            [] =>
             {
-                Err(e) => println!("{}", e),
-                Ok(elapsed) => 
+                println!("\"{}\" is not a location.", location);
+                None
+            },
+            [suggestion] =>
+            {
+                let answer = prompt(&format!("\"{}\" is not a location. Did you mean {}? (Y/n) ", location, suggestion));
+                let answer = answer.trim().to_lowercase();
+                Some(*suggestion).filter(|_| answer.is_empty() || answer.starts_with('y'))
+            },
+            _ =>
+            {
+                println!("\"{}\" is not a location. Did you mean:", location);
+                for (i, suggestion) in suggestions.iter().enumerate()
                 {
-                    println!("\nRunning Djikstra Algorithm...");
-                    let a_star_time = elapsed;
-                    let djik_time = self.find_shortest_route(&from, &to, false).unwrap();
-
-                    println!("--");
-                    println!("A* time to compute: {} micros.", a_star_time);
-                    println!("Djikstra time to compute: {} micros.\n", djik_time);
+                    println!("{}. {}", i + 1, suggestion);
                 }
-            };
-           
-            // Wait for ENTER as user looks over results
-            wait_for_enter();
-        }        
+                println!("Type its #, or press ENTER to choose again.");
+                let choice = input(false);
+                choice.trim().parse::<usize>().ok()
+                    .and_then(|number| suggestions.get(number.wrapping_sub(1)))
+                    .cloned()
+            },
+        }
     }
 
-    /// 
-    /// Computes the shortest route between two nodes on a Graph
-    /// Uses either A* or Djikstra's algorithm, depending on a_star value
-    /// 
-    /// - start: the start location on the Graph
-    /// - end: the end location on the Graph
-    /// - a_star: determines if A* heuristic method is implemented
-    /// 
-    /// - Return: Either an Ok Result with the amount of time taken to compute path,
-    ///   or an Err with message explaining problem
-    /// 
-    fn find_shortest_route(&self, start: &'a str, end: &'a str, a_star: bool) -> Result<u128, String>
+    ///
+    /// Ensures every route is two-way, for features which cannot follow
+    /// one-way routes
+    ///
+    /// - feature: the feature's name, for the message
+    ///
+    /// - Return: Err with message explaining problem if any route is one-way
+    ///
+    fn check_two_way(&self, feature: &str) -> Result<(), String>
     {
-        let mut sw = Stopwatch::new();
+        match self.arcs
+        {
+            Some(_) => Err(format!("{} cannot follow one-way routes.", feature)),
+            None => Ok(()),
+        }
+    }
 
-        // If provided start or end node does not exist, prompt the
-        // user of this, and return Err
-        if !self.route_dat.contains_node(start) ||
-           !self.route_dat.contains_node(end)
+    ///
+    /// - Return: each Step of a route found on the Graph, following
+    ///   one-way routes if any, or across the grid map, whose any-angle
+    ///   routes step between cells with no route between them
+    ///
+    fn route_steps<'r>(&self, route: &PathResult<'r>) -> Vec<Step<'r>> where 'a: 'r
+    {
+        match (&self.grid, &self.arcs)
         {
-            return Err(String::from("Cannot route: one or more locations do not exist."));
+            (Some(grid), _) => grid.steps(route),
+            (None, Some(arcs)) => route.steps(arcs),
+            (None, None) => route.steps(&self.route_dat),
         }
+    }
 
-        sw.start();
+    ///
+    /// - weight: a weight, in the unit's scale
+    ///
+    /// - Return: the weight written in the unit, such as "12.5 mi."
+    ///
+    fn distance(&self, weight: u64) -> String
+    {
+        self.unit.format(self.unit.from_weight(weight), self.precision)
+    }
 
-        // Create a priority queue, which will hold all route information,
-        // and automatically supply the shortest distance route
-        // Push start node onto queue
-        let mut route_dists = PriorityQueue::new();
-        route_dists.push(start, Reverse(0));
-
-        //
-        // A HashMap for each node's distance from start on the Graph.
-        // The key is the node in question, while the value is the distance.
-        //
-        // Ensures quick retrieval of distances, and stores base distance while
-        // performing A* search (routes_dists will store base distance + heuristic
-        // in this case)
-        //
-        let mut dist: HashMap<&str, u64> = HashMap::new();
-        dist.insert(start, 0);
-
-        // A marker for each node in the Graph, representing which adjacent
-        // node provides the path of least distance
-        let mut prev: HashMap<&str, &str> = HashMap::new();
-
-        // Counter for total # of nodes considered
-        let mut node_counter = 0;
-
-        // Loop through all routes
-        loop
+    ///
+    /// - Return: the time taken to travel the distance at the speed set,
+    ///   such as "1 hr. 5 min.", or None without a speed or distances in
+    ///   miles or kilometers
+    ///
+    fn travel_time(&self, weight: u64) -> Option<String>
+    {
+        let speed = self.speed.filter(|_| self.unit.speed().is_some())?;
+        let minutes = (self.unit.from_weight(weight) / speed * 60.0).round() as u64;
+        Some(match minutes / 60
+        {
+            0 => format!("{} min.", minutes),
+            hours => format!("{} hr. {} min.", hours, minutes % 60),
+        })
+    }
+
+    ///
+    /// Prints each step of a route, from start to finish, with the
+    /// distance so far (and the time so far, if a speed is set), followed
+    /// by its total distance, and writes it to any GPX file set
+    ///
+    /// - route: the route to print
+    ///
+    fn print_route(&self, route: &PathResult)
+    {
+        let steps = self.route_steps(route);
+        for step in steps
         {
-            match route_dists.pop()
+            print!("{}. Take {} to {}: {} ({} so far", step.index,
+                step.from.if_supports_color(Stream::Stdout, |name| name.cyan()), step.to.if_supports_color(Stream::Stdout, |name| name.cyan()),
+                self.distance(step.distance), self.distance(step.cumulative));
+            match self.travel_time(step.cumulative)
             {
-                // While there any existing routes
-                Some(min_route) => 
-                {
-                    node_counter += 1;
-                    // If min_route is the destination node
-                    if min_route.0 == end
-                    {
-                        sw.stop();
+                Some(time) => println!(", ETA {})", time),
+                None => println!(")"),
+            }
+        }
+
+        // Print total distance after path has been printed
+        let total = format!("Total {}: {}", self.unit.measure(), self.distance(route.cost));
+        println!("{}", total.if_supports_color(Stream::Stdout, |total| total.bold()));
+        if let (Some(time), Some(speed), Some(per_hour)) = (self.travel_time(route.cost), self.speed, self.unit.speed())
+        {
+            println!("Travel time at {} {}: {}", speed, per_hour, time);
+        }
 
-                        // Print # of nodes considered
-                        println!("{} nodes considered", node_counter);
+        if let Some(path) = &self.gpx
+        {
+            let gpx = match self.geographic
+            {
+                true => to_gpx(route, &self.coordinates),
+                false => Err(String::from("Cannot write the route as GPX: locations have no latitude and longitude.")),
+            };
+            match gpx.and_then(|gpx| export::write(path, &gpx))
+            {
+                Ok(()) => println!("Route written to {}", path.display()),
+                Err(e) => println!("{}", e),
+            }
+        }
+    }
 
-                        // Print shortest route (if A*)
-                        if a_star { self.print_shortest_route(prev, end, dist[end]); }
+    ///
+    /// Prints a page of the locations whose names hold some text, ignoring
+    /// case and spacing, in alphabetical order. With more than one page,
+    /// or a filter, which page this is and how to change it is printed
+    /// after
+    ///
+    /// - filter: the text each location printed holds, or "" for all
+    /// - page: which page to print, from 0. The last page is printed if
+    ///   there are fewer pages
+    ///
+    /// - Return: the # of pages of locations holding the text, which is at
+    ///   least 1
+    ///
+    fn print_location_page(&self, filter: &str, page: usize) -> usize
+    {
+        let normalized = names::normalize(filter);
+        let locations = self.locations().into_iter()
+            .filter(|location| names::normalize(location).contains(&normalized))
+            .collect::<Vec<&str>>();
+        let pages = locations.len().div_ceil(LOCATIONS_PER_PAGE).max(1);
+        let page = page.min(pages - 1);
 
-                        // Return time taken to compute (in microseconds)
-                        return Ok(sw.elapsed().as_micros());
-                    }
+        let shown = locations.iter().skip(page * LOCATIONS_PER_PAGE).take(LOCATIONS_PER_PAGE).cloned().collect::<Vec<&str>>();
+        print_columns(&shown, 15, 5);
 
-                    // For every frontier node for the min_route node
-                    for edge in self.route_dat.edges(min_route.0)
-                    {
-                        // min_route.0 and edge.0 will be the same value
+        if !filter.is_empty() || pages > 1
+        {
+            let matching = match filter
+            {
+                "" => String::new(),
+                filter => format!(" containing \"{}\"", filter),
+            };
+            println!("\nPage {} of {}, of {} cities{}.", page + 1, pages, locations.len(), matching);
+            println!("Type \"Next\" or \"Previous\" to turn the page, or \"Show <text>\" for the cities containing the text (\"Show\" alone for all).");
+        }
 
-                        // Find the total weight distance between min_route node and its
-                        // edge node.
-                        let alt_route = dist[min_route.0] + edge.2;
+        pages
+    }
 
-                        // If that value does not yet exist in dist, or if dist is greater,
-                        // update dist and prev, and push alt_route into queue
-                        if !dist.contains_key(edge.1) || alt_route < dist[edge.1]
-                        {
-                            // Set dist of edge node to alt_route value
-                            dist.insert(edge.1, alt_route);
-                            
-                            // Set prev of edge node to min_route - it is the new
-                            // previous node to the edge node
-                            prev.insert(edge.1, min_route.0);
-
-                            // Update edge node on routes priority queue to alt_route
-                            // Include heuristic if a_star
-                            if a_star { route_dists.push(edge.1, Reverse(alt_route + self.heur_map[&(edge.1, end)])); }
-                            else { route_dists.push(edge.1, Reverse(alt_route)); }
-                        }
-                    }
-                },
+    ///
+    /// Prints every location, in alphabetical order, with how many there
+    /// are
+    ///
+    fn print_locations(&self)
+    {
+        let locations = self.locations();
+        println!("{} cities:\n", locations.len());
+        print_columns(&locations, 25, 4);
+        println!();
+    }
 
-                // If no other routes exist, return Err - destination could not be reached
-                None => return Err(String::from("Route could not be completed!"))
-            };
+    ///
+    /// Prints each location reached directly from a location, nearest
+    /// first, with the distance to it (and the time taken, if a speed is
+    /// set). One-way routes are only listed in the direction they may be
+    /// travelled
+    ///
+    /// - location: the location's name
+    ///
+    fn print_neighbors(&self, location: &'a str)
+    {
+        let mut neighbors = match &self.arcs
+        {
+            Some(arcs) => arcs.edges(location).map(|(_, to, &weight)| (weight, to)).collect::<Vec<(u64, &str)>>(),
+            None => self.route_dat.edges(location).map(|(_, to, &weight)| (weight, to)).collect(),
+        };
+        neighbors.sort_unstable();
+
+        println!("Routes from {}:\n", location);
+        if neighbors.is_empty() { println!("None"); }
+        for (weight, to) in neighbors
+        {
+            print!("{}: {}", to, self.distance(weight));
+            match self.travel_time(weight)
+            {
+                Some(time) => println!(" ({})", time),
+                None => println!(),
+            }
         }
     }
 
     ///
-    /// Prints the shortest route starting at destination, recursively working back to start
-    /// 
-    /// - prev: all HashMap data associated with searched nodes and their minimum weighted
-    ///         previous nodes
-    /// - to: the ending location
-    /// - total_dist: the total distance in miles required to traverse path
-    /// 
-    fn print_shortest_route(&self, prev: HashMap<&str, &str>, to: &str, total_dist: u64)
+    /// Prints a location's details: how many routes it has, which
+    /// connected group of locations it belongs to, and its coordinates,
+    /// if known
+    ///
+    /// - location: the location's name
+    ///
+    fn print_info(&self, location: &'a str)
     {
-        // Pass end location into helper method
-        let prv: &str = prev[to];
-        self.print_shortest_route_helper(&prev, prv, to);
+        println!("{}\n", location);
+        match &self.arcs
+        {
+            Some(arcs) => println!("Routes: {} leaving, {} arriving",
+                arcs.neighbors_directed(location, Outgoing).count(), arcs.neighbors_directed(location, Incoming).count()),
+            None => println!("Routes: {}", self.route_dat.neighbors(location).count()),
+        }
 
-        // Print total distance after path has been printed
-        println!("Total distance: {:.1} mi.", (total_dist as f64) / 10.0);
+        let components = self.analyze().components;
+        if let Some((i, component)) = components.iter().enumerate().find(|(_, component)| component.contains(&location))
+        {
+            println!("Connected group: {} of {}, holding {} of the {} cities", i + 1, components.len(), component.len(), self.route_dat.node_count());
+        }
+
+        match (self.coordinates.get(location), self.geographic)
+        {
+            (Some((longitude, latitude)), true) => println!("Latitude and longitude: {}, {}", latitude, longitude),
+            (Some((x, y)), false) => println!("Coordinates: {}, {}", x, y),
+            (None, _) => println!("Coordinates: unknown"),
+        }
     }
 
     ///
-    /// Helper function that performs DFS, printing shortest path from start to finish
-    /// 
-    /// - prev: all HashMap data associated with searched nodes and their minimum weighted
-    ///         previous nodes
-    /// - prv: the current previous node being considered
-    /// - next: the node directly after prv in the shortest path
-    /// 
-    fn print_shortest_route_helper(&self, prev: &HashMap<&str, &str>, prv: &str, next: &str)
+    /// Prints the connected groups of locations in an Analysis, followed
+    /// by its bridges and articulation points
+    ///
+    /// - analysis: the Analysis to print
+    ///
+    fn print_analysis(&self, analysis: &Analysis)
     {
-        // If start node has yet to be reached, call method on previous node in path
-        if prev.contains_key(prv) { self.print_shortest_route_helper(prev, prev[prv], prv); }
+        println!("{} connected group(s) of cities:\n", analysis.components.len());
+        for component in &analysis.components
+        {
+            println!("{}", component.join(", "));
+        }
+
+        println!("\nRoutes whose closure would disconnect cities:\n");
+        for (from, to) in &analysis.bridges
+        {
+            println!("{} to {}", from, to);
+        }
 
-        // Print node information
-        println!("Take {} to {}: {:.1} mi.", prv, next, (*self.route_dat.edge_weight(prv, next).unwrap() as f64) / 10.0);
+        println!("\nCities whose closure would disconnect others:\n");
+        println!("{}", analysis.articulation_points.join(", "));
     }
-}
 
-/// 
-/// Build an Undirected Adjacency List Graph off of
-/// the supplied input
-/// 
-/// - route_dat: the input data, as a borrowed String
-/// 
-/// - return: an UnGraphMap with u64 weight edges. The float value
-///   provided from route_dat is rounded to 1 decimal place, and multipled
-///   by 10, to maintain precision, but allow complete ordering
-/// 
-fn build_map<'a>(route_dat: &'a String) -> UnGraphMap<&'a str, u64>
-{
-    // Define the graph to return
-    let mut graph = UnGraphMap::new();
+    ///
+    /// Changes a route as the "Edit" command asks, then runs the last
+    /// search again, repairing its distances rather than searching anew
+    ///
+    /// - cities: the two cities the route joins
+    /// - length: the route's new length, or empty to remove it
+    ///
+    /// - Return: Either an empty Ok Result, or an Err with message
+    ///   explaining problem
+    ///
+    fn edit_and_reroute(&mut self, cities: &[&str], length: &str) -> Result<(), String>
+    {
+        let (from, to) = match cities
+        {
+            [from, to] => (*from, *to),
+            _ => return Err(String::from("Name the two cities the route joins.")),
+        };
+        let weight = match length
+        {
+            "" => None,
+            length => match length.parse::<f64>()
+            {
+                Ok(length) if length.is_finite() && length >= 0.0 => Some(self.unit.to_weight(length).round() as u64),
+                _ => return Err(format!("\"{}\" is not a length.", length)),
+            },
+        };
+
+        // Search from the last route's start first, so that the edit
+        // repairs the search instead of discarding it
+        let last = match (self.last_route.first(), self.last_route.last())
+        {
+            (Some(start), Some(end)) => Some((start.clone(), end.clone())),
+            _ => None,
+        };
+        if let Some((start, end)) = &last { self.reroute(start, end).ok(); }
+
+        let repaired = self.edit_route(from, to, weight)?;
+        match weight
+        {
+            Some(weight) => println!("The route between {} and {} is now {}.", from, to, self.distance(weight)),
+            None => println!("The route between {} and {} is removed.", from, to),
+        }
+
+        if let (Some((start, end)), Some(settled)) = (last, repaired)
+        {
+            println!("\nRunning the last search from {} to {} again...", start, end);
+            let route = self.reroute(&start, &end)?;
+            self.print_route(&route);
+            self.remember_route(&route);
+            println!("--\nRepaired the search by settling {} of {} locations again.", settled, self.route_dat.node_count());
+        }
+        Ok(())
+    }
 
-    // Split the route data into separate lines
-    let route_dat = route_dat.split('\n')
-        .collect::<Vec<&'a str>>();
+    ///
+    /// Prints an itinerary: the stops in order, each leg's distance, and
+    /// then the whole route's steps and total, as one route
+    ///
+    /// - legs: each leg's route, in order
+    ///
+    fn print_itinerary(&mut self, legs: &[PathResult])
+    {
+        let stops = legs.iter().map(|leg| leg.nodes[0]).chain(legs.last().map(|leg| leg.nodes[leg.nodes.len() - 1]));
+        println!("Itinerary: {}\n", stops.collect::<Vec<&str>>().join(" -> "));
+        for (i, leg) in legs.iter().enumerate()
+        {
+            println!("Leg {}: {} to {}: {}", i + 1, leg.nodes[0], leg.nodes[leg.nodes.len() - 1], self.distance(leg.cost));
+        }
+        println!();
+
+        // The legs joined into one route, each leg's end starting the next
+        let mut route = PathResult { nodes: vec![legs[0].nodes[0]], cost: 0 };
+        for leg in legs
+        {
+            route.nodes.extend(&leg.nodes[1..]);
+            route.cost += leg.cost;
+        }
+        self.print_route(&route);
+        self.remember_route(&route);
+    }
 
-    // For each line, add two Nodes and
-    // Edge into the graph
-    for line in route_dat
+    ///
+    /// Prints the largest inadmissible and inconsistent estimates a
+    /// heuristic check found, and how many of each there were
+    ///
+    /// - check: the HeuristicCheck
+    ///
+    fn print_heuristic_check(&self, check: &HeuristicCheck)
     {
-        // Trim parens
-        let line = line.trim_matches(|c| { c == '(' || c == ')' });
+        if !check.overestimates.is_empty()
+        {
+            println!("Inadmissible estimates, above the shortest distance:\n");
+            for overestimate in check.overestimates.iter().take(PROBLEMS_SHOWN)
+            {
+                println!("{} to {}: estimated {}, but the shortest route is {} (over by {})",
+                    overestimate.node, overestimate.goal, self.distance(overestimate.estimate),
+                    self.distance(overestimate.distance), self.distance(overestimate.excess()));
+            }
+            println!();
+        }
+        if !check.inconsistencies.is_empty()
+        {
+            println!("Inconsistent estimates, falling by more than a route's length:\n");
+            for inconsistency in check.inconsistencies.iter().take(PROBLEMS_SHOWN)
+            {
+                println!("{} to {} (a route of {}), toward {}: estimated {} then {} (over by {})",
+                    inconsistency.from, inconsistency.to, self.distance(inconsistency.weight), inconsistency.goal,
+                    self.distance(inconsistency.from_estimate), self.distance(inconsistency.to_estimate), self.distance(inconsistency.excess()));
+            }
+            println!();
+        }
+
+        println!("--\n{} of {} estimate(s) inadmissible, and {} inconsistent across a route.",
+            check.overestimates.len(), check.pairs, check.inconsistencies.len());
+        if check.overestimates.len() > PROBLEMS_SHOWN || check.inconsistencies.len() > PROBLEMS_SHOWN
+        {
+            println!("The largest {} of each are shown.", PROBLEMS_SHOWN);
+        }
+    }
 
-        // Split by commas
-        let data = line.split(',')
-            .map(|val| { val.trim() })
-            .collect::<Vec<&'a str>>();
-        
-        // 1st item - the starting node
-        // 2nd item - the ending node
-        let (route_from, route_to) = (data[0], data[1]);
+    ///
+    /// Prints each route in a spanning tree, followed by their
+    /// total distance
+    ///
+    /// - tree: the spanning tree to print
+    ///
+    fn print_spanning_tree(&self, tree: &SpanningTree)
+    {
+        println!("Minimum spanning tree:\n");
+        for (from, to, weight) in &tree.edges
+        {
+            println!("Connect {} to {}: {}", from, to, self.distance(*weight));
+        }
 
-        // Round weight to nearest 10th, and convert to u64
-        let weight = (data[2].parse::<f64>().unwrap() * 10.0).round() as u64;
+        println!("Total {}: {}", self.unit.measure(), self.distance(tree.weight));
+    }
 
-        // Add the edge to the Graph.
-        graph.add_edge(route_from, route_to, weight);
+    ///
+    /// Prints the most recent searches in the history, each numbered by
+    /// its place in the history, oldest first
+    ///
+    /// - queries: every search in the history
+    ///
+    fn print_history(&self, queries: &[Query])
+    {
+        println!("Search history:\n");
+        let shown = queries.len().saturating_sub(HISTORY_SHOWN);
+        for (i, query) in queries.iter().enumerate().skip(shown)
+        {
+            let result = match query.cost
+            {
+                Some(cost) => self.unit.format(cost, self.precision),
+                None => String::from("no route"),
+            };
+            println!("{}. {}  {} to {} with {}: {} in {} micros", i + 1, format_time(query.time), query.from, query.to, query.algorithm, result, query.micros);
+        }
+        println!();
     }
+}
+
+///
+/// Interns the Graph routes are searched on: the one-way routes, if any,
+/// or else the two-way routes, with the heuristic's estimates by ID
+///
+/// - route_dat: the two-way routes
+/// - arcs: the one-way routes, if any
+/// - heuristic: the heuristic searches are given
+/// - reorder: whether to renumber locations so neighbours are close
+///
+fn interned<'a>(route_dat: &RouteGraph<'a>, arcs: &Option<DirectedRouteGraph<'a>>, heuristic: &dyn Heuristic, reorder: bool) -> InternedGraph<'a>
+{
+    let mut interned = match arcs
+    {
+        Some(arcs) => InternedGraph::new(arcs),
+        None => InternedGraph::new(route_dat),
+    };
+    if reorder { interned.reorder(); }
+    interned.set_heuristic(heuristic);
+    interned
+}
 
-    // Return the graph
-    graph
+///
+/// Advances a SplitMix64 generator, which draws well-spread numbers from
+/// any seed, including 0
+///
+/// - state: the generator's state, advanced in place
+///
+/// - return: the next number drawn
+///
+fn split_mix(state: &mut u64) -> u64
+{
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
 }
 
 ///
-/// Retrieves all Heuristic data from euclidian.txt
-/// Returns as a HashMap, with key values being the 2-ple of the
-/// two borrowed String slices, and the value being the distance between.
-/// 
-/// - input: the input-data, as a borrowed String
-/// 
-/// - return: the generated HashMap, with u64 type values. The float value
-///   provided from route_dat is rounded to 1 decimal place, and multipled
-///   by 10, to maintain precision, but allow complete ordering
-/// 
-fn build_heur_data<'a>(input: &'a String) -> HashMap<(&'a str, &'a str), u64>
+/// Finds a percentile of sorted values, by the nearest-rank method
+///
+/// - sorted: the values, in ascending order
+/// - percent: the percentile, from 0 to 100
+///
+/// - return: the smallest value which at least percent of the values are
+///   no greater than, or 0 if there are none
+///
+fn percentile(sorted: &[u128], percent: f64) -> u128
 {
-    // HashMap of data - returned value
-    let mut dist_dat = HashMap::new();
+    let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted.get(rank.saturating_sub(1)).cloned().unwrap_or(0)
+}
 
-    // Split input by line
-    let input = input.split('\n')
-        .collect::<Vec<&'a str>>();
+///
+/// Prints names in columns, a row at a time, or else as one line
+/// separated by commas when output is plain
+///
+/// - names: the names to print
+/// - width: the width of each column
+/// - columns: the # of columns in each row
+///
+fn print_columns(names: &[&str], width: usize, columns: usize)
+{
+    if is_plain() { return println!("{}", names.join(", ")); }
 
-    // For each line of input
-    for line in input
+    for (i, name) in names.iter().enumerate()
     {
-        // Collect the data, seperated by spaces
-        let data = line.split(' ').collect::<Vec<&str>>();
+        print!("{0:<1$}", name, width);
+        if i % columns == columns - 1 { println!(); }
+    }
+}
+
+///
+/// - arcs: the one-way routes, if any
+///
+/// - Return: whether there are one-way routes and they form no cycle, so
+///   that routes can be found in topological order
+///
+fn is_acyclic(arcs: &Option<DirectedRouteGraph>) -> bool
+{
+    arcs.as_ref().is_some_and(|arcs| topological_order(arcs).is_some())
+}
 
-        // Assign from and to node (edge) to vars
-        let (from, to) = (data[0], data[1]);
+#[cfg(test)]
+mod tests
+{
+    use bumpalo::Bump;
 
-        // Round distance to nearest 10th and convert to u64
-        let dist = (data[2].parse::<f64>().unwrap() * 10.0).round() as u64;
+    use super::Program;
+    use crate::algo::alt::Landmarks;
+    use crate::algo::grid::Connectivity;
+    use crate::algo::Algorithm;
+    use crate::load::grid;
 
-        // Insert data
-        dist_dat.insert((from, to), dist);
-    }
+    #[test]
+    fn grid_algorithms_need_a_diagonal_grid()
+    {
+        let arena = Bump::new();
+        for &(connectivity, diagonal) in &[(Connectivity::Four, false), (Connectivity::Eight, true)]
+        {
+            let routes = grid::parse("S..\n.#.\n..G\n", connectivity, &arena).unwrap();
+            let landmarks = Landmarks::new(&routes.graph, 1);
+            let mut prog = Program::from_graph(routes.graph, Box::new(landmarks));
+            assert!(prog.set_algorithms(vec![Algorithm::JumpPoint]).is_err());
 
-    dist_dat
-}
\ No newline at end of file
+            prog.set_grid(routes.grid);
+            assert_eq!(prog.set_algorithms(vec![Algorithm::ThetaStar, Algorithm::JumpPoint]).is_ok(), diagonal);
+            if diagonal
+            {
+                assert_eq!(prog.route(Algorithm::JumpPoint, "S", "G").map(|route| route.cost), Ok(40));
+            }
+            else
+            {
+                assert!(prog.algorithms().is_empty());
+            }
+        }
+    }
+}
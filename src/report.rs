@@ -0,0 +1,100 @@
+use serde::Serialize;
+
+use super::algo::context::Counters;
+
+///
+/// The results of routing between two locations, for other tools to
+/// read, as written by to_json
+///
+/// - start: the start location
+/// - end: the end location
+/// - unit: the name of the unit distances are measured in
+/// - results: the route found by each algorithm run, in the order run
+///
+#[derive(Serialize, Debug, Clone)]
+pub struct RouteReport<'a>
+{
+    pub start: &'a str,
+    pub end: &'a str,
+    pub unit: &'static str,
+    pub results: Vec<AlgorithmReport<'a>>,
+}
+
+///
+/// The route one algorithm found
+///
+/// - algorithm: the algorithm's name
+/// - route: every location on the route, from start to end
+/// - legs: each route taken between consecutive locations
+/// - total: the route's total distance
+/// - nodes_considered: the # of nodes the algorithm expanded
+/// - counters: what the search did beyond that, if it searched the
+///   interned Graph
+/// - micros: the time taken to find the route, in microseconds
+///
+#[derive(Serialize, Debug, Clone)]
+pub struct AlgorithmReport<'a>
+{
+    pub algorithm: &'static str,
+    pub route: Vec<&'a str>,
+    pub legs: Vec<Leg<'a>>,
+    pub total: f64,
+    pub nodes_considered: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub counters: Option<Counters>,
+    pub micros: u128,
+}
+
+///
+/// One route taken, between consecutive locations of a longer route
+///
+/// - index: the leg's place on the route, from 1
+/// - from, to: the locations the leg leaves and reaches
+/// - distance: the leg's distance
+/// - cumulative: the route's distance up to the end of the leg
+///
+#[derive(Serialize, Debug, Clone)]
+pub struct Leg<'a>
+{
+    pub index: usize,
+    pub from: &'a str,
+    pub to: &'a str,
+    pub distance: f64,
+    pub cumulative: f64,
+}
+
+impl<'a> RouteReport<'a>
+{
+    ///
+    /// Writes the report as JSON
+    ///
+    /// - return: the report, as a single line of JSON
+    ///
+    pub fn to_json(&self) -> String
+    {
+        serde_json::to_string(self).expect("Route reports are always valid JSON")
+    }
+}
+
+///
+/// Writes a query which could not be routed as JSON
+///
+/// - start: the start location asked for
+/// - end: the end location asked for
+/// - error: the message explaining why no route could be found
+///
+/// - return: a JSON object naming the locations and the error, as a
+///   single line
+///
+pub fn error_json(start: &str, end: &str, error: &str) -> String
+{
+    #[derive(Serialize)]
+    struct ErrorReport<'a>
+    {
+        start: &'a str,
+        end: &'a str,
+        error: &'a str,
+    }
+
+    serde_json::to_string(&ErrorReport { start, end, error }).expect("Error reports are always valid JSON")
+}
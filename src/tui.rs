@@ -0,0 +1,329 @@
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, List, ListItem, ListState, Paragraph, Row, Table, Tabs, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use super::algo::Algorithm;
+use super::names::normalize;
+use super::prog::Program;
+use super::report::RouteReport;
+
+/// The keys used, shown at the bottom of the screen
+const KEYS_HELP: &str = "Type to search  ↑/↓ move  Enter choose  Tab algorithm  Esc clear/quit  Ctrl-C quit";
+
+///
+/// The state of the terminal interface
+///
+/// - locations: every location, in alphabetical order
+/// - search: the text typed to filter the locations
+/// - list: which of the filtered locations is selected
+/// - start, end: the locations chosen to route between
+/// - algorithm: the algorithm chosen, as an index into the algorithm
+///   tabs, where 0 compares A*, Djikstra's algorithm and fringe search
+/// - result: the last route's report, or the error routing it
+///
+struct App<'a>
+{
+    locations: Vec<&'a str>,
+    search: String,
+    list: ListState,
+    start: Option<&'a str>,
+    end: Option<&'a str>,
+    algorithm: usize,
+    result: Option<Result<RouteReport<'a>, String>>,
+}
+
+impl<'a> App<'a>
+{
+    ///
+    /// - return: the locations whose names hold the search text, ignoring
+    ///   case and spacing
+    ///
+    fn filtered(&self) -> Vec<&'a str>
+    {
+        let search = normalize(&self.search);
+        self.locations.iter()
+            .filter(|location| normalize(location).contains(&search))
+            .cloned()
+            .collect()
+    }
+
+    ///
+    /// Moves the selection through the filtered locations, stopping at
+    /// either end
+    ///
+    /// - by: how many locations to move by, negative to move up
+    ///
+    fn move_selection(&mut self, by: isize)
+    {
+        let count = self.filtered().len();
+        if count == 0 { return self.list.select(None); }
+
+        let selected = self.list.selected().unwrap_or(0) as isize + by;
+        self.list.select(Some(selected.clamp(0, count as isize - 1) as usize));
+    }
+
+    ///
+    /// Changes the search text, selecting the first location it matches
+    ///
+    /// - search: the new search text
+    ///
+    fn set_search(&mut self, search: String)
+    {
+        self.search = search;
+        self.list.select(Some(0).filter(|_| !self.filtered().is_empty()));
+    }
+}
+
+///
+/// Runs the terminal interface: a searchable list of locations, tabs
+/// choosing the algorithm, and panels showing the chosen route beside
+/// each algorithm's statistics. Choosing a location with Enter sets the
+/// start, and then the end, of the route. The terminal is restored once
+/// the user quits
+///
+/// - program: the Program to route with
+///
+/// - return: Either an empty Ok Result, or an Err with message explaining
+///   why the terminal could not be used
+///
+pub fn run(program: &mut Program) -> Result<(), String>
+{
+    let mut app = App
+    {
+        locations: program.locations(),
+        search: String::new(),
+        list: ListState::default(),
+        start: None,
+        end: None,
+        algorithm: match program.algorithms()
+        {
+            [chosen] => Algorithm::ALL.iter().position(|algorithm| algorithm == chosen).map_or(0, |i| i + 1),
+            _ => 0,
+        },
+        result: None,
+    };
+    app.set_search(String::new());
+
+    let mut terminal = ratatui::init();
+    let result = run_app(&mut terminal, program, &mut app);
+    ratatui::restore();
+    result.map_err(|e| format!("Cannot use the terminal: {}", e))
+}
+
+///
+/// Draws the interface and handles each key pressed, until the user
+/// quits
+///
+/// - terminal: the terminal to draw on
+/// - program: the Program to route with
+/// - app: the interface's state
+///
+fn run_app<'a>(terminal: &mut DefaultTerminal, program: &mut Program<'a>, app: &mut App<'a>) -> std::io::Result<()>
+{
+    loop
+    {
+        terminal.draw(|frame| draw(frame, program, app))?;
+
+        let key = match event::read()?
+        {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+        if !handle_key(key, program, app) { return Ok(()); }
+    }
+}
+
+///
+/// Handles a key pressed
+///
+/// - key: the key pressed
+/// - program: the Program to route with
+/// - app: the interface's state
+///
+/// - return: false if the user quit, otherwise true
+///
+fn handle_key<'a>(key: KeyEvent, program: &mut Program<'a>, app: &mut App<'a>) -> bool
+{
+    match key.code
+    {
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+        KeyCode::Char(c) => app.set_search(format!("{}{}", app.search, c)),
+        KeyCode::Backspace =>
+        {
+            let mut search = app.search.clone();
+            search.pop();
+            app.set_search(search);
+        },
+        KeyCode::Up => app.move_selection(-1),
+        KeyCode::Down => app.move_selection(1),
+        KeyCode::PageUp => app.move_selection(-10),
+        KeyCode::PageDown => app.move_selection(10),
+        KeyCode::Home => app.move_selection(isize::MIN / 2),
+        KeyCode::End => app.move_selection(isize::MAX / 2),
+        KeyCode::Enter =>
+        {
+            let chosen = app.list.selected().and_then(|i| app.filtered().get(i).cloned());
+            if let Some(location) = chosen
+            {
+                match (app.start, app.end)
+                {
+                    (Some(_), None) => app.end = Some(location),
+                    _ => { app.start = Some(location); app.end = None; app.result = None; },
+                }
+                app.set_search(String::new());
+                route(program, app);
+            }
+        },
+        KeyCode::Tab | KeyCode::BackTab =>
+        {
+            let count = Algorithm::ALL.len() + 1;
+            app.algorithm = match key.code
+            {
+                KeyCode::Tab => (app.algorithm + 1) % count,
+                _ => (app.algorithm + count - 1) % count,
+            };
+            program.set_algorithms(app.algorithm.checked_sub(1).map(|i| Algorithm::ALL[i]).into_iter().collect())
+                .expect("Every algorithm in ALL searches any Graph");
+            route(program, app);
+        },
+        KeyCode::Esc =>
+        {
+            if !app.search.is_empty() { app.set_search(String::new()); }
+            else if app.start.is_some() { app.start = None; app.end = None; app.result = None; }
+            else { return false; }
+        },
+        _ => (),
+    }
+
+    true
+}
+
+///
+/// Routes between the chosen locations with the chosen algorithm, once
+/// both have been chosen
+///
+/// - program: the Program to route with
+/// - app: the interface's state, which the report is stored in
+///
+fn route<'a>(program: &mut Program<'a>, app: &mut App<'a>)
+{
+    if let (Some(start), Some(end)) = (app.start, app.end)
+    {
+        app.result = Some(program.report(start, end));
+    }
+}
+
+///
+/// Draws the interface
+///
+/// - frame: the frame to draw on
+/// - program: the Program routed with
+/// - app: the interface's state
+///
+fn draw(frame: &mut Frame, program: &Program, app: &mut App)
+{
+    let [search_area, tabs_area, main_area, help_area] = Layout::vertical(
+        [Constraint::Length(3), Constraint::Length(3), Constraint::Min(5), Constraint::Length(1)])
+        .areas(frame.area());
+    let [list_area, route_area, stats_area] = Layout::horizontal(
+        [Constraint::Percentage(25), Constraint::Percentage(45), Constraint::Percentage(30)])
+        .areas(main_area);
+
+    frame.render_widget(Paragraph::new(app.search.as_str())
+        .block(Block::default().borders(Borders::ALL).title(" Search ")), search_area);
+
+    let titles = std::iter::once("Compare").chain(Algorithm::ALL.iter().map(|algorithm| algorithm.name()));
+    frame.render_widget(Tabs::new(titles)
+        .select(app.algorithm)
+        .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+        .block(Block::default().borders(Borders::ALL).title(" Algorithm ")), tabs_area);
+
+    let filtered = app.filtered();
+    let title = format!(" Locations ({}/{}) ", filtered.len(), app.locations.len());
+    let items = filtered.iter().map(|&location| ListItem::new(location)).collect::<Vec<ListItem>>();
+    frame.render_stateful_widget(List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> "), list_area, &mut app.list);
+
+    draw_route(frame, program, app, route_area);
+    draw_stats(frame, program, app, stats_area);
+
+    frame.render_widget(Paragraph::new(KEYS_HELP).style(Style::default().fg(Color::DarkGray)), help_area);
+}
+
+///
+/// Draws the route panel: the steps of the first algorithm's route and
+/// its total, or else the error routing it, or what to choose next
+///
+/// - frame: the frame to draw on
+/// - program: the Program routed with
+/// - app: the interface's state
+/// - area: where to draw the panel
+///
+fn draw_route(frame: &mut Frame, program: &Program, app: &App, area: Rect)
+{
+    let (precision, unit) = (program.precision(), program.unit());
+    let lines = match (&app.result, app.start)
+    {
+        (Some(Ok(report)), _) => match report.results.first()
+        {
+            Some(result) =>
+            {
+                let mut lines = result.legs.iter()
+                    .map(|leg| Line::from(format!("{}. Take {} to {}: {} ({} so far)", leg.index, leg.from, leg.to,
+                        unit.format(leg.distance, precision), unit.format(leg.cumulative, precision))))
+                    .collect::<Vec<Line>>();
+                lines.push(Line::from(Span::styled(format!("Total {}: {}", unit.measure(), unit.format(result.total, precision)),
+                    Style::default().add_modifier(Modifier::BOLD))));
+                lines
+            },
+            None => Vec::new(),
+        },
+        (Some(Err(e)), _) => vec![Line::from(Span::styled(e.as_str(), Style::default().fg(Color::Red)))],
+        (None, Some(start)) => vec![Line::from(format!("From {}. Choose the destination.", start))],
+        (None, None) => vec![Line::from("Choose the starting location.")],
+    };
+
+    let title = match (app.start, app.end)
+    {
+        (Some(start), Some(end)) => format!(" Route: {} to {} ", start, end),
+        _ => String::from(" Route "),
+    };
+    frame.render_widget(Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title(title)), area);
+}
+
+///
+/// Draws the statistics panel: each algorithm's distance, # of nodes
+/// considered and time taken for the last route
+///
+/// - frame: the frame to draw on
+/// - program: the Program routed with
+/// - app: the interface's state
+/// - area: where to draw the panel
+///
+fn draw_stats(frame: &mut Frame, program: &Program, app: &App, area: Rect)
+{
+    let rows = match &app.result
+    {
+        Some(Ok(report)) => report.results.iter()
+            .map(|result| Row::new(vec![
+                Cell::from(result.algorithm),
+                Cell::from(program.unit().number(result.total, program.precision())),
+                Cell::from(result.nodes_considered.to_string()),
+                Cell::from(result.micros.to_string()),
+            ]))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let widths = [Constraint::Min(10), Constraint::Length(9), Constraint::Length(6), Constraint::Length(7)];
+    frame.render_widget(Table::new(rows, widths)
+        .header(Row::new(vec!["Algorithm", "Distance", "Nodes", "Micros"]).style(Style::default().add_modifier(Modifier::BOLD)))
+        .block(Block::default().borders(Borders::ALL).title(" Statistics ")), area);
+}